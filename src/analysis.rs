@@ -0,0 +1,270 @@
+//! Solvers for chess problems
+//!
+//! A problem is given by its position and stipulation. In a helpmate, [helpmate], the side to move cooperates with
+//! its opponent to get itself checkmated, and every way to do so is a solution. In a selfmate, [selfmate], the side
+//! to move forces its opponent to checkmate it however the opponent defends, and the solutions are the first moves
+//! that do, the keys. Both search every legal move to the full depth, so they are meant for the short stipulations
+//! of composed problems.
+//!
+//! The stipulation counts moves, not plies: a helpmate in 2 is four plies and a selfmate in 2 ends with the
+//! opponent's second move.
+//!
+//! [tactical_scan] looks for the short tactics of a game position instead, the kind a teaching tool points out
+//! when a move blunders material or allows a mate.
+
+use crate::ordering::static_exchange;
+use crate::{Game, Move, MoveList, Square, EMPTY, KING};
+
+/// Returns the legal moves of the position
+fn legal_moves(game: &Game) -> MoveList {
+    let mut moves = MoveList::new();
+    game.legal_moves_into(&mut moves);
+    return moves;
+}
+
+/// Returns every solution of the helpmate in 'n' moves: lines of 2n plies, starting with the side to move, after
+/// which the side to move is checkmated. Lines mating earlier don't count. By convention Black moves first in a
+/// helpmate, so the position usually has Black to move.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::analysis::helpmate;
+/// # use olindba_chess::notation::to_uci;
+/// // The black king steps to g8, where the rook mates it along the back rank
+/// let game = Game::new("7k/6p1/6K1/8/8/8/8/R7 b - - 0 1");
+/// let solutions = helpmate(&game, 1);
+/// let lines: Vec<Vec<String>> = solutions.iter().map(|line| line.iter().map(to_uci).collect()).collect();
+/// assert_eq!(lines, vec![vec!["h8g8".to_string(), "a1a8".to_string()]]);
+/// ```
+pub fn helpmate(game: &Game, n: usize) -> Vec<Vec<Move>> {
+    let mut game = game.clone();
+    let mut line = vec![];
+    let mut solutions = vec![];
+    helpmate_lines(&mut game, 2 * n, &mut line, &mut solutions);
+    return solutions;
+}
+
+/// Adds the lines of 'plies' plies from the position that checkmate the side that moves first to the solutions
+fn helpmate_lines(game: &mut Game, plies: usize, line: &mut Vec<Move>, solutions: &mut Vec<Vec<Move>>) {
+    if plies == 0 {
+        if game.is_checkmated() {
+            solutions.push(line.clone());
+        }
+        return;
+    }
+    for &mv in legal_moves(game).iter() {
+        // Only a check can be the mating move
+        if plies == 1 && !game.gives_check(&mv) {
+            continue;
+        }
+        game.make_move(mv);
+        line.push(mv);
+        helpmate_lines(game, plies - 1, line, solutions);
+        line.pop();
+        game.unmake_move();
+    }
+}
+
+/// Returns every key of the selfmate in 'n' moves: the moves of the side to move after which it can force the
+/// opponent to checkmate it within 'n' moves of the opponent, however the opponent defends.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::analysis::selfmate;
+/// # use olindba_chess::notation::to_uci;
+/// // After the check the black king has to take the queen, uncovering the rook's mate along the h-file
+/// let game = Game::new("8/8/7r/4Q3/6P1/7k/4Rp2/7K w - - 0 1");
+/// let keys: Vec<String> = selfmate(&game, 1).iter().map(to_uci).collect();
+/// assert_eq!(keys, vec!["e5g3"]);
+/// assert!(selfmate(&Game::starting_position(), 1).is_empty());
+/// ```
+pub fn selfmate(game: &Game, n: usize) -> Vec<Move> {
+    if n == 0 {
+        return vec![];
+    }
+    let mut game = game.clone();
+    let mut keys = vec![];
+    for &mv in legal_moves(&game).iter() {
+        game.make_move(mv);
+        if forces_selfmate(&mut game, n) {
+            keys.push(mv);
+        }
+        game.unmake_move();
+    }
+    return keys;
+}
+
+/// Returns true if every move of the side to move, which has at least one, checkmates its opponent within 'n'
+/// moves of its own while the opponent keeps forcing it to
+fn forces_selfmate(game: &mut Game, n: usize) -> bool {
+    let moves = legal_moves(game);
+    if moves.is_empty() {
+        return false;
+    }
+    for &mv in moves.iter() {
+        game.make_move(mv);
+        let forced = game.is_checkmated() || (n > 1 && has_selfmate_continuation(game, n - 1));
+        game.unmake_move();
+        if !forced {
+            return false;
+        }
+    }
+    return true;
+}
+
+/// Returns true if the side to move has a move after which its opponent is forced to checkmate it within 'n' moves
+fn has_selfmate_continuation(game: &mut Game, n: usize) -> bool {
+    for &mv in legal_moves(game).iter() {
+        game.make_move(mv);
+        let forced = forces_selfmate(game, n);
+        game.unmake_move();
+        if forced {
+            return true;
+        }
+    }
+    return false;
+}
+
+/// Something [tactical_scan] found in a position
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TacticalFinding {
+    /// The side to move checkmates by force with the line, however the opponent defends
+    ForcedMate(Vec<Move>),
+    /// The opponent would checkmate by force with the line if it were its turn
+    MateThreat(Vec<Move>),
+    /// A capture of the side to move that wins the centipawns by [static_exchange]
+    WinningCapture { mv: Move, gain: i32 },
+    /// A piece of the side to move the opponent could win the centipawns against if it were its turn
+    HangingPiece { square: Square, loss: i32 }
+}
+
+/// Returns the tactics of the position without searching it: forced mates of either side within 'depth' moves,
+/// the captures winning material for the side to move and its pieces the opponent could win material against.
+/// Mates come first, then captures and hanging pieces with the most material first. The opponent's mates are only
+/// looked for when the side to move isn't in check, as it otherwise couldn't pass the move.
+///
+/// A move blunders when the scan of the position after it finds a forced mate or a winning capture, both of which
+/// are the opponent's then.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::analysis::{tactical_scan, TacticalFinding};
+/// # use olindba_chess::notation::parse_uci;
+/// // The pawn can take the knight, but the bishop attacks the pawn
+/// let game = Game::new("4k3/7b/8/3n4/4P3/8/8/4K3 w - - 0 1");
+/// let capture = parse_uci(&game, "e4d5").unwrap();
+/// assert_eq!(tactical_scan(&game, 1), vec![
+///     TacticalFinding::WinningCapture { mv: capture, gain: 320 },
+///     TacticalFinding::HangingPiece { square: Square::E4, loss: 100 }
+/// ]);
+///
+/// // Black threatens mate on the back rank
+/// let game = Game::new("r5k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1");
+/// let threat = Game::new("r5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1");
+/// let mate = parse_uci(&threat, "a8a1").unwrap();
+/// assert_eq!(tactical_scan(&game, 1), vec![TacticalFinding::MateThreat(vec![mate])]);
+/// assert_eq!(tactical_scan(&threat, 1), vec![TacticalFinding::ForcedMate(vec![mate])]);
+/// ```
+pub fn tactical_scan(game: &Game, depth: usize) -> Vec<TacticalFinding> {
+    let mut findings = vec![];
+    if let Some(line) = forced_mate(&mut game.clone(), depth) {
+        findings.push(TacticalFinding::ForcedMate(line));
+    }
+    if let Ok(mut passed) = game.with_side_to_move(game.turn ^ 1) {
+        if let Some(line) = forced_mate(&mut passed, depth) {
+            findings.push(TacticalFinding::MateThreat(line));
+        }
+    }
+
+    let mut captures: Vec<(Move, i32)> = game.legal_moves_cached().iter()
+        .filter(|mv| mv.is_capture())
+        .map(|mv| (*mv, static_exchange(game, mv)))
+        .filter(|&(_, gain)| gain > 0)
+        .collect();
+    captures.sort_by_key(|&(_, gain)| -gain);
+    findings.extend(captures.into_iter().map(|(mv, gain)| TacticalFinding::WinningCapture { mv, gain }));
+
+    // The opponent's captures are the ones it could make if the side to move passed, so pins are ignored
+    let mut hanging: Vec<(Square, i32)> = vec![];
+    for from in 0..64 {
+        let piece = game.board[from];
+        if piece.get_type() == EMPTY || piece.get_color() == game.turn {
+            continue;
+        }
+        for mv in game.pseudo_legal_targets(from) {
+            let target = game.board[mv.get_to()];
+            if !mv.is_capture() || target.get_type() == KING || target.get_color() != game.turn {
+                continue;
+            }
+            let loss = static_exchange(game, &mv);
+            match hanging.iter_mut().find(|(square, _)| *square == mv.to_square()) {
+                Some((_, worst)) => *worst = (*worst).max(loss),
+                None => hanging.push((mv.to_square(), loss))
+            }
+        }
+    }
+    hanging.retain(|&(_, loss)| loss > 0);
+    hanging.sort_by_key(|&(square, loss)| (-loss, square));
+    findings.extend(hanging.into_iter().map(|(square, loss)| TacticalFinding::HangingPiece { square, loss }));
+    return findings;
+}
+
+/// Returns the shortest line in which the side to move checkmates within 'n' moves however the opponent defends,
+/// following the defence that holds out the longest
+fn forced_mate(game: &mut Game, n: usize) -> Option<Vec<Move>> {
+    for moves in 1..=n {
+        if let Some(line) = mate_in(game, moves) {
+            return Some(line);
+        }
+    }
+    return None;
+}
+
+/// Returns a line in which the side to move checkmates within 'n' moves however the opponent defends
+fn mate_in(game: &mut Game, n: usize) -> Option<Vec<Move>> {
+    for &mv in legal_moves(game).iter() {
+        // Only a check can be the mating move
+        if n == 1 && !game.gives_check(&mv) {
+            continue;
+        }
+        game.make_move(mv);
+        let defence = longest_defence(game, n);
+        game.unmake_move();
+        if let Some(mut line) = defence {
+            line.insert(0, mv);
+            return Some(line);
+        }
+    }
+    return None;
+}
+
+/// Returns the defence of the side to move that delays being checkmated the longest, if its opponent mates
+/// within 'n' moves counting the one just made
+fn longest_defence(game: &mut Game, n: usize) -> Option<Vec<Move>> {
+    if game.is_checkmated() {
+        return Some(vec![]);
+    }
+    let replies = legal_moves(game);
+    if n == 1 || replies.is_empty() {
+        return None;
+    }
+    let mut longest: Option<Vec<Move>> = None;
+    for &reply in replies.iter() {
+        game.make_move(reply);
+        let line = forced_mate(game, n - 1);
+        game.unmake_move();
+        let mut line = line?;
+        if longest.as_ref().is_some_and(|longest| longest.len() > line.len()) {
+            continue;
+        }
+        line.insert(0, reply);
+        longest = Some(line);
+    }
+    return longest;
+}
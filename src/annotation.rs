@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+
+use crate::Game;
+
+/// An evaluation of the position after a move, from White's point of view as in the '[%eval]' command of PGN
+/// comments
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Evaluation {
+    /// Positive when White is better, written in pawns as '[%eval 0.25]'
+    Centipawns(i32),
+    /// Moves until mate, positive when White mates, written as '[%eval #3]'
+    Mate(i32)
+}
+
+impl Evaluation {
+
+    /// Reads the value of an '[%eval]' command, either pawns such as '-1.50' or a mate such as '#-3'
+    pub fn parse(value: &str) -> Option<Evaluation> {
+        if let Some(mate) = value.strip_prefix('#') {
+            return mate.parse().ok().map(Evaluation::Mate);
+        }
+        let pawns: f64 = value.parse().ok()?;
+        if !pawns.is_finite() {
+            return None;
+        }
+        return Some(Evaluation::Centipawns((pawns * 100.0).round() as i32));
+    }
+}
+
+impl std::fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Evaluation::Centipawns(centipawns) => write!(f, "{:.2}", *centipawns as f64 / 100.0),
+            Evaluation::Mate(moves) => write!(f, "#{}", moves)
+        }
+    }
+}
+
+/// What analysis or a reader has to say about a move, see [Game::annotate]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Annotation {
+    /// The text of the comment after the move, without the evaluation
+    pub comment: Option<String>,
+    /// Numeric annotation glyphs, such as 1 for a good move and 2 for a mistake, written as '$1' in PGN
+    pub nags: Vec<u8>,
+    pub eval: Option<Evaluation>
+}
+
+impl Annotation {
+
+    /// Returns true if the annotation says nothing
+    pub fn is_empty(&self) -> bool {
+        return self.comment.is_none() && self.nags.is_empty() && self.eval.is_none();
+    }
+
+    /// Adds the text of a PGN comment, taking the evaluation out of an '[%eval]' command. Other commands such as
+    /// '[%clk]' are kept in the comment.
+    pub(crate) fn add_comment(&mut self, comment: &str) {
+        let mut text = comment.to_string();
+        if let Some(start) = text.find("[%eval") {
+            if let Some(length) = text[start..].find(']') {
+                let value = text[start + "[%eval".len()..start + length].trim().to_string();
+                if let Some(eval) = Evaluation::parse(&value) {
+                    self.eval = Some(eval);
+                    text.replace_range(start..start + length + 1, "");
+                }
+            }
+        }
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            return;
+        }
+        self.comment = match self.comment.take() {
+            Some(earlier) => Some(format!("{} {}", earlier, text)),
+            None => Some(text)
+        };
+    }
+
+    /// Returns the annotation as written after the move in PGN: the glyphs and a comment with the evaluation
+    pub(crate) fn pgn_tokens(&self) -> Vec<String> {
+        let mut tokens: Vec<String> = self.nags.iter().map(|nag| format!("${}", nag)).collect();
+        let mut words = vec![];
+        if let Some(eval) = self.eval {
+            words.push(format!("[%eval {}]", eval));
+        }
+        if let Some(comment) = &self.comment {
+            // A closing brace would end the comment early
+            words.extend(comment.replace('}', ")").split_whitespace().map(str::to_string));
+        }
+        if let Some(first) = words.first_mut() {
+            first.insert(0, '{');
+            words.last_mut().unwrap().push('}');
+        }
+        tokens.extend(words);
+        return tokens;
+    }
+}
+
+impl Game {
+
+    /// Attaches the annotation to the move made at the ply, counted from 0 as in [Game::history], replacing the one
+    /// the move had. An empty annotation removes it. Annotations belong to their moves, so taking a move back
+    /// removes its annotation, and they are written by [crate::pgn::PgnGame] and read back from PGN.
+    ///
+    /// # Panics
+    /// If no move has been made at the ply
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::Notation;
+    /// # use olindba_chess::pgn::{parse_game, PgnGame};
+    /// let mut game = Game::starting_position();
+    /// game.apply_move_list("f3 e5 g4 Qh4#", Notation::San).unwrap();
+    /// game.annotate(2, Annotation { nags: vec![4], eval: Some(Evaluation::Mate(-1)), ..Default::default() });
+    /// game.annotate(3, Annotation { comment: Some("Fool's mate".to_string()), ..Default::default() });
+    ///
+    /// let pgn = PgnGame::from(&game).to_string();
+    /// assert!(pgn.ends_with("1. f3 e5 2. g4 $4 {[%eval #-1]} 2... Qh4# {Fool's mate} 0-1\n"));
+    /// let read = parse_game(&pgn).unwrap().final_position();
+    /// assert_eq!(read.annotations(), game.annotations());
+    ///
+    /// game.unmake_move();
+    /// assert_eq!(game.annotation(3), None);
+    /// assert_eq!(game.annotation(2).unwrap().eval, Some(Evaluation::Mate(-1)));
+    /// ```
+    pub fn annotate(&mut self, ply: usize, annotation: Annotation) {
+        assert!(ply < self.history.len(), "no move has been made at ply {}", ply);
+        if annotation.is_empty() {
+            self.annotations.remove(&ply);
+        }
+        else {
+            self.annotations.insert(ply, annotation);
+        }
+    }
+
+    /// Returns the annotation of the move made at the ply, if it has one
+    pub fn annotation(&self, ply: usize) -> Option<&Annotation> {
+        return self.annotations.get(&ply);
+    }
+
+    /// Returns the annotations of the moves by their plies
+    pub fn annotations(&self) -> &BTreeMap<usize, Annotation> {
+        return &self.annotations;
+    }
+}
@@ -0,0 +1,155 @@
+//! Generating legal moves for many positions at once, for example to build training data
+//!
+//! A [MoveBatch] stores the moves of all positions in one buffer with the offset of each position's moves in
+//! another, so a batch can be refilled without allocating and the moves bypass the per-position cache of [Game].
+//! With the 'rayon' feature the positions can be split over all cores with [MoveBatch::par_fill].
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::batch::MoveBatch;
+//! let fens = ["rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"];
+//! let mut batch = MoveBatch::new();
+//! batch.fill_from_fens(&fens).unwrap();
+//! assert_eq!(batch.len(), 2);
+//! assert_eq!(batch.moves(0).len(), 20);
+//! assert!(batch.moves(1).is_empty());
+//! assert_eq!(batch.total_moves(), 20);
+//!
+//! assert!(batch.fill_from_fens(&[fens[0], "8/8/8 w - - 0 1"]).is_err());
+//! assert_eq!(batch.len(), 1);
+//! ```
+
+use crate::fen::{parse_fen, FenError};
+use crate::{Game, Move};
+
+/// The legal moves of a series of positions, stored back to back
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveBatch {
+    moves: Vec<Move>,
+    /// Where the moves of each position start in 'moves', followed by the total number of moves
+    offsets: Vec<usize>
+}
+
+impl Default for MoveBatch {
+    fn default() -> MoveBatch {
+        MoveBatch::new()
+    }
+}
+
+impl MoveBatch {
+
+    pub fn new() -> MoveBatch {
+        MoveBatch {
+            moves: vec![],
+            offsets: vec![0]
+        }
+    }
+
+    /// Removes all positions, keeping the allocated buffers
+    pub fn clear(&mut self) {
+        self.moves.clear();
+        self.offsets.clear();
+        self.offsets.push(0);
+    }
+
+    /// Returns the number of positions in the batch
+    pub fn len(&self) -> usize {
+        return self.offsets.len() - 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    /// Returns the number of moves of all positions together
+    pub fn total_moves(&self) -> usize {
+        return self.moves.len();
+    }
+
+    /// Returns the legal moves of the position with the given index
+    ///
+    /// # Panics
+    /// If the index is outside the batch
+    pub fn moves(&self, position: usize) -> &[Move] {
+        return &self.moves[self.offsets[position]..self.offsets[position + 1]];
+    }
+
+    /// Returns the legal moves of every position in order
+    pub fn iter(&self) -> impl Iterator<Item = &[Move]> {
+        return self.offsets.windows(2).map(|window| &self.moves[window[0]..window[1]]);
+    }
+
+    /// Adds the legal moves of the position at the end of the batch
+    pub fn push(&mut self, game: &Game) {
+        if !game.is_automatically_terminated() {
+            game.move_generator().generate_legal_moves(game, 0..64, &mut |mv| self.moves.push(mv));
+        }
+        self.offsets.push(self.moves.len());
+    }
+
+    /// Replaces the contents of the batch with the legal moves of the given positions
+    pub fn fill(&mut self, games: &[Game]) {
+        self.clear();
+        for game in games {
+            self.push(game);
+        }
+    }
+
+    /// Replaces the contents of the batch with the legal moves of the positions given as FEN strings. Returns the
+    /// error of the first invalid FEN, the batch then holds the positions before it.
+    pub fn fill_from_fens(&mut self, fens: &[&str]) -> Result<(), FenError> {
+        self.clear();
+        for fen in fens {
+            self.push(&parse_fen(fen)?);
+        }
+        return Ok(());
+    }
+
+    /// Same as [MoveBatch::fill] but with the positions split over the threads of rayon's global pool
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::batch::MoveBatch;
+    /// let mut games = vec![Game::starting_position()];
+    /// for _ in 0..100 {
+    ///     let mut game = games.last().unwrap().clone();
+    ///     match game.get_all_legal_moves().last() {
+    ///         Some(mv) => game.make_move(*mv),
+    ///         None => break
+    ///     }
+    ///     games.push(game);
+    /// }
+    /// let mut batch = MoveBatch::new();
+    /// let mut parallel_batch = MoveBatch::new();
+    /// batch.fill(&games);
+    /// parallel_batch.par_fill(&games);
+    /// assert_eq!(batch, parallel_batch);
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_fill(&mut self, games: &[Game]) {
+        use rayon::prelude::*;
+
+        let chunk_size = games.len().div_ceil(rayon::current_num_threads()).max(1);
+        let chunks: Vec<MoveBatch> = games.par_chunks(chunk_size).map(|chunk| {
+            let mut batch = MoveBatch::new();
+            batch.fill(chunk);
+            return batch;
+        }).collect();
+
+        self.clear();
+        for chunk in chunks {
+            let base = self.moves.len();
+            self.moves.extend_from_slice(&chunk.moves);
+            self.offsets.extend(chunk.offsets[1..].iter().map(|offset| base + offset));
+        }
+    }
+}
+
+/// Returns the number of legal moves of each position, without storing the moves
+pub fn count_legal_moves(games: &[Game]) -> Vec<usize> {
+    return games.iter().map(|game| game.count_legal_moves()).collect();
+}
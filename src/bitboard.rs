@@ -0,0 +1,387 @@
+use crate::{Square, WHITE};
+
+/// The squares between and the lines through every pair of squares, see [Bitboard::between] and [Bitboard::line]
+struct RayTables {
+    between: [[u64; 64]; 64],
+    line: [[u64; 64]; 64]
+}
+
+const fn generate_ray_tables() -> RayTables {
+    let mut tables = RayTables { between: [[0; 64]; 64], line: [[0; 64]; 64] };
+    let mut from = 0;
+    while from < 64 {
+        let mut to = 0;
+        while to < 64 {
+            let row_step = (to / 8) as isize - (from / 8) as isize;
+            let column_step = (to % 8) as isize - (from % 8) as isize;
+            let aligned = row_step == 0 || column_step == 0 || row_step.abs() == column_step.abs();
+            if from != to && aligned {
+                let step = (row_step.signum(), column_step.signum());
+
+                let mut row = (from / 8) as isize + step.0;
+                let mut column = (from % 8) as isize + step.1;
+                while (row * 8 + column) as usize != to {
+                    tables.between[from][to] |= 1 << (row * 8 + column);
+                    row += step.0;
+                    column += step.1;
+                }
+
+                // Back to the edge of the board, then across it in the direction of 'to'
+                row = (from / 8) as isize;
+                column = (from % 8) as isize;
+                while row - step.0 >= 0 && row - step.0 < 8 && column - step.1 >= 0 && column - step.1 < 8 {
+                    row -= step.0;
+                    column -= step.1;
+                }
+                while row >= 0 && row < 8 && column >= 0 && column < 8 {
+                    tables.line[from][to] |= 1 << (row * 8 + column);
+                    row += step.0;
+                    column += step.1;
+                }
+            }
+            to += 1;
+        }
+        from += 1;
+    }
+    return tables;
+}
+
+static RAY_TABLES: RayTables = generate_ray_tables();
+
+/// A set of squares stored as one bit per square, bit n being the square with index n of [crate::Game::board],
+/// so a8 is the lowest bit and h1 the highest. This is the same layout as the masks returned by
+/// [crate::Game::attacks_from] and [crate::Game::attack_map], which convert with [Bitboard::from].
+///
+/// Shifts move every square one step in a direction and drop the squares that would leave the board, north being
+/// towards the 8th rank and east towards the h-file.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// let game = Game::starting_position();
+/// let attacked = Bitboard::from(game.attack_map(WHITE));
+/// assert!(attacked.contains(Square::from_algebraic("f3").unwrap()));
+/// assert_eq!((attacked & Bitboard::rank(3)).count(), 8);
+///
+/// let e4 = Square::from_algebraic("e4").unwrap();
+/// let pawn = Bitboard::from(e4);
+/// assert_eq!(pawn.north(), Bitboard::from(Square::from_algebraic("e5").unwrap()));
+/// assert!(Bitboard::from(Square::from_algebraic("h4").unwrap()).east().is_empty());
+/// assert_eq!(Bitboard::king_ring(e4).count(), 8);
+///
+/// let squares: Vec<String> = (Bitboard::CENTER & Bitboard::LIGHT_SQUARES).iter().map(|square| square.to_string()).collect();
+/// assert_eq!(squares, ["d5", "e4"]);
+/// assert!(Bitboard::LIGHT_SQUARES.iter().all(|square| square.color() == SquareColor::Light));
+/// ```
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+
+    pub const EMPTY: Bitboard = Bitboard(0);
+    pub const ALL: Bitboard = Bitboard(!0);
+    /// The files from the a-file to the h-file
+    pub const FILES: [Bitboard; 8] = [
+        Bitboard(0x0101_0101_0101_0101),
+        Bitboard(0x0202_0202_0202_0202),
+        Bitboard(0x0404_0404_0404_0404),
+        Bitboard(0x0808_0808_0808_0808),
+        Bitboard(0x1010_1010_1010_1010),
+        Bitboard(0x2020_2020_2020_2020),
+        Bitboard(0x4040_4040_4040_4040),
+        Bitboard(0x8080_8080_8080_8080)
+    ];
+    /// The ranks from the 1st to the 8th rank
+    pub const RANKS: [Bitboard; 8] = [
+        Bitboard(0xff00_0000_0000_0000),
+        Bitboard(0x00ff_0000_0000_0000),
+        Bitboard(0x0000_ff00_0000_0000),
+        Bitboard(0x0000_00ff_0000_0000),
+        Bitboard(0x0000_0000_ff00_0000),
+        Bitboard(0x0000_0000_00ff_0000),
+        Bitboard(0x0000_0000_0000_ff00),
+        Bitboard(0x0000_0000_0000_00ff)
+    ];
+    /// d4, e4, d5 and e5
+    pub const CENTER: Bitboard = Bitboard(0x0000_0018_1800_0000);
+    /// The 16 squares from c3 to f6
+    pub const EXTENDED_CENTER: Bitboard = Bitboard(0x0000_3c3c_3c3c_0000);
+    pub const LIGHT_SQUARES: Bitboard = Bitboard(0xaa55_aa55_aa55_aa55);
+    pub const DARK_SQUARES: Bitboard = Bitboard(0x55aa_55aa_55aa_55aa);
+    /// The squares on the outer ring of the board
+    pub const EDGES: Bitboard = Bitboard(0xff81_8181_8181_81ff);
+
+    /// Returns the file of the given column, 0 being the a-file
+    pub fn file(column: usize) -> Bitboard {
+        return Bitboard::FILES[column];
+    }
+
+    /// Returns the rank as written in algebraic notation, from 1 to 8
+    ///
+    /// # Panics
+    /// If the rank isn't between 1 and 8
+    pub fn rank(rank: usize) -> Bitboard {
+        return Bitboard::RANKS[rank - 1];
+    }
+
+    /// Returns the squares a king on the given square attacks
+    pub fn king_ring(square: Square) -> Bitboard {
+        let king = Bitboard::from(square);
+        let row = king | king.east() | king.west();
+        return (row | row.north() | row.south()) & !king;
+    }
+
+    /// Returns the squares strictly between the two squares if they are on the same rank, file or diagonal, otherwise
+    /// the empty set. These are the squares that block a check or a pin along the line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let between = Bitboard::between(Square::C1, Square::F4);
+    /// assert_eq!(between, Bitboard::from(Square::D2) | Bitboard::from(Square::E3));
+    /// assert_eq!(Bitboard::between(Square::F4, Square::C1), between);
+    /// assert!(Bitboard::between(Square::E1, Square::E2).is_empty());
+    /// assert!(Bitboard::between(Square::B1, Square::C3).is_empty());
+    /// ```
+    pub fn between(from: Square, to: Square) -> Bitboard {
+        return Bitboard(RAY_TABLES.between[from.index()][to.index()]);
+    }
+
+    /// Returns every square of the rank, file or diagonal through both squares, from edge to edge, or the empty set
+    /// if they aren't on one or are the same square. A piece pinned along the line can only move on it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// assert_eq!(Bitboard::line(Square::E2, Square::E5), Bitboard::file(4));
+    /// assert_eq!(Bitboard::line(Square::C1, Square::D2).count(), 6);
+    /// assert!(Bitboard::line(Square::C1, Square::D2).contains(Square::H6));
+    /// assert!(Bitboard::line(Square::B1, Square::C3).is_empty());
+    /// ```
+    pub fn line(from: Square, to: Square) -> Bitboard {
+        return Bitboard(RAY_TABLES.line[from.index()][to.index()]);
+    }
+
+    pub fn contains(&self, square: Square) -> bool {
+        return self.0 & (1 << square.index()) != 0;
+    }
+
+    pub fn set(&mut self, square: Square) {
+        self.0 |= 1 << square.index();
+    }
+
+    pub fn clear(&mut self, square: Square) {
+        self.0 &= !(1 << square.index());
+    }
+
+    pub fn toggle(&mut self, square: Square) {
+        self.0 ^= 1 << square.index();
+    }
+
+    /// Returns the number of squares in the set
+    pub fn count(&self) -> usize {
+        return self.0.count_ones() as usize;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.0 == 0;
+    }
+
+    /// Returns the square with the lowest index, the one closest to a8
+    pub fn first(&self) -> Option<Square> {
+        if self.is_empty() {
+            return None;
+        }
+        return Some(Square::new(self.0.trailing_zeros() as usize));
+    }
+
+    /// Removes and returns the square with the lowest index
+    pub fn pop_first(&mut self) -> Option<Square> {
+        let square = self.first()?;
+        self.0 &= self.0 - 1;
+        return Some(square);
+    }
+
+    /// Returns an iterator over the squares from the lowest index to the highest
+    pub fn iter(&self) -> BitboardIter {
+        return BitboardIter { bitboard: *self };
+    }
+
+    /// Moves every square one rank towards the 8th rank
+    pub fn north(&self) -> Bitboard {
+        return Bitboard(self.0 >> 8);
+    }
+
+    /// Moves every square one rank towards the 1st rank
+    pub fn south(&self) -> Bitboard {
+        return Bitboard(self.0 << 8);
+    }
+
+    /// Moves every square one file towards the h-file
+    pub fn east(&self) -> Bitboard {
+        return Bitboard((self.0 & !Bitboard::FILES[7].0) << 1);
+    }
+
+    /// Moves every square one file towards the a-file
+    pub fn west(&self) -> Bitboard {
+        return Bitboard((self.0 & !Bitboard::FILES[0].0) >> 1);
+    }
+
+    pub fn north_east(&self) -> Bitboard {
+        return self.north().east();
+    }
+
+    pub fn north_west(&self) -> Bitboard {
+        return self.north().west();
+    }
+
+    pub fn south_east(&self) -> Bitboard {
+        return self.south().east();
+    }
+
+    pub fn south_west(&self) -> Bitboard {
+        return self.south().west();
+    }
+
+    /// Moves every square one rank forward as seen by the given color
+    pub fn forward(&self, color: usize) -> Bitboard {
+        if color == WHITE {
+            return self.north();
+        }
+        return self.south();
+    }
+}
+
+/// Iterates over the squares of a [Bitboard], see [Bitboard::iter]
+pub struct BitboardIter {
+    bitboard: Bitboard
+}
+
+impl Iterator for BitboardIter {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        return self.bitboard.pop_first();
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        return (self.bitboard.count(), Some(self.bitboard.count()));
+    }
+}
+
+impl IntoIterator for Bitboard {
+    type Item = Square;
+    type IntoIter = BitboardIter;
+
+    fn into_iter(self) -> BitboardIter {
+        return self.iter();
+    }
+}
+
+impl FromIterator<Square> for Bitboard {
+    fn from_iter<I: IntoIterator<Item = Square>>(squares: I) -> Bitboard {
+        let mut bitboard = Bitboard::EMPTY;
+        for square in squares {
+            bitboard.set(square);
+        }
+        return bitboard;
+    }
+}
+
+impl From<u64> for Bitboard {
+    fn from(mask: u64) -> Bitboard {
+        return Bitboard(mask);
+    }
+}
+
+impl From<Bitboard> for u64 {
+    fn from(bitboard: Bitboard) -> u64 {
+        return bitboard.0;
+    }
+}
+
+impl From<Square> for Bitboard {
+    fn from(square: Square) -> Bitboard {
+        return Bitboard(1 << square.index());
+    }
+}
+
+impl std::ops::BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, other: Bitboard) -> Bitboard {
+        return Bitboard(self.0 & other.0);
+    }
+}
+
+impl std::ops::BitOr for Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, other: Bitboard) -> Bitboard {
+        return Bitboard(self.0 | other.0);
+    }
+}
+
+impl std::ops::BitXor for Bitboard {
+    type Output = Bitboard;
+
+    fn bitxor(self, other: Bitboard) -> Bitboard {
+        return Bitboard(self.0 ^ other.0);
+    }
+}
+
+impl std::ops::Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Bitboard {
+        return Bitboard(!self.0);
+    }
+}
+
+impl std::ops::BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, other: Bitboard) {
+        self.0 &= other.0;
+    }
+}
+
+impl std::ops::BitOrAssign for Bitboard {
+    fn bitor_assign(&mut self, other: Bitboard) {
+        self.0 |= other.0;
+    }
+}
+
+impl std::ops::BitXorAssign for Bitboard {
+    fn bitxor_assign(&mut self, other: Bitboard) {
+        self.0 ^= other.0;
+    }
+}
+
+impl std::fmt::Debug for Bitboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Bitboard({:#018x})", self.0)
+    }
+}
+
+impl std::fmt::Display for Bitboard {
+    /// Draws the board with 'x' for the squares in the set and '.' for the others, the 8th rank first
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let text = Bitboard::king_ring(Square::from_algebraic("a8").unwrap()).to_string();
+    /// assert!(text.starts_with(". x . . . . . .\nx x . . . . . .\n. . . "));
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for row in 0..8 {
+            let squares: Vec<&str> = (0..8)
+                .map(|column| if self.contains(Square::from_column_row(column, row)) { "x" } else { "." })
+                .collect();
+            writeln!(f, "{}", squares.join(" "))?;
+        }
+        return Ok(());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{Game, MoveGenerator, EMPTY};
+
+/// The side of the board a king castles towards
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Wing {
+    /// Towards the h-file, O-O
+    KingSide,
+    /// Towards the a-file, O-O-O
+    QueenSide
+}
+
+/// Whether a color can castle towards a wing, and if not why, see [Game::can_castle]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CastleStatus {
+    /// The castling right has been lost since the king or the rook has moved or the rook was captured
+    NotAllowed,
+    /// A piece stands between the king and the rook or on their destinations
+    Blocked,
+    /// The king is in check
+    InCheck,
+    /// The king would pass through or land on an attacked square
+    ThroughCheck,
+    /// Castling is a legal move when it is the color's turn
+    Available
+}
+
+impl std::fmt::Display for CastleStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let description = match self {
+            CastleStatus::NotAllowed => "the king or rook has moved",
+            CastleStatus::Blocked => "there are pieces in the way",
+            CastleStatus::InCheck => "the king is in check",
+            CastleStatus::ThroughCheck => "the king would pass through or land on an attacked square",
+            CastleStatus::Available => "castling is possible"
+        };
+        write!(f, "{}", description)
+    }
+}
+
+impl Game {
+
+    /// Returns whether the color can castle towards the wing right now, or the first reason it can't in the order
+    /// of [CastleStatus], so a user can be told why a castling move isn't available. The position is judged as if
+    /// it were the color's turn, Chess960 castling included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::new("r3k2r/8/8/8/8/4n3/8/R1B1K2R w KQq - 0 1");
+    /// assert_eq!(game.can_castle(WHITE, Wing::KingSide), CastleStatus::ThroughCheck);
+    /// assert_eq!(game.can_castle(WHITE, Wing::QueenSide), CastleStatus::Blocked);
+    /// assert_eq!(game.can_castle(BLACK, Wing::KingSide), CastleStatus::NotAllowed);
+    /// assert_eq!(game.can_castle(BLACK, Wing::QueenSide), CastleStatus::Available);
+    ///
+    /// let game = Game::new("r3k2r/8/8/8/8/8/4q3/R3K2R w KQkq - 0 1");
+    /// assert_eq!(game.can_castle(WHITE, Wing::KingSide), CastleStatus::InCheck);
+    /// ```
+    pub fn can_castle(&self, color: usize, wing: Wing) -> CastleStatus {
+        return self.castle_status(&MoveGenerator::new(), color, wing);
+    }
+
+    /// Same as [Game::can_castle] with the attacks found by the given move generator
+    pub(crate) fn castle_status(&self, move_gen: &MoveGenerator, color: usize, wing: Wing) -> CastleStatus {
+        let king_side = wing == Wing::KingSide;
+        let rook_square = match self.castling_rook_square(color, king_side) {
+            Some(rook_square) => rook_square,
+            None => return CastleStatus::NotAllowed
+        };
+        let king_square = self.king_square[color];
+        let back_rank = king_square - self.get_column(king_square);
+        let king_destination;
+        let rook_destination;
+        if king_side {
+            king_destination = back_rank + 6;
+            rook_destination = back_rank + 5;
+        }
+        else {
+            king_destination = back_rank + 2;
+            rook_destination = back_rank + 3;
+        }
+
+        let lowest = king_square.min(king_destination).min(rook_square).min(rook_destination);
+        let highest = king_square.max(king_destination).max(rook_square).max(rook_destination);
+        let path_empty = (lowest..=highest).all(|square|
+            square == king_square || square == rook_square || self.board[square].get_type() == EMPTY);
+        if !path_empty {
+            return CastleStatus::Blocked;
+        }
+
+        if move_gen.is_attacked(self, king_square, color) {
+            return CastleStatus::InCheck;
+        }
+        // The rook is treated as gone since it can shield the king's path along the back rank in Chess960
+        let king_path = king_square.min(king_destination)..=king_square.max(king_destination);
+        let path_safe = king_path.into_iter()
+            .all(|square| !move_gen.is_attacked_through(self, square, color, rook_square));
+        if !path_safe {
+            return CastleStatus::ThroughCheck;
+        }
+        return CastleStatus::Available;
+    }
+}
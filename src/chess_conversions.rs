@@ -0,0 +1,82 @@
+//! Conversions to and from the types of the [chess](https://docs.rs/chess) crate, which only plays standard chess
+
+use std::str::FromStr;
+
+use chess::{Board, ChessMove, File, Piece, Rank};
+
+use crate::fen::to_fen;
+use crate::notation::parse_uci;
+use crate::{Game, Move, Square, BISHOP, KING, KNIGHT, QUEEN, ROOK};
+
+impl From<Square> for chess::Square {
+    fn from(square: Square) -> chess::Square {
+        return chess::Square::make_square(Rank::from_index(square.rank() - 1), File::from_index(square.column()));
+    }
+}
+
+impl From<chess::Square> for Square {
+    fn from(square: chess::Square) -> Square {
+        return Square::from_a1_indexing(square.to_index());
+    }
+}
+
+/// Converts the position, which fails for Chess960 castling rights and positions the chess crate rejects
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// use chess::{Board, MoveGen};
+/// let game = Game::new("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+/// let board = Board::try_from(&game).unwrap();
+/// assert_eq!(MoveGen::new_legal(&board).len(), game.count_legal_moves());
+/// assert_eq!(Game::from(&board).to_string(), game.to_string());
+/// ```
+impl TryFrom<&Game> for Board {
+    type Error = chess::Error;
+
+    fn try_from(game: &Game) -> Result<Board, chess::Error> {
+        return Board::from_str(&to_fen(game));
+    }
+}
+
+/// Converts the board. The chess crate doesn't keep the halfmove clock and move number, so the game starts at
+/// move 1 with the clock at 0.
+impl From<&Board> for Game {
+    fn from(board: &Board) -> Game {
+        return Game::new(&board.to_string());
+    }
+}
+
+/// Converts the move, castling is written as the king moving to its destination as in the chess crate
+impl From<Move> for ChessMove {
+    fn from(mv: Move) -> ChessMove {
+        let promotion = match mv.get_promotion_type() {
+            KNIGHT => Some(Piece::Knight),
+            BISHOP => Some(Piece::Bishop),
+            ROOK => Some(Piece::Rook),
+            QUEEN => Some(Piece::Queen),
+            KING => Some(Piece::King),
+            _ => None
+        };
+        return ChessMove::new(mv.from_square().into(), mv.to_square().into(), promotion);
+    }
+}
+
+impl Game {
+
+    /// Returns the legal move of the game matching a move of the chess crate, or None if there is no such move
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::new("r3k3/1P6/8/8/8/8/8/4K2R w K - 0 1");
+    /// for mv in game.get_all_legal_moves() {
+    ///     assert_eq!(game.from_chess_move(chess::ChessMove::from(mv)), Some(mv));
+    /// }
+    /// ```
+    pub fn from_chess_move(&self, mv: ChessMove) -> Option<Move> {
+        return parse_uci(self, &mv.to_string()).ok();
+    }
+}
@@ -0,0 +1,333 @@
+//! Chess clocks with simulated time
+//!
+//! A [Clock] doesn't read the system time, it is charged the time each move took. That makes games with time
+//! controls reproducible when the players report simulated times, as in a [crate::matchplay::TimedMatch].
+//!
+//! A [TimeControl] is made of [Period]s, each with a Fischer increment added after every move and a delay of the
+//! [kind](DelayKind) the time control uses. Time controls are read from the TimeControl tag of PGN, and
+//! [move_times] works out the time each move of a game took from the clock times in its comments.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::clock::{Clock, TimeControl};
+//! # use std::time::Duration;
+//! let mut clock = Clock::new(TimeControl::new(Duration::from_secs(60), Duration::from_secs(1)));
+//! assert!(clock.record_move(WHITE, Duration::from_secs(5)));
+//! assert_eq!(clock.remaining(WHITE), Duration::from_secs(56));
+//!
+//! let limits = clock.limits(BLACK);
+//! assert_eq!(limits.time, [Some(Duration::from_secs(56)), Some(Duration::from_secs(60))]);
+//!
+//! assert!(!clock.record_move(BLACK, Duration::from_secs(61)));
+//! assert_eq!(clock.flagged(), Some(BLACK));
+//! assert_eq!(clock.remaining(BLACK), Duration::ZERO);
+//! ```
+
+use std::time::Duration;
+
+use crate::pgn::PgnGame;
+use crate::uci::SearchLimits;
+
+/// How the delay of a [Period] is given
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DelayKind {
+    /// The clock only starts running when the delay has passed, also called US delay
+    #[default]
+    Simple,
+    /// The clock runs from the start of the move, and the time used is given back after the move up to the delay
+    Bronstein
+}
+
+/// A stage of a [TimeControl]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Period {
+    /// The time added to the clock when the period starts
+    pub time: Duration,
+    /// The Fischer increment, added after every move of the period
+    pub increment: Duration,
+    /// The delay of every move of the period
+    pub delay: Duration,
+    /// The number of moves in the period, None for a period lasting the rest of the game
+    pub moves: Option<usize>
+}
+
+impl Period {
+
+    /// Creates a period lasting the rest of the game
+    pub fn sudden_death(time: Duration, increment: Duration) -> Period {
+        Period {
+            time,
+            increment,
+            delay: Duration::ZERO,
+            moves: None
+        }
+    }
+}
+
+/// The time each player gets
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeControl {
+    /// The periods in the order they are played. When the last period has a number of moves it is repeated.
+    pub periods: Vec<Period>,
+    pub delay_kind: DelayKind
+}
+
+impl TimeControl {
+
+    /// Creates a time control with a single period
+    pub fn new(initial: Duration, increment: Duration) -> TimeControl {
+        TimeControl {
+            periods: vec![Period::sudden_death(initial, increment)],
+            delay_kind: DelayKind::Simple
+        }
+    }
+
+    /// Reads a time control as given by the TimeControl tag of PGN: periods separated by ':', each made of the
+    /// number of moves and a '/' unless it is the last, the seconds and '+' and the seconds of the increment, as in
+    /// '40/5400+30:1800+30'. Tournament notation such as '40/90+30, SD/30+30' is read as well, with the periods
+    /// separated by commas and the last marked 'SD/' or 'G/', where the time of a period is given in minutes.
+    /// A delay in seconds is written after 'd', as in '300d5', and is taken to be a [DelayKind::Simple] delay.
+    ///
+    /// Returns None for '?' and '-', which mean that the time control is unknown or that there is none, for
+    /// sandclock time controls and anything that can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::clock::{Period, TimeControl};
+    /// # use std::time::Duration;
+    /// let classical = TimeControl::parse("40/90+30, SD/30+30").unwrap();
+    /// assert_eq!(classical, TimeControl::parse("40/5400+30:1800+30").unwrap());
+    /// assert_eq!(classical.periods[0].moves, Some(40));
+    /// assert_eq!(classical.periods[1], Period::sudden_death(Duration::from_secs(1800), Duration::from_secs(30)));
+    /// assert_eq!(classical.to_string(), "40/5400+30:1800+30");
+    ///
+    /// assert_eq!(TimeControl::parse("G/60;d5").unwrap().periods[0].delay, Duration::from_secs(5));
+    /// assert_eq!(TimeControl::parse("?"), None);
+    /// ```
+    pub fn parse(tag: &str) -> Option<TimeControl> {
+        let tag = tag.trim();
+        let tournament = tag.contains(',') || tag.contains("SD/") || tag.contains("G/");
+        let separator = if tournament { ',' } else { ':' };
+        let periods = tag.split(separator).map(|period| parse_period(period.trim(), tournament))
+            .collect::<Option<Vec<Period>>>()?;
+        if periods.is_empty() || periods[..periods.len() - 1].iter().any(|period| period.moves.is_none()) {
+            return None;
+        }
+        return Some(TimeControl { periods, delay_kind: DelayKind::Simple });
+    }
+}
+
+/// Reads a period of a time control, see [TimeControl::parse]
+fn parse_period(period: &str, tournament: bool) -> Option<Period> {
+    let seconds = |text: &str| text.trim().parse::<u64>().ok().map(Duration::from_secs);
+    let (moves, rest) = match period.split_once('/') {
+        Some(("SD", rest)) | Some(("G", rest)) if tournament => (None, rest),
+        Some((moves, rest)) => (Some(moves.trim().parse::<usize>().ok().filter(|&moves| moves > 0)?), rest),
+        None => (None, period)
+    };
+    let (rest, delay) = match rest.split_once('d') {
+        Some((rest, delay)) => (rest.trim_end_matches(';'), seconds(delay)?),
+        None => (rest, Duration::ZERO)
+    };
+    let (time, increment) = match rest.split_once('+') {
+        Some((time, increment)) => (seconds(time)?, seconds(increment)?),
+        None => (seconds(rest)?, Duration::ZERO)
+    };
+    let time = if tournament { time * 60 } else { time };
+    return Some(Period { time, increment, delay, moves });
+}
+
+impl std::fmt::Display for TimeControl {
+    /// Writes the time control as in the TimeControl tag of PGN, '300+2' or '40/5400:1800+30', with the seconds
+    /// rounded down. PGN has no notation for delays, they are written after 'd' as read by [TimeControl::parse].
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, period) in self.periods.iter().enumerate() {
+            if i > 0 {
+                write!(f, ":")?;
+            }
+            if let Some(moves) = period.moves {
+                write!(f, "{}/", moves)?;
+            }
+            write!(f, "{}", period.time.as_secs())?;
+            if period.moves.is_none() || !period.increment.is_zero() {
+                write!(f, "+{}", period.increment.as_secs())?;
+            }
+            if !period.delay.is_zero() {
+                write!(f, "d{}", period.delay.as_secs())?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+/// The clocks of both players, see the [module documentation](self)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clock {
+    control: TimeControl,
+    remaining: [Duration; 2],
+    /// The period each color is in and the moves it has made in it
+    period: [usize; 2],
+    moves_in_period: [usize; 2],
+    flagged: Option<usize>
+}
+
+impl Clock {
+
+    /// Creates the clocks of a time control
+    ///
+    /// # Panics
+    /// If the time control has no periods
+    pub fn new(control: TimeControl) -> Clock {
+        let initial = control.periods.first().expect("a time control needs a period").time;
+        Clock {
+            control,
+            remaining: [initial; 2],
+            period: [0; 2],
+            moves_in_period: [0; 2],
+            flagged: None
+        }
+    }
+
+    pub fn time_control(&self) -> &TimeControl {
+        return &self.control;
+    }
+
+    /// Returns the time the color has left
+    pub fn remaining(&self, color: usize) -> Duration {
+        return self.remaining[color];
+    }
+
+    /// Returns the color whose time has run out, if any
+    pub fn flagged(&self) -> Option<usize> {
+        return self.flagged;
+    }
+
+    /// Returns the period the color is in
+    pub fn period(&self, color: usize) -> &Period {
+        return &self.control.periods[self.period[color]];
+    }
+
+    /// Charges the time a move took to the color that made it, less the delay. Returns false if the time ran out,
+    /// otherwise the increment is added, and the time of the next period when the move completes a period.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::clock::{Clock, DelayKind, Period, TimeControl};
+    /// # use std::time::Duration;
+    /// let period = Period { time: Duration::from_secs(10), increment: Duration::ZERO, delay: Duration::ZERO,
+    ///                       moves: Some(2) };
+    /// let mut clock = Clock::new(TimeControl { periods: vec![period], delay_kind: DelayKind::Simple });
+    /// clock.record_move(WHITE, Duration::from_secs(4));
+    /// assert_eq!(clock.limits(WHITE).moves_to_go, Some(1));
+    /// clock.record_move(WHITE, Duration::from_secs(4));
+    /// assert_eq!(clock.remaining(WHITE), Duration::from_secs(12));
+    /// assert_eq!(clock.limits(WHITE).moves_to_go, Some(2));
+    ///
+    /// // A simple delay keeps the clock from running, a Bronstein delay gives the time back after the move
+    /// let delayed = Period { delay: Duration::from_secs(5), moves: None, ..period };
+    /// for delay_kind in [DelayKind::Simple, DelayKind::Bronstein] {
+    ///     let mut clock = Clock::new(TimeControl { periods: vec![delayed], delay_kind });
+    ///     clock.record_move(WHITE, Duration::from_secs(3));
+    ///     clock.record_move(BLACK, Duration::from_secs(8));
+    ///     assert_eq!(clock.remaining(WHITE), Duration::from_secs(10));
+    ///     assert_eq!(clock.remaining(BLACK), Duration::from_secs(7));
+    ///     assert_eq!(clock.record_move(WHITE, Duration::from_secs(12)), delay_kind == DelayKind::Simple);
+    /// }
+    /// ```
+    pub fn record_move(&mut self, color: usize, used: Duration) -> bool {
+        let period = *self.period(color);
+        let (charged, limit) = match self.control.delay_kind {
+            DelayKind::Simple => (used.saturating_sub(period.delay), used.saturating_sub(period.delay)),
+            DelayKind::Bronstein => (used - used.min(period.delay), used)
+        };
+        if limit > self.remaining[color] {
+            self.remaining[color] = Duration::ZERO;
+            self.flagged = Some(color);
+            return false;
+        }
+        self.remaining[color] -= charged;
+        self.remaining[color] += period.increment;
+        self.moves_in_period[color] += 1;
+        if period.moves == Some(self.moves_in_period[color]) {
+            self.period[color] = (self.period[color] + 1).min(self.control.periods.len() - 1);
+            self.moves_in_period[color] = 0;
+            self.remaining[color] += self.period(color).time;
+        }
+        return true;
+    }
+
+    /// Returns the limits of a search by the given color as sent with 'go' in UCI
+    pub fn limits(&self, color: usize) -> SearchLimits {
+        return SearchLimits {
+            time: [Some(self.remaining[0]), Some(self.remaining[1])],
+            increment: [Some(self.period(0).increment), Some(self.period(1).increment)],
+            moves_to_go: self.period(color).moves.map(|moves| moves - self.moves_in_period[color]),
+            ..Default::default()
+        };
+    }
+}
+
+/// Returns the time each move of the game took, worked out from the time control of its TimeControl tag and the
+/// clock times after the moves, given in '[%clk]' commands of their comments. Recording the times with
+/// [Clock::record_move] rebuilds the clocks of the game. A move that took less than the delay counts as taking no
+/// time, since the clock doesn't tell how long it took.
+///
+/// Returns None when the game has no time control that can be read or a move has no clock time.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::clock::{move_times, Clock, TimeControl};
+/// # use olindba_chess::pgn::parse_game;
+/// # use std::time::Duration;
+/// let game = parse_game("[TimeControl \"180+2\"]\n\n\
+///     1. e4 {[%clk 0:03:01]} e5 {[%clk 0:02:55]} 2. Nf3 {[%clk 0:02:50.5]} *").unwrap();
+/// let times = move_times(&game).unwrap();
+/// assert_eq!(times, [Duration::from_secs(1), Duration::from_secs(7), Duration::from_millis(12500)]);
+///
+/// let mut clock = Clock::new(TimeControl::parse(game.header("TimeControl").unwrap()).unwrap());
+/// for (ply, used) in times.into_iter().enumerate() {
+///     clock.record_move(ply % 2, used);
+/// }
+/// assert_eq!(clock.remaining(WHITE), Duration::from_millis(170500));
+/// ```
+pub fn move_times(game: &PgnGame) -> Option<Vec<Duration>> {
+    let mut clock = Clock::new(TimeControl::parse(game.header("TimeControl")?)?);
+    let mut times = vec![];
+    for ply in 0..game.moves.len() {
+        let color = game.start.turn ^ (ply % 2);
+        let comment = game.annotations.get(&ply)?.comment.as_deref()?;
+        let after = clock_time(comment)?;
+        let period = *clock.period(color);
+        // The clock after the move if it took no time beyond the delay
+        let mut unused = clock.remaining[color] + period.increment;
+        if period.moves == Some(clock.moves_in_period[color] + 1) {
+            unused += clock.control.periods[(clock.period[color] + 1).min(clock.control.periods.len() - 1)].time;
+        }
+        let charged = unused.saturating_sub(after);
+        let used = if charged.is_zero() { Duration::ZERO } else { charged + period.delay };
+        clock.record_move(color, used);
+        times.push(used);
+    }
+    return Some(times);
+}
+
+/// Reads the time of a '[%clk h:mm:ss]' command in a comment, where the seconds may have a fraction
+fn clock_time(comment: &str) -> Option<Duration> {
+    let start = comment.find("[%clk")? + "[%clk".len();
+    let length = comment[start..].find(']')?;
+    let mut seconds = 0.0;
+    for field in comment[start..start + length].trim().split(':') {
+        seconds = seconds * 60.0 + field.parse::<f64>().ok()?;
+    }
+    if !seconds.is_finite() || seconds < 0.0 {
+        return None;
+    }
+    return Some(Duration::from_secs_f64(seconds));
+}
@@ -0,0 +1,164 @@
+use crate::{Game, Square, SquareColor, BISHOP, BLACK, EMPTY, KING, KNIGHT, PAWN, WHITE};
+
+impl Game {
+
+    /// Returns true if neither side can checkmate by any sequence of legal moves, which ends the game as a draw
+    /// under FIDE Article 5.2.2. This is stricter than [crate::GameState::InsufficientMaterial], which also
+    /// covers positions such as two knights against a bare king where a checkmate is possible but can't be forced.
+    ///
+    /// Two kinds of positions are recognized:
+    /// * a king with at most one minor piece against a king, or kings with only bishops on squares of the same color
+    /// * kings and pawns where every pawn is blocked by another pawn, no pawn can capture and neither king can reach
+    ///   an enemy pawn that isn't defended by a pawn
+    ///
+    /// Other dead positions exist, so false only means that the position wasn't recognized as dead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// assert!(Game::new("8/8/4k3/8/8/2B5/8/4K3 w - - 0 1").is_dead_position());
+    /// assert!(Game::new("8/4b3/4k3/8/8/2B5/8/4K3 w - - 0 1").is_dead_position());
+    /// // Bishops on squares of different colors or two knights can still mate with the help of the opponent
+    /// assert!(!Game::new("8/3b4/4k3/8/8/2B5/8/4K3 w - - 0 1").is_dead_position());
+    /// assert!(!Game::new("8/8/4k3/8/8/2NN4/8/4K3 w - - 0 1").is_dead_position());
+    ///
+    /// // A locked pawn chain the kings can't get through
+    /// let locked = Game::new("8/4k3/8/p1p1p1p1/P1P1P1P1/8/3K4/8 w - - 0 1");
+    /// assert!(locked.is_dead_position());
+    /// assert_eq!(locked.get_game_state(), GameState::InProgress);
+    /// // With a gap in the chain the white king can reach the black pawns
+    /// assert!(!Game::new("8/4k3/8/p1p1p3/P1P1P3/8/3K4/8 w - - 0 1").is_dead_position());
+    /// // Pawns that can still move or capture
+    /// assert!(!Game::new("8/4k3/8/p1p1p3/P1P1P1P1/8/3K4/8 w - - 0 1").is_dead_position());
+    /// assert!(!Game::new("8/4k3/8/p1p1pp2/P1P1P1P1/8/3K4/8 w - - 0 1").is_dead_position());
+    /// ```
+    pub fn is_dead_position(&self) -> bool {
+        let mut has_pawns = false;
+        let mut minor_pieces = 0;
+        let mut bishop_square_colors = [false; 2];
+        let mut only_bishops = true;
+        for square in 0..64 {
+            match self.board[square].get_type() {
+                EMPTY | KING => {},
+                PAWN => has_pawns = true,
+                KNIGHT => {
+                    minor_pieces += 1;
+                    only_bishops = false;
+                },
+                BISHOP => {
+                    minor_pieces += 1;
+                    bishop_square_colors[(Square::new(square).color() == SquareColor::Dark) as usize] = true;
+                },
+                _ => return false
+            }
+        }
+
+        if !has_pawns {
+            return minor_pieces <= 1 || (only_bishops && !(bishop_square_colors[0] && bishop_square_colors[1]));
+        }
+        if minor_pieces > 0 || self.en_passant_square().is_some() {
+            return false;
+        }
+        return self.is_locked_pawn_position();
+    }
+
+    /// Checks a position with only kings and pawns for a permanent pawn lock, see [Game::is_dead_position]
+    fn is_locked_pawn_position(&self) -> bool {
+        for square in 0..64 {
+            let pawn = self.board[square];
+            if pawn.get_type() != PAWN {
+                continue;
+            }
+            let pawn_square = Square::new(square);
+            let front_square;
+            if pawn.get_color() == WHITE {
+                front_square = square.checked_sub(8);
+            }
+            else {
+                front_square = Some(square + 8).filter(|&front_square| front_square < 64);
+            }
+            if front_square.is_none_or(|front_square| self.board[front_square].get_type() != PAWN) {
+                return false;
+            }
+            for capture in self.pawn_attacks(pawn_square) {
+                let target = self.board[capture.index()];
+                if target.get_type() == PAWN && target.get_color() != pawn.get_color() {
+                    return false;
+                }
+            }
+        }
+
+        // Since the pawns never move, the squares a pawn attacks stay attacked and a pawn defended by another pawn
+        // can never be captured. Each king is walked through every square it can ever reach.
+        for color in [WHITE, BLACK] {
+            let mut attacked_by_enemy: u64 = 0;
+            for square in 0..64 {
+                let piece = self.board[square];
+                if piece.get_type() == PAWN && piece.get_color() != color {
+                    for attacked in self.pawn_attacks(Square::new(square)) {
+                        attacked_by_enemy |= 1 << attacked.index();
+                    }
+                }
+            }
+
+            let mut reached: u64 = 1 << self.king_square(color).index();
+            let mut stack = vec![self.king_square(color)];
+            while let Some(square) = stack.pop() {
+                for neighbour in king_neighbours(square) {
+                    let bit = 1 << neighbour.index();
+                    if reached & bit != 0 || attacked_by_enemy & bit != 0 {
+                        continue;
+                    }
+                    let piece = self.board[neighbour.index()];
+                    if piece.get_type() == PAWN {
+                        if piece.get_color() != color {
+                            return false;
+                        }
+                        continue;
+                    }
+                    reached |= bit;
+                    stack.push(neighbour);
+                }
+            }
+        }
+        return true;
+    }
+
+    /// Returns the squares the pawn on the given square attacks
+    fn pawn_attacks(&self, square: Square) -> Vec<Square> {
+        let row;
+        if self.board[square.index()].get_color() == WHITE {
+            if square.row() == 0 {
+                return vec![];
+            }
+            row = square.row() - 1;
+        }
+        else {
+            if square.row() == 7 {
+                return vec![];
+            }
+            row = square.row() + 1;
+        }
+        let mut attacks = vec![];
+        if square.column() > 0 {
+            attacks.push(Square::from_column_row(square.column() - 1, row));
+        }
+        if square.column() < 7 {
+            attacks.push(Square::from_column_row(square.column() + 1, row));
+        }
+        return attacks;
+    }
+}
+
+fn king_neighbours(square: Square) -> Vec<Square> {
+    let mut neighbours = vec![];
+    for row in square.row().saturating_sub(1)..=(square.row() + 1).min(7) {
+        for column in square.column().saturating_sub(1)..=(square.column() + 1).min(7) {
+            if row != square.row() || column != square.column() {
+                neighbours.push(Square::from_column_row(column, row));
+            }
+        }
+    }
+    return neighbours;
+}
@@ -0,0 +1,101 @@
+use crate::{Game, Piece, Square, EMPTY, PAWN};
+
+/// A change to the board between two positions
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BoardDelta {
+    /// A piece is removed from the board, for example a captured piece
+    Removed { square: Square, piece: Piece },
+    /// A piece moves from one square to another. For a promotion the piece arriving,
+    /// given here, differs from the pawn leaving.
+    Moved { from: Square, to: Square, piece: Piece },
+    /// A piece appears on the board without moving there from another square
+    Added { square: Square, piece: Piece }
+}
+
+fn same_piece(a: &Piece, b: &Piece) -> bool {
+    if a.get_type() == EMPTY || b.get_type() == EMPTY {
+        return a.get_type() == b.get_type();
+    }
+    return a.get_type() == b.get_type() && a.get_color() == b.get_color();
+}
+
+fn distance(a: usize, b: usize) -> usize {
+    let column_distance = (a % 8).abs_diff(b % 8);
+    let row_distance = (a / 8).abs_diff(b / 8);
+    return column_distance.max(row_distance);
+}
+
+impl Game {
+
+    /// Returns the changes that turn the board of this game into the board of the other game,
+    /// removals first, then relocations and then additions.
+    ///
+    /// Pieces leaving a square are matched with pieces of the same type and color arriving at another square,
+    /// the closest one first, so castling gives two relocations and an en passant capture gives a relocation
+    /// and a removal. A pawn leaving a square that can't be matched is matched with a promoted piece of its color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let before = Game::new("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+    /// let mut after = before.clone();
+    /// after.make_move_from_to(28, 19, None);
+    /// let deltas = before.diff(&after);
+    /// assert_eq!(deltas.len(), 2);
+    /// assert!(matches!(deltas[0], BoardDelta::Removed { square, .. } if square == Square::new(27)));
+    /// assert!(matches!(deltas[1], BoardDelta::Moved { from, to, .. } if from == Square::new(28) && to == Square::new(19)));
+    /// ```
+    pub fn diff(&self, other: &Game) -> Vec<BoardDelta> {
+        let mut vacated = vec![];
+        let mut filled = vec![];
+        for square in 0..64 {
+            if same_piece(&self.board[square], &other.board[square]) {
+                continue;
+            }
+            if self.board[square].get_type() != EMPTY {
+                vacated.push(square);
+            }
+            if other.board[square].get_type() != EMPTY {
+                filled.push(square);
+            }
+        }
+
+        let mut moved = vec![];
+        let mut added = vec![];
+        let mut unmatched = vec![];
+        for &to in filled.iter() {
+            let closest = vacated.iter().enumerate()
+                .filter(|(_, &from)| same_piece(&self.board[from], &other.board[to]))
+                .min_by_key(|(_, &from)| distance(from, to))
+                .map(|(i, _)| i);
+            match closest {
+                Some(i) => moved.push((vacated.remove(i), to)),
+                None => unmatched.push(to)
+            }
+        }
+        for to in unmatched {
+            let closest = vacated.iter().enumerate()
+                .filter(|(_, &from)| self.board[from].get_type() == PAWN &&
+                    self.board[from].get_color() == other.board[to].get_color())
+                .min_by_key(|(_, &from)| distance(from, to))
+                .map(|(i, _)| i);
+            match closest {
+                Some(i) => moved.push((vacated.remove(i), to)),
+                None => added.push(to)
+            }
+        }
+
+        let mut deltas = vec![];
+        for square in vacated {
+            deltas.push(BoardDelta::Removed { square: Square::new(square), piece: self.board[square] });
+        }
+        for (from, to) in moved {
+            deltas.push(BoardDelta::Moved { from: Square::new(from), to: Square::new(to), piece: other.board[to] });
+        }
+        for square in added {
+            deltas.push(BoardDelta::Added { square: Square::new(square), piece: other.board[square] });
+        }
+        return deltas;
+    }
+}
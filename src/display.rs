@@ -0,0 +1,204 @@
+//! Drawing the board as text for terminals
+//!
+//! A [BoardFormatter] draws the board with letters or Unicode chess symbols, optionally with coordinates, with the
+//! squares shaded by ANSI escape codes and with some squares highlighted, for example the last move or the
+//! squares a selected piece can move to. Every square is three characters wide.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::display::{BoardFormatter, PieceStyle};
+//! let game = Game::new("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+//! let formatter = BoardFormatter {
+//!     coordinates: true,
+//!     highlights: vec![Square::new(52)],
+//!     ..Default::default()
+//! };
+//! let board = formatter.format(&game);
+//! let lines: Vec<&str> = board.lines().collect();
+//! assert_eq!(lines[0], "8  .  .  .  .  k  .  .  . ");
+//! assert_eq!(lines[6], "2  .  .  .  . [P] .  .  . ");
+//! assert_eq!(lines[8], "   a  b  c  d  e  f  g  h ");
+//!
+//! let formatter = BoardFormatter {
+//!     pieces: PieceStyle::Unicode,
+//!     perspective: BLACK,
+//!     ..Default::default()
+//! };
+//! assert_eq!(formatter.format(&game).lines().next(), Some(" ·  ·  ·  ♔  ·  ·  ·  · "));
+//! assert!(formatter.format(&game).ends_with(" ·  ·  ·  ♚  ·  ·  ·  · \n"));
+//!
+//! let formatter = BoardFormatter {
+//!     colored_squares: true,
+//!     ..Default::default()
+//! };
+//! assert!(formatter.format(&game).starts_with("\x1b[48;5;180m   \x1b[48;5;137m   "));
+//! println!("{}", formatter.display(&game));
+//! ```
+
+use crate::notation::piece_letter;
+use crate::{Game, Piece, Square, SquareColor, BISHOP, EMPTY, KING, KNIGHT, PAWN, QUEEN, ROOK, WHITE};
+
+/// How pieces are drawn
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PieceStyle {
+    /// The letters of FEN, uppercase for White and lowercase for Black, with '.' for an empty square
+    #[default]
+    Letters,
+    /// The Unicode chess symbols, with '·' for an empty square
+    Unicode
+}
+
+/// Settings for drawing the board, see the [module documentation](self)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoardFormatter {
+    pub pieces: PieceStyle,
+    /// Shades the light and dark squares and the highlighted squares with ANSI background colors
+    pub colored_squares: bool,
+    /// Writes the ranks to the left of the board and the files below it
+    pub coordinates: bool,
+    /// Squares drawn in another color, or between brackets when the squares aren't colored
+    pub highlights: Vec<Square>,
+    /// The side whose perspective the board is drawn from, with its pieces at the bottom
+    pub perspective: usize
+}
+
+impl Default for BoardFormatter {
+    fn default() -> BoardFormatter {
+        BoardFormatter {
+            pieces: PieceStyle::Letters,
+            colored_squares: false,
+            coordinates: false,
+            highlights: vec![],
+            perspective: WHITE
+        }
+    }
+}
+
+const LIGHT_SQUARE: &str = "\x1b[48;5;180m";
+const DARK_SQUARE: &str = "\x1b[48;5;137m";
+const LIGHT_HIGHLIGHT: &str = "\x1b[48;5;186m";
+const DARK_HIGHLIGHT: &str = "\x1b[48;5;143m";
+const WHITE_PIECE: &str = "\x1b[97m";
+const BLACK_PIECE: &str = "\x1b[30m";
+const RESET: &str = "\x1b[0m";
+
+fn unicode_symbol(piece: &Piece) -> char {
+    let symbols;
+    if piece.get_color() == WHITE {
+        symbols = ['♙', '♘', '♗', '♖', '♕', '♔'];
+    }
+    else {
+        symbols = ['♟', '♞', '♝', '♜', '♛', '♚'];
+    }
+    return match piece.get_type() {
+        PAWN => symbols[0],
+        KNIGHT => symbols[1],
+        BISHOP => symbols[2],
+        ROOK => symbols[3],
+        QUEEN => symbols[4],
+        KING => symbols[5],
+        _ => '·'
+    };
+}
+
+impl BoardFormatter {
+
+    /// Returns a value that draws the board of the game when formatted with '{}'
+    pub fn display<'a>(&'a self, game: &'a Game) -> BoardDisplay<'a> {
+        return BoardDisplay {
+            formatter: self,
+            game
+        };
+    }
+
+    /// Draws the board of the game, one line per rank
+    pub fn format(&self, game: &Game) -> String {
+        return self.display(game).to_string();
+    }
+
+    fn symbol(&self, piece: &Piece) -> char {
+        if self.pieces == PieceStyle::Unicode {
+            return unicode_symbol(piece);
+        }
+        if piece.get_type() == EMPTY {
+            return '.';
+        }
+        let letter = piece_letter(piece.get_type()).unwrap_or('P');
+        if piece.get_color() == WHITE {
+            return letter;
+        }
+        return letter.to_ascii_lowercase();
+    }
+
+    /// Returns the square drawn at the given column and row, counted from the top left corner
+    fn square_at(&self, column: usize, row: usize) -> Square {
+        if self.perspective == WHITE {
+            return Square::from_column_row(column, row);
+        }
+        return Square::from_column_row(7 - column, 7 - row);
+    }
+}
+
+/// The board drawn by a [BoardFormatter], returned by [BoardFormatter::display]
+pub struct BoardDisplay<'a> {
+    formatter: &'a BoardFormatter,
+    game: &'a Game
+}
+
+impl std::fmt::Display for BoardDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let formatter = self.formatter;
+        for row in 0..8 {
+            if formatter.coordinates {
+                write!(f, "{} ", formatter.square_at(0, row).rank())?;
+            }
+            for column in 0..8 {
+                let square = formatter.square_at(column, row);
+                let piece = self.game.board[square.index()];
+                let highlighted = formatter.highlights.contains(&square);
+                let symbol = formatter.symbol(&piece);
+
+                if !formatter.colored_squares {
+                    if highlighted {
+                        write!(f, "[{}]", symbol)?;
+                    }
+                    else {
+                        write!(f, " {} ", symbol)?;
+                    }
+                    continue;
+                }
+
+                let light = square.color() == SquareColor::Light;
+                let background = match (light, highlighted) {
+                    (true, false) => LIGHT_SQUARE,
+                    (false, false) => DARK_SQUARE,
+                    (true, true) => LIGHT_HIGHLIGHT,
+                    (false, true) => DARK_HIGHLIGHT
+                };
+                if piece.get_type() == EMPTY {
+                    write!(f, "{}   ", background)?;
+                }
+                else if piece.get_color() == WHITE {
+                    write!(f, "{}{} {} ", background, WHITE_PIECE, symbol)?;
+                }
+                else {
+                    write!(f, "{}{} {} ", background, BLACK_PIECE, symbol)?;
+                }
+            }
+            if formatter.colored_squares {
+                write!(f, "{}", RESET)?;
+            }
+            writeln!(f)?;
+        }
+        if formatter.coordinates {
+            write!(f, "  ")?;
+            for column in 0..8 {
+                write!(f, " {} ", (b'a' + formatter.square_at(column, 0).column() as u8) as char)?;
+            }
+            writeln!(f)?;
+        }
+        return Ok(());
+    }
+}
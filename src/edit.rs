@@ -0,0 +1,96 @@
+use crate::{Game, InvariantError, Piece, Square, EMPTY, HAS_MOVED, KING};
+
+impl Game {
+
+    /// Returns a copy of the position with the given color to move, for questions like what the other side
+    /// would play here. The copy has no history, and the en passant right is dropped unless the turn stays the
+    /// same. Fails when the side that would not be moving is in check, or when the position was already invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::new("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+    /// let black_to_move = game.with_side_to_move(BLACK).unwrap();
+    /// assert_eq!(black_to_move.turn, BLACK);
+    /// assert_eq!(black_to_move.count_legal_moves(), 5);
+    ///
+    /// // White is in check, so it can't be Black to move
+    /// let game = Game::new("4k3/8/8/8/8/8/4r3/4K3 w - - 0 1");
+    /// assert_eq!(game.with_side_to_move(BLACK).err(), Some(InvariantError::OpponentInCheck));
+    /// ```
+    pub fn with_side_to_move(&self, color: usize) -> Result<Game, InvariantError> {
+        let mut game = self.detached();
+        if color != self.turn {
+            game = self.edited();
+            game.turn = color;
+        }
+        game.check_invariants()?;
+        return Ok(game);
+    }
+
+    /// Returns a copy of the position with the square emptied. The copy has no history and no en passant right.
+    /// Fails when the result isn't a valid position, like when the square holds a king or removing a piece
+    /// uncovers a check on the side not to move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::starting_position();
+    /// let odds = game.without_piece(Square::D1).unwrap();
+    /// assert_eq!(odds.material_signature(), "KRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP");
+    /// assert_eq!(game.without_piece(Square::E1).err(), Some(InvariantError::KingCount { color: WHITE, count: 0 }));
+    /// ```
+    pub fn without_piece(&self, square: Square) -> Result<Game, InvariantError> {
+        let mut game = self.edited();
+        game.board[square.index()] = Piece::empty();
+        game.refresh_eval_terms();
+        game.check_invariants()?;
+        return Ok(game);
+    }
+
+    /// Returns a copy of the position with a piece of the given type and color on the square, replacing whatever
+    /// stood there. The piece counts as moved, so a placed king or rook gives no castling rights. The copy has no
+    /// history and no en passant right. Fails when the result isn't a valid position, like when the color
+    /// already has a king or a pawn is placed on the back rank.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::new("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    /// let game = game.with_piece(Square::A1, ROOK, WHITE).unwrap();
+    /// assert!(game.get_all_legal_moves().iter().any(|mv| mv.to_square() == Square::A8));
+    /// assert!(!game.castling_rights().white_queen_side);
+    ///
+    /// assert_eq!(game.with_piece(Square::A8, PAWN, WHITE).err(), Some(InvariantError::PawnOnBackRank(Square::A8)));
+    /// assert_eq!(game.with_piece(Square::E7, ROOK, WHITE).err(), Some(InvariantError::OpponentInCheck));
+    /// ```
+    pub fn with_piece(&self, square: Square, piece_type: usize, color: usize) -> Result<Game, InvariantError> {
+        let square = square.index();
+        let mut game = self.edited();
+        if piece_type == EMPTY {
+            game.board[square] = Piece::empty();
+        }
+        else {
+            game.board[square] = Piece::new(piece_type, color, HAS_MOVED);
+        }
+        // A second king is left for the invariants to report, the stored square only follows a king that was replaced
+        let king = game.board[game.king_square[color]];
+        if piece_type == KING && (king.get_type() != KING || king.get_color() != color) {
+            game.king_square[color] = square;
+        }
+        game.refresh_eval_terms();
+        game.check_invariants()?;
+        return Ok(game);
+    }
+
+    /// Returns a copy of the position to edit, which no longer follows from the moves that led to it
+    fn edited(&self) -> Game {
+        let mut game = self.detached();
+        game.possible_ep_capture = 64;
+        game.last_move = None;
+        return game;
+    }
+}
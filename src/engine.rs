@@ -0,0 +1,678 @@
+//! A small reference engine built on the crate
+//!
+//! [Engine] searches with iterative deepening and alpha-beta, extended by a quiescence search of captures and
+//! promotions. Results are kept in a [TranspositionTable]. Moves are ordered by the move stored in the table or the
+//! previous principal variation, [MVV-LVA](crate::ordering::mvv_lva_score), killer moves and history, and positions
+//! are evaluated by material and piece-square tables the game keeps up to date with the terms of [crate::eval]. It
+//! is meant as a starting point and for analysis of simple positions, not as a strong player.
+//!
+//! [Engine::analyze] returns the best lines of a position, several of them when asked for more than one principal
+//! variation, and [Engine::analyze_with] reports the lines of every completed depth while the search goes on.
+//! [Engine::start] runs a search limited by time or nodes on its own thread, where it can be stopped and told about
+//! a ponder hit, and the engine implements [SearchEngine] so it can be run as a UCI engine with [crate::uci::run].
+//! With a [simulated speed](Engine::set_simulated_speed) its time management depends on the nodes searched instead
+//! of the system time, so games in a [crate::matchplay::TimedMatch] can be reproduced.
+//! In a [crate::matchplay::Match] it searches every move within [fixed limits](Engine::set_play_limits).
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::engine::Engine;
+//! # use olindba_chess::notation::to_uci;
+//! # use olindba_chess::search::Score;
+//! let mut engine = Engine::new();
+//! // White mates with the rook on the back rank
+//! let lines = engine.analyze(&Game::new("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1"), 3, 1);
+//! assert_eq!(lines.len(), 1);
+//! assert_eq!(lines[0].score, Score::Mate(1));
+//! assert_eq!(to_uci(&lines[0].pv[0]), "a1a8");
+//! ```
+
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::eval::{Material, PieceSquareTables};
+use crate::matchplay::{Player, TimedPlayer};
+use crate::ordering::{mvv_lva_score, HistoryTable, KillerTable};
+use crate::search::{Score, SearchInfo, MATE_THRESHOLD};
+use crate::transposition::{Bound, Entry, TranspositionTable};
+use crate::uci::{EngineOption, OptionKind, SearchControl, SearchEngine, SearchLimits};
+use crate::{Game, Move, MoveList, RepetitionTracker, WHITE};
+
+pub use crate::search::MATE_SCORE;
+
+const INFINITY: i32 = MATE_SCORE + 1;
+/// Killer moves are kept for this many plies from the root
+const MAX_PLY: usize = 128;
+/// The depth searched to when the depth isn't limited
+const MAX_DEPTH: usize = 64;
+/// The size of the transposition table of a new engine in megabytes
+const DEFAULT_HASH_SIZE: usize = 16;
+
+
+/// An alpha-beta engine, see the [module documentation](self)
+pub struct Engine {
+    killers: KillerTable,
+    history: HistoryTable,
+    table: TranspositionTable,
+    /// The simulated nodes per second, if any
+    simulated_speed: Option<u64>,
+    /// The nodes searched by the last search
+    nodes: u64,
+    /// The limits of the searches when playing as a [Player]
+    play_limits: SearchLimits,
+    /// The score of the last search as a [Player]
+    last_score: Option<Score>
+}
+
+impl Default for Engine {
+    fn default() -> Engine {
+        Engine::new()
+    }
+}
+
+impl Engine {
+
+    pub fn new() -> Engine {
+        Engine {
+            killers: KillerTable::new(MAX_PLY),
+            history: HistoryTable::new(),
+            table: TranspositionTable::new(DEFAULT_HASH_SIZE),
+            simulated_speed: None,
+            nodes: 0,
+            play_limits: SearchLimits { depth: Some(4), ..Default::default() },
+            last_score: None
+        }
+    }
+
+    /// Makes the engine measure time as if it searched the given number of nodes per second, or by the system time
+    /// with None. Time limits then depend only on the nodes searched, and the times reported are the simulated ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::engine::Engine;
+    /// # use olindba_chess::uci::{SearchControl, SearchLimits};
+    /// # use std::time::Duration;
+    /// let limits = SearchLimits { movetime: Some(Duration::from_millis(500)), ..Default::default() };
+    /// let mut results = vec![];
+    /// for _ in 0..2 {
+    ///     let mut engine = Engine::new();
+    ///     engine.set_simulated_speed(Some(10000));
+    ///     let lines = engine.search_lines(&Game::starting_position(), &limits, 1, &SearchControl::new(false), |_| {});
+    ///     assert!(engine.nodes() <= 5000 + 1024);
+    ///     assert!(lines[0].time <= Duration::from_millis(500));
+    ///     results.push((engine.nodes(), lines[0].pv.clone()));
+    /// }
+    /// assert_eq!(results[0], results[1]);
+    /// ```
+    pub fn set_simulated_speed(&mut self, nodes_per_second: Option<u64>) {
+        self.simulated_speed = nodes_per_second.filter(|&speed| speed > 0);
+    }
+
+    /// Sets the limits every move is searched with when the engine plays as a [Player], a depth of 4 by default.
+    /// Limits by depth or nodes make the games reproducible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::engine::Engine;
+    /// # use olindba_chess::matchplay::{Adjudication, Match, RandomPlayer};
+    /// # use olindba_chess::uci::SearchLimits;
+    /// let mut engine = Engine::new();
+    /// engine.set_play_limits(SearchLimits { depth: Some(2), ..Default::default() });
+    /// let mut engine_match = Match::new(Box::new(engine), Box::new(RandomPlayer::new(7)));
+    /// engine_match.set_adjudication(Adjudication { material_margin: Some(900), ..Default::default() });
+    /// engine_match.play(2);
+    /// assert_eq!(engine_match.score().wins, 2);
+    /// assert_eq!(engine_match.games()[0].header("White"), Some("olindba-chess"));
+    /// ```
+    pub fn set_play_limits(&mut self, limits: SearchLimits) {
+        self.play_limits = limits;
+    }
+
+    /// Returns the number of nodes searched by the last search
+    pub fn nodes(&self) -> u64 {
+        return self.nodes;
+    }
+
+    /// Returns the transposition table, to resize it or to read the results of a search from it
+    pub fn transposition_table(&mut self) -> &mut TranspositionTable {
+        return &mut self.table;
+    }
+
+    /// Searches the position to the given depth and returns the best 'multipv' lines, best first. Fewer lines are
+    /// returned when the position has fewer legal moves, and none when the game is over.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::engine::Engine;
+    /// # use olindba_chess::search::Score;
+    /// // The knight on e5 can be taken by the pawn or the bishop, or left hanging
+    /// let game = Game::new("4k3/8/8/4n3/3P4/8/1B6/4K3 w - - 0 1");
+    /// let lines = Engine::new().analyze(&game, 2, 3);
+    /// assert_eq!(lines.len(), 3);
+    /// assert!(lines.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    /// assert!(lines[0].score > Score::Centipawns(200));
+    /// assert_ne!(lines[0].pv[0], lines[1].pv[0]);
+    /// ```
+    pub fn analyze(&mut self, game: &Game, depth: usize, multipv: usize) -> Vec<SearchInfo> {
+        return self.analyze_with(game, depth, multipv, |_| {});
+    }
+
+    /// Like [Engine::analyze], and calls 'on_depth' with the lines of every depth when it is completed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::engine::Engine;
+    /// let mut depths = vec![];
+    /// Engine::new().analyze_with(&Game::starting_position(), 3, 2, |lines| {
+    ///     assert_eq!(lines.len(), 2);
+    ///     depths.push(lines[0].depth);
+    /// });
+    /// assert_eq!(depths, vec![1, 2, 3]);
+    /// ```
+    pub fn analyze_with<F: FnMut(&[SearchInfo])>(&mut self, game: &Game, depth: usize, multipv: usize,
+                                                 on_depth: F) -> Vec<SearchInfo> {
+        let limits = SearchLimits { depth: Some(depth), ..Default::default() };
+        return self.search_lines(game, &limits, multipv, &SearchControl::new(false), on_depth);
+    }
+
+    /// Searches the position within the limits until the search is stopped through the control, and returns the
+    /// best 'multipv' lines of the last completed depth. The first depth is always completed.
+    ///
+    /// Without a depth or node limit the search goes on until the time runs out, which is 'movetime' if given and
+    /// otherwise a share of the remaining time of the side to move and most of its increment. A search without any
+    /// limit runs until it is stopped. While pondering the time limits are ignored, and after
+    /// [SearchControl::ponderhit] they count from the moment it was noticed.
+    pub fn search_lines<F: FnMut(&[SearchInfo])>(&mut self, game: &Game, limits: &SearchLimits, multipv: usize,
+                                                 control: &SearchControl, mut on_depth: F) -> Vec<SearchInfo> {
+        self.killers.clear();
+        self.history.age();
+        self.table.new_search();
+        let mut search = Search {
+            killers: &mut self.killers,
+            history: &mut self.history,
+            table: &mut self.table,
+            control,
+            node_limit: limits.nodes,
+            time_budget: time_budget(limits, game.turn),
+            start: Instant::now(),
+            clock_start: None,
+            simulated_speed: self.simulated_speed,
+            repetitions: RepetitionTracker::new(game),
+            nodes: 0,
+            abortable: false,
+            aborted: false
+        };
+
+        let mut game = game.clone();
+        game.set_eval_terms(Some((Material::default(), PieceSquareTables::centralization())));
+        let game = &game;
+        let mut root_moves = MoveList::new();
+        game.legal_moves_into(&mut root_moves);
+        let mut root_moves = root_moves.to_vec();
+
+        let mut lines: Vec<SearchInfo> = vec![];
+        for current_depth in 1..=limits.depth.unwrap_or(MAX_DEPTH) {
+            // The best moves of the previous depth are searched first, in their order
+            for (i, line) in lines.iter().enumerate() {
+                if let Some(position) = root_moves.iter().position(|&mv| mv == line.pv[0]) {
+                    root_moves.swap(i, position);
+                }
+            }
+
+            let mut depth_lines: Vec<SearchInfo> = vec![];
+            for _ in 0..multipv.min(root_moves.len()) {
+                let mut best_score = -INFINITY;
+                let mut best_pv = vec![];
+                for &mv in root_moves.iter() {
+                    if depth_lines.iter().any(|line| line.pv[0] == mv) {
+                        continue;
+                    }
+                    let mut pv = vec![];
+                    let score = -search.search_move(game, mv, current_depth - 1, 1, -INFINITY, -best_score, &mut pv);
+                    if score > best_score {
+                        best_score = score;
+                        best_pv = vec![mv];
+                        best_pv.extend(pv);
+                    }
+                }
+                depth_lines.push(SearchInfo {
+                    depth: current_depth,
+                    score: Score::from_value(best_score),
+                    bound: Bound::Exact,
+                    nodes: search.nodes,
+                    time: search.elapsed(0),
+                    pv: best_pv
+                });
+            }
+            if search.aborted {
+                break;
+            }
+            lines = depth_lines;
+            on_depth(&lines);
+            let node_limit_reached = limits.nodes.is_some_and(|limit| search.nodes >= limit);
+            if node_limit_reached || search.out_of_time(true) || control.is_stopped() {
+                break;
+            }
+            // From the second depth on the search can be aborted in the middle of a depth
+            search.abortable = true;
+        }
+        self.nodes = search.nodes;
+        return lines;
+    }
+
+    /// Starts a search of the position on its own thread and returns a handle to stop it, tell it about a ponder
+    /// hit and wait for its lines, see [Engine::search_lines]. The engine is returned when the search ends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::engine::Engine;
+    /// # use olindba_chess::uci::SearchLimits;
+    /// # use std::time::Duration;
+    /// let limits = SearchLimits { infinite: true, ..Default::default() };
+    /// let handle = Engine::new().start(&Game::starting_position(), limits, 1);
+    /// std::thread::sleep(Duration::from_millis(20));
+    /// assert!(!handle.is_finished());
+    /// handle.stop();
+    /// let (_engine, lines) = handle.wait();
+    /// assert_eq!(lines.len(), 1);
+    ///
+    /// // Pondering ignores the time until the ponder hit
+    /// let limits = SearchLimits { movetime: Some(Duration::from_millis(10)), ponder: true, ..Default::default() };
+    /// let handle = Engine::new().start(&Game::starting_position(), limits, 1);
+    /// std::thread::sleep(Duration::from_millis(50));
+    /// assert!(!handle.is_finished());
+    /// handle.ponderhit();
+    /// let (_engine, lines) = handle.wait();
+    /// assert!(!lines[0].pv.is_empty());
+    /// ```
+    pub fn start(mut self, game: &Game, limits: SearchLimits, multipv: usize) -> SearchHandle {
+        let control = Arc::new(SearchControl::new(limits.ponder));
+        let game = game.clone();
+        let search_control = Arc::clone(&control);
+        let thread = std::thread::spawn(move || {
+            let lines = self.search_lines(&game, &limits, multipv, &search_control, |_| {});
+            return (self, lines);
+        });
+        return SearchHandle { control, thread };
+    }
+}
+
+impl SearchEngine for Engine {
+    fn name(&self) -> String {
+        return "olindba-chess".to_string();
+    }
+
+    fn author(&self) -> String {
+        return "olindba".to_string();
+    }
+
+    fn options(&self) -> Vec<EngineOption> {
+        return vec![EngineOption {
+            name: "Hash".to_string(),
+            kind: OptionKind::Spin { default: DEFAULT_HASH_SIZE as i64, min: 1, max: 65536 }
+        }];
+    }
+
+    fn set_option(&mut self, name: &str, value: &str) {
+        if name.eq_ignore_ascii_case("Hash") {
+            if let Ok(megabytes) = value.parse::<usize>() {
+                self.table.resize(megabytes.clamp(1, 65536));
+            }
+        }
+    }
+
+    fn new_game(&mut self) {
+        self.killers.clear();
+        self.history.clear();
+        self.table.clear();
+    }
+
+    fn search(&mut self, game: &Game, limits: &SearchLimits, control: &SearchControl,
+              info: &mut dyn FnMut(&SearchInfo)) -> Option<Move> {
+        let lines = self.search_lines(game, limits, 1, control, |lines| info(&lines[0]));
+        return lines.first().map(|line| line.pv[0]);
+    }
+}
+
+impl TimedPlayer for Engine {
+    fn name(&self) -> String {
+        return SearchEngine::name(self);
+    }
+
+    fn new_game(&mut self) {
+        SearchEngine::new_game(self);
+    }
+
+    /// Searches the position within the limits and reports the simulated time, or the time it really took
+    fn choose_move(&mut self, game: &Game, limits: &SearchLimits) -> (Option<Move>, Duration) {
+        let start = Instant::now();
+        let lines = self.search_lines(game, limits, 1, &SearchControl::new(false), |_| {});
+        let used = match self.simulated_speed {
+            Some(speed) => simulated_time(self.nodes, speed),
+            None => start.elapsed()
+        };
+        return (lines.first().map(|line| line.pv[0]), used);
+    }
+}
+
+impl Player for Engine {
+    fn name(&self) -> String {
+        return SearchEngine::name(self);
+    }
+
+    fn new_game(&mut self) {
+        SearchEngine::new_game(self);
+    }
+
+    /// Searches the position within the [play limits](Engine::set_play_limits)
+    fn choose_move(&mut self, game: &Game) -> Option<Move> {
+        let limits = self.play_limits.clone();
+        let lines = self.search_lines(game, &limits, 1, &SearchControl::new(false), |_| {});
+        self.last_score = lines.first().map(|line| line.score);
+        return lines.first().map(|line| line.pv[0]);
+    }
+
+    fn last_score(&self) -> Option<i32> {
+        return self.last_score.map(|score| score.to_value());
+    }
+}
+
+/// A search running on its own thread, started by [Engine::start]
+pub struct SearchHandle {
+    control: Arc<SearchControl>,
+    thread: JoinHandle<(Engine, Vec<SearchInfo>)>
+}
+
+impl SearchHandle {
+
+    /// Tells the search to stop, it returns the lines of its last completed depth
+    pub fn stop(&self) {
+        self.control.stop();
+    }
+
+    /// Tells a pondering search that the expected move was played, so its time limits start to count
+    pub fn ponderhit(&self) {
+        self.control.ponderhit();
+    }
+
+    /// Returns true if the search has ended
+    pub fn is_finished(&self) -> bool {
+        return self.thread.is_finished();
+    }
+
+    /// Waits for the search to end and returns the engine and the lines it found
+    pub fn wait(self) -> (Engine, Vec<SearchInfo>) {
+        return self.thread.join().expect("the search thread panicked");
+    }
+}
+
+/// Returns the time it takes to search the nodes at the given speed in nodes per second
+fn simulated_time(nodes: u64, speed: u64) -> Duration {
+    return Duration::from_nanos((nodes as u128 * 1_000_000_000 / speed as u128) as u64);
+}
+
+/// Returns the time to search for with the given limits, or None if the time isn't limited
+fn time_budget(limits: &SearchLimits, turn: usize) -> Option<Duration> {
+    if limits.movetime.is_some() {
+        return limits.movetime;
+    }
+    let time = limits.time[turn]?;
+    let increment = limits.increment[turn].unwrap_or(Duration::ZERO);
+    let moves_to_go = limits.moves_to_go.unwrap_or(30).max(1) as u32;
+    // Some time is kept back for the overhead of sending the move
+    let budget = time / moves_to_go + increment * 3 / 4;
+    return Some(budget.min(time.saturating_sub(Duration::from_millis(50))));
+}
+
+/// The state of a running search
+struct Search<'a> {
+    killers: &'a mut KillerTable,
+    history: &'a mut HistoryTable,
+    table: &'a mut TranspositionTable,
+    control: &'a SearchControl,
+    node_limit: Option<u64>,
+    time_budget: Option<Duration>,
+    start: Instant,
+    /// When the time limits started to count, which is later than the start for a pondering search,
+    /// with the nodes searched until then
+    clock_start: Option<(Instant, u64)>,
+    simulated_speed: Option<u64>,
+    /// The positions of the game and the line being searched
+    repetitions: RepetitionTracker,
+    nodes: u64,
+    /// Set when the search may stop in the middle of a depth
+    abortable: bool,
+    aborted: bool
+}
+
+impl Search<'_> {
+
+    /// Returns true if the time is up. A soft limit is used between depths, since a new depth
+    /// usually takes longer than all earlier depths together.
+    fn out_of_time(&mut self, soft: bool) -> bool {
+        let budget = match self.time_budget {
+            Some(budget) => budget,
+            None => return false
+        };
+        if self.control.is_pondering() {
+            return false;
+        }
+        let nodes = self.nodes;
+        let (clock_start, start_nodes) = *self.clock_start.get_or_insert_with(|| (Instant::now(), nodes));
+        let elapsed = match self.simulated_speed {
+            Some(_) => self.elapsed(start_nodes),
+            None => clock_start.elapsed()
+        };
+        if soft {
+            return elapsed >= budget / 2;
+        }
+        return elapsed >= budget;
+    }
+
+    /// Returns the time since the start of the search, or the simulated time of the nodes searched after the
+    /// first 'start_nodes' with a simulated speed
+    fn elapsed(&self, start_nodes: u64) -> Duration {
+        return match self.simulated_speed {
+            Some(speed) => simulated_time(self.nodes - start_nodes, speed),
+            None => self.start.elapsed()
+        };
+    }
+
+    /// Counts a node and returns true if the search has to be aborted
+    fn visit(&mut self) -> bool {
+        self.nodes += 1;
+        if !self.abortable {
+            return false;
+        }
+        // Stopping and the time are checked every 1024 nodes
+        let stopped = self.nodes.is_multiple_of(1024) && (self.control.is_stopped() || self.out_of_time(false));
+        if stopped || self.node_limit.is_some_and(|limit| self.nodes >= limit) {
+            self.aborted = true;
+        }
+        return self.aborted;
+    }
+
+    /// Makes the move on a copy of the game and searches the resulting position
+    #[allow(clippy::too_many_arguments)]
+    fn search_move(&mut self, game: &Game, mv: Move, depth: usize, ply: usize, alpha: i32, beta: i32,
+                   pv: &mut Vec<Move>) -> i32 {
+        let mut child = game.clone();
+        child.make_move(mv);
+        self.repetitions.push(&child);
+        let score;
+        if depth == 0 {
+            score = self.quiescence(&child, alpha, beta);
+        }
+        else {
+            score = self.alpha_beta(&child, depth, ply, alpha, beta, pv);
+        }
+        self.repetitions.pop();
+        return score;
+    }
+
+    /// Returns the score of the position from the point of view of the side to move and fills in the principal
+    /// variation when the score is between alpha and beta
+    #[allow(clippy::too_many_arguments)]
+    fn alpha_beta(&mut self, game: &Game, depth: usize, ply: usize, mut alpha: i32, beta: i32,
+                  pv: &mut Vec<Move>) -> i32 {
+        if self.visit() {
+            return 0;
+        }
+        let mut moves = MoveList::new();
+        game.legal_moves_into(&mut moves);
+        if moves.is_empty() {
+            if game.is_in_check() {
+                return -MATE_SCORE + ply as i32;
+            }
+            return 0;
+        }
+        if self.repetitions.is_repetition() || game.halfmove_clock() >= 100 {
+            return 0;
+        }
+
+        let key = game.hash();
+        let entry = self.table.probe(key);
+        let table_move = entry.and_then(|entry| entry.best_move).filter(|mv| moves.contains(mv));
+        if let Some(entry) = entry.filter(|entry| entry.depth as usize >= depth) {
+            let score = score_from_table(entry.score, ply);
+            let cutoff = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha
+            };
+            if cutoff {
+                if score > alpha && score < beta {
+                    pv.clear();
+                    pv.extend(self.table.principal_variation(game, depth));
+                }
+                return score;
+            }
+        }
+
+        let mut moves = moves.to_vec();
+        moves.sort_by_cached_key(|mv| -self.order_score(game, ply, mv, table_move));
+        let mut best_move = None;
+        for mv in moves {
+            let mut child_pv = vec![];
+            let score = -self.search_move(game, mv, depth - 1, ply + 1, -beta, -alpha, &mut child_pv);
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                if !mv.is_capture() {
+                    self.killers.store(ply, mv);
+                    self.history.update(game.turn, mv, depth);
+                }
+                self.store(key, Some(mv), score, depth, ply, Bound::Lower);
+                return score;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(mv);
+                pv.clear();
+                pv.push(mv);
+                pv.extend(child_pv);
+            }
+        }
+        let bound = if best_move.is_some() { Bound::Exact } else { Bound::Upper };
+        self.store(key, best_move, alpha, depth, ply, bound);
+        return alpha;
+    }
+
+    /// Stores the result of searching the position in the transposition table
+    fn store(&mut self, key: u64, best_move: Option<Move>, score: i32, depth: usize, ply: usize, bound: Bound) {
+        self.table.store(key, Entry {
+            best_move,
+            score: score_to_table(score, ply),
+            depth: depth as u8,
+            bound
+        });
+    }
+
+    /// Searches captures and promotions until the position is quiet, the side to move can always stand pat
+    fn quiescence(&mut self, game: &Game, mut alpha: i32, beta: i32) -> i32 {
+        if self.visit() {
+            return 0;
+        }
+        let stand_pat = evaluate(game);
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut moves = MoveList::new();
+        game.legal_moves_into(&mut moves);
+        let mut moves: Vec<Move> = moves.iter().copied().filter(|mv| mv.is_capture() || mv.is_promotion()).collect();
+        moves.sort_by_cached_key(|mv| -mvv_lva_score(game, mv));
+        for mv in moves {
+            let mut child = game.clone();
+            child.make_move(mv);
+            let score = -self.quiescence(&child, -beta, -alpha);
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        return alpha;
+    }
+
+    /// Returns a score that puts the move from the transposition table first, then captures and promotions,
+    /// killer moves and quiet moves by history
+    fn order_score(&self, game: &Game, ply: usize, mv: &Move, table_move: Option<Move>) -> i32 {
+        if table_move == Some(*mv) {
+            return 2_000_000;
+        }
+        if mv.is_capture() || mv.is_promotion() {
+            return 1_000_000 + mvv_lva_score(game, mv);
+        }
+        if self.killers.is_killer(ply, *mv) {
+            return 500_000;
+        }
+        return self.history.score(game.turn, *mv).min(499_999);
+    }
+}
+
+/// Returns the material and piece-square score from the point of view of the side to move
+fn evaluate(game: &Game) -> i32 {
+    let (material, tables) = game.eval_terms().expect("the search keeps the terms of its games up to date");
+    let score = material.balance() + tables.balance();
+    if game.turn == WHITE {
+        return score;
+    }
+    return -score;
+}
+
+/// Mate scores are stored relative to the position rather than the root, so they stay correct when the position
+/// is reached at another ply
+fn score_to_table(score: i32, ply: usize) -> i16 {
+    if score > MATE_THRESHOLD {
+        return (score + ply as i32) as i16;
+    }
+    if score < -MATE_THRESHOLD {
+        return (score - ply as i32) as i16;
+    }
+    return score as i16;
+}
+
+fn score_from_table(score: i16, ply: usize) -> i32 {
+    let score = score as i32;
+    if score > MATE_THRESHOLD {
+        return score - ply as i32;
+    }
+    if score < -MATE_THRESHOLD {
+        return score + ply as i32;
+    }
+    return score;
+}
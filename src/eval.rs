@@ -0,0 +1,456 @@
+//! Evaluation terms that are updated move by move instead of being recomputed from the whole board
+//!
+//! An [IncrementalTerm] is told about every piece that is added to or removed from the board. It is filled once
+//! from a position with [IncrementalTerm::refresh] and then kept up to date move by move, so reading its value
+//! costs nothing per node. A [Game] keeps [material](Material) and [piece-square tables](PieceSquareTables) up to
+//! date itself once they are [set](Game::set_eval_terms): making a move updates them, and the move's
+//! [history entry](crate::HistoryEntry) saves them so taking it back restores them. Other terms are updated with
+//! [update_for_move] before each move is made, and a search copies them along with the game it clones for each move.
+//!
+//! [Material], [PieceSquareTables] and [MaterialSignature] are provided, and two terms are combined by using a pair of
+//! them as a term.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::eval::{IncrementalTerm, Material, PieceSquareTables};
+//! let mut game = Game::new("r3k3/1P6/8/8/8/8/8/4K2R w K - 0 1");
+//! game.set_eval_terms(Some((Material::default(), PieceSquareTables::centralization())));
+//!
+//! // Capture the rook on a8 and promote to a queen
+//! let mv = game.get_all_legal_moves().into_iter()
+//!     .find(|mv| mv.get_to() == 0 && mv.get_promotion_type() == QUEEN).unwrap();
+//! game.make_move(mv);
+//!
+//! let terms = game.eval_terms().unwrap();
+//! assert_eq!(terms.0.balance(), game.material_balance());
+//! let mut recomputed = (Material::default(), PieceSquareTables::centralization());
+//! recomputed.refresh(&game);
+//! assert_eq!(terms, &recomputed);
+//! ```
+
+use std::sync::OnceLock;
+
+use crate::ordering::piece_value;
+use crate::{Game, Move, Square, SquareColor, BISHOP, BLACK, EMPTY, KING, KNIGHT, PAWN, QUEEN, ROOK, WHITE};
+
+/// A part of an evaluation that follows the pieces as they are added to and removed from the board
+pub trait IncrementalTerm {
+    fn add_piece(&mut self, piece_type: usize, color: usize, square: Square);
+    fn remove_piece(&mut self, piece_type: usize, color: usize, square: Square);
+    /// Resets the term to an empty board
+    fn clear(&mut self);
+
+    /// Recomputes the term from every piece on the board of the game
+    fn refresh(&mut self, game: &Game) {
+        self.clear();
+        for square in 0..64 {
+            let piece = game.board[square];
+            if piece.get_type() != EMPTY {
+                self.add_piece(piece.get_type(), piece.get_color(), Square::new(square));
+            }
+        }
+    }
+}
+
+impl<A: IncrementalTerm, B: IncrementalTerm> IncrementalTerm for (A, B) {
+    fn add_piece(&mut self, piece_type: usize, color: usize, square: Square) {
+        self.0.add_piece(piece_type, color, square);
+        self.1.add_piece(piece_type, color, square);
+    }
+
+    fn remove_piece(&mut self, piece_type: usize, color: usize, square: Square) {
+        self.0.remove_piece(piece_type, color, square);
+        self.1.remove_piece(piece_type, color, square);
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+        self.1.clear();
+    }
+
+    fn refresh(&mut self, game: &Game) {
+        self.0.refresh(game);
+        self.1.refresh(game);
+    }
+}
+
+/// The terms a [Game] can keep up to date itself, see [Game::set_eval_terms]
+pub type EvalTerms = (Material, PieceSquareTables<'static>);
+
+/// Updates the term for a legal move of the game. Has to be called before the move is made.
+pub fn update_for_move(term: &mut impl IncrementalTerm, game: &Game, mv: &Move) {
+    let from = mv.get_from();
+    let to = mv.get_to();
+    let color = game.turn;
+    let piece_type = game.board[from].get_type();
+
+    if mv.is_castle() {
+        // Both pieces are removed before they are added since the king can land on the rook's square in Chess960
+        let (rook_from, rook_to) = game.castling_rook_move(mv);
+        let rook_type = game.board[rook_from].get_type();
+        term.remove_piece(piece_type, color, Square::new(from));
+        term.remove_piece(rook_type, color, Square::new(rook_from));
+        term.add_piece(piece_type, color, Square::new(to));
+        term.add_piece(rook_type, color, Square::new(rook_to));
+        return;
+    }
+
+    if mv.is_ep_capture() {
+        let captured = Square::from_column_row(to % 8, from / 8);
+        term.remove_piece(game.board[captured.index()].get_type(), color ^ 1, captured);
+    }
+    else if mv.is_capture() {
+        term.remove_piece(game.board[to].get_type(), color ^ 1, Square::new(to));
+    }
+    term.remove_piece(piece_type, color, Square::new(from));
+    if mv.is_promotion() {
+        term.add_piece(mv.get_promotion_type(), color, Square::new(to));
+    }
+    else {
+        term.add_piece(piece_type, color, Square::new(to));
+    }
+}
+
+/// The material of both sides in centipawns as given by [piece_value], kings not counted
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Material {
+    material: [i32; 2]
+}
+
+impl Material {
+
+    /// Returns the material of the given color
+    pub fn of(&self, color: usize) -> i32 {
+        return self.material[color];
+    }
+
+    /// Returns the material of White minus the material of Black, the same as [Game::material_balance]
+    pub fn balance(&self) -> i32 {
+        return self.material[WHITE] - self.material[WHITE ^ 1];
+    }
+}
+
+impl IncrementalTerm for Material {
+    fn add_piece(&mut self, piece_type: usize, color: usize, _square: Square) {
+        if piece_type != KING {
+            self.material[color] += piece_value(piece_type);
+        }
+    }
+
+    fn remove_piece(&mut self, piece_type: usize, color: usize, _square: Square) {
+        if piece_type != KING {
+            self.material[color] -= piece_value(piece_type);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.material = [0; 2];
+    }
+}
+
+/// The sum of a bonus for each piece depending on its square. The tables are given from White's perspective,
+/// indexed like [Game::board] with a8 first, and Black's pieces look up the vertically mirrored square.
+/// The tables are borrowed so that copying the term only copies the sums.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PieceSquareTables<'a> {
+    /// One table per piece type, indexed by the piece type minus one
+    tables: &'a [[i32; 64]; 6],
+    sums: [i32; 2]
+}
+
+impl<'a> PieceSquareTables<'a> {
+
+    /// Creates the term with the given tables, one per piece type from PAWN to KING
+    pub fn new(tables: &'a [[i32; 64]; 6]) -> PieceSquareTables<'a> {
+        PieceSquareTables {
+            tables,
+            sums: [0; 2]
+        }
+    }
+
+    /// Tables that give every piece but the king 10 centipawns for each step closer to the center it stands,
+    /// and pawns 10 centipawns for each rank they have advanced
+    pub fn centralization() -> PieceSquareTables<'static> {
+        static TABLES: OnceLock<[[i32; 64]; 6]> = OnceLock::new();
+        let tables = TABLES.get_or_init(|| {
+            let mut tables = [[0; 64]; 6];
+            for square in 0..64 {
+                let column = (square % 8) as i32;
+                let row = (square / 8) as i32;
+                let center_distance = (2 * column - 7).abs().max((2 * row - 7).abs()) / 2;
+                for table in tables.iter_mut().take(5).skip(1) {
+                    table[square] = 10 * (3 - center_distance);
+                }
+                tables[0][square] = 10 * (6 - row);
+            }
+            return tables;
+        });
+        return PieceSquareTables::new(tables);
+    }
+
+    /// Returns the sum of the bonuses of the pieces of the given color
+    pub fn of(&self, color: usize) -> i32 {
+        return self.sums[color];
+    }
+
+    /// Returns the sum for White minus the sum for Black
+    pub fn balance(&self) -> i32 {
+        return self.sums[WHITE] - self.sums[WHITE ^ 1];
+    }
+
+    fn bonus(&self, piece_type: usize, color: usize, square: Square) -> i32 {
+        return self.tables[piece_type - 1][square.relative_to(color).index()];
+    }
+}
+
+impl IncrementalTerm for PieceSquareTables<'_> {
+    fn add_piece(&mut self, piece_type: usize, color: usize, square: Square) {
+        self.sums[color] += self.bonus(piece_type, color, square);
+    }
+
+    fn remove_piece(&mut self, piece_type: usize, color: usize, square: Square) {
+        self.sums[color] -= self.bonus(piece_type, color, square);
+    }
+
+    fn clear(&mut self) {
+        self.sums = [0; 2];
+    }
+}
+
+/// The pieces of both sides counted by type, written as in tablebase file names with White's pieces first, for
+/// example "KRPvKR". Following it as a term keeps it up to date through captures and promotions, so evaluation and
+/// tablebase probing can dispatch on the material at every node.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::eval::{update_for_move, EndgameClass, IncrementalTerm, MaterialSignature};
+/// let mut game = Game::new("8/5k2/8/8/8/8/1p3K2/2R5 b - - 0 1");
+/// let mut signature = MaterialSignature::default();
+/// signature.refresh(&game);
+/// assert_eq!(signature.to_string(), "KRvKP");
+/// assert_eq!(signature.endgame_class(), EndgameClass::RookVsPawns);
+///
+/// // Capture the rook on c1 and promote to a queen
+/// let mv = game.get_all_legal_moves().into_iter().find(|mv| mv.get_promotion_type() == QUEEN && mv.is_capture())
+///     .unwrap();
+/// update_for_move(&mut signature, &game, &mv);
+/// game.make_move(mv);
+/// assert_eq!(signature.to_string(), "KvKQ");
+/// assert_eq!(signature.endgame_class(), EndgameClass::ElementaryMate);
+/// assert_eq!(game.material_signature(), "KvKQ");
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MaterialSignature {
+    /// The number of pieces indexed by color and piece type
+    counts: [[u8; 7]; 2],
+    /// The number of bishops of each color on light and on dark squares
+    bishops: [[u8; 2]; 2]
+}
+
+/// A family of endings by the material on the board, see [MaterialSignature::endgame_class]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EndgameClass {
+    /// Only the kings
+    BareKings,
+    /// Kings and pawns
+    Pawn,
+    /// A lone king without pawns against a queen, a rook, two bishops on different colors or a bishop and a knight
+    ElementaryMate,
+    /// One knight each, besides pawns
+    Knight,
+    /// One bishop each on squares of the same color, besides pawns
+    Bishop,
+    /// One bishop each on squares of different colors, besides pawns
+    OppositeColoredBishops,
+    /// A bishop against a knight, besides pawns
+    BishopVsKnight,
+    /// One rook each, besides pawns
+    Rook,
+    /// A rook against only pawns
+    RookVsPawns,
+    /// A rook against a bishop or a knight, besides pawns
+    RookVsMinor,
+    /// One queen each, besides pawns
+    Queen,
+    /// A queen against a rook, besides pawns
+    QueenVsRook,
+    /// Any other material, including every middlegame
+    Other
+}
+
+/// The piece types in the order they are written in a signature
+const SIGNATURE_ORDER: [(usize, char); 6] = [(KING, 'K'), (QUEEN, 'Q'), (ROOK, 'R'), (BISHOP, 'B'), (KNIGHT, 'N'),
+                                              (PAWN, 'P')];
+
+impl MaterialSignature {
+
+    /// Returns the number of pieces of the color and type
+    pub fn count(&self, color: usize, piece_type: usize) -> usize {
+        return self.counts[color][piece_type] as usize;
+    }
+
+    /// Returns the number of pieces on the board, kings included, as tablebases count them
+    pub fn total(&self) -> usize {
+        return self.counts.iter().flatten().map(|&count| count as usize).sum();
+    }
+
+    /// Returns the pieces of the color other than the king and pawns, by type from knights to queens
+    fn pieces(&self, color: usize) -> [u8; 4] {
+        return [self.counts[color][KNIGHT], self.counts[color][BISHOP], self.counts[color][ROOK],
+                self.counts[color][QUEEN]];
+    }
+
+    /// Returns the family of the ending
+    pub fn endgame_class(&self) -> EndgameClass {
+        let pawns = [self.counts[WHITE][PAWN], self.counts[BLACK][PAWN]];
+        let pieces = [self.pieces(WHITE), self.pieces(BLACK)];
+        let none = [0; 4];
+        let knight = [1, 0, 0, 0];
+        let bishop = [0, 1, 0, 0];
+        let rook = [0, 0, 1, 0];
+        let queen = [0, 0, 0, 1];
+
+        if pieces == [none, none] {
+            return if pawns == [0, 0] { EndgameClass::BareKings } else { EndgameClass::Pawn };
+        }
+        for color in [WHITE, BLACK] {
+            let (strong, weak) = (pieces[color], pieces[color ^ 1]);
+            let bishops_on_both_colors = self.bishops[color] == [1, 1];
+            let mating = strong == queen || strong == rook || (strong == [0, 2, 0, 0] && bishops_on_both_colors)
+                || strong == [1, 1, 0, 0];
+            if pawns == [0, 0] && weak == none && mating {
+                return EndgameClass::ElementaryMate;
+            }
+            if strong == rook && weak == none {
+                return EndgameClass::RookVsPawns;
+            }
+            if strong == rook && (weak == knight || weak == bishop) {
+                return EndgameClass::RookVsMinor;
+            }
+            if strong == queen && weak == rook {
+                return EndgameClass::QueenVsRook;
+            }
+            if strong == bishop && weak == knight {
+                return EndgameClass::BishopVsKnight;
+            }
+        }
+        if pieces == [knight, knight] {
+            return EndgameClass::Knight;
+        }
+        if pieces == [bishop, bishop] {
+            if self.bishops[WHITE] == self.bishops[BLACK] {
+                return EndgameClass::Bishop;
+            }
+            return EndgameClass::OppositeColoredBishops;
+        }
+        if pieces == [rook, rook] {
+            return EndgameClass::Rook;
+        }
+        if pieces == [queen, queen] {
+            return EndgameClass::Queen;
+        }
+        return EndgameClass::Other;
+    }
+
+    fn bishop_square(square: Square) -> usize {
+        return if square.color() == SquareColor::Light { 0 } else { 1 };
+    }
+}
+
+impl std::fmt::Display for MaterialSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for color in [WHITE, BLACK] {
+            if color == BLACK {
+                write!(f, "v")?;
+            }
+            for (piece_type, letter) in SIGNATURE_ORDER {
+                for _ in 0..self.counts[color][piece_type] {
+                    write!(f, "{}", letter)?;
+                }
+            }
+        }
+        return Ok(());
+    }
+}
+
+impl IncrementalTerm for MaterialSignature {
+    fn add_piece(&mut self, piece_type: usize, color: usize, square: Square) {
+        self.counts[color][piece_type] += 1;
+        if piece_type == BISHOP {
+            self.bishops[color][MaterialSignature::bishop_square(square)] += 1;
+        }
+    }
+
+    fn remove_piece(&mut self, piece_type: usize, color: usize, square: Square) {
+        self.counts[color][piece_type] -= 1;
+        if piece_type == BISHOP {
+            self.bishops[color][MaterialSignature::bishop_square(square)] -= 1;
+        }
+    }
+
+    fn clear(&mut self) {
+        *self = MaterialSignature::default();
+    }
+}
+
+impl Game {
+
+    /// Makes the game keep the terms up to date through [Game::make_move] and [Game::unmake_move], filled from the
+    /// current position, or stops it with None. A game made from this one by moves or edits keeps them too.
+    /// Changes to [Game::board] made by hand aren't followed, set the terms again after them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::eval::{Material, PieceSquareTables};
+    /// # use olindba_chess::notation::parse_uci;
+    /// let mut game = Game::new("r3k3/1P6/8/8/8/8/8/4K2R w K - 0 1");
+    /// game.set_eval_terms(Some((Material::default(), PieceSquareTables::centralization())));
+    /// let before = *game.eval_terms().unwrap();
+    ///
+    /// game.make_move(parse_uci(&game, "b7a8q").unwrap());
+    /// assert_eq!(game.eval_terms().unwrap().0.balance(), game.material_balance());
+    /// game.unmake_move();
+    /// assert_eq!(game.eval_terms(), Some(&before));
+    /// ```
+    pub fn set_eval_terms(&mut self, terms: Option<EvalTerms>) {
+        self.eval_terms = terms;
+        // The moves already made are taken back by refreshing, the terms they saved may have other tables
+        for entry in self.history.iter_mut() {
+            entry.eval_terms = None;
+        }
+        self.refresh_eval_terms();
+    }
+
+    /// Returns the evaluation terms the game keeps up to date, see [Game::set_eval_terms]
+    pub fn eval_terms(&self) -> Option<&EvalTerms> {
+        return self.eval_terms.as_ref();
+    }
+
+    /// Fills the terms the game keeps from the board, after it changed other than by a move
+    pub(crate) fn refresh_eval_terms(&mut self) {
+        if let Some(mut terms) = self.eval_terms {
+            terms.refresh(self);
+            self.eval_terms = Some(terms);
+        }
+    }
+
+    /// Returns the material of the position as a [MaterialSignature] string, for example "KRPvKR"
+    pub fn material_signature(&self) -> String {
+        let mut signature = MaterialSignature::default();
+        signature.refresh(self);
+        return signature.to_string();
+    }
+
+    /// Returns the family of the ending on the board, see [MaterialSignature::endgame_class]
+    pub fn endgame_class(&self) -> EndgameClass {
+        let mut signature = MaterialSignature::default();
+        signature.refresh(self);
+        return signature.endgame_class();
+    }
+}
@@ -0,0 +1,377 @@
+//! Reading and writing positions in Forsyth-Edwards notation
+//!
+//! [parse_fen] reads a FEN string and tells what is wrong with it instead of panicking like [Game::new], and is
+//! what `str::parse::<Game>()` uses. By default it accepts the shortened and loosely written FEN found in EPD files
+//! and typed by hand, [parse_fen_with] takes [FenOptions] to be stricter.
+//!
+//! [to_fen] writes a whole FEN string. The piece placement field can also be written on its own, for diagram tools
+//! and other programs that only take the placement, from either side's perspective and with markers around
+//! highlighted squares.
+
+use crate::notation::piece_letter;
+use crate::{Game, InvariantError, Piece, Square, BLACK, EMPTY, ROOK, WHITE};
+
+/// What [parse_fen_with] accepts besides standard FEN. The default accepts everything, [FenOptions::strict] nothing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FenOptions {
+    /// The halfmove clock and fullmove number may be left out, as in EPD, and are then 0 and 1
+    pub optional_move_numbers: bool,
+    /// The castling field may name rooks by their file as in X-FEN and Shredder-FEN, for Chess960
+    pub castling_files: bool,
+    /// Fields may be separated by any whitespace, and dashes such as '–' may stand for '-'
+    pub loose_formatting: bool
+}
+
+impl FenOptions {
+
+    /// Only accepts FEN exactly as the standard writes it, with all six fields
+    pub fn strict() -> FenOptions {
+        FenOptions {
+            optional_move_numbers: false,
+            castling_files: false,
+            loose_formatting: false
+        }
+    }
+}
+
+impl Default for FenOptions {
+    fn default() -> FenOptions {
+        FenOptions {
+            optional_move_numbers: true,
+            castling_files: true,
+            loose_formatting: true
+        }
+    }
+}
+
+/// The reason a FEN string couldn't be read, see [parse_fen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FenError {
+    /// The string doesn't have the number of space separated fields allowed by the [FenOptions]
+    FieldCount(usize),
+    /// The piece placement field doesn't describe eight ranks of eight squares
+    Placement(String),
+    /// The side to move is neither 'w' nor 'b'
+    SideToMove(String),
+    /// The castling field has a character that isn't a castling right
+    Castling(String),
+    /// The en passant field is neither '-' nor a square on the 3rd or 6th rank
+    EnPassant(String),
+    /// The halfmove clock or fullmove number isn't a number
+    MoveNumber(String),
+    /// The fields are well formed but the position isn't a possible one, for example a side without a king
+    Invariant(InvariantError)
+}
+
+impl std::fmt::Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FenError::FieldCount(count) => write!(f, "wrong number of fields: {}", count),
+            FenError::Placement(field) => write!(f, "'{}' is not a valid piece placement", field),
+            FenError::SideToMove(field) => write!(f, "'{}' is not a side to move", field),
+            FenError::Castling(field) => write!(f, "'{}' is not valid castling rights", field),
+            FenError::EnPassant(field) => write!(f, "'{}' is not an en passant square", field),
+            FenError::MoveNumber(field) => write!(f, "'{}' is not a move number", field),
+            FenError::Invariant(error) => write!(f, "{}", error)
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+/// Returns true if the piece placement field has eight ranks of eight squares of known pieces
+fn valid_placement(field: &str) -> bool {
+    let ranks: Vec<&str> = field.split('/').collect();
+    if ranks.len() != 8 {
+        return false;
+    }
+    return ranks.iter().all(|rank| {
+        let mut squares = 0;
+        for c in rank.chars() {
+            if let Some(empty) = c.to_digit(10).filter(|empty| (1..=8).contains(empty)) {
+                squares += empty as usize;
+            }
+            else if "pnbrqkPNBRQK".contains(c) {
+                squares += 1;
+            }
+            else {
+                return false;
+            }
+        }
+        return squares == 8;
+    });
+}
+
+/// Reads a FEN string, or an X-FEN or Shredder-FEN string for Chess960, and returns the game or what is wrong
+/// with the string. Everything allowed by the default [FenOptions] is accepted.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::fen::{parse_fen, to_fen, FenError};
+/// let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+/// assert_eq!(to_fen(&parse_fen(fen).unwrap()), fen);
+///
+/// let error = parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1").unwrap_err();
+/// assert_eq!(error, FenError::SideToMove("x".to_string()));
+/// assert_eq!(error.to_string(), "'x' is not a side to move");
+/// assert_eq!(parse_fen("8/8/8/8/8/8/8/8 w - - 0 1").unwrap_err(),
+///     FenError::Invariant(InvariantError::KingCount { color: WHITE, count: 0 }));
+/// ```
+pub fn parse_fen(fen: &str) -> Result<Game, FenError> {
+    return parse_fen_with(fen, &FenOptions::default());
+}
+
+/// Reads a FEN string like [parse_fen], accepting only what the options allow
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::fen::{parse_fen_with, to_fen, FenError, FenOptions};
+/// let epd = "4k3/8/8/8/8/8/8/R3K2R  w  K  –";
+/// let game = parse_fen_with(epd, &FenOptions::default()).unwrap();
+/// assert_eq!(to_fen(&game), "4k3/8/8/8/8/8/8/R3K2R w K - 0 1");
+/// assert_eq!(parse_fen_with(epd, &FenOptions::strict()).err(), Some(FenError::FieldCount(7)));
+///
+/// let shredder = "1r2k1r1/8/8/8/8/8/8/R2RK3 w Dg - 0 1";
+/// assert!(parse_fen_with(shredder, &FenOptions::default()).is_ok());
+/// assert_eq!(parse_fen_with(shredder, &FenOptions::strict()).err(), Some(FenError::Castling("Dg".to_string())));
+/// ```
+pub fn parse_fen_with(fen: &str, options: &FenOptions) -> Result<Game, FenError> {
+    let normalized;
+    let mut fields: Vec<&str>;
+    if options.loose_formatting {
+        normalized = fen.replace(['\u{2010}', '\u{2011}', '\u{2012}', '\u{2013}', '\u{2014}', '\u{2212}'], "-");
+        fields = normalized.split_whitespace().collect();
+    }
+    else {
+        fields = fen.split(' ').collect();
+    }
+    let min_fields = if options.optional_move_numbers { 4 } else { 6 };
+    if fields.len() < min_fields || fields.len() > 6 {
+        return Err(FenError::FieldCount(fields.len()));
+    }
+    if !valid_placement(fields[0]) {
+        return Err(FenError::Placement(fields[0].to_string()));
+    }
+    if fields[1] != "w" && fields[1] != "b" {
+        return Err(FenError::SideToMove(fields[1].to_string()));
+    }
+    // File letters name the castling rook in X-FEN and Shredder-FEN
+    let valid_right = |c: char| "KQkq".contains(c) || (options.castling_files && "abcdefghABCDEFGH".contains(c));
+    let valid_castling = fields[2] == "-"
+        || (!fields[2].is_empty() && fields[2].chars().all(valid_right));
+    if !valid_castling {
+        return Err(FenError::Castling(fields[2].to_string()));
+    }
+    let en_passant = Square::from_algebraic(fields[3]);
+    if fields[3] != "-" && !en_passant.is_some_and(|square| square.rank() == 3 || square.rank() == 6) {
+        return Err(FenError::EnPassant(fields[3].to_string()));
+    }
+    for field in &fields[4..] {
+        if field.parse::<usize>().is_err() {
+            return Err(FenError::MoveNumber(field.to_string()));
+        }
+    }
+
+    if fields.len() < 5 {
+        fields.push("0");
+    }
+    let game = Game::new(&fields.join(" "));
+    game.check_invariants().map_err(FenError::Invariant)?;
+    return Ok(game);
+}
+
+impl std::str::FromStr for Game {
+    type Err = FenError;
+
+    /// Reads a FEN string, see [parse_fen]
+    fn from_str(fen: &str) -> Result<Game, FenError> {
+        return parse_fen(fen);
+    }
+}
+
+/// Settings for writing the piece placement field
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlacementConfig {
+    /// The side whose perspective the board is written from. From White's the ranks run from 8 to 1 and
+    /// the files from a to h, as in FEN, from Black's both are reversed.
+    pub perspective: usize,
+    /// Squares written between the two markers, for example the squares of the last move.
+    /// An empty highlighted square is written as '1' between the markers.
+    pub highlights: Vec<Square>,
+    /// The characters written before and after a highlighted square
+    pub markers: (char, char),
+    /// If set, only the pieces on these squares are written and the rest of the board is left empty
+    pub visible: Option<Vec<Square>>
+}
+
+impl Default for PlacementConfig {
+    fn default() -> PlacementConfig {
+        PlacementConfig {
+            perspective: WHITE,
+            highlights: vec![],
+            markers: ('(', ')'),
+            visible: None
+        }
+    }
+}
+
+/// Returns the letter of a piece in FEN, uppercase for White and lowercase for Black
+fn fen_letter(piece: &Piece) -> char {
+    let letter = piece_letter(piece.get_type()).unwrap_or('P');
+    if piece.get_color() == WHITE {
+        return letter;
+    }
+    return letter.to_ascii_lowercase();
+}
+
+/// Writes the piece placement field of the position, for example 'rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR'
+pub fn placement(game: &Game) -> String {
+    return placement_with(game, &PlacementConfig::default());
+}
+
+/// Writes the piece placement field of the position with the settings of the given config
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::fen::{placement_with, PlacementConfig};
+/// let mut game = Game::starting_position();
+/// game.make_move_from_to(52, 36, None);
+///
+/// let config = PlacementConfig {
+///     highlights: vec![Square::new(52), Square::new(36)],
+///     ..Default::default()
+/// };
+/// assert_eq!(placement_with(&game, &config), "rnbqkbnr/pppppppp/8/8/4(P)3/8/PPPP(1)PPP/RNBQKBNR");
+///
+/// let config = PlacementConfig {
+///     perspective: BLACK,
+///     visible: Some(vec![Square::new(36), Square::new(60), Square::new(4)]),
+///     ..Default::default()
+/// };
+/// assert_eq!(placement_with(&game, &config), "3K4/8/8/3P4/8/8/8/3k4");
+/// ```
+pub fn placement_with(game: &Game, config: &PlacementConfig) -> String {
+    let mut placement = String::new();
+    for row in 0..8 {
+        if row != 0 {
+            placement.push('/');
+        }
+        let mut empty_squares = 0;
+        for column in 0..8 {
+            let square;
+            if config.perspective == WHITE {
+                square = Square::from_column_row(column, row);
+            }
+            else {
+                square = Square::from_column_row(7 - column, 7 - row);
+            }
+
+            let mut piece = game.board[square.index()];
+            if config.visible.as_ref().is_some_and(|visible| !visible.contains(&square)) {
+                piece = Piece::empty();
+            }
+
+            if config.highlights.contains(&square) {
+                if empty_squares != 0 {
+                    placement.push_str(&empty_squares.to_string());
+                    empty_squares = 0;
+                }
+                placement.push(config.markers.0);
+                if piece.get_type() == EMPTY {
+                    placement.push('1');
+                }
+                else {
+                    placement.push(fen_letter(&piece));
+                }
+                placement.push(config.markers.1);
+            }
+            else if piece.get_type() == EMPTY {
+                empty_squares += 1;
+            }
+            else {
+                if empty_squares != 0 {
+                    placement.push_str(&empty_squares.to_string());
+                    empty_squares = 0;
+                }
+                placement.push(fen_letter(&piece));
+            }
+        }
+        if empty_squares != 0 {
+            placement.push_str(&empty_squares.to_string());
+        }
+    }
+    return placement;
+}
+
+/// Writes the position as a FEN string.
+/// Castling rights are written as 'KQkq' unless another rook stands further out than the castling rook,
+/// as can happen in Chess960, in which case the file of the castling rook is written instead.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::fen::to_fen;
+/// let mut game = Game::starting_position();
+/// game.make_move_from_to(52, 36, None);
+/// assert_eq!(to_fen(&game), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+///
+/// let fen = "1r2k1r1/8/8/8/8/8/8/R2RK3 w Dg - 0 1";
+/// assert_eq!(to_fen(&Game::new(fen)), "1r2k1r1/8/8/8/8/8/8/R2RK3 w Dk - 0 1");
+/// ```
+pub fn to_fen(game: &Game) -> String {
+    let side_to_move;
+    if game.turn == WHITE {
+        side_to_move = 'w';
+    }
+    else {
+        side_to_move = 'b';
+    }
+
+    let mut castling = String::new();
+    for color in [WHITE, BLACK] {
+        for king_side in [true, false] {
+            let rook_square = match game.castling_rook_square(color, king_side) {
+                Some(rook_square) => rook_square,
+                None => continue
+            };
+            let back_rank = rook_square - game.get_column(rook_square);
+            let outer_squares;
+            if king_side {
+                outer_squares = rook_square + 1..back_rank + 8;
+            }
+            else {
+                outer_squares = back_rank..rook_square;
+            }
+            let outermost = !outer_squares.into_iter().any(|square| 
+                game.board[square].get_type() == ROOK && game.board[square].get_color() == color);
+
+            let mut letter;
+            if outermost && king_side {
+                letter = 'K';
+            }
+            else if outermost {
+                letter = 'Q';
+            }
+            else {
+                letter = (b'A' + game.get_column(rook_square) as u8) as char;
+            }
+            if color != WHITE {
+                letter = letter.to_ascii_lowercase();
+            }
+            castling.push(letter);
+        }
+    }
+    if castling.is_empty() {
+        castling.push('-');
+    }
+
+    let en_passant = game.en_passant_square().map_or("-".to_string(), |square| square.to_string());
+    return format!("{} {} {} {} {} {}", placement(game), side_to_move, castling, en_passant,
+        game.halfmove_clock(), game.fullmove_number());
+}
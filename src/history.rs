@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+
+use crate::eval::EvalTerms;
+use crate::{CastlingRights, Game, Move, Piece, PositionCache, Square, BLACK, KING, PAWN};
+
+/// A move made in a [Game] with everything needed to take it back, see [Game::history]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The hash of the position before the move
+    pub hash: u64,
+    pub mv: Move,
+    /// The piece that moved as it was before the move, a pawn for a promotion
+    pub piece: Piece,
+    /// The captured piece and the square it stood on, which isn't the destination of an en passant capture
+    pub captured: Option<(Piece, Square)>,
+    /// The castling rights before the move
+    pub castling_rights: CastlingRights,
+    /// The en passant square before the move, as written in FEN
+    pub en_passant: Option<Square>,
+    /// The halfmove clock before the move
+    pub halfmove_clock: usize,
+    /// What stood on the destination before the move
+    pub(crate) destination: Piece,
+    /// The castling rook with the squares it moved from and to, and what stood on the square it moved to
+    pub(crate) rook: Option<(Piece, usize, usize, Piece)>,
+    pub(crate) possible_ep_capture: usize,
+    pub(crate) last_irreversible_ply: usize,
+    pub(crate) last_move: Option<Move>,
+    /// The evaluation terms before the move, None when the game didn't keep them when the move was made
+    pub(crate) eval_terms: Option<EvalTerms>,
+    /// The hash of the moves of the history up to this one and the positions they were made in
+    pub(crate) line_hash: u64
+}
+
+impl Game {
+
+    /// Returns the moves made since the position was set up, oldest first
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::parse_uci;
+    /// let mut game = Game::new("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1");
+    /// game.make_move(parse_uci(&game, "d4e3").unwrap());
+    /// let entry = game.history()[0];
+    /// let (captured, square) = entry.captured.unwrap();
+    /// assert_eq!((captured.get_type(), captured.get_color()), (PAWN, WHITE));
+    /// assert_eq!(square, Square::from_algebraic("e4").unwrap());
+    /// assert_eq!(entry.en_passant, Square::from_algebraic("e3"));
+    /// ```
+    pub fn history(&self) -> &[HistoryEntry] {
+        return &self.history;
+    }
+
+    /// Returns the types of the pieces the color has captured since the position was set up, in the order they
+    /// were captured, as a GUI shows them next to the player. A piece a pawn promoted to counts as a pawn when it
+    /// is captured, so the captures are always pieces of the starting set and a promoted queen that is captured
+    /// doesn't show up as a second queen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::Notation;
+    /// let mut game = Game::new("3rk3/P7/8/8/8/3p4/8/R3K3 w - - 0 1");
+    /// game.apply_move_list("1. a8=Q Rxa8 2. Rxa8+ Kd7 3. Ra3 d2+ 4. Kxd2", Notation::San).unwrap();
+    /// assert_eq!(game.captured_pieces(BLACK), vec![PAWN]);
+    /// assert_eq!(game.captured_pieces(WHITE), vec![ROOK, PAWN]);
+    /// ```
+    pub fn captured_pieces(&self, color: usize) -> Vec<usize> {
+        let mut captured_pieces = vec![];
+        // The squares of the pieces pawns have promoted to
+        let mut promoted: u64 = 0;
+        for entry in self.history.iter() {
+            let (from, to) = (entry.mv.get_from(), entry.mv.get_to());
+            if let Some((captured, square)) = entry.captured {
+                let mut piece_type = captured.get_type();
+                if promoted & 1 << square.index() != 0 {
+                    piece_type = PAWN;
+                    promoted &= !(1 << square.index());
+                }
+                if entry.piece.get_color() == color {
+                    captured_pieces.push(piece_type);
+                }
+            }
+            if promoted & 1 << from != 0 || entry.mv.is_promotion() {
+                promoted = promoted & !(1 << from) | 1 << to;
+            }
+        }
+        return captured_pieces;
+    }
+
+    /// Takes back the last move and returns it, or returns None if no move has been made since the position was
+    /// set up. The game is left exactly as it was before the move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::fen::to_fen;
+    /// # use olindba_chess::notation::parse_uci;
+    /// let fen = "r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 3 20";
+    /// let mut game = Game::new(fen);
+    /// for mv in ["e1c1", "e8g8", "b7a8q"] {
+    ///     game.make_move(parse_uci(&game, mv).unwrap());
+    /// }
+    /// assert_eq!(to_fen(&game), "Q4rk1/8/8/8/8/8/8/2KR3R b - - 0 21");
+    /// while game.unmake_move().is_some() {}
+    /// assert_eq!(to_fen(&game), fen);
+    /// assert_eq!(game.last_move(), None);
+    /// ```
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let entry = self.history.pop()?;
+        self.annotations.remove(&self.history.len());
+        self.ending = None;
+        let mv = entry.mv;
+        self.cache.clear();
+        self.turn ^= 1;
+        if self.turn == BLACK {
+            self.full_move_number -= 1;
+        }
+
+        // The destinations are restored before the pieces are put back since in Chess960 the king and rook can land on
+        // each other's starting squares
+        self.board[mv.get_to()] = entry.destination;
+        if let Some((rook, from, to, destination)) = entry.rook {
+            self.board[to] = destination;
+            self.board[from] = rook;
+        }
+        self.board[mv.get_from()] = entry.piece;
+        if let Some((captured, square)) = entry.captured {
+            self.board[square.index()] = captured;
+        }
+        if entry.piece.get_type() == KING && mv.get_to() == self.king_square[self.turn] {
+            self.king_square[self.turn] = mv.get_from();
+        }
+
+        self.possible_ep_capture = entry.possible_ep_capture;
+        self.half_move_clock = entry.halfmove_clock;
+        self.last_irreversible_ply = entry.last_irreversible_ply;
+        self.last_move = entry.last_move;
+        if self.eval_terms.is_some() {
+            match entry.eval_terms {
+                Some(terms) => self.eval_terms = Some(terms),
+                None => self.refresh_eval_terms()
+            }
+        }
+        return Some(mv);
+    }
+}
+
+/// The position of a [Game] at one point of the game, taken with [Game::position_snapshot] and put back with
+/// [Game::restore]. Unlike a clone of the game it holds no history, so taking one never allocates.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PositionSnapshot {
+    board: [Piece; 64],
+    turn: usize,
+    possible_ep_capture: usize,
+    king_square: [usize; 2],
+    half_move_clock: usize,
+    full_move_number: usize,
+    last_irreversible_ply: usize,
+    last_move: Option<Move>,
+    ply: usize,
+    /// The hash of the moves of the history and the positions they were made in, to tell the line the snapshot
+    /// was taken on apart from others
+    line_hash: u64
+}
+
+impl PositionSnapshot {
+
+    /// Returns the number of moves that had been made in the game when the snapshot was taken
+    pub fn ply(&self) -> usize {
+        return self.ply;
+    }
+}
+
+impl Game {
+
+    /// Returns a copy of the current position, see [PositionSnapshot]. Not to be confused with [Game::snapshot],
+    /// the position as a message for clients.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::fen::to_fen;
+    /// # use olindba_chess::notation::parse_uci;
+    /// let mut game = Game::starting_position();
+    /// game.make_move(parse_uci(&game, "e2e4").unwrap());
+    /// let snapshot = game.position_snapshot();
+    /// let fen = to_fen(&game);
+    /// for mv in ["e7e5", "g1f3", "b8c6"] {
+    ///     game.make_move(parse_uci(&game, mv).unwrap());
+    /// }
+    /// game.restore(&snapshot);
+    /// assert_eq!(to_fen(&game), fen);
+    /// assert_eq!(game.ply(), 1);
+    /// assert_eq!(game.last_move(), parse_uci(&Game::starting_position(), "e2e4").ok());
+    /// ```
+    pub fn position_snapshot(&self) -> PositionSnapshot {
+        return PositionSnapshot {
+            board: self.board,
+            turn: self.turn,
+            possible_ep_capture: self.possible_ep_capture,
+            king_square: self.king_square,
+            half_move_clock: self.half_move_clock,
+            full_move_number: self.full_move_number,
+            last_irreversible_ply: self.last_irreversible_ply,
+            last_move: self.last_move,
+            ply: self.history.len(),
+            line_hash: self.line_hash()
+        };
+    }
+
+    /// Puts back the position of the snapshot. The moves made since the snapshot was taken are removed from the
+    /// history. A snapshot that doesn't lie on the game's history, such as one taken before moves were taken back
+    /// or on another line, even one transposing into the same position, has no history to go with it, so the
+    /// history is cleared as by [Game::set_board_state].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::Notation;
+    /// let mut game = Game::starting_position();
+    /// game.apply_move_list("1. e4 e5", Notation::San).unwrap();
+    /// let snapshot = game.position_snapshot();
+    /// game.unmake_move();
+    /// game.unmake_move();
+    /// game.apply_move_list("1. d4 d5 2. c4", Notation::San).unwrap();
+    ///
+    /// game.restore(&snapshot);
+    /// assert!(game.history().is_empty());
+    /// assert_eq!(game.unmake_move(), None);
+    /// assert_eq!(game.check_invariants(), Ok(()));
+    ///
+    /// // The same position and last move reached by other moves
+    /// let mut game = Game::starting_position();
+    /// game.apply_move_list("1. Nf3 Nf6 2. Nc3 Nc6 3. e4", Notation::San).unwrap();
+    /// let snapshot = game.position_snapshot();
+    /// while game.unmake_move().is_some() {}
+    /// game.apply_move_list("1. Nc3 Nc6 2. Nf3 Nf6 3. e4 e5", Notation::San).unwrap();
+    /// game.restore(&snapshot);
+    /// assert!(game.history().is_empty());
+    /// ```
+    pub fn restore(&mut self, snapshot: &PositionSnapshot) {
+        self.board = snapshot.board;
+        self.turn = snapshot.turn;
+        self.possible_ep_capture = snapshot.possible_ep_capture;
+        self.king_square = snapshot.king_square;
+        self.half_move_clock = snapshot.half_move_clock;
+        self.full_move_number = snapshot.full_move_number;
+        self.last_move = snapshot.last_move;
+        self.ending = None;
+        // The whole line has to match, the same position can be reached by other moves
+        let on_history = snapshot.ply <= self.history.len() && self.line_hash_at(snapshot.ply) == snapshot.line_hash;
+        if on_history {
+            self.history.truncate(snapshot.ply);
+            self.annotations.retain(|&ply, _| ply < snapshot.ply);
+            self.last_irreversible_ply = snapshot.last_irreversible_ply;
+        }
+        else {
+            self.history.clear();
+            self.annotations.clear();
+            self.last_irreversible_ply = 0;
+        }
+        self.refresh_eval_terms();
+        self.cache.clear();
+    }
+
+    /// Returns the hash of the moves of the history and the positions they were made in
+    pub(crate) fn line_hash(&self) -> u64 {
+        return self.line_hash_at(self.history.len());
+    }
+
+    /// Returns the line hash of the first 'ply' moves of the history
+    fn line_hash_at(&self, ply: usize) -> u64 {
+        return match ply.checked_sub(1) {
+            Some(last) => self.history[last].line_hash,
+            None => 0
+        };
+    }
+
+    /// Returns a game in the current position without its history or cache, cheap to make moves on when only
+    /// the resulting board matters
+    pub(crate) fn detached(&self) -> Game {
+        return Game {
+            board: self.board,
+            turn: self.turn,
+            possible_ep_capture: self.possible_ep_capture,
+            king_square: self.king_square,
+            half_move_clock: self.half_move_clock,
+            full_move_number: self.full_move_number,
+            history: vec![],
+            last_irreversible_ply: 0,
+            last_move: self.last_move,
+            annotations: BTreeMap::new(),
+            ending: None,
+            automatic_termination: self.automatic_termination,
+            analysis_mode: self.analysis_mode,
+            rules: self.rules,
+            eval_terms: self.eval_terms,
+            cache: PositionCache::new()
+        };
+    }
+}
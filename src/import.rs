@@ -0,0 +1,227 @@
+//! Importing games from the JSON APIs of lichess and chess.com
+//!
+//! Games are converted to [PgnGame]s, with the players, ratings and result as PGN headers, so they can be replayed,
+//! written as PGN or analysed like games read from a PGN file.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::import::{from_lichess_json, ImportError};
+//! let json = r#"{"id":"q7ZvsdUF","rated":true,"variant":"standard","speed":"blitz","createdAt":1514505150384,
+//!     "status":"mate","winner":"white","moves":"e4 e5 Qh5 Nc6 Bc4 Nf6 Qxf7#",
+//!     "players":{"white":{"user":{"name":"Alice"},"rating":1500},"black":{"user":{"name":"Bob"},"rating":1480}}}"#;
+//! let game = from_lichess_json(json).unwrap();
+//! assert_eq!(game.moves.len(), 7);
+//! assert_eq!(game.result, "1-0");
+//! assert_eq!(game.header("Site"), Some("https://lichess.org/q7ZvsdUF"));
+//! assert_eq!(game.header("Date"), Some("2017.12.28"));
+//! assert_eq!(game.header("BlackElo"), Some("1480"));
+//! assert_eq!(game.header("Termination"), Some("normal"));
+//!
+//! let json = r#"{"id":"x","variant":"fromPosition","initialFen":"8/8/8 w - - 0 1","moves":"","players":{}}"#;
+//! assert!(matches!(from_lichess_json(json), Err(ImportError::InvalidFen(_))));
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::fen::{parse_fen, FenError};
+use crate::notation::{parse_san, NotationError};
+use crate::pgn::{parse_game, PgnError, PgnGame};
+use crate::{Game, Termination};
+
+/// The reason a game couldn't be imported
+#[derive(Debug)]
+pub enum ImportError {
+    /// The text isn't valid JSON
+    Json(serde_json::Error),
+    /// A field needed to rebuild the game is missing or has the wrong type
+    MissingField(&'static str),
+    /// The game is of a variant the crate doesn't play
+    UnsupportedVariant(String),
+    /// The move at the given ply, counted from 0, isn't a legal move in SAN
+    InvalidMove { ply: usize, error: NotationError },
+    /// The initial FEN of a game from a set up position doesn't describe a position
+    InvalidFen(FenError),
+    /// The PGN of a chess.com game couldn't be read
+    Pgn(PgnError)
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ImportError::Json(error) => write!(f, "invalid JSON: {}", error),
+            ImportError::MissingField(field) => write!(f, "missing field '{}'", field),
+            ImportError::UnsupportedVariant(variant) => write!(f, "unsupported variant '{}'", variant),
+            ImportError::InvalidMove { ply, error } => write!(f, "ply {}: {}", ply + 1, error),
+            ImportError::InvalidFen(error) => write!(f, "invalid initial FEN: {}", error),
+            ImportError::Pgn(error) => write!(f, "{}", error)
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<serde_json::Error> for ImportError {
+    fn from(error: serde_json::Error) -> ImportError {
+        ImportError::Json(error)
+    }
+}
+
+impl From<PgnError> for ImportError {
+    fn from(error: PgnError) -> ImportError {
+        ImportError::Pgn(error)
+    }
+}
+
+/// Converts days since 1970-01-01 to a PGN date such as '2017.12.28'
+fn pgn_date(days: i64) -> String {
+    // Howard Hinnant's civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    return format!("{:04}.{:02}.{:02}", year, month, day);
+}
+
+/// Reads a game as returned by the lichess game export API in JSON format.
+/// Standard, Chess960 and from-position games can be imported.
+pub fn from_lichess_json(json: &str) -> Result<PgnGame, ImportError> {
+    let value: Value = serde_json::from_str(json)?;
+    return lichess_game(&value);
+}
+
+/// Reads every game of an NDJSON stream from the lichess API, one game per line
+pub fn from_lichess_ndjson(ndjson: &str) -> Result<Vec<PgnGame>, ImportError> {
+    return ndjson.lines().filter(|line| !line.trim().is_empty()).map(from_lichess_json).collect();
+}
+
+fn lichess_game(value: &Value) -> Result<PgnGame, ImportError> {
+    let variant = value["variant"].as_str().unwrap_or("standard");
+    if variant != "standard" && variant != "chess960" && variant != "fromPosition" {
+        return Err(ImportError::UnsupportedVariant(variant.to_string()));
+    }
+    let id = value["id"].as_str().ok_or(ImportError::MissingField("id"))?;
+    let moves = value["moves"].as_str().ok_or(ImportError::MissingField("moves"))?;
+
+    let result = match (value["winner"].as_str(), value["status"].as_str()) {
+        (Some("white"), _) => "1-0",
+        (Some("black"), _) => "0-1",
+        (_, Some("created")) | (_, Some("started")) => "*",
+        _ => "1/2-1/2"
+    };
+
+    let mut event = String::new();
+    if value["rated"].as_bool() == Some(true) {
+        event.push_str("Rated ");
+    }
+    else {
+        event.push_str("Casual ");
+    }
+    event.push_str(value["speed"].as_str().unwrap_or("correspondence"));
+    event.push_str(" game");
+
+    let mut headers = vec![
+        ("Event".to_string(), event),
+        ("Site".to_string(), format!("https://lichess.org/{}", id))
+    ];
+    if let Some(created_at) = value["createdAt"].as_i64() {
+        headers.push(("Date".to_string(), pgn_date(created_at.div_euclid(86_400_000))));
+    }
+    for (color, key) in [("white", "White"), ("black", "Black")] {
+        let player = &value["players"][color];
+        let name = player["user"]["name"].as_str()
+            .or_else(|| player["aiLevel"].as_u64().map(|_| "lichess AI"))
+            .unwrap_or("?");
+        headers.push((key.to_string(), name.to_string()));
+    }
+    headers.push(("Result".to_string(), result.to_string()));
+    let termination = match value["status"].as_str() {
+        Some("mate") | Some("stalemate") | Some("draw") => Some(Termination::Normal),
+        Some("resign") => Some(Termination::Resignation),
+        Some("outoftime") => Some(Termination::TimeForfeit),
+        // Lichess calls a game the opponent left 'timeout'
+        Some("timeout") => Some(Termination::Abandoned),
+        Some("cheat") => Some(Termination::RulesInfraction),
+        _ => None
+    };
+    if let Some(termination) = termination {
+        headers.push(("Termination".to_string(), termination.to_string()));
+    }
+    for (color, key) in [("white", "WhiteElo"), ("black", "BlackElo")] {
+        if let Some(rating) = value["players"][color]["rating"].as_u64() {
+            headers.push((key.to_string(), rating.to_string()));
+        }
+    }
+
+    let start;
+    if let Some(fen) = value["initialFen"].as_str() {
+        headers.push(("SetUp".to_string(), "1".to_string()));
+        headers.push(("FEN".to_string(), fen.to_string()));
+        start = parse_fen(fen).map_err(ImportError::InvalidFen)?;
+    }
+    else {
+        start = Game::starting_position();
+    }
+    if variant == "chess960" {
+        headers.push(("Variant".to_string(), "Chess960".to_string()));
+    }
+
+    let mut game = start.clone();
+    let mut played = vec![];
+    for (ply, san) in moves.split_whitespace().enumerate() {
+        let mv = parse_san(&game, san).map_err(|error| ImportError::InvalidMove { ply, error })?;
+        game.make_move(mv);
+        played.push(mv);
+    }
+
+    return Ok(PgnGame {
+        headers,
+        start,
+        moves: played,
+        annotations: BTreeMap::new(),
+        result: result.to_string()
+    });
+}
+
+/// Reads a game as returned by the chess.com published data API, which includes the game as PGN
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::import::from_chess_com_json;
+/// let json = r#"{"url":"https://www.chess.com/game/live/1","rules":"chess",
+///     "pgn":"[Event \"Live Chess\"]\n[White \"Alice\"]\n[Black \"Bob\"]\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n",
+///     "white":{"username":"Alice","rating":1200,"result":"checkmated"},
+///     "black":{"username":"Bob","rating":1300,"result":"win"}}"#;
+/// let game = from_chess_com_json(json).unwrap();
+/// assert_eq!(game.moves.len(), 4);
+/// assert_eq!(game.header("Black"), Some("Bob"));
+/// assert_eq!(game.final_position().get_game_state(), olindba_chess::GameState::Checkmate);
+/// ```
+pub fn from_chess_com_json(json: &str) -> Result<PgnGame, ImportError> {
+    let value: Value = serde_json::from_str(json)?;
+    return chess_com_game(&value);
+}
+
+/// Reads every game of a monthly archive from the chess.com published data API, an object with a 'games' array
+pub fn from_chess_com_archive(json: &str) -> Result<Vec<PgnGame>, ImportError> {
+    let value: Value = serde_json::from_str(json)?;
+    let games = value["games"].as_array().ok_or(ImportError::MissingField("games"))?;
+    return games.iter().map(chess_com_game).collect();
+}
+
+fn chess_com_game(value: &Value) -> Result<PgnGame, ImportError> {
+    let rules = value["rules"].as_str().unwrap_or("chess");
+    if rules != "chess" && rules != "chess960" {
+        return Err(ImportError::UnsupportedVariant(rules.to_string()));
+    }
+    let pgn = value["pgn"].as_str().ok_or(ImportError::MissingField("pgn"))?;
+    return Ok(parse_game(pgn)?);
+}
@@ -0,0 +1,216 @@
+//! Turning the presses and releases of a pointer on the board into moves
+//!
+//! An [InputHandler] follows what the user does with the pieces of the side to move. A piece can be dragged, pressed
+//! on its square and released on the destination, or clicked, pressed and released on its square and then pressed
+//! on the destination. The handler only emits legal moves, which the caller makes on the game, and asks the
+//! [PromotionPolicy] which piece to promote to when a pawn reaches the last rank.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::input::{InputEvent, InputHandler};
+//! let mut game = Game::starting_position();
+//! let mut input = InputHandler::new();
+//!
+//! // Dragging the e-pawn
+//! assert_eq!(input.press(&game, Square::E2), InputEvent::Selected(Square::E2));
+//! let InputEvent::Move(mv) = input.release(&game, Square::E4) else { panic!() };
+//! game.make_move(mv);
+//!
+//! // Clicking the knight and then its destination
+//! assert_eq!(input.press(&game, Square::G8), InputEvent::Selected(Square::G8));
+//! assert_eq!(input.release(&game, Square::G8), InputEvent::Ignored);
+//! assert_eq!(input.destinations(&game), vec![Square::F6, Square::H6]);
+//! let InputEvent::Move(mv) = input.press(&game, Square::F6) else { panic!() };
+//! game.make_move(mv);
+//! assert_eq!(input.selected(), None);
+//!
+//! // A press that isn't a move of the selected piece selects another piece or clears the selection
+//! assert_eq!(input.press(&game, Square::D2), InputEvent::Selected(Square::D2));
+//! assert_eq!(input.press(&game, Square::D6), InputEvent::Deselected);
+//! ```
+
+use crate::{square, Game, Move, Promotion, Square, EMPTY};
+
+/// A function choosing a promotion, see [PromotionPolicy::Ask]
+pub type PromotionChooser = Box<dyn FnMut(Square, Square, &[Promotion]) -> Option<Promotion>>;
+
+/// How the piece a pawn promotes to is chosen when a move is entered
+pub enum PromotionPolicy {
+    /// Promote to a queen, or the strongest piece allowed when the rules don't allow queens
+    AlwaysQueen,
+    /// Call the function with the squares of the move and the promotions allowed, from the queen down. Returning
+    /// None or a promotion that isn't allowed cancels the move.
+    Ask(PromotionChooser)
+}
+
+impl Default for PromotionPolicy {
+    fn default() -> PromotionPolicy {
+        return PromotionPolicy::AlwaysQueen;
+    }
+}
+
+/// What a press or release did
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InputEvent {
+    /// Nothing changed
+    Ignored,
+    /// The piece on the square was selected, its destinations can be highlighted
+    Selected(Square),
+    /// The selection was cleared, by pressing elsewhere, dropping a piece where it can't go or cancelling a promotion
+    Deselected,
+    /// A legal move was entered, the caller makes it on the game
+    Move(Move)
+}
+
+/// Tracks the selected piece between presses and releases, see the [module documentation](self)
+#[derive(Default)]
+pub struct InputHandler {
+    selected: Option<Square>,
+    /// The piece is being dragged, the pointer was pressed on it and not released yet
+    dragging: bool,
+    /// The piece was selected before the current press, so releasing on its square deselects it
+    was_selected: bool,
+    promotion_policy: PromotionPolicy
+}
+
+impl InputHandler {
+
+    /// Creates a handler that promotes to a queen
+    pub fn new() -> InputHandler {
+        return InputHandler::default();
+    }
+
+    /// Creates a handler that chooses promotions with the given policy
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::input::{InputEvent, InputHandler, PromotionPolicy};
+    /// let game = Game::new("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    /// let mut input = InputHandler::with_promotion_policy(PromotionPolicy::Ask(Box::new(|_, to, allowed| {
+    ///     assert_eq!(allowed, [Promotion::Queen, Promotion::Rook, Promotion::Bishop, Promotion::Knight]);
+    ///     Some(Promotion::Knight).filter(|_| to == Square::A8)
+    /// })));
+    /// input.press(&game, Square::A7);
+    /// let InputEvent::Move(mv) = input.release(&game, Square::A8) else { panic!() };
+    /// assert_eq!(mv.promotion(), Some(Promotion::Knight));
+    ///
+    /// let mut input = InputHandler::new();
+    /// input.press(&game, Square::A7);
+    /// let InputEvent::Move(mv) = input.release(&game, Square::A8) else { panic!() };
+    /// assert_eq!(mv.promotion(), Some(Promotion::Queen));
+    /// ```
+    pub fn with_promotion_policy(promotion_policy: PromotionPolicy) -> InputHandler {
+        return InputHandler { promotion_policy, ..Default::default() };
+    }
+
+    /// Returns the square of the selected piece
+    pub fn selected(&self) -> Option<Square> {
+        return self.selected;
+    }
+
+    /// Returns the squares the selected piece can move to, each once even when it can promote to several pieces
+    pub fn destinations(&self, game: &Game) -> Vec<Square> {
+        let mut destinations = vec![];
+        if let Some(from) = self.selected {
+            for mv in game.get_legal_moves(from) {
+                if !destinations.contains(&mv.to_square()) {
+                    destinations.push(mv.to_square());
+                }
+            }
+        }
+        return destinations;
+    }
+
+    /// Clears the selection, for example when the position changed by other means than the handler's moves
+    pub fn clear(&mut self) {
+        self.selected = None;
+        self.dragging = false;
+        self.was_selected = false;
+    }
+
+    /// Handles the pointer being pressed on the square. With a piece selected that can move there the move is
+    /// entered, otherwise a piece of the side to move with legal moves is selected and anything else clears the
+    /// selection.
+    pub fn press(&mut self, game: &Game, square: impl TryInto<Square>) -> InputEvent {
+        let square = match square::to_square(square) {
+            Some(square) => square,
+            None => return self.deselect()
+        };
+        if let Some(from) = self.selected {
+            if from != square && self.destinations(game).contains(&square) {
+                return self.enter_move(game, from, square);
+            }
+        }
+
+        let piece = game.board[square.index()];
+        if piece.get_type() != EMPTY && piece.get_color() == game.turn && !game.get_legal_moves(square).is_empty() {
+            self.was_selected = self.selected == Some(square);
+            self.selected = Some(square);
+            self.dragging = true;
+            return InputEvent::Selected(square);
+        }
+        return self.deselect();
+    }
+
+    /// Handles the pointer being released on the square. Dropping a dragged piece on a destination enters the move,
+    /// dropping it back on its square keeps it selected for a click on the destination, unless it was already
+    /// selected, and dropping it anywhere else clears the selection.
+    pub fn release(&mut self, game: &Game, square: impl TryInto<Square>) -> InputEvent {
+        let from = match self.selected {
+            Some(from) if self.dragging => from,
+            _ => return InputEvent::Ignored
+        };
+        self.dragging = false;
+        let square = match square::to_square(square) {
+            Some(square) => square,
+            None => return self.deselect()
+        };
+        if square == from {
+            if self.was_selected {
+                return self.deselect();
+            }
+            return InputEvent::Ignored;
+        }
+        if self.destinations(game).contains(&square) {
+            return self.enter_move(game, from, square);
+        }
+        return self.deselect();
+    }
+
+    /// Returns the move between the squares with the promotion chosen by the policy and clears the selection
+    fn enter_move(&mut self, game: &Game, from: Square, to: Square) -> InputEvent {
+        self.clear();
+        let moves: Vec<Move> = game.get_legal_moves(from).into_iter().filter(|mv| mv.to_square() == to).collect();
+        if !moves[0].is_promotion() {
+            return InputEvent::Move(moves[0]);
+        }
+
+        let strongest_first = [
+            Promotion::Queen, Promotion::Rook, Promotion::Bishop, Promotion::Knight, Promotion::King
+        ];
+        let allowed: Vec<Promotion> = strongest_first.into_iter()
+            .filter(|&promotion| moves.iter().any(|mv| mv.promotion() == Some(promotion)))
+            .collect();
+        let promotion = match &mut self.promotion_policy {
+            PromotionPolicy::AlwaysQueen => Some(allowed[0]),
+            PromotionPolicy::Ask(ask) => ask(from, to, &allowed)
+        };
+        return match moves.into_iter().find(|mv| promotion.is_some() && mv.promotion() == promotion) {
+            Some(mv) => InputEvent::Move(mv),
+            None => InputEvent::Deselected
+        };
+    }
+
+    fn deselect(&mut self) -> InputEvent {
+        let had_selection = self.selected.is_some();
+        self.clear();
+        if had_selection {
+            return InputEvent::Deselected;
+        }
+        return InputEvent::Ignored;
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{Game, MoveGenerator, Square, BLACK, EMPTY, KING, PAWN, ROOK, WHITE};
+
+/// An internal inconsistency of a [Game] found by [Game::check_invariants]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InvariantError {
+    /// The turn is neither WHITE nor BLACK
+    InvalidTurn(usize),
+    /// A color doesn't have exactly one king
+    KingCount { color: usize, count: usize },
+    /// The stored king square of a color doesn't hold its king
+    KingSquare { color: usize, square: Square },
+    /// A pawn stands on the first or last rank
+    PawnOnBackRank(Square),
+    /// The pawn that can be captured en passant isn't a pawn of the side not to move that just made a double push
+    EnPassant(Square),
+    /// A rook is marked as unmoved even though its color could never castle with it
+    UnmovedRook(Square),
+    /// The side that just moved is in check
+    OpponentInCheck,
+    /// The cached legal moves differ from the legal moves generated again
+    StaleCache
+}
+
+impl std::fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InvariantError::InvalidTurn(turn) => write!(f, "the turn {} is not a color", turn),
+            InvariantError::KingCount { color, count } => write!(f, "color {} has {} kings", color, count),
+            InvariantError::KingSquare { color, square } => write!(f, "the king of color {} is not on {}", color, square),
+            InvariantError::PawnOnBackRank(square) => write!(f, "pawn on the back rank at {}", square),
+            InvariantError::EnPassant(square) => write!(f, "no pawn on {} can be captured en passant", square),
+            InvariantError::UnmovedRook(square) => write!(f, "the rook on {} is marked as unmoved but can't castle", square),
+            InvariantError::OpponentInCheck => write!(f, "the side not to move is in check"),
+            InvariantError::StaleCache => write!(f, "the cached legal moves are out of date")
+        }
+    }
+}
+
+impl std::error::Error for InvariantError {}
+
+impl Game {
+
+    /// Checks that the internal state of the game is consistent, meant for fuzzing code that builds on the crate.
+    /// Returns the first inconsistency found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let mut game = Game::starting_position();
+    /// game.make_move_from_to(52, 36, None);
+    /// assert_eq!(game.check_invariants(), Ok(()));
+    ///
+    /// game.board[60] = game.board[59];
+    /// assert_eq!(game.check_invariants(), Err(InvariantError::KingCount { color: WHITE, count: 0 }));
+    /// ```
+    pub fn check_invariants(&self) -> Result<(), InvariantError> {
+        if self.turn != WHITE && self.turn != BLACK {
+            return Err(InvariantError::InvalidTurn(self.turn));
+        }
+
+        for color in [WHITE, BLACK] {
+            let count = (0..64).filter(|&square|
+                self.board[square].get_type() == KING && self.board[square].get_color() == color).count();
+            if count != 1 {
+                return Err(InvariantError::KingCount { color, count });
+            }
+            let king = self.board[self.king_square[color]];
+            if king.get_type() != KING || king.get_color() != color {
+                return Err(InvariantError::KingSquare { color, square: Square::new(self.king_square[color]) });
+            }
+        }
+
+        for square in (0..8).chain(56..64) {
+            if self.board[square].get_type() == PAWN {
+                return Err(InvariantError::PawnOnBackRank(Square::new(square)));
+            }
+        }
+
+        if self.possible_ep_capture < 64 {
+            let pawn = self.board[self.possible_ep_capture];
+            let double_push_row;
+            let behind;
+            if self.turn == BLACK {
+                double_push_row = 4;
+                behind = [self.possible_ep_capture + 8, self.possible_ep_capture + 16];
+            }
+            else {
+                double_push_row = 3;
+                behind = [self.possible_ep_capture - 8, self.possible_ep_capture - 16];
+            }
+            if pawn.get_type() != PAWN || pawn.get_color() == self.turn || self.get_row(self.possible_ep_capture) != double_push_row ||
+                behind.iter().any(|&square| self.board[square].get_type() != EMPTY) {
+                return Err(InvariantError::EnPassant(Square::new(self.possible_ep_capture)));
+            }
+        }
+
+        // Unmoved rooks stand on their back rank, and while the king hasn't moved there is at most one on each side
+        let mut unmoved_rooks = [[0; 2]; 2];
+        for square in 0..64 {
+            let rook = self.board[square];
+            if rook.get_type() != ROOK || rook.has_moved() {
+                continue;
+            }
+            let color = rook.get_color();
+            let back_row;
+            if color == WHITE {
+                back_row = 7;
+            }
+            else {
+                back_row = 0;
+            }
+            if self.get_row(square) != back_row {
+                return Err(InvariantError::UnmovedRook(Square::new(square)));
+            }
+            // Once the king has moved the rooks can no longer castle and which side they were on is unknown
+            let king_square = self.king_square[color];
+            if self.board[king_square].has_moved() || self.get_row(king_square) != back_row {
+                continue;
+            }
+            let side = (self.get_column(square) < self.get_column(king_square)) as usize;
+            unmoved_rooks[color][side] += 1;
+            if unmoved_rooks[color][side] > 1 {
+                return Err(InvariantError::UnmovedRook(Square::new(square)));
+            }
+        }
+
+        let move_gen = MoveGenerator::new();
+        if move_gen.is_attacked(self, self.king_square[self.turn ^ 1], self.turn ^ 1) {
+            return Err(InvariantError::OpponentInCheck);
+        }
+
+        if let Some(legal_moves) = self.cache.get(&self.position_key()).and_then(|cached| cached.legal_moves) {
+            if *legal_moves != *self.generate_all_legal_moves() {
+                return Err(InvariantError::StaleCache);
+            }
+        }
+        return Ok(());
+    }
+}
@@ -0,0 +1,196 @@
+//! A referee for Kriegspiel, chess where neither player sees the opponent's pieces
+//!
+//! The [Referee] keeps the true game hidden. Players try moves, which the referee either rejects as illegal, in
+//! which case the player tries again, or makes and answers with the public [Announcement]s: where a piece was
+//! captured, from which directions the king is checked and how many captures the pawns of the side to move can make.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::kriegspiel::{Answer, CheckDirection, Referee};
+//! let mut referee = Referee::new();
+//! assert!(matches!(referee.try_move(52, 36, None), Answer::Legal(_)));
+//! assert!(matches!(referee.try_move(13, 29, None), Answer::Legal(_)));
+//! // White doesn't know the f-pawn moved, so the queen's path to h5 could have been blocked
+//! match referee.try_move(59, 31, None) {
+//!     Answer::Legal(announcement) => {
+//!         assert_eq!(announcement.checks, vec![CheckDirection::ShortDiagonal]);
+//!         assert_eq!(announcement.capture, None);
+//!     }
+//!     _ => panic!()
+//! }
+//! // Black can't capture the queen, the king has to block or move
+//! assert_eq!(referee.try_move(12, 28, None), Answer::Illegal);
+//! assert_eq!(referee.try_move(50, 42, None), Answer::Impossible);
+//! ```
+
+use crate::{square, Game, GameState, Promotion, Square, VisibilityRules, BoardView, EMPTY, KNIGHT, PAWN};
+
+/// The line along which a check is given, as announced by the referee
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CheckDirection {
+    Rank,
+    File,
+    /// The longer of the two diagonals through the king's square
+    LongDiagonal,
+    /// The shorter of the two diagonals through the king's square
+    ShortDiagonal,
+    Knight
+}
+
+/// What the referee announces to both players after a legal move
+#[derive(Clone, Debug, PartialEq)]
+pub struct Announcement {
+    /// The square a piece was captured on. For an en passant capture it is the square of the captured pawn.
+    pub capture: Option<Square>,
+    /// The directions the side to move is checked from, two for a double check
+    pub checks: Vec<CheckDirection>,
+    /// The number of legal captures the pawns of the side to move can make
+    pub pawn_tries: usize,
+    /// The state of the game after the move
+    pub game_state: GameState
+}
+
+/// The referee's answer to a move attempt
+#[derive(Clone, Debug, PartialEq)]
+pub enum Answer {
+    /// The move was made
+    Legal(Announcement),
+    /// The move is illegal in the true position and the player has to try another one
+    Illegal,
+    /// The move can't be right even from what the player sees, there is no piece of theirs on the square it starts
+    /// from or the game is over. Not announced to the opponent.
+    Impossible
+}
+
+/// Keeps the true game of a Kriegspiel game and judges the moves of the players
+pub struct Referee {
+    game: Game
+}
+
+impl Default for Referee {
+    fn default() -> Referee {
+        Referee::new()
+    }
+}
+
+impl Referee {
+
+    /// Creates a referee for a game from the starting position
+    pub fn new() -> Referee {
+        return Referee::from_game(Game::starting_position());
+    }
+
+    /// Creates a referee for a game from the given position
+    pub fn from_game(game: Game) -> Referee {
+        Referee {
+            game
+        }
+    }
+
+    /// Returns the color to move
+    pub fn turn(&self) -> usize {
+        return self.game.turn;
+    }
+
+    /// Returns what the given player sees of the board, their own pieces
+    pub fn view(&self, color: usize) -> BoardView {
+        return self.game.view_for(color, VisibilityRules::OWN_PIECES);
+    }
+
+    /// Returns the true game, which should only be shown to the players once the game is over
+    pub fn game(&self) -> &Game {
+        return &self.game;
+    }
+
+    /// Tries a move for the side to move. 'promotion' is used the same way as in [Game::make_move_from_to].
+    pub fn try_move(&mut self, from: impl TryInto<Square>, to: impl TryInto<Square>,
+                    promotion: impl Into<Option<Promotion>>) -> Answer {
+        let (from, to) = match (square::to_square(from), square::to_square(to)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return Answer::Impossible
+        };
+        let promotion = promotion.into();
+        let piece = self.game.board[from.index()];
+        if piece.get_type() == EMPTY || piece.get_color() != self.game.turn || self.game.legal_moves_cached().is_empty() {
+            return Answer::Impossible;
+        }
+
+        let mv = match self.game.legal_moves_cached().iter().find(|mv| mv.from_square() == from &&
+            mv.to_square() == to && (!mv.is_promotion() || mv.promotion() == promotion)) {
+            Some(mv) => *mv,
+            None => return Answer::Illegal
+        };
+
+        let capture;
+        if mv.is_ep_capture() {
+            capture = Some(Square::from_column_row(to.column(), from.row()));
+        }
+        else if mv.is_capture() {
+            capture = Some(to);
+        }
+        else {
+            capture = None;
+        }
+        self.game.make_move(mv);
+
+        return Answer::Legal(Announcement {
+            capture,
+            checks: self.check_directions(),
+            pawn_tries: self.pawn_tries(),
+            game_state: self.game.get_game_state()
+        });
+    }
+
+    /// Returns the number of legal captures the pawns of the side to move can make
+    pub fn pawn_tries(&self) -> usize {
+        return self.game.legal_moves_cached().iter()
+            .filter(|mv| mv.is_capture() && self.game.board[mv.get_from()].get_type() == PAWN)
+            .count();
+    }
+
+    fn check_directions(&self) -> Vec<CheckDirection> {
+        let king = self.game.king_square(self.game.turn);
+        let mut checks = vec![];
+        for checker in self.game.checkers() {
+            let direction;
+            if self.game.board[checker.index()].get_type() == KNIGHT {
+                direction = CheckDirection::Knight;
+            }
+            else if checker.row() == king.row() {
+                direction = CheckDirection::Rank;
+            }
+            else if checker.column() == king.column() {
+                direction = CheckDirection::File;
+            }
+            else {
+                // Diagonals where the column minus the row stays the same hold 8 - |column - row| squares,
+                // the others 8 - |column + row - 7|
+                let column = king.column() as isize;
+                let row = king.row() as isize;
+                let falling_length = 8 - (column - row).abs();
+                let rising_length = 8 - (column + row - 7).abs();
+                let falling = checker.column() as isize - column == checker.row() as isize - row;
+                let length;
+                let other_length;
+                if falling {
+                    length = falling_length;
+                    other_length = rising_length;
+                }
+                else {
+                    length = rising_length;
+                    other_length = falling_length;
+                }
+                if length > other_length {
+                    direction = CheckDirection::LongDiagonal;
+                }
+                else {
+                    direction = CheckDirection::ShortDiagonal;
+                }
+            }
+            checks.push(direction);
+        }
+        return checks;
+    }
+}
@@ -3,21 +3,179 @@
 //! ## How to use:
 //! The chess game is handled within the [Game] struct.
 //! It can be initialized to the starting position with [Game::starting_position] or
-//! set to any position from a FEN string with [Game::new]. Squares on the board are indexed from 0-63 
-//! and can be accessed with [Game::board].
+//! set to any position from a FEN string with [Game::new]. Squares on the board are indexed from 0-63, 
+//! starting at a8, and can be accessed with [Game::board]. Functions taking a square accept either such an index
+//! or a [Square], which can also be created from and converted to indices starting at a1.
+//! `use olindba_chess::prelude::*` imports these together with the color and piece constants and the common
+//! notation functions, see [prelude].
 //! ### Make moves on the board:
 //! * The function [Game::make_move_from_to] can be used without first generating legal moves, 
-//! but if the move is illegal the game will ignore it. Note that the user has to know if the move is a promotion
-//! and then pass the decided promotion to the function. To avoid this, [Game::make_move] can be used.
+//!   but if the move is illegal the game will ignore it and the returned [MoveOutcome] tells why. Note that the
+//!   user has to know if the move is a promotion and then pass the decided [Promotion] to the function. To avoid
+//!   this, [Game::make_move] can be used.
 //! * The function [Game::make_move] takes a move that has already been generated by either [Game::get_all_legal_moves]
-//! or [Game::get_legal_moves] and updates the board accordingly. 
-//! The user can check if the move is a promotion with [Move::is_promotion]
+//!   or [Game::get_legal_moves] and updates the board accordingly. 
+//!   The user can check if the move is a promotion with [Move::is_promotion]
 //! 
 //! ### Current game state
 //! The function [Game::get_game_state] can be called at any moment and returns the current game state.
 //! In the case of draw by 50-move rule or draw by insufficient material, moves can still be generated and made
 //! but this funtion will continuously return Draw and what type of draw 
 //! 
+//! Fivefold repetition and the 75-move rule end the game automatically, so once either is reached no legal moves
+//! are generated. This can be turned off with [Game::set_automatic_termination].
+//!
+//! [Game::is_game_over] tells whether the game has ended. After that [Game::make_move_from_to] and
+//! [Game::try_make_move] refuse to make moves, unless the game is in analysis mode, see [Game::set_analysis_mode].
+//! [Game::make_move] is never checked, it is meant for moves just generated in the current position.
+//! 
+//! The limits of the 50- and 75-move rules, what a stalemate means and which promotions are allowed can be changed for
+//! variants and events with [Game::set_rules].
+//! 
+//! ### Caching
+//! The legal moves, check status and game state of the current position are cached inside [Game], so calling
+//! [Game::get_game_state] or [Game::legal_moves_cached] every frame from a GUI is cheap. 
+//! The cache is keyed on the board, turn, king squares, en passant square and halfmove clock, so it is also correct
+//! if [Game::board] or [Game::turn] are modified directly. The game only tracks the kings through its own moves though,
+//! so a position with kings moved by hand has to be set up again with [Game::set_board_state].
+//!
+//! ### Features
+//! * 'rand' adds 'Game::random_legal_move' and 'Game::random_playout' for random playouts with any [rand](https://docs.rs/rand) RNG.
+//! * 'testing' adds the 'testing' module with [proptest](https://docs.rs/proptest) strategies for random games,
+//!   to fuzz code built on the crate against [Game::check_invariants].
+//! * 'rayon' adds 'batch::MoveBatch::par_fill', which generates the moves of many positions on all cores.
+//! * 'serde_json' adds the 'import' module, which reads games from the JSON APIs of lichess and chess.com.
+//! * 'shakmaty' and 'chess' add conversions between [Game], [Move] and [Square] and the types of the
+//!   [shakmaty](https://docs.rs/shakmaty) and [chess](https://docs.rs/chess) crates, to adopt the crate step by step
+//!   or compare its move generation with theirs.
+//! * 'nnue' adds the 'nnue' module, which evaluates positions with the NNUE networks of Stockfish 12.
+//! * 'server' adds the 'server' module, a reference server relaying the moves of two clients as JSON over TCP.
+//! * 'prost' adds the 'proto' module with [protocol buffer](https://protobuf.dev) messages for positions, moves,
+//!   clocks and games, and the schema to generate clients in other languages from.
+//!
+//! ### Castling
+//! Castling follows the Chess960 rules, which include the standard ones. The king always lands on the c or g file
+//! and the rook on the d or f file. In a FEN string the rights can be given as 'KQkq', meaning the outermost rook
+//! on each side of the king, or as the files of the castling rooks like 'HAha'.
+//!
+//! ```
+//! # use olindba_chess::*;
+//! let castles = |fen: &str| {
+//!     let game = Game::new(fen);
+//!     return game.get_all_legal_moves().into_iter().filter(|mv| mv.is_castle()).count();
+//! };
+//! assert_eq!(castles("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"), 2);
+//! // Not out of check, through an attacked square or into check
+//! assert_eq!(castles("r3k2r/8/8/8/8/8/4r3/R3K2R w KQkq - 0 1"), 0);
+//! assert_eq!(castles("r3k2r/8/8/8/8/8/5b2/R3K2R w KQkq - 0 1"), 0);
+//! assert_eq!(castles("r3k2r/8/8/8/8/8/6r1/R3K2R w KQkq - 0 1"), 1);
+//! // b1 may be attacked but has to be empty
+//! assert_eq!(castles("r3k2r/8/8/8/8/8/1r6/R3K2R w KQkq - 0 1"), 2);
+//! assert_eq!(castles("r3k2r/8/8/8/8/8/8/Rn2K2R w KQkq - 0 1"), 1);
+//! // Rights are only kept for the rooks given in the FEN string
+//! assert_eq!(castles("r3k2r/8/8/8/8/8/8/R3K2R w Kkq - 0 1"), 1);
+//! // Chess960, the king already stands on its destination and the rook on b1 shields it from the rook on a1
+//! assert_eq!(castles("4k3/8/8/8/8/8/8/rRK4R w B - 0 1"), 0);
+//! assert_eq!(castles("4k3/8/8/8/8/8/8/1RK3R1 w BG - 0 1"), 2);
+//! // The rook on f1 stands in the way of castling with the rook on g1
+//! assert_eq!(castles("4k3/8/8/8/8/8/8/4KRRr w G - 0 1"), 0);
+//! // The king and the rook swap squares
+//! let mut game = Game::new("4k3/8/8/8/8/8/8/5KR1 w G - 0 1");
+//! let castle = game.get_all_legal_moves().into_iter().find(|mv| mv.is_king_castle()).unwrap();
+//! game.make_move(castle);
+//! assert_eq!(game.board[61].get_type(), ROOK);
+//! assert_eq!(game.board[62].get_type(), KING);
+//! ```
+//!
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+mod square;
+mod bitboard;
+mod diff;
+mod zobrist;
+mod summary;
+mod invariants;
+mod edit;
+mod snapshot;
+mod view;
+mod planes;
+mod move_list;
+mod dead_position;
+mod timeout;
+mod history;
+mod timeline;
+mod castling;
+mod repetition;
+mod piece_list;
+mod shared;
+mod retro;
+mod annotation;
+mod serialization;
+#[cfg(feature = "rand")]
+mod random;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "serde_json")]
+pub mod import;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "prost")]
+pub mod proto;
+#[cfg(feature = "shakmaty")]
+mod shakmaty_conversions;
+#[cfg(feature = "chess")]
+mod chess_conversions;
+#[cfg(feature = "nnue")]
+pub mod nnue;
+pub mod ordering;
+pub mod notation;
+pub mod puzzle;
+pub mod pgn;
+pub mod fen;
+pub mod matchplay;
+pub mod sprt;
+pub mod rating;
+pub mod clock;
+pub mod uci;
+pub mod kriegspiel;
+pub mod input;
+pub mod batch;
+pub mod policy;
+pub mod display;
+pub mod eval;
+pub mod search;
+pub mod engine;
+pub mod transposition;
+pub mod tree;
+pub mod mcts;
+pub mod analysis;
+pub mod tablebase;
+pub mod repertoire;
+pub mod stats;
+pub mod selftest;
+pub mod prelude;
+
+pub use square::{File, Rank, Square, SquareColor, SquareIndexError};
+pub use bitboard::{Bitboard, BitboardIter};
+pub use diff::BoardDelta;
+pub use summary::{GameResult, GameSummary, Termination};
+pub use invariants::InvariantError;
+pub use piece_list::PieceListError;
+pub use snapshot::Snapshot;
+pub use history::{HistoryEntry, PositionSnapshot};
+use eval::{update_for_move, EvalTerms};
+pub use timeline::Timeline;
+pub use castling::{CastleStatus, Wing};
+pub use shared::SharedGame;
+pub use retro::Unmove;
+pub use annotation::{Annotation, Evaluation};
+pub use serialization::DecodeError;
+pub use view::{BoardView, VisibilityRules};
+pub use planes::N_PLANES;
+pub use move_list::MoveList;
+pub use repetition::{DrawCounters, RepetitionTracker};
 
 pub const EMPTY: usize = 0;
 pub const PAWN: usize = 1;
@@ -33,7 +191,7 @@ pub const BLACK: usize = 1;
 const HAS_MOVED: usize = 1;
 
 /// The pieces on the board
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Piece {
     piece: usize
 }
@@ -70,95 +228,486 @@ const KING_CASTLE: usize =			0b0010;
 const QUEEN_CASTLE: usize =			0b0011;
 const CAPTURE: usize =				0b0100;
 const EP_CAPTURE: usize =			0b0101;
-pub const KNIGHT_PROMOTION: usize =	0b1000;
-pub const BISHOP_PROMOTION: usize =	0b1001;
-pub const ROOK_PROMOTION: usize	=	0b1010;
-pub const QUEEN_PROMOTION: usize =	0b1011;
+const KING_PROMOTION_QUIET: usize =	0b0110;
+const KING_PROMOTION_CAP: usize =	0b0111;
+const KNIGHT_PROMOTION_QUIET: usize =	0b1000;
+const BISHOP_PROMOTION_QUIET: usize =	0b1001;
+const ROOK_PROMOTION_QUIET: usize =	0b1010;
+const QUEEN_PROMOTION_QUIET: usize =	0b1011;
 const KNIGHT_PROMOTION_CAP: usize =	0b1100;
 const BISHOP_PROMOTION_CAP: usize =	0b1101;
 const ROOK_PROMOTION_CAP: usize	=	0b1110;
 const QUEEN_PROMOTION_CAP: usize =	0b1111;
 
-#[derive(Copy, Clone)]
+#[deprecated(note = "pass Promotion::King to Game::make_move_from_to, or convert with Promotion::from_flag")]
+pub const KING_PROMOTION: usize = KING_PROMOTION_QUIET;
+#[deprecated(note = "pass Promotion::Knight to Game::make_move_from_to, or convert with Promotion::from_flag")]
+pub const KNIGHT_PROMOTION: usize = KNIGHT_PROMOTION_QUIET;
+#[deprecated(note = "pass Promotion::Bishop to Game::make_move_from_to, or convert with Promotion::from_flag")]
+pub const BISHOP_PROMOTION: usize = BISHOP_PROMOTION_QUIET;
+#[deprecated(note = "pass Promotion::Rook to Game::make_move_from_to, or convert with Promotion::from_flag")]
+pub const ROOK_PROMOTION: usize = ROOK_PROMOTION_QUIET;
+#[deprecated(note = "pass Promotion::Queen to Game::make_move_from_to, or convert with Promotion::from_flag")]
+pub const QUEEN_PROMOTION: usize = QUEEN_PROMOTION_QUIET;
+
+/// The piece a pawn is promoted to, chosen when a move is given by its squares as in [Game::make_move_from_to]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Promotion {
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    /// Only allowed by rules with [PromotionSet::WITH_KING]
+    King
+}
+
+impl Promotion {
+
+    /// Returns the piece type of the promoted piece, such as QUEEN
+    pub fn piece_type(&self) -> usize {
+        return match self {
+            Promotion::Knight => KNIGHT,
+            Promotion::Bishop => BISHOP,
+            Promotion::Rook => ROOK,
+            Promotion::Queen => QUEEN,
+            Promotion::King => KING
+        };
+    }
+
+    /// Returns the promotion to the piece type, or None if pawns can't be promoted to it
+    pub fn from_piece_type(piece_type: usize) -> Option<Promotion> {
+        return match piece_type {
+            KNIGHT => Some(Promotion::Knight),
+            BISHOP => Some(Promotion::Bishop),
+            ROOK => Some(Promotion::Rook),
+            QUEEN => Some(Promotion::Queen),
+            KING => Some(Promotion::King),
+            _ => None
+        };
+    }
+
+    /// Returns the promotion of one of the deprecated constants such as [QUEEN_PROMOTION], or None for anything else
+    /// like the EMPTY that was passed for moves without a promotion. Eases moving calls of
+    /// [Game::make_move_from_to] written for the constants to [Promotion].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![allow(deprecated)]
+    /// # use olindba_chess::*;
+    /// let mut game = Game::new("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    /// assert!(game.make_move_from_to(8, 0, Promotion::from_flag(QUEEN_PROMOTION)).is_applied());
+    /// assert_eq!(Promotion::from_flag(EMPTY), None);
+    /// ```
+    #[deprecated(note = "use Promotion directly")]
+    pub fn from_flag(flag: usize) -> Option<Promotion> {
+        #[allow(deprecated)]
+        return match flag {
+            KNIGHT_PROMOTION => Some(Promotion::Knight),
+            BISHOP_PROMOTION => Some(Promotion::Bishop),
+            ROOK_PROMOTION => Some(Promotion::Rook),
+            QUEEN_PROMOTION => Some(Promotion::Queen),
+            KING_PROMOTION => Some(Promotion::King),
+            _ => None
+        };
+    }
+}
+
+/// A move packed into 16 bits, see [Move::to_u16] for the layout
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Move {
-    chess_move: usize
+    chess_move: u16
 }
 
 impl Move {
 
     fn new(from: usize, to: usize, flags: usize) -> Move {
         Move {
-            chess_move: ((flags & 0xf) << 12) | ((from & 0x3f) << 6) | (to & 0x3f)
+            chess_move: (((flags & 0xf) << 12) | ((from & 0x3f) << 6) | (to & 0x3f)) as u16
         }
     }
     
-    pub fn get_to(&self) -> usize { return self.chess_move & 0x3f; }
-	pub fn get_from(&self) -> usize { return (self.chess_move >> 6) & 0x3f; }
-	fn get_flags(&self) -> usize { return (self.chess_move >> 12) & 0x0f; }
+    pub fn get_to(&self) -> usize { return (self.chess_move & 0x3f) as usize; }
+	pub fn get_from(&self) -> usize { return ((self.chess_move >> 6) & 0x3f) as usize; }
+	pub fn to_square(&self) -> Square { return Square::new(self.get_to()); }
+	pub fn from_square(&self) -> Square { return Square::new(self.get_from()); }
+	fn get_flags(&self) -> usize { return ((self.chess_move >> 12) & 0x0f) as usize; }
+
+    /// Returns the move encoded as 16 bits, meant for sending moves between two instances of this crate.
+    /// 
+    /// The encoding is stable across versions of the crate:
+    /// * bits 0-5: the square the move is made to
+    /// * bits 6-11: the square the move is made from
+    /// * bits 12-15: the move flags, 0 quiet move, 1 double pawn push, 2 king castle, 3 queen castle, 4 capture,
+    ///   5 en passant capture, 6 king promotion, 7 king promotion with a capture, 8-11 knight/bishop/rook/queen
+    ///   promotion and 12-15 the same promotions with a capture
+    /// 
+    /// Squares are indexed the same way as [Game::board], from 0 (a8) to 63 (h1).
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::starting_position();
+    /// let e2e4 = game.get_legal_moves(52).into_iter().find(|mv| mv.get_to() == 36).unwrap();
+    /// assert_eq!(e2e4.to_u16(), 0x1d24);
+    /// assert_eq!(Move::from_u16(0x1d24), Some(e2e4));
+    /// ```
+    pub fn to_u16(&self) -> u16 {
+        return self.chess_move;
+    }
+
+    /// Decodes a move encoded by [Move::to_u16]. Returns None if the squares can't go with the flags in any
+    /// position, such as a move to its own square, a castling that doesn't end on the c or g file of a back rank, or
+    /// a promotion that doesn't reach the last rank. Whether the move is legal in a position isn't checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// // e2e4 as a double pawn push, a quiet move and an en passant capture
+    /// assert!(Move::from_u16(0x1d24).is_some());
+    /// assert!(Move::from_u16(0x0d24).is_some());
+    /// assert_eq!(Move::from_u16(0x5d24), None);
+    /// // a8a8
+    /// assert_eq!(Move::from_u16(0x0000), None);
+    /// ```
+    pub fn from_u16(encoded: u16) -> Option<Move> {
+        let mv = Move { chess_move: encoded };
+        let (from, to) = (mv.get_from(), mv.get_to());
+        let (from_row, to_row) = (from / 8, to / 8);
+        let column_distance = (from % 8).abs_diff(to % 8);
+        let possible = match mv.get_flags() {
+            DOUBLE_PAWN_PUSH => column_distance == 0
+                && ((from_row == 6 && to_row == 4) || (from_row == 1 && to_row == 3)),
+            // In Chess960 the king can already stand on the square it castles to
+            KING_CASTLE => from_row == to_row && (from_row == 0 || from_row == 7) && to % 8 == 6,
+            QUEEN_CASTLE => from_row == to_row && (from_row == 0 || from_row == 7) && to % 8 == 2,
+            EP_CAPTURE => column_distance == 1 && ((from_row == 3 && to_row == 2) || (from_row == 4 && to_row == 5)),
+            _ if mv.is_promotion() => column_distance == mv.is_capture() as usize
+                && ((from_row == 1 && to_row == 0) || (from_row == 6 && to_row == 7)),
+            _ => from != to
+        };
+        if !possible {
+            return None;
+        }
+        return Some(mv);
+    }
 
-	pub fn is_capture(&self) -> bool { return self.get_flags() & CAPTURE != 0; }
-	pub fn is_promotion(&self) -> bool { return self.get_flags() & (1 << 3) != 0; }
+	pub fn is_capture(&self) -> bool { return self.get_flags() & CAPTURE != 0 && self.get_flags() != KING_PROMOTION_QUIET; }
+	pub fn is_promotion(&self) -> bool { return self.get_flags() & (1 << 3) != 0 || self.get_flags() >> 1 == 0b011; }
 	pub fn is_ep_capture(&self) -> bool { return self.get_flags() == EP_CAPTURE; }
 	pub fn is_castle(&self) -> bool { return (self.get_flags() | 1) == QUEEN_CASTLE; }
 	pub fn is_double_pawn_push(&self) -> bool { return self.get_flags() == DOUBLE_PAWN_PUSH; }
 	pub fn is_queen_castle(&self) -> bool { return self.get_flags() == QUEEN_CASTLE; }
 	pub fn is_king_castle(&self) -> bool { return self.get_flags() == KING_CASTLE; }
+
+    /// Returns the piece type a pawn is promoted to, or EMPTY if the move isn't a promotion
+    pub fn get_promotion_type(&self) -> usize {
+        if !self.is_promotion() {
+            return EMPTY;
+        }
+        if self.get_flags() & (1 << 3) == 0 {
+            return KING;
+        }
+        return KNIGHT + (self.get_flags() & 0b11);
+    }
+
+    /// Returns the promotion of the move, or None if it isn't a promotion
+    pub fn promotion(&self) -> Option<Promotion> {
+        return Promotion::from_piece_type(self.get_promotion_type());
+    }
+
+    /// Returns what kind of move this is, as one enum to match on instead of the 'is_' functions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::new("r3k3/1P6/8/3pP3/8/8/8/4K2R w Kq d6 0 1");
+    /// let mut kinds = vec![];
+    /// for mv in game.get_all_legal_moves() {
+    ///     match mv.kind() {
+    ///         MoveKind::Promotion { piece, capture: true } => kinds.push(format!("capture promotion {}", piece)),
+    ///         MoveKind::Promotion { .. } => kinds.push("promotion".to_string()),
+    ///         MoveKind::EnPassant => kinds.push("en passant".to_string()),
+    ///         MoveKind::CastleKingside => kinds.push("castle".to_string()),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// assert_eq!(kinds.iter().filter(|kind| kind.starts_with("capture promotion")).count(), 4);
+    /// assert_eq!(kinds.iter().filter(|kind| *kind == "promotion").count(), 4);
+    /// assert!(kinds.contains(&"en passant".to_string()));
+    /// assert!(kinds.contains(&"castle".to_string()));
+    /// ```
+    pub fn kind(&self) -> MoveKind {
+        return match self.get_flags() {
+            QUIET_MOVE => MoveKind::Quiet,
+            DOUBLE_PAWN_PUSH => MoveKind::DoublePush,
+            KING_CASTLE => MoveKind::CastleKingside,
+            QUEEN_CASTLE => MoveKind::CastleQueenside,
+            CAPTURE => MoveKind::Capture,
+            EP_CAPTURE => MoveKind::EnPassant,
+            _ => MoveKind::Promotion {
+                piece: self.get_promotion_type(),
+                capture: self.is_capture()
+            }
+        };
+    }
+}
+
+/// Moves are ordered by the square they are made from, then by the square they are made to, both in the order of
+/// [Square] where a8 comes first and h1 last, then by the piece promoted to in the order of the piece constants.
+/// In Chess960 a king can castle to a square it could also step to, the castling move comes after the step.
+///
+/// Legal moves are always generated in this order, so lists of moves are the same on every platform and in
+/// every version of the crate.
+impl Ord for Move {
+    fn cmp(&self, other: &Move) -> std::cmp::Ordering {
+        let key = |mv: &Move| (mv.get_from(), mv.get_to(), mv.get_promotion_type(), mv.get_flags());
+        return key(self).cmp(&key(other));
+    }
+}
+
+impl PartialOrd for Move {
+    fn partial_cmp(&self, other: &Move) -> Option<std::cmp::Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+/// The kind of a [Move], returned by [Move::kind]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MoveKind {
+    Quiet,
+    /// A pawn moving two squares from its starting rank
+    DoublePush,
+    CastleKingside,
+    CastleQueenside,
+    /// A capture other than en passant or a promotion, the captured piece is on [Move::get_to] before the move
+    Capture,
+    EnPassant,
+    /// A promotion to 'piece', which is KNIGHT, BISHOP, ROOK or QUEEN, or KING if the rules allow it
+    Promotion { piece: usize, capture: bool }
 }
 
+/// How a move checks the opponent's king, see [Game::check_kind]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CheckKind {
+    None,
+    /// The piece that moved gives check, for castling the rook
+    Direct,
+    /// The move uncovers a check from another piece, or the captured pawn uncovers one in an en passant capture
+    Discovered,
+    /// Two pieces give check at once, which the opponent can only answer with a king move
+    Double
+}
 
 struct Mailbox {
     mailbox64: [usize; 64],
     mailbox120: [isize; 120]
 }
 
+/// The mailbox tables, shared by every move generator instead of being built for each call
+static MAILBOX: Mailbox = {
+    let mailbox64 = [
+        21, 22, 23, 24, 25, 26, 27, 28,
+        31, 32, 33, 34, 35, 36, 37, 38,
+        41, 42, 43, 44, 45, 46, 47, 48,
+        51, 52, 53, 54, 55, 56, 57, 58,
+        61, 62, 63, 64, 65, 66, 67, 68,
+        71, 72, 73, 74, 75, 76, 77, 78,
+        81, 82, 83, 84, 85, 86, 87, 88,
+        91, 92, 93, 94, 95, 96, 97, 98
+    ];
+
+    let mailbox120 = [
+        -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+        -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+        -1,  0,  1,  2,  3,  4,  5,  6,  7, -1,
+        -1,  8,  9, 10, 11, 12, 13, 14, 15, -1,
+        -1, 16, 17, 18, 19, 20, 21, 22, 23, -1,
+        -1, 24, 25, 26, 27, 28, 29, 30, 31, -1,
+        -1, 32, 33, 34, 35, 36, 37, 38, 39, -1,
+        -1, 40, 41, 42, 43, 44, 45, 46, 47, -1,
+        -1, 48, 49, 50, 51, 52, 53, 54, 55, -1,
+        -1, 56, 57, 58, 59, 60, 61, 62, 63, -1,
+        -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+        -1, -1, -1, -1, -1, -1, -1, -1, -1, -1
+    ];
+
+    Mailbox {
+        mailbox64,
+        mailbox120
+    }
+};
+
 impl Mailbox {
 
-    fn new() -> Mailbox {
-        let mailbox64 = [
-            21, 22, 23, 24, 25, 26, 27, 28,
-            31, 32, 33, 34, 35, 36, 37, 38,
-            41, 42, 43, 44, 45, 46, 47, 48,
-            51, 52, 53, 54, 55, 56, 57, 58,
-            61, 62, 63, 64, 65, 66, 67, 68,
-            71, 72, 73, 74, 75, 76, 77, 78,
-            81, 82, 83, 84, 85, 86, 87, 88,
-            91, 92, 93, 94, 95, 96, 97, 98
-        ];
+    fn new() -> &'static Mailbox {
+        return &MAILBOX;
+    }
 
-        let mailbox120 = [
-            -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-            -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-            -1,  0,  1,  2,  3,  4,  5,  6,  7, -1,
-            -1,  8,  9, 10, 11, 12, 13, 14, 15, -1,
-            -1, 16, 17, 18, 19, 20, 21, 22, 23, -1,
-            -1, 24, 25, 26, 27, 28, 29, 30, 31, -1,
-            -1, 32, 33, 34, 35, 36, 37, 38, 39, -1,
-            -1, 40, 41, 42, 43, 44, 45, 46, 47, -1,
-            -1, 48, 49, 50, 51, 52, 53, 54, 55, -1,
-            -1, 56, 57, 58, 59, 60, 61, 62, 63, -1,
-            -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
-            -1, -1, -1, -1, -1, -1, -1, -1, -1, -1
-        ];
+    fn get_square_with_offset(&self, from: usize, offset: isize) -> isize {
+        return self.mailbox120[(self.mailbox64[from] as isize + offset) as usize];
+    }
+}
+
+/// Checkers and pins of the side to move, as masks with one bit per square
+struct CheckInfo {
+    n_checkers: usize,
+    check_mask: u64,
+    pin_ray: [u64; 64]
+}
+
+/// The squares from which each piece type of the side to move would attack the opponent's king, and for each piece 
+/// of the side to move standing between the king and one of its own sliding pieces, the ray it has to stay on 
+/// to not uncover a check
+struct CheckSquares {
+    direct: [u64; 7],
+    discovered_ray: [u64; 64]
+}
+
+/// A legal move together with the piece it captures and whether it gives check
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MoveInfo {
+    pub mv: Move,
+    /// The type of the captured piece, EMPTY if the move isn't a capture
+    pub captured: usize,
+    pub gives_check: bool
+}
+
+/// A set of piece types pawns can be promoted to
+///
+/// A king promoted to, as in Antichess, is an ordinary piece moving like a king: checks, checkmate and castling only
+/// concern the king the side started with. Positions with more than one king of a color can't be read from FEN.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::notation::parse_uci;
+/// let mut game = Game::new("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+/// assert_eq!(game.get_legal_moves(8).len(), 4);
+///
+/// game.set_rules(Rules { promotions: PromotionSet::WITH_KING, ..Default::default() });
+/// assert_eq!(game.get_legal_moves(8).len(), 5);
+/// let mv = parse_uci(&game, "a7a8k").unwrap();
+/// assert_eq!(mv.kind(), MoveKind::Promotion { piece: KING, capture: false });
+/// game.make_move(mv);
+/// assert_eq!(game.king_square(WHITE), Square::E1);
+/// assert_eq!(Move::from_u16(mv.to_u16()), Some(mv));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PromotionSet {
+    pieces: u8
+}
 
-        return Mailbox {
-            mailbox64,
-            mailbox120
+impl PromotionSet {
+
+    /// Knight, bishop, rook and queen
+    pub const ALL: PromotionSet = PromotionSet { pieces: (1 << KNIGHT) | (1 << BISHOP) | (1 << ROOK) | (1 << QUEEN) };
+    /// Only queen promotions, for engines that never underpromote
+    pub const QUEEN_ONLY: PromotionSet = PromotionSet { pieces: 1 << QUEEN };
+    /// Knight, bishop, rook, queen and king, as in Antichess
+    pub const WITH_KING: PromotionSet = PromotionSet { pieces: PromotionSet::ALL.pieces | (1 << KING) };
+
+    /// Creates a set of the given piece types, types pawns can't be promoted to are ignored
+    pub fn new(piece_types: &[usize]) -> PromotionSet {
+        let mut pieces = 0;
+        for &piece_type in piece_types {
+            if (KNIGHT..=KING).contains(&piece_type) {
+                pieces |= 1 << piece_type;
+            }
         }
+        return PromotionSet { pieces };
     }
 
-    fn get_square_with_offset(&self, from: usize, offset: isize) -> isize {
-        return self.mailbox120[(self.mailbox64[from] as isize + offset) as usize];
+    /// Returns true if the piece type is in the set
+    pub fn contains(&self, piece_type: usize) -> bool {
+        return piece_type < 8 && self.pieces & (1 << piece_type) != 0;
+    }
+}
+
+impl Default for PromotionSet {
+    fn default() -> PromotionSet {
+        PromotionSet::ALL
+    }
+}
+
+/// Options for move generation, the default generates every legal move
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MoveGenOptions {
+    /// The piece types promotions are generated for
+    pub promotions: PromotionSet,
+    /// Only captures are generated when there is a legal capture, as in Antichess
+    pub mandatory_capture: bool
+}
+
+/// What a stalemate means for the game
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum StalemateOutcome {
+    #[default]
+    Draw,
+    /// The stalemated side wins
+    Win,
+    /// The stalemated side loses
+    Loss
+}
+
+/// Which rule decides whether running out of time loses, see [Game::can_force_mate]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TimeoutRule {
+    /// FIDE Article 6.9: the game is drawn if the opponent can't checkmate by any series of legal moves
+    #[default]
+    Fide,
+    /// US Chess rule 14E: the game is drawn if the opponent has a lone king, a king and a minor piece or a king and
+    /// two knights, without pawns
+    Uscf
+}
+
+/// The parameters of the rules that events and variants may change, the default is standard chess
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    /// The halfmove clock value from which the game is reported as [GameState::DrawBy50MoveRule], 100 by default
+    pub claimable_draw_halfmoves: usize,
+    /// The halfmove clock value from which the game is reported as [GameState::DrawBy75MoveRule], 150 by default
+    pub automatic_draw_halfmoves: usize,
+    pub stalemate: StalemateOutcome,
+    /// The piece types pawns may be promoted to
+    pub promotions: PromotionSet,
+    /// When running out of time is a loss rather than a draw
+    pub timeout: TimeoutRule,
+    /// Captures are compulsory when one is available, as in Antichess, see [MoveGenOptions::mandatory_capture]
+    pub mandatory_capture: bool
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules {
+            claimable_draw_halfmoves: 100,
+            automatic_draw_halfmoves: 150,
+            stalemate: StalemateOutcome::Draw,
+            promotions: PromotionSet::ALL,
+            timeout: TimeoutRule::Fide,
+            mandatory_capture: false
+        }
     }
 }
 
 struct MoveGenerator {
     piece_offset: [[isize; 8]; 6],
     piece_offsets: [usize; 6],
-    sliding_piece: [bool; 6]
+    sliding_piece: [bool; 6],
+    promotions: PromotionSet,
+    mandatory_capture: bool
 }
 
 impl MoveGenerator {
     fn new() -> MoveGenerator {
+        return MoveGenerator::with_options(&MoveGenOptions::default());
+    }
+
+    fn with_options(options: &MoveGenOptions) -> MoveGenerator {
         let piece_offset = [
             [   0,   0,  0,  0, 0,  0,  0,  0 ], // EMPTY
 		    [ -21, -19,-12, -8, 8, 12, 19, 21 ], // KNIGHT
@@ -173,25 +722,26 @@ impl MoveGenerator {
         return MoveGenerator { 
             piece_offset,
             piece_offsets,
-            sliding_piece
+            sliding_piece,
+            promotions: options.promotions,
+            mandatory_capture: options.mandatory_capture
          }
     }
 
-    fn generate_pseudo_legal_moves(&self, game: &Game, square: usize) -> Vec<Move> {
+    /// Calls 'emit' for every pseudo legal move of the piece on the given square, if it belongs to the side to move
+    fn generate_pseudo_legal_moves(&self, game: &Game, square: usize, emit: &mut impl FnMut(Move)) {
         if game.board[square].get_type() == EMPTY || game.board[square].get_color() != game.turn {
-            return vec![];
+            return;
         }
         if game.board[square].get_type() == PAWN {
-            return self.generate_pawn_moves(game, square);
+            self.generate_pawn_moves(game, square, emit);
         }
         else {
-            return self.generate_non_pawn_moves(game, square);
+            self.generate_non_pawn_moves(game, square, emit);
         }
     }
 
-    fn generate_pawn_moves(&self, game: &Game, square: usize) -> Vec<Move> {
-        let mut pseudo_legal_moves = vec![];
-
+    fn generate_pawn_moves(&self, game: &Game, square: usize, emit: &mut impl FnMut(Move)) {
         let forward_offset: isize;
         if game.turn == WHITE {
             forward_offset = -8;
@@ -203,75 +753,71 @@ impl MoveGenerator {
         let next_square = square as isize + forward_offset;
         if game.get_row(next_square as usize) == 0 || game.get_row(next_square as usize) == 7 {
             if self.pawn_can_capture_left(game, next_square as usize) {
-                pseudo_legal_moves.append(
-                    &mut vec![
-                        Move::new(square, (next_square - 1) as usize, KNIGHT_PROMOTION_CAP),
-                        Move::new(square, (next_square - 1) as usize, BISHOP_PROMOTION_CAP),
-                        Move::new(square, (next_square - 1) as usize, ROOK_PROMOTION_CAP),
-                        Move::new(square, (next_square - 1) as usize, QUEEN_PROMOTION_CAP)
-                    ]
-                );
+                self.push_promotions(emit, square, (next_square - 1) as usize, true);
             }
-
             if self.pawn_can_capture_right(game, next_square as usize) {
-                pseudo_legal_moves.append(
-                    &mut vec![
-                        Move::new(square, (next_square + 1) as usize, KNIGHT_PROMOTION_CAP),
-                        Move::new(square, (next_square + 1) as usize, BISHOP_PROMOTION_CAP),
-                        Move::new(square, (next_square + 1) as usize, ROOK_PROMOTION_CAP),
-                        Move::new(square, (next_square + 1) as usize, QUEEN_PROMOTION_CAP)
-                    ]
-                );
+                self.push_promotions(emit, square, (next_square + 1) as usize, true);
             }
         }
         else {
             if self.pawn_can_capture_left(game, next_square as usize) {
-                pseudo_legal_moves.push(Move::new(square, (next_square - 1) as usize, CAPTURE));
+                emit(Move::new(square, (next_square - 1) as usize, CAPTURE));
             }
             if self.pawn_can_capture_right(game, next_square as usize) {
-                pseudo_legal_moves.push(Move::new(square, (next_square + 1) as usize, CAPTURE));
+                emit(Move::new(square, (next_square + 1) as usize, CAPTURE));
             }
         }
 
         if game.board[next_square as usize].get_type() == EMPTY {
 
             if game.get_row(next_square as usize) == 0 || game.get_row(next_square as usize) == 7 {
-                pseudo_legal_moves.append(
-                    &mut vec![
-                        Move::new(square, next_square as usize, KNIGHT_PROMOTION),
-                        Move::new(square, next_square as usize, BISHOP_PROMOTION),
-                        Move::new(square, next_square as usize, ROOK_PROMOTION),
-                        Move::new(square, next_square as usize, QUEEN_PROMOTION)
-                    ]
-                );
+                self.push_promotions(emit, square, next_square as usize, false);
             }
             else {
-                pseudo_legal_moves.push(Move::new(square, next_square as usize, QUIET_MOVE));
+                emit(Move::new(square, next_square as usize, QUIET_MOVE));
 
                 let next_square = next_square + forward_offset;
 
                 if (game.get_row(square) == 1 || game.get_row(square) == 6) &&
                 game.board[next_square as usize].get_type() == EMPTY {
-                    pseudo_legal_moves.push(Move::new(square, next_square as usize, DOUBLE_PAWN_PUSH));
+                    emit(Move::new(square, next_square as usize, DOUBLE_PAWN_PUSH));
                 }
             }
         }
 
         if game.possible_ep_capture < 64 {
             if game.get_column(square) != 0 && square - 1 == game.possible_ep_capture { 
-                pseudo_legal_moves.push(Move::new(square, next_square as usize - 1, EP_CAPTURE));
+                emit(Move::new(square, next_square as usize - 1, EP_CAPTURE));
             }
             if game.get_column(square) != 7 && square + 1 == game.possible_ep_capture { 
-                pseudo_legal_moves.push(Move::new(square, next_square as usize + 1, EP_CAPTURE));
+                emit(Move::new(square, next_square as usize + 1, EP_CAPTURE));
             }
         }
-
-        return pseudo_legal_moves;
     }
 
-    fn generate_non_pawn_moves(&self, game: &Game, square: usize) -> Vec<Move> {
-        let mut pseudo_legal_moves = vec![];
+    /// Adds a promotion to every piece type in the generator's promotion set
+    fn push_promotions(&self, emit: &mut impl FnMut(Move), from: usize, to: usize, capture: bool) {
+        let promotions = [
+            (KNIGHT, KNIGHT_PROMOTION_QUIET, KNIGHT_PROMOTION_CAP),
+            (BISHOP, BISHOP_PROMOTION_QUIET, BISHOP_PROMOTION_CAP),
+            (ROOK, ROOK_PROMOTION_QUIET, ROOK_PROMOTION_CAP),
+            (QUEEN, QUEEN_PROMOTION_QUIET, QUEEN_PROMOTION_CAP),
+            (KING, KING_PROMOTION_QUIET, KING_PROMOTION_CAP)
+        ];
+        for (piece_type, promotion, promotion_capture) in promotions {
+            if !self.promotions.contains(piece_type) {
+                continue;
+            }
+            if capture {
+                emit(Move::new(from, to, promotion_capture));
+            }
+            else {
+                emit(Move::new(from, to, promotion));
+            }
+        }
+    }
 
+    fn generate_non_pawn_moves(&self, game: &Game, square: usize, emit: &mut impl FnMut(Move)) {
         let mailbox = Mailbox::new();
         for j in 0..self.piece_offsets[game.board[square].get_type() - 1] {
             let mut to_square: isize = square as isize;
@@ -285,12 +831,12 @@ impl MoveGenerator {
                 
                 if game.board[to_square as usize].get_type() != EMPTY {
                     if game.board[to_square as usize].get_color() != game.turn {
-                        pseudo_legal_moves.push(Move::new(square, to_square as usize, CAPTURE));
+                        emit(Move::new(square, to_square as usize, CAPTURE));
                     }
                     break;
                 }
 
-                pseudo_legal_moves.push(Move::new(square, to_square as usize, QUIET_MOVE));
+                emit(Move::new(square, to_square as usize, QUIET_MOVE));
 
                 if !self.sliding_piece[game.board[square].get_type() - 1] {
                     break;
@@ -298,144 +844,554 @@ impl MoveGenerator {
             }
         }
 
-        if game.board[square].get_type() == KING && !game.board[square].has_moved() {
-            let king_rook;
-            let queen_rook;
-            if game.turn == WHITE {
-                king_rook = game.board[7 * 8 + 7];
-                queen_rook = game.board[7 * 8];
+        if game.board[square].get_type() == KING {
+            self.generate_castling_moves(game, square, emit);
+        }
+    }
+
+    /// Calls 'emit' for every castling move of the king on the given square. Unlike other moves these are fully legal,
+    /// the king may not be in check, pass through or land on an attacked square, and every square between the king,
+    /// the rook and their destinations has to be empty apart from the king and the rook themselves.
+    /// The king and rook can start on any square of the back rank as in Chess960.
+    fn generate_castling_moves(&self, game: &Game, square: usize, emit: &mut impl FnMut(Move)) {
+        let color = game.board[square].get_color();
+        let back_rank = square - game.get_column(square);
+        if game.castle_status(self, color, Wing::KingSide) == CastleStatus::Available {
+            emit(Move::new(square, back_rank + 6, KING_CASTLE));
+        }
+        if game.castle_status(self, color, Wing::QueenSide) == CastleStatus::Available {
+            emit(Move::new(square, back_rank + 2, QUEEN_CASTLE));
+        }
+    }
+
+    /// Calls 'emit' for every legal move of the pieces on the given squares, in the order of [Move]'s Ord
+    fn generate_legal_moves(&self, game: &Game, squares: std::ops::Range<usize>, emit: &mut impl FnMut(Move)) {
+        let check_info = self.compute_check_info(game);
+        let captures_only = self.mandatory_capture && self.has_legal_capture(game, &check_info);
+        // A piece has at most 27 moves, a queen in the middle of an empty board
+        let mut piece_moves = [Move::new(0, 0, QUIET_MOVE); 32];
+        for square in squares {
+            let mut n_moves = 0;
+            self.generate_pseudo_legal_moves(game, square, &mut |mv| {
+                if (!captures_only || mv.is_capture()) && self.is_legal(game, &check_info, mv) {
+                    piece_moves[n_moves] = mv;
+                    n_moves += 1;
+                }
+            });
+            // Moves of one piece only come from the same square, so sorting them sorts the whole list
+            piece_moves[..n_moves].sort_unstable();
+            for &mv in piece_moves[..n_moves].iter() {
+                emit(mv);
             }
-            else {
-                king_rook = game.board[0 * 8 + 7];
-                queen_rook = game.board[0 * 8];
+        }
+    }
+
+    /// Returns true if the side to move has a legal capture anywhere on the board
+    fn has_legal_capture(&self, game: &Game, check_info: &CheckInfo) -> bool {
+        let mut found = false;
+        for square in 0..64 {
+            self.generate_pseudo_legal_moves(game, square, &mut |mv| {
+                found = found || (mv.is_capture() && self.is_legal(game, check_info, mv));
+            });
+            if found {
+                return true;
             }
+        }
+        return false;
+    }
 
-            let mut king_side_empty = true;
-            let mut queen_side_empty = true;
+    /// Returns true if the side to move has any legal move, stopping at the first piece that has one
+    fn has_legal_move(&self, game: &Game) -> bool {
+        let check_info = self.compute_check_info(game);
+        let mut found = false;
+        for square in 0..64 {
+            self.generate_pseudo_legal_moves(game, square, &mut |mv| {
+                found = found || self.is_legal(game, &check_info, mv);
+            });
+            if found {
+                return true;
+            }
+        }
+        return false;
+    }
 
-            if queen_rook.get_type() == ROOK && !queen_rook.has_moved() {
-                for j in 0..3 {
-                    if game.board[square - j - 1].get_type() != EMPTY {
-                        queen_side_empty = false;
-                        break;
-                    }
+    /// Counts the squares the piece on the given square can move to, for whichever color it has.
+    /// Pins and castling are ignored and a promotion counts once regardless of the promotion piece.
+    fn count_pseudo_legal_destinations(&self, game: &Game, square: usize) -> usize {
+        let piece = game.board[square];
+        let mailbox = Mailbox::new();
+        let mut destinations = 0;
+
+        if piece.get_type() == PAWN {
+            let forward_offset: isize;
+            let capture_offsets: [isize; 2];
+            let start_row;
+            if piece.get_color() == WHITE {
+                forward_offset = -10;
+                capture_offsets = [-11, -9];
+                start_row = 6;
+            }
+            else {
+                forward_offset = 10;
+                capture_offsets = [9, 11];
+                start_row = 1;
+            }
+            let next_square = mailbox.get_square_with_offset(square, forward_offset);
+            if next_square != -1 && game.board[next_square as usize].get_type() == EMPTY {
+                destinations += 1;
+                let second_square = mailbox.get_square_with_offset(next_square as usize, forward_offset);
+                if game.get_row(square) == start_row && game.board[second_square as usize].get_type() == EMPTY {
+                    destinations += 1;
                 }
-                if queen_side_empty {
-                    pseudo_legal_moves.push(Move::new(square, square - 2, QUEEN_CASTLE));
+            }
+            for offset in capture_offsets {
+                let to_square = mailbox.get_square_with_offset(square, offset);
+                if to_square != -1 && game.board[to_square as usize].get_type() != EMPTY &&
+                game.board[to_square as usize].get_color() != piece.get_color() {
+                    destinations += 1;
                 }
             }
+            return destinations;
+        }
 
-            if king_rook.get_type() == ROOK && !king_rook.has_moved() {
-                for j in 0..2 {
-                    if game.board[square + j + 1].get_type() != EMPTY {
-                        king_side_empty = false;
-                        break;
+        for j in 0..self.piece_offsets[piece.get_type() - 1] {
+            let mut to_square: isize = square as isize;
+            loop {
+                to_square = mailbox.get_square_with_offset(to_square as usize, 
+                    self.piece_offset[piece.get_type() - 1][j]);
+
+                if to_square == -1 {
+                    break;
+                }
+                if game.board[to_square as usize].get_type() != EMPTY {
+                    if game.board[to_square as usize].get_color() != piece.get_color() {
+                        destinations += 1;
                     }
+                    break;
+                }
+                destinations += 1;
+
+                if !self.sliding_piece[piece.get_type() - 1] {
+                    break;
                 }
-                if king_side_empty {
-                    pseudo_legal_moves.push(Move::new(square, square + 2, KING_CASTLE));
+            }
+        }
+        return destinations;
+    }
+
+    /// Returns a mask with one bit per square attacked by the piece on the given square. 
+    /// A ray ends at the first piece in its way, which is included whatever its color.
+    fn attacks_from(&self, game: &Game, square: usize) -> u64 {
+        let piece = game.board[square];
+        let mailbox = Mailbox::new();
+        let mut attacks: u64 = 0;
+
+        if piece.get_type() == EMPTY {
+            return 0;
+        }
+        if piece.get_type() == PAWN {
+            let capture_offsets: [isize; 2];
+            if piece.get_color() == WHITE {
+                capture_offsets = [-11, -9];
+            }
+            else {
+                capture_offsets = [9, 11];
+            }
+            for offset in capture_offsets {
+                let to_square = mailbox.get_square_with_offset(square, offset);
+                if to_square != -1 {
+                    attacks |= 1 << to_square;
                 }
             }
+            return attacks;
         }
 
-        return pseudo_legal_moves;
+        for j in 0..self.piece_offsets[piece.get_type() - 1] {
+            let mut to_square: isize = square as isize;
+            loop {
+                to_square = mailbox.get_square_with_offset(to_square as usize, 
+                    self.piece_offset[piece.get_type() - 1][j]);
+
+                if to_square == -1 {
+                    break;
+                }
+                attacks |= 1 << to_square;
+                if game.board[to_square as usize].get_type() != EMPTY || !self.sliding_piece[piece.get_type() - 1] {
+                    break;
+                }
+            }
+        }
+        return attacks;
     }
 
-    fn filter_pseudo_legal_moves(&self, game: &Game, pseudo_legal_moves: Vec<Move>) -> Vec<Move> {
-        let mut legal_moves = vec![];
-        for mv in pseudo_legal_moves {
-            
-            if mv.is_castle() {
-                let square_besides_king;
-                if mv.is_queen_castle() {
-                    square_besides_king = mv.get_from() - 1;
+    /// Finds the pieces giving check to the side to move and the pieces pinned to its king
+    fn compute_check_info(&self, game: &Game) -> CheckInfo {
+        let mailbox = Mailbox::new();
+        let king_square = game.king_square[game.turn];
+        let mut check_info = CheckInfo {
+            n_checkers: 0,
+            check_mask: 0,
+            pin_ray: [0; 64]
+        };
+
+        for &offset in self.piece_offset[QUEEN - 1].iter() {
+            let diagonal = offset.abs() == 9 || offset.abs() == 11;
+            let mut ray: u64 = 0;
+            let mut pinned_square = 64;
+            let mut square: isize = king_square as isize;
+            loop {
+                square = mailbox.get_square_with_offset(square as usize, offset);
+                if square == -1 {
+                    break;
                 }
-                else {
-                    square_besides_king = mv.get_from() + 1;
+                ray |= 1 << square;
+
+                let piece = game.board[square as usize];
+                if piece.get_type() == EMPTY {
+                    continue;
                 }
-                if self.is_attacked(game, mv.get_from(), game.turn) || 
-                self.is_attacked(game, square_besides_king, game.turn) {
+                if piece.get_color() == game.turn {
+                    if pinned_square != 64 {
+                        break;
+                    }
+                    pinned_square = square as usize;
                     continue;
                 }
+
+                let slides_along_ray = piece.get_type() == QUEEN ||
+                    (diagonal && piece.get_type() == BISHOP) || (!diagonal && piece.get_type() == ROOK);
+                if slides_along_ray {
+                    if pinned_square != 64 {
+                        check_info.pin_ray[pinned_square] = ray;
+                    }
+                    else {
+                        check_info.n_checkers += 1;
+                        check_info.check_mask |= ray;
+                    }
+                }
+                break;
             }
+        }
 
-            let mut game_copy = game.clone();
-            game_copy.make_move(mv);
-            if self.is_attacked(&game_copy, game_copy.king_square[game_copy.turn ^ 1], game_copy.turn ^ 1) {
-                continue;
+        for &offset in self.piece_offset[KNIGHT - 1].iter() {
+            let square = mailbox.get_square_with_offset(king_square, offset);
+            if square != -1 {
+                let piece = game.board[square as usize];
+                if piece.get_type() == KNIGHT && piece.get_color() != game.turn {
+                    check_info.n_checkers += 1;
+                    check_info.check_mask |= 1 << square;
+                }
             }
+        }
 
-            legal_moves.push(mv);
+        // Only a king promoted to can stand next to the king, see [PromotionSet]
+        for &offset in self.piece_offset[KING - 1].iter() {
+            let square = mailbox.get_square_with_offset(king_square, offset);
+            if square != -1 {
+                let piece = game.board[square as usize];
+                if piece.get_type() == KING && piece.get_color() != game.turn {
+                    check_info.n_checkers += 1;
+                    check_info.check_mask |= 1 << square;
+                }
+            }
         }
-        return legal_moves;
-    }
 
-    fn is_attacked(&self, game: &Game, square: usize, color: usize) -> bool {
+        let pawn_offsets: [isize; 2];
+        if game.turn == WHITE {
+            pawn_offsets = [-11, -9];
+        }
+        else {
+            pawn_offsets = [9, 11];
+        }
+        for offset in pawn_offsets {
+            let square = mailbox.get_square_with_offset(king_square, offset);
+            if square != -1 {
+                let piece = game.board[square as usize];
+                if piece.get_type() == PAWN && piece.get_color() != game.turn {
+                    check_info.n_checkers += 1;
+                    check_info.check_mask |= 1 << square;
+                }
+            }
+        }
 
-        let mailbox = Mailbox::new();
-        for piece in KNIGHT..=KING {
-            for i in 0..self.piece_offsets[piece - 1] {
-                let mut to_square: isize = square as isize;
-                loop {
-                    to_square = mailbox.get_square_with_offset(to_square as usize, 
-                        self.piece_offset[piece - 1][i]);
+        return check_info;
+    }
 
-                    if to_square == -1 {
-                        break;
+    /// Finds the squares the side to move can give check from, see [CheckSquares]
+    fn compute_check_squares(&self, game: &Game) -> CheckSquares {
+        let mailbox = Mailbox::new();
+        let king_square = game.king_square[game.turn ^ 1];
+        let mut check_squares = CheckSquares {
+            direct: [0; 7],
+            discovered_ray: [0; 64]
+        };
+
+        for &offset in self.piece_offset[QUEEN - 1].iter() {
+            let diagonal = offset.abs() == 9 || offset.abs() == 11;
+            let mut ray: u64 = 0;
+            let mut blocker_square = 64;
+            let mut square: isize = king_square as isize;
+            loop {
+                square = mailbox.get_square_with_offset(square as usize, offset);
+                if square == -1 {
+                    break;
+                }
+                ray |= 1 << square;
+                if blocker_square == 64 {
+                    if diagonal {
+                        check_squares.direct[BISHOP] |= 1 << square;
                     }
-                    
-                    let attacking_piece = &game.board[to_square as usize];
-                    if attacking_piece.get_type() != EMPTY {
-                        if attacking_piece.get_color() != color && attacking_piece.get_type() == piece {
-                            return true;
-                        }
-                        break;
+                    else {
+                        check_squares.direct[ROOK] |= 1 << square;
                     }
+                    check_squares.direct[QUEEN] |= 1 << square;
+                }
 
-                    if !self.sliding_piece[piece - 1] {
-                        break;
+                let piece = game.board[square as usize];
+                if piece.get_type() == EMPTY {
+                    continue;
+                }
+                if blocker_square != 64 || piece.get_color() != game.turn {
+                    let slides_along_ray = piece.get_type() == QUEEN ||
+                        (diagonal && piece.get_type() == BISHOP) || (!diagonal && piece.get_type() == ROOK);
+                    if blocker_square != 64 && piece.get_color() == game.turn && slides_along_ray {
+                        check_squares.discovered_ray[blocker_square] = ray;
                     }
+                    break;
                 }
+                blocker_square = square as usize;
             }
         }
-        if !((color == WHITE && game.get_row(square) <= 1) || (color == BLACK && game.get_row(square) >= 6)) {
-            let forward_offset: isize;
-            if color == WHITE {
-                forward_offset = -8;
+
+        for &offset in self.piece_offset[KNIGHT - 1].iter() {
+            let square = mailbox.get_square_with_offset(king_square, offset);
+            if square != -1 {
+                check_squares.direct[KNIGHT] |= 1 << square;
             }
-            else {
-                forward_offset = 8;
+        }
+        for &offset in self.piece_offset[KING - 1].iter() {
+            let square = mailbox.get_square_with_offset(king_square, offset);
+            if square != -1 {
+                check_squares.direct[KING] |= 1 << square;
             }
-            let possible_pawn_cap1: &Piece = &game.board[(square as isize + forward_offset) as usize + 1];
-            let possible_pawn_cap2: &Piece = &game.board[(square as isize + forward_offset) as usize - 1];
+        }
 
-            if (possible_pawn_cap1.get_type() == PAWN && possible_pawn_cap1.get_color() != color) ||
-            (possible_pawn_cap2.get_type() == PAWN && possible_pawn_cap2.get_color() != color) {
-                return true;
+        // The squares the opponent's king would attack if it were a pawn of the opponent
+        let pawn_offsets: [isize; 2];
+        if game.turn == WHITE {
+            pawn_offsets = [9, 11];
+        }
+        else {
+            pawn_offsets = [-11, -9];
+        }
+        for offset in pawn_offsets {
+            let square = mailbox.get_square_with_offset(king_square, offset);
+            if square != -1 {
+                check_squares.direct[PAWN] |= 1 << square;
             }
         }
 
-        return false;
+        return check_squares;
     }
 
-    fn pawn_can_capture_left(&self, game: &Game, next_square: usize) -> bool {
-        return game.get_column(next_square) != 0 && game.board[(next_square - 1) as usize].get_color() != game.turn &&
-        game.board[(next_square - 1) as usize].get_type() != EMPTY;
+    /// Returns true if the legal move gives check. Castling, en passant and promotions, which move or remove
+    /// more than one piece, are made on a copy of the position, everything else is looked up in the check squares.
+    fn gives_check(&self, game: &Game, check_squares: &CheckSquares, mv: Move) -> bool {
+        if mv.is_castle() || mv.is_ep_capture() || mv.is_promotion() {
+            let mut game_copy = game.detached();
+            game_copy.make_move(mv);
+            return self.is_attacked(&game_copy, game_copy.king_square[game_copy.turn], game_copy.turn);
+        }
+        let from = mv.get_from();
+        let to = mv.get_to();
+        if check_squares.direct[game.board[from].get_type()] & (1 << to) != 0 {
+            return true;
+        }
+        return check_squares.discovered_ray[from] != 0 && check_squares.discovered_ray[from] & (1 << to) == 0;
     }
 
-    fn pawn_can_capture_right(&self, game: &Game, next_square: usize) -> bool {
-        return game.get_column(next_square) != 7 && game.board[(next_square + 1) as usize].get_color() != game.turn &&
-        game.board[(next_square + 1) as usize].get_type() != EMPTY;
-    }   
-}
+    /// Returns how the legal move checks, see [Game::check_kind]. Like [MoveGenerator::gives_check] only castling,
+    /// en passant and promotions are made on a copy of the position.
+    fn check_kind(&self, game: &Game, check_squares: &CheckSquares, mv: Move) -> CheckKind {
+        if mv.is_castle() || mv.is_ep_capture() || mv.is_promotion() {
+            let mut game_copy = game.detached();
+            game_copy.make_move(mv);
+            let checkers = game_copy.checkers();
+            let mut moved_to = mv.get_to();
+            if mv.is_castle() {
+                moved_to = game.castling_rook_move(&mv).1;
+            }
+            return match checkers.as_slice() {
+                [] => CheckKind::None,
+                [checker] if checker.index() == moved_to => CheckKind::Direct,
+                [_] => CheckKind::Discovered,
+                _ => CheckKind::Double
+            };
+        }
+        let from = mv.get_from();
+        let to = mv.get_to();
+        let direct = check_squares.direct[game.board[from].get_type()] & (1 << to) != 0;
+        let discovered = check_squares.discovered_ray[from] != 0 && check_squares.discovered_ray[from] & (1 << to) == 0;
+        return match (direct, discovered) {
+            (true, true) => CheckKind::Double,
+            (true, false) => CheckKind::Direct,
+            (false, true) => CheckKind::Discovered,
+            (false, false) => CheckKind::None
+        };
+    }
 
-/// Converts an algebraic notation, example 'e3' to an integer between 0 and 63 inclusive
-pub fn convert_algebraic_notation_to_number(alg_not: &str) -> usize {
-    let mut square = 0;
-    square += match alg_not.chars().nth(0).unwrap() {
-        'a' => 0,
-        'b' => 1,
-        'c' => 2,
+    /// Checks a pseudo legal move against the checkers and pins of the position.
+    /// Castling moves are already fully checked when they are generated.
+    fn is_legal(&self, game: &Game, check_info: &CheckInfo, mv: Move) -> bool {
+        let from = mv.get_from();
+        let to = mv.get_to();
+
+        if from == game.king_square[game.turn] {
+            if mv.is_castle() {
+                return true;
+            }
+            return !self.is_attacked_through(game, to, game.turn, from);
+        }
+
+        if check_info.n_checkers > 1 {
+            return false;
+        }
+
+        if mv.is_ep_capture() {
+            return self.ep_capture_legal(game, check_info, mv);
+        }
+
+        if check_info.n_checkers == 1 && check_info.check_mask & (1 << to) == 0 {
+            return false;
+        }
+        if check_info.pin_ray[from] != 0 && check_info.pin_ray[from] & (1 << to) == 0 {
+            return false;
+        }
+        return true;
+    }
+
+    /// Checks a pseudo legal en passant capture. The capture removes two pawns from the rank of the capturing pawn,
+    /// which can uncover the king to a rook or queen along that rank even though neither pawn is pinned on its own,
+    /// and the captured pawn can also stand between the king and a bishop or queen on a diagonal.
+    /// The rays from the king are therefore walked again with both pawns gone and the capturing pawn on its destination.
+    fn ep_capture_legal(&self, game: &Game, check_info: &CheckInfo, mv: Move) -> bool {
+        let from = mv.get_from();
+        let to = mv.get_to();
+        let captured = game.possible_ep_capture;
+
+        // The only check an en passant capture can answer is one by the captured pawn or along a ray through 'to'.
+        // Checks by sliders are confirmed by walking the rays below.
+        if check_info.n_checkers > 1 {
+            return false;
+        }
+        if check_info.n_checkers == 1 && check_info.check_mask & (1 << to | 1 << captured) == 0 {
+            return false;
+        }
+        if check_info.pin_ray[from] != 0 && check_info.pin_ray[from] & (1 << to) == 0 {
+            return false;
+        }
+
+        let mailbox = Mailbox::new();
+        for &offset in self.piece_offset[QUEEN - 1].iter() {
+            let diagonal = offset.abs() == 9 || offset.abs() == 11;
+            let mut square: isize = game.king_square[game.turn] as isize;
+            loop {
+                square = mailbox.get_square_with_offset(square as usize, offset);
+                if square == -1 || square as usize == to {
+                    break;
+                }
+                if square as usize == from || square as usize == captured {
+                    continue;
+                }
+
+                let piece = game.board[square as usize];
+                if piece.get_type() == EMPTY {
+                    continue;
+                }
+                let slides_along_ray = piece.get_type() == QUEEN ||
+                    (diagonal && piece.get_type() == BISHOP) || (!diagonal && piece.get_type() == ROOK);
+                if piece.get_color() != game.turn && slides_along_ray {
+                    return false;
+                }
+                break;
+            }
+        }
+        return true;
+    }
+
+    fn is_attacked(&self, game: &Game, square: usize, color: usize) -> bool {
+        return self.is_attacked_through(game, square, color, 64);
+    }
+
+    /// Same as 'is_attacked' but the 'vacated' square is treated as empty, 
+    /// which lets the king's destination be tested without moving it on the board
+    fn is_attacked_through(&self, game: &Game, square: usize, color: usize, vacated: usize) -> bool {
+
+        let mailbox = Mailbox::new();
+        for piece in KNIGHT..=KING {
+            for i in 0..self.piece_offsets[piece - 1] {
+                let mut to_square: isize = square as isize;
+                loop {
+                    to_square = mailbox.get_square_with_offset(to_square as usize, 
+                        self.piece_offset[piece - 1][i]);
+
+                    if to_square == -1 {
+                        break;
+                    }
+                    
+                    let attacking_piece = &game.board[to_square as usize];
+                    if attacking_piece.get_type() != EMPTY && to_square as usize != vacated {
+                        if attacking_piece.get_color() != color && attacking_piece.get_type() == piece {
+                            return true;
+                        }
+                        break;
+                    }
+
+                    if !self.sliding_piece[piece - 1] {
+                        break;
+                    }
+                }
+            }
+        }
+
+        let pawn_offsets: [isize; 2];
+        if color == WHITE {
+            pawn_offsets = [-11, -9];
+        }
+        else {
+            pawn_offsets = [9, 11];
+        }
+        for offset in pawn_offsets {
+            let pawn_square = mailbox.get_square_with_offset(square, offset);
+            if pawn_square != -1 {
+                let possible_pawn_cap = &game.board[pawn_square as usize];
+                if possible_pawn_cap.get_type() == PAWN && possible_pawn_cap.get_color() != color {
+                    return true;
+                }
+            }
+        }
+
+        return false;
+    }
+
+    fn pawn_can_capture_left(&self, game: &Game, next_square: usize) -> bool {
+        return game.get_column(next_square) != 0 && game.board[next_square - 1].get_color() != game.turn &&
+        game.board[next_square - 1].get_type() != EMPTY;
+    }
+
+    fn pawn_can_capture_right(&self, game: &Game, next_square: usize) -> bool {
+        return game.get_column(next_square) != 7 && game.board[next_square + 1].get_color() != game.turn &&
+        game.board[next_square + 1].get_type() != EMPTY;
+    }   
+}
+
+/// Converts an algebraic notation, example 'e3' to an integer between 0 and 63 inclusive
+pub fn convert_algebraic_notation_to_number(alg_not: &str) -> usize {
+    let mut square = 0;
+    square += match alg_not.chars().next().unwrap() {
+        'a' => 0,
+        'b' => 1,
+        'c' => 2,
         'd' => 3,
         'e' => 4,
         'f' => 5,
@@ -504,18 +1460,40 @@ fn convert_fen_to_game(fen: &str) -> Game {
         _ => EMPTY
     };
 
-    let castle_rights = fen_parts[2];
-    if !castle_rights.contains('K') {
-        board[7 * 8 + 7].set_flags(HAS_MOVED);
-    }
-    if !castle_rights.contains('Q') {
-        board[7 * 8].set_flags(HAS_MOVED);
-    }
-    if !castle_rights.contains('k') {
-        board[0 * 8 + 7].set_flags(HAS_MOVED);
+    // Only the rooks named by the castling rights count as unmoved. 'K' and 'Q' name the outermost rook on 
+    // that side of the king, while a file letter as in Shredder-FEN names the rook on that file for Chess960
+    for square in 0..64 {
+        if board[square].get_type() == ROOK {
+            board[square].set_flags(HAS_MOVED);
+        }
     }
-    if !castle_rights.contains('q') {
-        board[0 * 8].set_flags(HAS_MOVED);
+    for right in fen_parts[2].chars() {
+        let color;
+        let back_row;
+        if right.is_ascii_uppercase() {
+            color = WHITE;
+            back_row = 7;
+        }
+        else {
+            color = BLACK;
+            back_row = 0;
+        }
+        if king_square[color] / 8 != back_row {
+            continue;
+        }
+        let king_column = king_square[color] % 8;
+        let is_rook = |column: &usize| {
+            return board[back_row * 8 + column].get_type() == ROOK && board[back_row * 8 + column].get_color() == color;
+        };
+        let column = match right.to_ascii_lowercase() {
+            'k' => (king_column + 1..8).rev().find(is_rook),
+            'q' => (0..king_column).find(is_rook),
+            'a'..='h' => Some(right.to_ascii_lowercase() as usize - 'a' as usize).filter(is_rook),
+            _ => None
+        };
+        if let Some(column) = column {
+            board[back_row * 8 + column].set_flags(EMPTY);
+        }
     }
 
     let mut possible_ep_capture = 64;
@@ -529,13 +1507,25 @@ fn convert_fen_to_game(fen: &str) -> Game {
         }
     }
     let half_move_clock = fen_parts[4].parse::<usize>().unwrap();
+    let full_move_number = fen_parts.get(5).and_then(|number| number.parse::<usize>().ok()).unwrap_or(1);
 
     return Game {
         board,
         turn,
         possible_ep_capture,
         king_square,
-        half_move_clock
+        half_move_clock,
+        full_move_number,
+        history: vec![],
+        last_irreversible_ply: 0,
+        last_move: None,
+        annotations: BTreeMap::new(),
+        ending: None,
+        automatic_termination: true,
+        analysis_mode: false,
+        rules: Rules::default(),
+        eval_terms: None,
+        cache: PositionCache::new()
     }
 }
 
@@ -546,17 +1536,197 @@ pub enum GameState {
     Checkmate,
     Stalemate,
     InsufficientMaterial,
-    DrawBy50MoveRule
+    DrawBy50MoveRule,
+    /// The same position has occurred five times, which ends the game automatically
+    FivefoldRepetition,
+    /// 75 moves by each side without a capture or pawn move, which ends the game automatically
+    DrawBy75MoveRule
+}
+
+/// How much an attacker of each piece type, indexed by type, adds to [KingZoneAttacks::weight]
+pub const KING_ZONE_ATTACK_WEIGHTS: [i32; 7] = [0, 10, 20, 20, 40, 80, 0];
+
+/// The attacks of one color against the zone around the other color's king, see [Game::king_zone_attacks]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct KingZoneAttacks {
+    /// The king's square and the squares next to it, as a mask
+    pub zone: u64,
+    /// The number of pieces attacking at least one square of the zone
+    pub attackers: usize,
+    /// The number of attacks on squares of the zone, counting a square once per piece attacking it
+    pub attacks: usize,
+    /// The number of squares of the zone attacked by at least one piece
+    pub attacked_squares: usize,
+    /// The sum of [KING_ZONE_ATTACK_WEIGHTS] over the attacking pieces
+    pub weight: i32
+}
+
+/// What happened to a move given by squares, see [Game::make_move_from_to]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MoveOutcome {
+    /// The move was made
+    Applied,
+    /// There is no legal move between the squares
+    IllegalMove,
+    /// The move is a promotion and the promotion doesn't say which piece to promote to
+    AmbiguousPromotion,
+    /// The piece on the square the move is made from belongs to the side not to move
+    WrongTurn,
+    /// The game is over, see [Game::is_game_over]
+    GameOver
+}
+
+impl MoveOutcome {
+
+    /// Returns true if the move was made
+    pub fn is_applied(&self) -> bool {
+        return *self == MoveOutcome::Applied;
+    }
+}
+
+impl std::fmt::Display for MoveOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let description = match self {
+            MoveOutcome::Applied => "the move was made",
+            MoveOutcome::IllegalMove => "the move is not legal",
+            MoveOutcome::AmbiguousPromotion => "choose a piece to promote to",
+            MoveOutcome::WrongTurn => "it is the other side's turn",
+            MoveOutcome::GameOver => "the game is over"
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// Which castling moves each color may still make at some point in the game
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CastlingRights {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool
+}
+
+impl CastlingRights {
+
+    /// Returns the rights in the order White king side, White queen side, Black king side, Black queen side
+    pub fn as_array(&self) -> [bool; 4] {
+        return [self.white_king_side, self.white_queen_side, self.black_king_side, self.black_queen_side];
+    }
+
+    /// Creates rights from an array in the order of [CastlingRights::as_array]
+    pub fn from_array(rights: [bool; 4]) -> CastlingRights {
+        CastlingRights {
+            white_king_side: rights[0],
+            white_queen_side: rights[1],
+            black_king_side: rights[2],
+            black_queen_side: rights[3]
+        }
+    }
+}
+
+impl std::fmt::Display for CastlingRights {
+    /// Writes the rights as in a FEN string, for example 'KQkq' or '-'
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut rights = String::new();
+        for (right, letter) in self.as_array().into_iter().zip(['K', 'Q', 'k', 'q']) {
+            if right {
+                rights.push(letter);
+            }
+        }
+        if rights.is_empty() {
+            rights.push('-');
+        }
+        write!(f, "{}", rights)
+    }
+}
+
+/// The parts of a position that the cached values depend on
+#[derive(Clone, PartialEq)]
+struct PositionKey {
+    board: [Piece; 64],
+    turn: usize,
+    king_square: [usize; 2],
+    possible_ep_capture: usize,
+    half_move_clock: usize
+}
+
+#[derive(Clone)]
+struct CachedPosition {
+    key: PositionKey,
+    legal_moves: Option<Arc<[Move]>>,
+    in_check: Option<bool>,
+    game_state: Option<GameState>
+}
+
+/// Values derived from the current position, computed on first request
+struct PositionCache {
+    entry: Mutex<Option<CachedPosition>>
+}
+
+impl PositionCache {
+
+    fn new() -> PositionCache {
+        PositionCache {
+            entry: Mutex::new(None)
+        }
+    }
+
+    /// Returns the cached entry for the given position, if there is one
+    fn get(&self, key: &PositionKey) -> Option<CachedPosition> {
+        let entry = self.entry.lock().unwrap();
+        return entry.as_ref().filter(|cached| cached.key == *key).cloned();
+    }
+
+    /// Stores a value for the given position, discarding values cached for any other position
+    fn update(&self, key: PositionKey, update: impl FnOnce(&mut CachedPosition)) {
+        let mut entry = self.entry.lock().unwrap();
+        if entry.as_ref().is_none_or(|cached| cached.key != key) {
+            *entry = Some(CachedPosition {
+                key,
+                legal_moves: None,
+                in_check: None,
+                game_state: None
+            });
+        }
+        update(entry.as_mut().unwrap());
+    }
+
+    fn clear(&mut self) {
+        *self.entry.get_mut().unwrap() = None;
+    }
+}
+
+impl Clone for PositionCache {
+    fn clone(&self) -> PositionCache {
+        PositionCache {
+            entry: Mutex::new(self.entry.lock().unwrap().clone())
+        }
+    }
 }
 
 /// The chess game
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct Game {
     pub board: [Piece; 64],
     pub turn: usize,
     possible_ep_capture: usize,
     king_square: [usize; 2],
-    half_move_clock: usize
+    half_move_clock: usize,
+    full_move_number: usize,
+    history: Vec<HistoryEntry>,
+    /// The length of the history after the last irreversible move
+    last_irreversible_ply: usize,
+    last_move: Option<Move>,
+    /// The annotations of the moves of the history by their plies
+    annotations: BTreeMap<usize, Annotation>,
+    /// The result and reason the game was ended with off the board
+    ending: Option<(GameResult, Termination)>,
+    automatic_termination: bool,
+    analysis_mode: bool,
+    rules: Rules,
+    /// The evaluation terms kept up to date move by move, see [Game::set_eval_terms]
+    eval_terms: Option<EvalTerms>,
+    cache: PositionCache
 }
 
 impl Game {
@@ -582,49 +1752,637 @@ impl Game {
         self.possible_ep_capture = new_game.possible_ep_capture;
         self.king_square = new_game.king_square;
         self.half_move_clock = new_game.half_move_clock;
+        self.full_move_number = new_game.full_move_number;
+        self.history.clear();
+        self.annotations.clear();
+        self.ending = None;
+        self.last_irreversible_ply = 0;
+        self.last_move = None;
+        self.refresh_eval_terms();
+        self.cache.clear();
     }
 
-    /// Returns all legal moves in the current position
+    /// Returns all legal moves in the current position, sorted in the order of [Move]'s Ord
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::to_uci;
+    /// let game = Game::new("4k3/1P6/8/8/8/8/8/R3K3 w Q - 0 1");
+    /// let moves: Vec<String> = game.get_all_legal_moves().iter().map(to_uci).collect();
+    /// assert_eq!(&moves[..6], ["b7b8n", "b7b8b", "b7b8r", "b7b8q", "a1a8", "a1a7"]);
+    /// assert_eq!(&moves[moves.len() - 3..], ["e1c1", "e1d1", "e1f1"]);
+    ///
+    /// let mut sorted = Game::starting_position().get_all_legal_moves();
+    /// sorted.sort();
+    /// assert_eq!(sorted, Game::starting_position().get_all_legal_moves());
+    /// ```
     pub fn get_all_legal_moves(&self) -> Vec<Move> {
+        let mut moves = MoveList::new();
+        self.legal_moves_into(&mut moves);
+        return moves.to_vec();
+    }
+
+    /// Clears the list and fills it with the legal moves in the current position, without allocating.
+    /// Moves already in the game's cache are copied from it, otherwise they are generated straight into the list
+    /// and not cached, which makes this the fastest way to get the moves of many positions, for example in a search.
+    pub fn legal_moves_into(&self, moves: &mut MoveList) {
+        moves.clear();
+        if self.is_automatically_terminated() {
+            return;
+        }
+        if let Some(legal_moves) = self.cache.get(&self.position_key()).and_then(|cached| cached.legal_moves) {
+            for mv in legal_moves.iter() {
+                moves.push(*mv);
+            }
+            return;
+        }
+        self.move_generator().generate_legal_moves(self, 0..64, &mut |mv| moves.push(mv));
+    }
+
+    /// Returns all legal moves in the current position as a slice shared with the game's cache.
+    /// The moves are only generated the first time they are requested for a position.
+    pub fn legal_moves_cached(&self) -> Arc<[Move]> {
+        if self.is_automatically_terminated() {
+            return Arc::new([]);
+        }
+        return self.generated_legal_moves();
+    }
+
+    /// Returns the cached legal moves, ignoring automatic termination
+    fn generated_legal_moves(&self) -> Arc<[Move]> {
+        let key = self.position_key();
+        if let Some(legal_moves) = self.cache.get(&key).and_then(|cached| cached.legal_moves) {
+            return legal_moves;
+        }
+        let legal_moves: Arc<[Move]> = self.generate_all_legal_moves().into();
+        self.cache.update(key, |cached| cached.legal_moves = Some(legal_moves.clone()));
+        return legal_moves;
+    }
+
+    /// Returns true if the side to move is checkmated, ignoring whether the game already ended by the automatic draw
+    /// rules, which leave no legal moves to generate
+    pub(crate) fn is_checkmated(&self) -> bool {
+        return self.is_in_check() && !self.move_generator().has_legal_move(self);
+    }
+
+    /// Returns true if the side to move is in check
+    pub fn is_in_check(&self) -> bool {
+        let key = self.position_key();
+        if let Some(in_check) = self.cache.get(&key).and_then(|cached| cached.in_check) {
+            return in_check;
+        }
         let move_gen = MoveGenerator::new();
-        let mut pseudo_legal_moves = vec![];
+        let in_check = move_gen.is_attacked(self, self.king_square[self.turn], self.turn);
+        self.cache.update(key, |cached| cached.in_check = Some(in_check));
+        return in_check;
+    }
 
+    /// Returns the squares of the pieces giving check to the side to move
+    pub fn checkers(&self) -> Vec<Square> {
+        let king_square = self.king_square[self.turn];
+        let mut checkers = vec![];
         for square in 0..64 {
-            if self.board[square].get_type() != EMPTY && self.board[square].get_color() == self.turn {
-                pseudo_legal_moves.append(&mut move_gen.generate_pseudo_legal_moves(self, square));
+            let piece = self.board[square];
+            if piece.get_type() != EMPTY && piece.get_color() != self.turn &&
+                self.attacks_from(square) & (1 << king_square) != 0 {
+                checkers.push(Square::new(square));
             }
         }
-        let legal_moves = move_gen.filter_pseudo_legal_moves(self, pseudo_legal_moves);
+        return checkers;
+    }
+
+    fn position_key(&self) -> PositionKey {
+        PositionKey {
+            board: self.board,
+            turn: self.turn,
+            king_square: self.king_square,
+            possible_ep_capture: self.possible_ep_capture,
+            half_move_clock: self.half_move_clock
+        }
+    }
+
+    /// Returns the legal moves in the current position generated with the given options. 
+    /// These moves are not cached.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::new("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    /// let options = MoveGenOptions { promotions: PromotionSet::QUEEN_ONLY, ..Default::default() };
+    /// let promotions = game.legal_moves_with(&options).into_iter().filter(|mv| mv.is_promotion()).count();
+    /// assert_eq!(promotions, 1);
+    ///
+    /// // With captures compulsory the pawn on e4 has to take on d5
+    /// let game = Game::new("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2");
+    /// let options = MoveGenOptions { mandatory_capture: true, ..Default::default() };
+    /// assert_eq!(game.legal_moves_with(&options).len(), 1);
+    /// assert_eq!(game.legal_moves_with(&MoveGenOptions::default()).len(), 31);
+    /// ```
+    pub fn legal_moves_with(&self, options: &MoveGenOptions) -> Vec<Move> {
+        return self.generate_legal_moves_with(&MoveGenerator::with_options(options));
+    }
+
+    fn generate_all_legal_moves(&self) -> Vec<Move> {
+        return self.generate_legal_moves_with(&self.move_generator());
+    }
+
+    fn generate_legal_moves_with(&self, move_gen: &MoveGenerator) -> Vec<Move> {
+        let mut legal_moves = vec![];
+        move_gen.generate_legal_moves(self, 0..64, &mut |mv| legal_moves.push(mv));
         return legal_moves;
     }
 
-    /// Returns the legal moves from the given square, in the current position
-    pub fn get_legal_moves(&self, square: usize) -> Vec<Move> {
+    /// Returns the legal moves in the current position together with the piece each move captures and 
+    /// whether it gives check, without making the moves on a copy of the game except for castling, en passant 
+    /// and promotions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// // The bishop on c4 uncovers a check from the rook on c1 and can capture the knight on f7
+    /// let game = Game::new("2k5/5n2/8/8/2B5/8/8/2R1K3 w - - 0 1");
+    /// let moves = game.legal_moves_with_info();
+    /// let bxf7 = moves.iter().find(|info| info.mv.get_to() == 13).unwrap();
+    /// assert_eq!(bxf7.captured, KNIGHT);
+    /// assert!(bxf7.gives_check);
+    /// assert_eq!(moves.iter().filter(|info| info.gives_check).count(), game.get_legal_moves(34).len());
+    /// ```
+    pub fn legal_moves_with_info(&self) -> Vec<MoveInfo> {
         let move_gen = MoveGenerator::new();
-        let pseudo_legal_moves = move_gen.generate_pseudo_legal_moves(self, square);
-        return move_gen.filter_pseudo_legal_moves(self, pseudo_legal_moves);
+        let check_squares = move_gen.compute_check_squares(self);
+        return self.legal_moves_cached().iter().map(|&mv| {
+            let captured;
+            if mv.is_ep_capture() {
+                captured = PAWN;
+            }
+            else if mv.is_capture() {
+                captured = self.board[mv.get_to()].get_type();
+            }
+            else {
+                captured = EMPTY;
+            }
+            return MoveInfo {
+                mv,
+                captured,
+                gives_check: move_gen.gives_check(self, &check_squares, mv)
+            };
+        }).collect();
     }
 
-    /// Returns the game state of the current position, everything but 3-fold repetition is included
-    pub fn get_game_state(&self) -> GameState {
+    /// Returns true if the legal move gives check, see [Game::check_kind] for how
+    pub fn gives_check(&self, mv: &Move) -> bool {
+        let move_gen = MoveGenerator::new();
+        return move_gen.gives_check(self, &move_gen.compute_check_squares(self), *mv);
+    }
+
+    /// Returns how the legal move checks the opponent's king, found before the move is made: directly by the piece
+    /// that moves, by a piece whose line the move opens, or by both at once
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::parse_uci;
+    /// // The knight on e4 blocks the rook on e1
+    /// let game = Game::new("4k3/8/8/8/4N3/8/8/R3R1K1 w - - 0 1");
+    /// let check_kind = |uci| game.check_kind(&parse_uci(&game, uci).unwrap());
+    /// assert_eq!(check_kind("a1a8"), CheckKind::Direct);
+    /// assert_eq!(check_kind("e4c5"), CheckKind::Discovered);
+    /// assert_eq!(check_kind("e4f6"), CheckKind::Double);
+    /// assert_eq!(check_kind("a1a2"), CheckKind::None);
+    ///
+    /// // Castling checks with the rook, and an en passant capture can uncover a check along the rank
+    /// let game = Game::new("3k4/8/8/8/8/8/8/R3K3 w Q - 0 1");
+    /// assert_eq!(game.check_kind(&parse_uci(&game, "e1c1").unwrap()), CheckKind::Direct);
+    /// let game = Game::new("8/8/8/R2pP2k/8/8/8/4K3 w - d6 0 1");
+    /// assert_eq!(game.check_kind(&parse_uci(&game, "e5d6").unwrap()), CheckKind::Discovered);
+    /// ```
+    pub fn check_kind(&self, mv: &Move) -> CheckKind {
+        let move_gen = MoveGenerator::new();
+        return move_gen.check_kind(self, &move_gen.compute_check_squares(self), *mv);
+    }
+
+    /// Returns the legal moves that give check. The squares checks can come from are found once, so only castling,
+    /// en passant and promotions are made on a copy of the position, see [Game::legal_moves_with_info].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::to_uci;
+    /// // The rook on a1 checks along the back rank and every knight move uncovers the rook on e1
+    /// let game = Game::new("4k3/8/8/8/4N3/8/8/R3R1K1 w - - 0 1");
+    /// let checks: Vec<String> = game.moves_giving_check().iter().map(to_uci).collect();
+    /// assert_eq!(checks, ["e4d6", "e4f6", "e4c5", "e4g5", "e4c3", "e4g3", "e4d2", "e4f2", "a1a8"]);
+    /// ```
+    pub fn moves_giving_check(&self) -> Vec<Move> {
+        let move_gen = MoveGenerator::new();
+        let check_squares = move_gen.compute_check_squares(self);
+        return self.legal_moves_cached().iter().copied()
+            .filter(|&mv| move_gen.gives_check(self, &check_squares, mv))
+            .collect();
+    }
+
+    /// Returns the legal moves that checkmate. Only the moves giving check are made, on a copy without history,
+    /// and generating the replies stops at the first legal one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::to_uci;
+    /// let game = Game::new("6k1/5ppp/8/8/8/8/8/R2R2K1 w - - 0 1");
+    /// let mates: Vec<String> = game.mating_moves().iter().map(to_uci).collect();
+    /// assert_eq!(mates, ["a1a8", "d1d8"]);
+    /// assert!(Game::starting_position().mating_moves().is_empty());
+    /// ```
+    pub fn mating_moves(&self) -> Vec<Move> {
+        return self.moves_giving_check().into_iter().filter(|&mv| {
+            let mut game = self.detached();
+            game.make_move(mv);
+            return !game.move_generator().has_legal_move(&game);
+        }).collect();
+    }
+
+    /// Returns true if the pawn on the given square can legally capture en passant, ignoring whether the game has
+    /// ended. Useful for debugging move generation, since en passant is the only capture that removes a piece
+    /// from a square other than its destination.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let ep_legal = |fen: &str, from: &str| Game::new(fen).ep_capture_legal(Square::from_algebraic(from).unwrap());
+    /// // Both pawns leave the rank, uncovering the king to the rook
+    /// assert!(!ep_legal("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 1", "b5"));
+    /// assert!(!ep_legal("8/8/8/r1pP2K1/8/8/8/4k3 w - c6 0 1", "d5"));
+    /// assert!(!ep_legal("8/8/8/8/k2pP2Q/8/8/4K3 b - e3 0 1", "d4"));
+    /// // Another piece or a second pawn on the rank still shields the king
+    /// assert!(ep_legal("8/8/8/KPp1N2r/8/8/8/4k3 w - c6 0 1", "b5"));
+    /// assert!(ep_legal("8/8/8/KPpp3r/8/8/8/4k3 w - c6 0 1", "b5"));
+    /// assert!(ep_legal("8/8/8/KPp5/8/8/8/4k3 w - c6 0 1", "b5"));
+    /// // A rook on the rank that isn't an opponent's slider
+    /// assert!(ep_legal("8/8/8/KPp4R/8/8/8/4k3 w - c6 0 1", "b5"));
+    /// assert!(ep_legal("8/8/8/KPp4n/8/8/8/4k3 w - c6 0 1", "b5"));
+    /// // A bishop pinning the capturing pawn along the diagonal it captures on
+    /// assert!(ep_legal("8/8/8/1k6/2pP4/8/8/5B1K b - d3 0 1", "c4"));
+    /// // Pins the capture leaves, diagonally and along the file
+    /// assert!(!ep_legal("8/8/4B3/8/2pP4/1k6/8/7K b - d3 0 1", "c4"));
+    /// assert!(!ep_legal("2k5/8/8/8/2pP4/8/8/2R4K b - d3 0 1", "c4"));
+    /// // The captured pawn gives check and is removed
+    /// assert!(ep_legal("8/8/8/4k3/2pP4/8/8/7K b - d3 0 1", "c4"));
+    /// // The double push uncovered a check from the bishop, which the capture doesn't block
+    /// assert!(!ep_legal("8/8/8/8/3Pp3/4k3/8/2B4K b - d3 0 1", "e4"));
+    /// // The captured pawn stands between the king and a bishop
+    /// assert!(!ep_legal("8/4b3/8/1Pp5/8/K7/8/4k3 w - c6 0 1", "b5"));
+    /// // No pawn that can capture en passant
+    /// assert!(!ep_legal("8/8/8/KPp5/8/8/8/4k3 w - c6 0 1", "a5"));
+    /// assert!(!ep_legal("8/8/8/KPp5/8/8/8/4k3 w - - 0 1", "b5"));
+    /// ```
+    pub fn ep_capture_legal(&self, from: impl TryInto<Square>) -> bool {
+        let from = match square::to_square(from) {
+            Some(from) => from.index(),
+            None => return false
+        };
+        let move_gen = self.move_generator();
+        let mut ep_capture = None;
+        move_gen.generate_pseudo_legal_moves(self, from, &mut |mv| {
+            if mv.is_ep_capture() {
+                ep_capture = Some(mv);
+            }
+        });
+        return match ep_capture {
+            Some(mv) => move_gen.ep_capture_legal(self, &move_gen.compute_check_info(self), mv),
+            None => false
+        };
+    }
+
+    /// Returns the number of legal moves in the current position, without storing the moves
+    pub fn count_legal_moves(&self) -> usize {
+        if self.is_automatically_terminated() {
+            return 0;
+        }
+        if let Some(legal_moves) = self.cache.get(&self.position_key()).and_then(|cached| cached.legal_moves) {
+            return legal_moves.len();
+        }
+        let mut n_moves = 0;
+        self.move_generator().generate_legal_moves(self, 0..64, &mut |_| n_moves += 1);
+        return n_moves;
+    }
+
+    /// Returns the number of squares the piece on the given square can move to, regardless of whose turn it is.
+    /// Pins and castling are ignored and a promotion counts once. Returns 0 for an empty square.
+    pub fn piece_mobility(&self, square: impl TryInto<Square>) -> usize {
+        let square = match square::to_square(square) {
+            Some(square) => square.index(),
+            None => return 0
+        };
+        if self.board[square].get_type() == EMPTY {
+            return 0;
+        }
+        return MoveGenerator::new().count_pseudo_legal_destinations(self, square);
+    }
+
+    /// Returns the sum of [Game::piece_mobility] over all pieces of the given color
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::starting_position();
+    /// assert_eq!(game.count_legal_moves(), 20);
+    /// assert_eq!(game.mobility(WHITE), 20);
+    /// assert_eq!(game.mobility(BLACK), 20);
+    /// ```
+    pub fn mobility(&self, color: usize) -> usize {
         let move_gen = MoveGenerator::new();
+        let mut mobility = 0;
+        for square in 0..64 {
+            if self.board[square].get_type() != EMPTY && self.board[square].get_color() == color {
+                mobility += move_gen.count_pseudo_legal_destinations(self, square);
+            }
+        }
+        return mobility;
+    }
+
+    /// Returns the squares attacked by the piece on the given square as a mask where bit n is set if square n
+    /// is attacked, regardless of whose turn it is. Sliding pieces attack up to and including the first piece 
+    /// in their way and pawns attack their two diagonal squares. Returns 0 for an empty square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::starting_position();
+    /// // The knight on g1 attacks e2, f3 and h3
+    /// assert_eq!(game.attacks_from(62), (1 << 52) | (1 << 45) | (1 << 47));
+    /// // The rook on a1 is boxed in by the pawn and knight
+    /// assert_eq!(game.attacks_from(56), (1 << 48) | (1 << 57));
+    /// assert_eq!(game.attacks_from(36), 0);
+    /// ```
+    pub fn attacks_from(&self, square: impl TryInto<Square>) -> u64 {
+        return match square::to_square(square) {
+            Some(square) => MoveGenerator::new().attacks_from(self, square.index()),
+            None => 0
+        };
+    }
+
+    /// Returns every square attacked by a piece of the given color as a mask, the union of [Game::attacks_from]
+    /// over the pieces of that color
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::starting_position();
+    /// // White attacks all of the second and third ranks and every square of the first except a1 and h1
+    /// assert_eq!(game.attack_map(WHITE), 0x7eff_ff00_0000_0000);
+    /// assert_eq!(game.attack_map(BLACK), 0x0000_0000_00ff_ff7e);
+    /// ```
+    pub fn attack_map(&self, color: usize) -> u64 {
+        let move_gen = MoveGenerator::new();
+        let mut attacks = 0;
+        for square in 0..64 {
+            if self.board[square].get_type() != EMPTY && self.board[square].get_color() == color {
+                attacks |= move_gen.attacks_from(self, square);
+            }
+        }
+        return attacks;
+    }
+
+    /// Returns how strongly the pieces of the other color attack the zone around the king of the given color,
+    /// the king's square and the squares next to it. Kings aren't counted as attackers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// assert_eq!(Game::starting_position().king_zone_attacks(WHITE).attackers, 0);
+    ///
+    /// // The queen and knight both hit f7 and h7 next to the black king
+    /// let game = Game::new("6k1/5ppp/8/6NQ/8/8/8/6K1 w - - 0 1");
+    /// let attacks = game.king_zone_attacks(BLACK);
+    /// assert_eq!(attacks.zone.count_ones(), 6);
+    /// assert_eq!((attacks.attackers, attacks.attacks, attacks.attacked_squares), (2, 4, 2));
+    /// assert_eq!(attacks.weight, KING_ZONE_ATTACK_WEIGHTS[KNIGHT] + KING_ZONE_ATTACK_WEIGHTS[QUEEN]);
+    /// ```
+    pub fn king_zone_attacks(&self, color: usize) -> KingZoneAttacks {
+        let move_gen = MoveGenerator::new();
+        let king_square = self.king_square[color];
+        let zone = move_gen.attacks_from(self, king_square) | (1 << king_square);
+        let mut king_zone_attacks = KingZoneAttacks {
+            zone,
+            attackers: 0,
+            attacks: 0,
+            attacked_squares: 0,
+            weight: 0
+        };
+
+        let mut attacked = 0;
+        for square in 0..64 {
+            let piece = self.board[square];
+            if piece.get_type() == EMPTY || piece.get_type() == KING || piece.get_color() == color {
+                continue;
+            }
+            let attacks = move_gen.attacks_from(self, square) & zone;
+            if attacks != 0 {
+                king_zone_attacks.attackers += 1;
+                king_zone_attacks.attacks += attacks.count_ones() as usize;
+                king_zone_attacks.weight += KING_ZONE_ATTACK_WEIGHTS[piece.get_type()];
+                attacked |= attacks;
+            }
+        }
+        king_zone_attacks.attacked_squares = attacked.count_ones() as usize;
+        return king_zone_attacks;
+    }
+
+    /// Returns the legal moves from the given square, in the current position
+    pub fn get_legal_moves(&self, square: impl TryInto<Square>) -> Vec<Move> {
+        let square = match square::to_square(square) {
+            Some(square) => square.index(),
+            None => return vec![]
+        };
+        if self.is_automatically_terminated() {
+            return vec![];
+        }
+        let mut legal_moves = vec![];
+        self.move_generator().generate_legal_moves(self, square..square + 1, &mut |mv| legal_moves.push(mv));
+        return legal_moves;
+    }
+
+    /// Returns the pseudo legal moves of the piece on the given square, whichever color it has: the moves it could
+    /// make if it were its color's turn, without checking whether they leave the king in check. Moves of a pinned
+    /// piece and king moves into check are included, so a board can show how a piece moves and mark the moves that
+    /// aren't legal, which are the ones missing from [Game::get_legal_moves]. Castling moves are only included when
+    /// legal and en passant only for the side to move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// // The knight on e2 is pinned by the rook on e7
+    /// let game = Game::new("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1");
+    /// assert_eq!(game.pseudo_legal_targets(Square::E2).len(), 6);
+    /// assert!(game.get_legal_moves(Square::E2).is_empty());
+    ///
+    /// // The rook is Black's, and could move along the file and the rank
+    /// let targets = game.pseudo_legal_targets(Square::E7);
+    /// assert_eq!(targets.len(), 12);
+    /// assert!(targets.iter().any(|mv| mv.to_square() == Square::E2 && mv.is_capture()));
+    /// ```
+    pub fn pseudo_legal_targets(&self, square: impl TryInto<Square>) -> Vec<Move> {
+        let square = match square::to_square(square) {
+            Some(square) => square.index(),
+            None => return vec![]
+        };
+        let piece = self.board[square];
+        let mut moves = vec![];
+        if piece.get_type() == EMPTY {
+            return moves;
+        }
+        if piece.get_color() == self.turn {
+            self.move_generator().generate_pseudo_legal_moves(self, square, &mut |mv| moves.push(mv));
+        }
+        else {
+            let mut flipped = self.detached();
+            flipped.turn ^= 1;
+            flipped.possible_ep_capture = 64;
+            flipped.move_generator().generate_pseudo_legal_moves(&flipped, square, &mut |mv| moves.push(mv));
+        }
+        return moves;
+    }
+
+    /// Returns true if the side not to move could make the move from 'from' to 'to' after some reply of the side to
+    /// move, so a client can accept it as a premove. The move has to be legal after at least one legal reply, which
+    /// may for example put a piece on the square to be captured or move a piece out of the way. Promotions count
+    /// whatever the promotion piece and castling is given as the king moving to its destination.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// // White premoves while Black is thinking
+    /// let game = Game::new("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    /// assert!(game.is_plausible_premove(Square::D2, Square::D4));
+    /// // Black can put a pawn on d5 to be captured, but can't get the pawn on d2 out of the queen's way
+    /// assert!(game.is_plausible_premove(Square::E4, Square::D5));
+    /// assert!(!game.is_plausible_premove(Square::D1, Square::D3));
+    /// // Black is to move, so its moves aren't premoves
+    /// assert!(!game.is_plausible_premove(Square::E7, Square::E5));
+    /// ```
+    pub fn is_plausible_premove(&self, from: impl TryInto<Square>, to: impl TryInto<Square>) -> bool {
+        let (from, to) = match (square::to_square(from), square::to_square(to)) {
+            (Some(from), Some(to)) => (from.index(), to.index()),
+            _ => return false
+        };
+        let piece = self.board[from];
+        if piece.get_type() == EMPTY || piece.get_color() == self.turn {
+            return false;
+        }
+
+        let mut game = self.clone();
+        for &reply in self.legal_moves_cached().iter() {
+            game.make_move(reply);
+            let plausible = game.get_legal_moves(from).iter().any(|mv| mv.get_to() == to);
+            game.unmake_move();
+            if plausible {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /// Returns the game state of the current position, everything but 3-fold repetition is included. The state of
+    /// an impossible position, such as one where the side not to move is in check, isn't meaningful, see
+    /// [Game::state_for] for a state that checks the position first.
+    pub fn get_game_state(&self) -> GameState {
+        let key = self.position_key();
+        if let Some(game_state) = self.cache.get(&key).and_then(|cached| cached.game_state) {
+            return game_state;
+        }
+        let game_state = self.compute_game_state();
+        self.cache.update(key, |cached| cached.game_state = Some(game_state));
+        return game_state;
+    }
+
+    fn compute_game_state(&self) -> GameState {
         let mut game_state = GameState::InProgress;
         
-        if move_gen.is_attacked(self, self.king_square[self.turn], self.turn) {
+        if self.is_in_check() {
             game_state = GameState::Check;
 
-            let legal_moves = self.get_all_legal_moves();
-            if legal_moves.len() == 0 {
+            if self.generated_legal_moves().is_empty() {
                 return GameState::Checkmate;
             }
         }
-        else {
-            let legal_moves = self.get_all_legal_moves();
-            if legal_moves.len() == 0 {
-                return GameState::Stalemate;
-            }
+        else if self.generated_legal_moves().is_empty() {
+            return GameState::Stalemate;
         }
 
+        return self.draw_state().unwrap_or(game_state);
+    }
+
+    /// Returns the draw the game is in regardless of which side is to move, if any
+    fn draw_state(&self) -> Option<GameState> {
+        if self.repetition_count() >= 5 {
+            return Some(GameState::FivefoldRepetition);
+        }
+        if self.half_move_clock >= self.rules.automatic_draw_halfmoves {
+            return Some(GameState::DrawBy75MoveRule);
+        }
+
+        if self.has_insufficient_material() {
+            return Some(GameState::InsufficientMaterial);
+        }
+        
+        if self.half_move_clock >= self.rules.claimable_draw_halfmoves {
+            return Some(GameState::DrawBy50MoveRule);
+        }
+        return None;
+    }
+
+    /// Returns the game state for the given color as if it were its turn to move, which for the side to move is
+    /// [Game::get_game_state]. The side not to move can't be in check in a legal position, so for it the state is
+    /// a stalemate when it would have no legal moves, and otherwise a draw of the game or in progress.
+    ///
+    /// Positions from analysis tools can be impossible, so the position is validated first with
+    /// [Game::check_invariants], which among other things returns [InvariantError::OpponentInCheck] when the side
+    /// not to move is in check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// // Black would be stalemated if it were to move
+    /// let game = Game::new("k7/2Q5/1K6/8/8/8/8/8 w - - 0 1");
+    /// assert_eq!(game.state_for(WHITE), Ok(GameState::InProgress));
+    /// assert_eq!(game.state_for(BLACK), Ok(GameState::Stalemate));
+    ///
+    /// // White to move, but the black king is already in check
+    /// let game = Game::new("k7/8/8/8/8/8/8/R3K3 w - - 0 1");
+    /// assert_eq!(game.state_for(WHITE), Err(InvariantError::OpponentInCheck));
+    /// assert_eq!(game.state_for(BLACK), Err(InvariantError::OpponentInCheck));
+    /// ```
+    pub fn state_for(&self, color: usize) -> Result<GameState, InvariantError> {
+        self.check_invariants()?;
+        if color == self.turn {
+            return Ok(self.get_game_state());
+        }
+        let mut flipped = self.detached();
+        flipped.turn ^= 1;
+        flipped.possible_ep_capture = 64;
+        if flipped.generated_legal_moves().is_empty() {
+            return Ok(GameState::Stalemate);
+        }
+        return Ok(self.draw_state().unwrap_or(GameState::InProgress));
+    }
+
+    /// Returns true if neither side has enough pieces left to checkmate
+    fn has_insufficient_material(&self) -> bool {
         let mut n_pieces = [[0; 7]; 2];
         for square in 0..64 {
             if self.board[square].get_type() != EMPTY {
@@ -632,22 +2390,309 @@ impl Game {
                 n_pieces[self.board[square].get_color()][self.board[square].get_type()] += 1;
             }
         }
-        if n_pieces[WHITE][0] <= 3 && n_pieces[BLACK][0] <= 3 && 
+        return n_pieces[WHITE][0] <= 3 && n_pieces[BLACK][0] <= 3 && 
 			(n_pieces[WHITE][0] == 1 || 
 			(n_pieces[WHITE][0] == 2 && (n_pieces[WHITE][BISHOP] == 1 || n_pieces[WHITE][KNIGHT] == 1)) ||
 			(n_pieces[WHITE][0] == 3 && n_pieces[WHITE][KNIGHT] == 2))
 			&&
 			(n_pieces[BLACK][0] == 1 ||
 			(n_pieces[BLACK][0] == 2 && (n_pieces[BLACK][BISHOP] == 1 || n_pieces[BLACK][KNIGHT] == 1)) ||
-			(n_pieces[BLACK][0] == 3 && n_pieces[BLACK][KNIGHT] == 2)) {
-                return GameState::InsufficientMaterial;
+			(n_pieces[BLACK][0] == 3 && n_pieces[BLACK][KNIGHT] == 2));
+    }
+
+    /// Returns how many times the current position has occurred in the game, including now.
+    /// Only positions since the game was created from FEN are known.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use olindba_chess::*;
+    /// let mut game = Game::starting_position();
+    /// for _ in 0..4 {
+    ///     game.make_move_from_to(62, 45, None);
+    ///     game.make_move_from_to(6, 21, None);
+    ///     game.make_move_from_to(45, 62, None);
+    ///     game.make_move_from_to(21, 6, None);
+    /// }
+    /// assert_eq!(game.repetition_count(), 5);
+    /// assert_eq!(game.get_game_state(), GameState::FivefoldRepetition);
+    /// assert_eq!(game.count_legal_moves(), 0);
+    /// 
+    /// game.set_automatic_termination(false);
+    /// assert_eq!(game.count_legal_moves(), 20);
+    /// ```
+    pub fn repetition_count(&self) -> usize {
+        let hash = self.hash();
+        let reversible_plies = self.history.len() - self.last_irreversible_ply;
+        let earlier = self.history.iter().rev().take(reversible_plies).filter(|earlier| earlier.hash == hash).count();
+        return earlier + 1;
+    }
+
+    /// Sets the rules the game is played by
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let mut game = Game::new("k7/P7/1K6/8/8/8/8/8 b - - 60 80");
+    /// assert_eq!(game.result(), Some(GameResult::Draw));
+    ///
+    /// game.set_rules(Rules {
+    ///     claimable_draw_halfmoves: 40,
+    ///     stalemate: StalemateOutcome::Loss,
+    ///     promotions: PromotionSet::QUEEN_ONLY,
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(game.result(), Some(GameResult::WhiteWins));
+    ///
+    /// let mut game = Game::new("4k3/P7/8/8/8/8/8/4K3 w - - 60 80");
+    /// game.set_rules(Rules {
+    ///     claimable_draw_halfmoves: 40,
+    ///     promotions: PromotionSet::QUEEN_ONLY,
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(game.get_game_state(), GameState::DrawBy50MoveRule);
+    /// assert_eq!(game.get_legal_moves(8).len(), 1);
+    /// ```
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+        self.cache.clear();
+    }
+
+    /// Returns the rules the game is played by
+    pub fn rules(&self) -> Rules {
+        return self.rules;
+    }
+
+    /// Returns a move generator for the promotions allowed by the rules
+    fn move_generator(&self) -> MoveGenerator {
+        return MoveGenerator::with_options(&MoveGenOptions {
+            promotions: self.rules.promotions,
+            mandatory_capture: self.rules.mandatory_capture
+        });
+    }
+
+    /// Sets whether fivefold repetition and the 75-move rule stop move generation, which they do by default.
+    /// [Game::get_game_state] reports these draws either way.
+    pub fn set_automatic_termination(&mut self, enabled: bool) {
+        self.automatic_termination = enabled;
+    }
+
+    /// Sets whether moves can be made after the game is over, for exploring a finished game. In analysis mode
+    /// [Game::make_move_from_to] and [Game::try_make_move] accept any legal move, and fivefold repetition and the
+    /// 75-move rule don't stop move generation. [Game::is_game_over] still reports the end of the game.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let mut game = Game::new("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    /// assert!(game.is_game_over());
+    /// assert_eq!(game.make_move_from_to(60, 52, None), MoveOutcome::GameOver);
+    ///
+    /// game.set_analysis_mode(true);
+    /// assert_eq!(game.make_move_from_to(60, 52, None), MoveOutcome::Applied);
+    /// assert!(game.is_game_over());
+    /// ```
+    pub fn set_analysis_mode(&mut self, enabled: bool) {
+        self.analysis_mode = enabled;
+    }
+
+    /// Returns true if moves can be made after the game is over, see [Game::set_analysis_mode]
+    pub fn is_analysis_mode(&self) -> bool {
+        return self.analysis_mode;
+    }
+
+    /// Returns true if the game has ended: by checkmate, stalemate or a position where neither side can checkmate,
+    /// or by fivefold repetition or the 75-move rule when automatic termination is enabled. A draw by the 50-move
+    /// rule has to be claimed, so it doesn't end the game.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// assert!(!Game::starting_position().is_game_over());
+    /// assert!(Game::new("k7/1Q6/1K6/8/8/8/8/8 b - - 0 1").is_game_over());
+    /// assert!(!Game::new("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").is_game_over());
+    /// assert!(Game::new("4k3/8/8/8/8/8/8/R3K3 w - - 150 80").is_game_over());
+    /// ```
+    pub fn is_game_over(&self) -> bool {
+        return match self.get_game_state() {
+            GameState::Checkmate | GameState::Stalemate | GameState::InsufficientMaterial => true,
+            GameState::FivefoldRepetition | GameState::DrawBy75MoveRule => self.automatic_termination,
+            _ => false
+        };
+    }
+
+    /// Makes the move if it is legal and the game isn't over, or in analysis mode, and tells whether it was made.
+    /// Unlike [Game::make_move] the move doesn't have to come from the current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::parse_uci;
+    /// let mut game = Game::new("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    /// let mate = parse_uci(&game, "a1a8").unwrap();
+    /// let stale = parse_uci(&game, "g1f1").unwrap();
+    /// assert_eq!(game.try_make_move(mate), MoveOutcome::Applied);
+    /// assert_eq!(game.try_make_move(stale), MoveOutcome::GameOver);
+    /// assert_eq!(game.fullmove_number(), 1);
+    /// ```
+    pub fn try_make_move(&mut self, mv: Move) -> MoveOutcome {
+        if !self.analysis_mode && self.is_game_over() {
+            return MoveOutcome::GameOver;
+        }
+        if !self.legal_moves_cached().contains(&mv) {
+            return MoveOutcome::IllegalMove;
+        }
+        self.make_move(mv);
+        return MoveOutcome::Applied;
+    }
+
+    /// Returns true if the game has ended by fivefold repetition or the 75-move rule and 
+    /// automatic termination is enabled outside analysis mode. Checkmate takes precedence over both.
+    /// The rules are checked directly instead of through [Game::get_game_state], so no move list is allocated.
+    fn is_automatically_terminated(&self) -> bool {
+        if !self.automatic_termination || self.analysis_mode {
+            return false;
+        }
+        // A fifth occurrence needs at least 16 reversible plies, so the hash is only compared after that many
+        let reversible_plies = self.history.len() - self.last_irreversible_ply;
+        if self.half_move_clock < self.rules.automatic_draw_halfmoves
+            && (reversible_plies < 16 || self.repetition_count() < 5) {
+            return false;
+        }
+        return !self.is_checkmated();
+    }
+
+    /// Returns the castling rights of both colors.
+    /// A right is kept as long as neither the king nor the rook on that side has moved.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use olindba_chess::*;
+    /// let mut game = Game::new("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1");
+    /// assert_eq!(game.castling_rights().to_string(), "Kq");
+    /// 
+    /// // The rook on h1 leaves its square and the black king has to move out of check
+    /// game.make_move_from_to(63, 7, None);
+    /// game.make_move_from_to(4, 12, None);
+    /// assert_eq!(game.castling_rights().to_string(), "-");
+    /// ```
+    pub fn castling_rights(&self) -> CastlingRights {
+        let mut castling_rights = [false; 4];
+        for color in [WHITE, BLACK] {
+            for (i, king_side) in [true, false].into_iter().enumerate() {
+                castling_rights[color * 2 + i] = self.castling_rook_square(color, king_side).is_some();
             }
-        
-        if self.half_move_clock >= 100 {
-            return GameState::DrawBy50MoveRule;
         }
+        return CastlingRights::from_array(castling_rights);
+    }
 
-        return game_state;
+    /// Returns the square of the rook the given color can still castle with on the given side, if any.
+    /// The king has to be unmoved on its back rank and the rook unmoved on the same side of the king,
+    /// which also covers Chess960 starting positions.
+    pub(crate) fn castling_rook_square(&self, color: usize, king_side: bool) -> Option<usize> {
+        let king_square = self.king_square[color];
+        let king = self.board[king_square];
+        let back_row;
+        if color == WHITE {
+            back_row = 7;
+        }
+        else {
+            back_row = 0;
+        }
+        if king.get_type() != KING || king.get_color() != color || king.has_moved() || self.get_row(king_square) != back_row {
+            return None;
+        }
+
+        let king_column = self.get_column(king_square);
+        let is_castling_rook = |column: &usize| {
+            let rook = self.board[back_row * 8 + column];
+            return rook.get_type() == ROOK && rook.get_color() == color && !rook.has_moved();
+        };
+        let column;
+        if king_side {
+            column = (king_column + 1..8).rev().find(is_castling_rook);
+        }
+        else {
+            column = (0..king_column).find(is_castling_rook);
+        }
+        return column.map(|column| back_row * 8 + column);
+    }
+
+    /// Returns the last move made with [Game::make_move], or None if no move has been made since the position was set
+    pub fn last_move(&self) -> Option<Move> {
+        return self.last_move;
+    }
+
+    /// Returns the number of moves made since the position was set up, the length of the game's history
+    pub fn ply(&self) -> usize {
+        return self.history.len();
+    }
+
+    /// Returns the [ply](Game::ply) after the last irreversible move: a capture, a pawn move or a move that changed
+    /// the castling rights. It is 0 if no such move has been made since the position was set up. The positions
+    /// before it can't occur again, so repetitions are only looked for after it.
+    ///
+    /// Unlike the [halfmove clock](Game::halfmove_clock) this also counts the loss of castling rights, and it
+    /// doesn't reach back past the position the game was set up with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::parse_uci;
+    /// let mut game = Game::starting_position();
+    /// for mv in ["g1f3", "g8f6", "e2e4", "b8c6", "h1g1", "c6b8"] {
+    ///     game.make_move(parse_uci(&game, mv).unwrap());
+    /// }
+    /// assert_eq!(game.ply(), 6);
+    /// // Moving the rook gave up castling kingside
+    /// assert_eq!(game.last_irreversible_ply(), 5);
+    /// assert_eq!(game.halfmove_clock(), 3);
+    /// ```
+    pub fn last_irreversible_ply(&self) -> usize {
+        return self.last_irreversible_ply;
+    }
+
+    /// Returns the number of half moves since the last capture or pawn move
+    pub fn halfmove_clock(&self) -> usize {
+        return self.half_move_clock;
+    }
+
+    /// Returns the number of the current full move, starting at 1 and increased after every move by Black
+    pub fn fullmove_number(&self) -> usize {
+        return self.full_move_number;
+    }
+
+    /// Returns the square a pawn can be captured en passant on, behind the pawn that just made a double push,
+    /// the same square as in FEN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let mut game = Game::starting_position();
+    /// assert_eq!(game.en_passant_square(), None);
+    /// game.make_move_from_to(52, 36, None);
+    /// assert_eq!(game.en_passant_square(), Square::from_algebraic("e3"));
+    /// ```
+    pub fn en_passant_square(&self) -> Option<Square> {
+        if self.possible_ep_capture >= 64 || self.board[self.possible_ep_capture].get_type() != PAWN {
+            return None;
+        }
+        if self.board[self.possible_ep_capture].get_color() == WHITE {
+            return Some(Square::new(self.possible_ep_capture + 8));
+        }
+        return Some(Square::new(self.possible_ep_capture - 8));
+    }
+
+    /// Returns the square the king of the given color stands on
+    pub fn king_square(&self, color: usize) -> Square {
+        return Square::new(self.king_square[color]);
     }
 
     /// Makes a move from a given square to another given square
@@ -655,52 +2700,121 @@ impl Game {
     /// # Arguments
     /// * 'from' - the square the move is made from
     /// * 'to' - the square the made is made to
-    /// * 'promotion' the selected promotion if the move is a promotion, otherwise leave as EMPTY
+    /// * 'promotion' the selected promotion if the move is a promotion, otherwise leave as None
     /// 
     /// # Returns
-    /// * bool - True if the move is legal and false otherwise
+    /// * MoveOutcome - Applied if the move was made, otherwise why it wasn't. A promotion without a piece is
+    ///   ambiguous, the promotion of a move that isn't a promotion is ignored.
     /// 
     /// # Examples
     /// 
     /// ```
-    /// game.make_move_from_to(3, 11, EMPTY);
-    /// game.make_move_from_to(8, 0, QUEEN_PROMOTION);
+    /// # use olindba_chess::*;
+    /// let mut game = Game::starting_position();
+    /// assert!(game.make_move_from_to(52, 36, None).is_applied());
+    /// assert_eq!(game.make_move_from_to(51, 35, None), MoveOutcome::WrongTurn);
+    /// assert_eq!(game.make_move_from_to(12, 36, None), MoveOutcome::IllegalMove);
+    /// assert_eq!(game.make_move_from_to(99, 0, None), MoveOutcome::IllegalMove);
+    /// assert!(game.make_move_from_to(Square::from_a1_indexing(52), Square::from_a1_indexing(36), None).is_applied());
+    /// 
+    /// let mut game = Game::new("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    /// assert_eq!(game.make_move_from_to(8, 0, None), MoveOutcome::AmbiguousPromotion);
+    /// assert_eq!(game.make_move_from_to(8, 0, Promotion::King), MoveOutcome::IllegalMove);
+    /// assert_eq!(game.make_move_from_to(8, 0, Promotion::Queen), MoveOutcome::Applied);
+    /// assert_eq!(game.last_move().unwrap().promotion(), Some(Promotion::Queen));
+    ///
+    /// let mut game = Game::new("k7/1Q6/1K6/8/8/8/8/8 b - - 0 1");
+    /// assert_eq!(game.make_move_from_to(0, 1, None), MoveOutcome::GameOver);
     /// ```
     /// 
-    pub fn make_move_from_to(&mut self, from: usize, to: usize, promotion: usize) -> bool {
-        let legal_moves = self.get_all_legal_moves();
-        for mv in legal_moves {
+    pub fn make_move_from_to(&mut self, from: impl TryInto<Square>, to: impl TryInto<Square>,
+                             promotion: impl Into<Option<Promotion>>) -> MoveOutcome {
+        let (from, to) = match (square::to_square(from), square::to_square(to)) {
+            (Some(from), Some(to)) => (from.index(), to.index()),
+            _ => return MoveOutcome::IllegalMove
+        };
+        let promotion = promotion.into();
+        if !self.analysis_mode && self.is_game_over() {
+            return MoveOutcome::GameOver;
+        }
+        let legal_moves = self.legal_moves_cached();
+        if legal_moves.is_empty() {
+            return MoveOutcome::GameOver;
+        }
+        if self.board[from].get_type() != EMPTY && self.board[from].get_color() != self.turn {
+            return MoveOutcome::WrongTurn;
+        }
+
+        let mut is_promotion = false;
+        for &mv in legal_moves.iter() {
             if mv.get_from() == from && mv.get_to() == to {
-                if mv.is_promotion() && (mv.get_flags() & !CAPTURE) != promotion {
+                if mv.is_promotion() && mv.promotion() != promotion {
+                    is_promotion = true;
                     continue;
                 }
                 self.make_move(mv);
-                return true;
+                return MoveOutcome::Applied;
             }
         }
-        return false;
+        if is_promotion && promotion.is_none() {
+            return MoveOutcome::AmbiguousPromotion;
+        }
+        return MoveOutcome::IllegalMove;
     }
     
     /// Makes the given move on the current board. 
     /// The move struct is given by either 'Game::generate_all_legal_moves' or 'Game::generate_legal_moves'.
+    /// The move is recorded in [Game::history] and can be taken back with [Game::unmake_move].
     pub fn make_move(&mut self, mv: Move) {
 
-        self.half_move_clock += 1;
-        if self.board[mv.get_from()].get_type() == KING {
-            self.king_square[self.turn] = mv.get_to();
-        }
-        if self.board[mv.get_from()].get_type() == PAWN {
-            self.half_move_clock = 0;
-        }
-
+        self.cache.clear();
+        self.ending = None;
+        let moving = self.board[mv.get_from()];
+        let mut captured = None;
         if mv.is_capture() {
-            self.half_move_clock = 0;
             let mut captured_square = mv.get_to();
             if mv.is_ep_capture() {
                 captured_square = (mv.get_from() as isize + 
                 (self.get_column(mv.get_to()) as isize - self.get_column(mv.get_from()) as isize)) as usize;
             }
-            self.board[captured_square].set_type(EMPTY);
+            captured = Some((self.board[captured_square], Square::new(captured_square)));
+        }
+        let mut rook = None;
+        if mv.is_castle() {
+            let (rook_from, rook_to) = self.castling_rook_move(&mv);
+            rook = Some((self.board[rook_from], rook_from, rook_to, self.board[rook_to]));
+        }
+        let castling_rights = self.castling_rights();
+        let hash = self.hash();
+        self.history.push(HistoryEntry {
+            hash,
+            mv,
+            piece: moving,
+            captured,
+            castling_rights,
+            en_passant: self.en_passant_square(),
+            halfmove_clock: self.half_move_clock,
+            destination: self.board[mv.get_to()],
+            rook,
+            possible_ep_capture: self.possible_ep_capture,
+            last_irreversible_ply: self.last_irreversible_ply,
+            last_move: self.last_move,
+            eval_terms: self.eval_terms,
+            line_hash: zobrist::extend_line(self.line_hash(), hash, &mv)
+        });
+        if let Some(mut terms) = self.eval_terms {
+            update_for_move(&mut terms, self, &mv);
+            self.eval_terms = Some(terms);
+        }
+
+        self.last_move = Some(mv);
+        self.half_move_clock += 1;
+        if moving.get_type() == PAWN {
+            self.half_move_clock = 0;
+        }
+        if let Some((_, captured_square)) = captured {
+            self.half_move_clock = 0;
+            self.board[captured_square.index()].set_type(EMPTY);
         }
         if self.possible_ep_capture < 64 {
             self.possible_ep_capture = 64;
@@ -708,49 +2822,128 @@ impl Game {
         if mv.is_double_pawn_push() {
             self.possible_ep_capture = mv.get_to();
         }
-        self.board[mv.get_to()] = self.board[mv.get_from()];
-        self.board[mv.get_from()].set_type(EMPTY);
+        let piece = self.board[mv.get_from()];
+        if let Some((rook, rook_from, rook_to, _)) = rook {
+            // Both pieces are lifted before either is placed since in Chess960 the rook can land where the king stood
+            // and the king where the rook stood
+            self.board[mv.get_from()].set_type(EMPTY);
+            self.board[rook_from].set_type(EMPTY);
+            self.board[rook_to] = rook;
+            self.board[rook_to].set_flags(HAS_MOVED);
+        }
+        else {
+            self.board[mv.get_from()].set_type(EMPTY);
+        }
+        self.board[mv.get_to()] = piece;
         self.board[mv.get_to()].set_flags(HAS_MOVED);
-        
-        if mv.is_castle() {
-            let rook_move;
-            if mv.is_queen_castle() {
-                rook_move = (mv.get_from() - 4, mv.get_from() - 1);
-            }
-            else {  
-                rook_move = (mv.get_from() + 3, mv.get_from() + 1);
-            }
-            self.board[rook_move.1] = self.board[rook_move.0];
-            self.board[rook_move.0].set_type(EMPTY);
+        // A king promoted to isn't the one checks are about
+        if piece.get_type() == KING && mv.get_from() == self.king_square[self.turn] {
+            self.king_square[self.turn] = mv.get_to();
         }
         
         if mv.is_promotion() {
-            let promotion_type = mv.get_flags() & !(CAPTURE);
+            self.board[mv.get_to()].set_type(mv.get_promotion_type());
+        }
+        if self.turn == BLACK {
+            self.full_move_number += 1;
+        }
+        self.turn ^= 1;
+        // Only moving an unmoved king or rook can give up castling rights, captures are irreversible anyway
+        let may_lose_rights = (moving.get_type() == KING || moving.get_type() == ROOK) && !moving.has_moved();
+        if self.half_move_clock == 0 || (may_lose_rights && castling_rights != self.castling_rights()) {
+            self.last_irreversible_ply = self.history.len();
+        }
+    }
 
-            if promotion_type == BISHOP_PROMOTION {
-                self.board[mv.get_to()].set_type(BISHOP);
-            }
-            if promotion_type == KNIGHT_PROMOTION {
-                self.board[mv.get_to()].set_type(KNIGHT);
-            }
-            if promotion_type == ROOK_PROMOTION {
-                self.board[mv.get_to()].set_type(ROOK);
-            }
-            if promotion_type == QUEEN_PROMOTION {
-                self.board[mv.get_to()].set_type(QUEEN);
+    /// Returns the position with the colors of all pieces swapped and the board flipped vertically.
+    /// The side to move is swapped as well, so the mirrored position is equivalent to the current one
+    /// with the roles of White and Black exchanged.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// # use olindba_chess::*;
+    /// let mut game = Game::starting_position();
+    /// game.make_move_from_to(52, 36, None);
+    /// let mirrored = game.mirrored();
+    /// assert_eq!(mirrored.turn, WHITE);
+    /// assert_eq!(mirrored.board[Square::new(36).flip_vertical().index()].get_color(), BLACK);
+    /// ```
+    pub fn mirrored(&self) -> Game {
+        let mut board = [Piece::empty(); 64];
+        for square in 0..64 {
+            let mut piece = self.board[Square::new(square).flip_vertical().index()];
+            if piece.get_type() != EMPTY {
+                piece = Piece::new(piece.get_type(), piece.get_color() ^ 1, piece.get_flags());
             }
+            board[square] = piece;
         }
-        self.turn ^= 1;
+
+        let mut possible_ep_capture = 64;
+        if self.possible_ep_capture < 64 {
+            possible_ep_capture = Square::new(self.possible_ep_capture).flip_vertical().index();
+        }
+
+        return Game {
+            board,
+            turn: self.turn ^ 1,
+            possible_ep_capture,
+            king_square: [
+                Square::new(self.king_square[BLACK]).flip_vertical().index(),
+                Square::new(self.king_square[WHITE]).flip_vertical().index()
+            ],
+            half_move_clock: self.half_move_clock,
+            full_move_number: self.full_move_number,
+            history: vec![],
+            last_irreversible_ply: 0,
+            last_move: None,
+            annotations: BTreeMap::new(),
+            ending: None,
+            automatic_termination: self.automatic_termination,
+            analysis_mode: self.analysis_mode,
+            rules: self.rules,
+            eval_terms: None,
+            cache: PositionCache::new()
+        }
+    }
+
+    /// Returns the square the rook of a castling move starts on and the square it ends on.
+    /// Has to be called before the move is made.
+    pub(crate) fn castling_rook_move(&self, mv: &Move) -> (usize, usize) {
+        let color = self.board[mv.get_from()].get_color();
+        let back_rank = mv.get_from() - self.get_column(mv.get_from());
+        let rook_square = self.castling_rook_square(color, mv.is_king_castle()).unwrap_or(mv.get_from());
+        if mv.is_queen_castle() {
+            return (rook_square, back_rank + 3);
+        }
+        return (rook_square, back_rank + 5);
     }
 
     /// Returns the collumn of the given square, indexed from left to right
-    pub fn get_column(&self, square: usize) -> usize {
-        return square % 8;
+    pub fn get_column(&self, square: impl Into<usize>) -> usize {
+        return square.into() % 8;
     }
 
     /// Returns the row of the given square, indexed from up to down
-    pub fn get_row(&self, square: usize) -> usize {
-        return square / 8;
+    pub fn get_row(&self, square: impl Into<usize>) -> usize {
+        return square.into() / 8;
+    }
+}
+
+impl Default for Game {
+    /// Returns a game in the starting position, the same as parsing its FEN string
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::fen::to_fen;
+    /// let game: Game = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".parse().unwrap();
+    /// assert_eq!(to_fen(&game), to_fen(&Game::default()));
+    /// assert!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w".parse::<Game>().is_err());
+    /// ```
+    fn default() -> Game {
+        return Game::starting_position();
     }
 }
 
@@ -759,7 +2952,7 @@ impl std::fmt::Display for Game {
         let mut board_string: String = "".to_string();
         for i in 0..64 {
             if i != 0 && i % 8 == 0 {
-                board_string.push_str("\n");
+                board_string.push('\n');
             }
 
             if self.board[i].get_type() != EMPTY {
@@ -786,4 +2979,9 @@ impl std::fmt::Display for Game {
         }
         write!(f, "{}", board_string)
     }
-}
\ No newline at end of file
+}
+impl std::fmt::Debug for Game {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Game(\"{}\")", fen::to_fen(self))
+    }
+}
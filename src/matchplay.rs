@@ -0,0 +1,649 @@
+//! Playing matches between two players, for example to test engines against each other
+//!
+//! A [Match] plays a series of games between two [Player]s, such as [crate::engine::Engine] and a [RandomPlayer],
+//! alternating colors, ends games early by the rules of its [Adjudication] and keeps every game as a [PgnGame] so
+//! the whole match can be exported as PGN.
+//! The same rules can be applied to any game with [adjudicate], for example by a server.
+//!
+//! A [TimedMatch] plays games with a [TimeControl] between [TimedPlayer]s, which report the time each move took.
+//! The time is charged to a simulated [Clock], so games are reproducible when the players simulate their time too,
+//! as [crate::engine::Engine] does with [Engine::set_simulated_speed](crate::engine::Engine::set_simulated_speed).
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::matchplay::{Match, RandomPlayer};
+//! let mut random_match = Match::new(Box::new(RandomPlayer::new(1)), Box::new(RandomPlayer::new(2)));
+//! random_match.play(2);
+//! let score = random_match.score();
+//! assert_eq!(score.wins + score.draws + score.losses, 2);
+//! assert_eq!(random_match.games()[1].header("White"), Some("Random 2"));
+//! assert!(random_match.to_pgn().contains("[Round \"2\"]"));
+//! ```
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::clock::{Clock, TimeControl};
+use crate::pgn::PgnGame;
+use crate::rating;
+use crate::sprt::{Sprt, SprtStatus};
+use crate::uci::SearchLimits;
+use crate::{Game, GameResult, GameState, Move, Termination};
+
+/// A participant in a [Match]
+#[allow(unused_variables)]
+pub trait Player {
+
+    /// Returns the name written in the PGN headers
+    fn name(&self) -> String;
+
+    /// Called before every game
+    fn new_game(&mut self) {}
+
+    /// Returns the move to play in the position, or None to resign
+    fn choose_move(&mut self, game: &Game) -> Option<Move>;
+
+    /// Returns the player's evaluation in centipawns, from its own point of view, of the last position it chose
+    /// a move in. Used for resign adjudication.
+    fn last_score(&self) -> Option<i32> { None }
+}
+
+/// A player making uniformly random legal moves, seeded so games can be reproduced
+pub struct RandomPlayer {
+    seed: u64,
+    state: u64
+}
+
+impl RandomPlayer {
+
+    pub fn new(seed: u64) -> RandomPlayer {
+        RandomPlayer {
+            seed,
+            state: seed
+        }
+    }
+
+    fn next_random(&mut self) -> u64 {
+        // splitmix64
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        return z ^ (z >> 31);
+    }
+}
+
+impl Player for RandomPlayer {
+
+    fn name(&self) -> String {
+        return format!("Random {}", self.seed);
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Option<Move> {
+        let moves = game.legal_moves_cached();
+        if moves.is_empty() {
+            return None;
+        }
+        let index = (self.next_random() % moves.len() as u64) as usize;
+        return Some(moves[index]);
+    }
+}
+
+/// An endgame tablebase, or anything else that knows the result of some positions with perfect play
+pub trait Tablebase {
+
+    /// Returns the result of the position with perfect play by both sides, or None if it isn't known
+    fn probe(&self, game: &Game) -> Option<GameResult>;
+}
+
+/// Resign a game for a player whose own score has been at or below -'score' for 'moves' of its moves in a row
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ResignRule {
+    pub score: i32,
+    pub moves: usize
+}
+
+/// The rules a [Match] uses to end games before checkmate, stalemate, fivefold repetition or the 75-move rule
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Adjudication {
+    /// Draw as soon as the 50-move rule could be claimed
+    pub fifty_move_rule: bool,
+    /// Draw as soon as neither side has the material to checkmate
+    pub insufficient_material: bool,
+    /// Draw as soon as the position is dead as recognized by [Game::is_dead_position], for example a locked pawn chain
+    pub dead_position: bool,
+    /// Draw once the same position has occurred three times
+    pub threefold_repetition: bool,
+    /// Draw after this many moves by each side, counted by the full move number
+    pub max_moves: Option<usize>,
+    /// Win for the side that is ahead by at least this much material, in centipawns
+    pub material_margin: Option<i32>,
+    pub resign: Option<ResignRule>
+}
+
+impl Default for Adjudication {
+    fn default() -> Adjudication {
+        Adjudication {
+            fifty_move_rule: true,
+            insufficient_material: true,
+            dead_position: false,
+            threefold_repetition: false,
+            max_moves: None,
+            material_margin: None,
+            resign: None
+        }
+    }
+}
+
+/// The results of a match from the point of view of the first player
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MatchScore {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize
+}
+
+impl MatchScore {
+
+    /// Returns how many Elo points stronger the first player performed, None before it has both scored and dropped
+    /// points
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::matchplay::MatchScore;
+    /// let score = MatchScore { wins: 60, draws: 100, losses: 40 };
+    /// assert!((score.elo_difference().unwrap() - 34.86).abs() < 0.01);
+    /// assert!((score.elo_error_margin().unwrap() - 34.16).abs() < 0.01);
+    /// assert_eq!(MatchScore { wins: 3, draws: 0, losses: 0 }.elo_difference(), None);
+    /// ```
+    pub fn elo_difference(&self) -> Option<f64> {
+        let (mean, _) = self.mean_and_deviation()?;
+        return Some(rating::elo_difference(mean));
+    }
+
+    /// Returns the half width of the 95% confidence interval of [MatchScore::elo_difference]
+    pub fn elo_error_margin(&self) -> Option<f64> {
+        let (mean, deviation) = self.mean_and_deviation()?;
+        let margin = 1.959964 * deviation;
+        let (low, high) = ((mean - margin).max(f64::MIN_POSITIVE), (mean + margin).min(1.0 - f64::EPSILON));
+        return Some((rating::elo_difference(high) - rating::elo_difference(low)) / 2.0);
+    }
+
+    /// Returns the mean score per game and the standard deviation of that mean
+    fn mean_and_deviation(&self) -> Option<(f64, f64)> {
+        let games = (self.wins + self.draws + self.losses) as f64;
+        if self.wins + self.draws == 0 || self.draws + self.losses == 0 {
+            return None;
+        }
+        let wins = self.wins as f64 / games;
+        let draws = self.draws as f64 / games;
+        let mean = wins + draws / 2.0;
+        let variance = wins + draws / 4.0 - mean * mean;
+        return Some((mean, (variance / games).sqrt()));
+    }
+}
+
+/// A series of games between two players. The first player has White in the odd numbered games.
+pub struct Match {
+    players: [Box<dyn Player>; 2],
+    adjudication: Adjudication,
+    tablebase: Option<Box<dyn Tablebase>>,
+    event: String,
+    games: Vec<PgnGame>
+}
+
+impl Match {
+
+    pub fn new(first: Box<dyn Player>, second: Box<dyn Player>) -> Match {
+        Match {
+            players: [first, second],
+            adjudication: Adjudication::default(),
+            tablebase: None,
+            event: "Match".to_string(),
+            games: vec![]
+        }
+    }
+
+    pub fn set_adjudication(&mut self, adjudication: Adjudication) {
+        self.adjudication = adjudication;
+    }
+
+    /// Sets a tablebase to end games as soon as it knows their result
+    pub fn set_tablebase(&mut self, tablebase: Box<dyn Tablebase>) {
+        self.tablebase = Some(tablebase);
+    }
+
+    /// Sets the Event header of the games
+    pub fn set_event(&mut self, event: &str) {
+        self.event = event.to_string();
+    }
+
+    /// Plays the given number of games
+    pub fn play(&mut self, n_games: usize) {
+        for _ in 0..n_games {
+            self.play_game();
+        }
+    }
+
+    /// Plays pairs of games, so both players have had White equally often, until the test accepts a hypothesis
+    /// about the first player or 'max_games' games have been played, counting the games played before.
+    /// Returns the status of the test.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::matchplay::{Match, RandomPlayer};
+    /// # use olindba_chess::sprt::{Sprt, SprtStatus};
+    /// let mut random_match = Match::new(Box::new(RandomPlayer::new(1)), Box::new(RandomPlayer::new(2)));
+    /// let sprt = Sprt::new(0.0, 5.0);
+    /// assert_eq!(random_match.play_sprt(&sprt, 4), SprtStatus::Continue);
+    /// assert_eq!(random_match.games().len(), 4);
+    /// ```
+    pub fn play_sprt(&mut self, sprt: &Sprt, max_games: usize) -> SprtStatus {
+        let mut status = sprt.status(&self.score());
+        while status == SprtStatus::Continue && self.games.len() + 2 <= max_games {
+            self.play_game();
+            self.play_game();
+            status = sprt.status(&self.score());
+        }
+        return status;
+    }
+
+    /// Plays one game and returns it
+    pub fn play_game(&mut self) -> &PgnGame {
+        let round = self.games.len() + 1;
+        // The index of the player with White
+        let white = self.games.len() % 2;
+        for player in self.players.iter_mut() {
+            player.new_game();
+        }
+
+        let start = Game::starting_position();
+        let mut game = start.clone();
+        let mut moves = vec![];
+        let mut low_scores = [0; 2];
+        let result;
+        let termination;
+        loop {
+            let state = game.get_game_state();
+            if state == GameState::Checkmate || state == GameState::Stalemate ||
+                state == GameState::FivefoldRepetition || state == GameState::DrawBy75MoveRule {
+                result = game.result().unwrap().to_string();
+                termination = Termination::Normal;
+                break;
+            }
+            if let Some(adjudicated) = adjudicate(&game, &self.adjudication, self.tablebase.as_deref()) {
+                result = adjudicated.to_string();
+                termination = Termination::Adjudication;
+                break;
+            }
+
+            let player = &mut self.players[white ^ game.turn];
+            let mv = match player.choose_move(&game) {
+                Some(mv) => mv,
+                None => {
+                    result = GameResult::win_for(game.turn ^ 1).to_string();
+                    termination = Termination::Normal;
+                    break;
+                }
+            };
+            if !game.legal_moves_cached().contains(&mv) {
+                result = GameResult::win_for(game.turn ^ 1).to_string();
+                termination = Termination::RulesInfraction;
+                break;
+            }
+
+            if let Some(resign) = self.adjudication.resign {
+                if player.last_score().is_some_and(|score| score <= -resign.score) {
+                    low_scores[game.turn] += 1;
+                }
+                else {
+                    low_scores[game.turn] = 0;
+                }
+                if low_scores[game.turn] >= resign.moves {
+                    result = GameResult::win_for(game.turn ^ 1).to_string();
+                    termination = Termination::Adjudication;
+                    break;
+                }
+            }
+
+            game.make_move(mv);
+            moves.push(mv);
+        }
+
+        let names = [self.players[white].name(), self.players[white ^ 1].name()];
+        let headers = game_headers(&self.event, round, names, &result, termination);
+        self.games.push(PgnGame {
+            headers,
+            start,
+            moves,
+            annotations: BTreeMap::new(),
+            result
+        });
+        return self.games.last().unwrap();
+    }
+
+    /// Returns the games played so far
+    pub fn games(&self) -> &[PgnGame] {
+        return &self.games;
+    }
+
+    /// Returns the results of the games played so far for the first player
+    pub fn score(&self) -> MatchScore {
+        return match_score(&self.games);
+    }
+
+    /// Writes all games played so far as PGN
+    pub fn to_pgn(&self) -> String {
+        return self.games.iter().map(|game| game.to_string()).collect::<Vec<_>>().join("\n");
+    }
+}
+
+/// A participant in a [TimedMatch]
+#[allow(unused_variables)]
+pub trait TimedPlayer {
+
+    /// Returns the name written in the PGN headers
+    fn name(&self) -> String;
+
+    /// Called before every game
+    fn new_game(&mut self) {}
+
+    /// Returns the move to play in the position, or None to resign, with the time it took. The limits hold the
+    /// time left on the clocks as sent with 'go' in UCI.
+    fn choose_move(&mut self, game: &Game, limits: &SearchLimits) -> (Option<Move>, Duration);
+}
+
+/// A [Player] taking the same time for every move
+pub struct FixedTime<P: Player> {
+    pub player: P,
+    pub time: Duration
+}
+
+impl<P: Player> TimedPlayer for FixedTime<P> {
+
+    fn name(&self) -> String {
+        return self.player.name();
+    }
+
+    fn new_game(&mut self) {
+        self.player.new_game();
+    }
+
+    fn choose_move(&mut self, game: &Game, _limits: &SearchLimits) -> (Option<Move>, Duration) {
+        return (self.player.choose_move(game), self.time);
+    }
+}
+
+/// A series of games with a time control between two players, see the [module documentation](self).
+/// The first player has White in the odd numbered games.
+///
+/// A player whose time runs out loses, unless the opponent can't checkmate by [Game::timeout_result].
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::clock::TimeControl;
+/// # use olindba_chess::matchplay::{FixedTime, RandomPlayer, TimedMatch};
+/// # use std::time::Duration;
+/// let slow = FixedTime { player: RandomPlayer::new(1), time: Duration::from_secs(2) };
+/// let fast = FixedTime { player: RandomPlayer::new(2), time: Duration::from_secs(1) };
+/// let control = TimeControl::new(Duration::from_secs(10), Duration::ZERO);
+/// let mut timed_match = TimedMatch::new(Box::new(slow), Box::new(fast), control);
+/// let game = timed_match.play_game();
+/// assert_eq!(game.header("TimeControl"), Some("10+0"));
+/// assert_eq!(game.header("Termination"), Some("time forfeit"));
+/// // White runs out of time on its 6th move
+/// assert_eq!(game.moves.len(), 10);
+/// assert_eq!(timed_match.move_times()[0][..2], [Duration::from_secs(2), Duration::from_secs(1)]);
+///
+/// // Engines with a simulated speed play the same game every time
+/// # use olindba_chess::engine::Engine;
+/// # use olindba_chess::matchplay::Adjudication;
+/// let play = || {
+///     let mut engines = [Engine::new(), Engine::new()];
+///     for engine in engines.iter_mut() {
+///         engine.set_simulated_speed(Some(20000));
+///     }
+///     let [first, second] = engines;
+///     let control = TimeControl::new(Duration::from_secs(3), Duration::from_millis(100));
+///     let mut timed_match = TimedMatch::new(Box::new(first), Box::new(second), control);
+///     timed_match.set_adjudication(Adjudication { max_moves: Some(4), ..Default::default() });
+///     timed_match.play_game();
+///     return (timed_match.to_pgn(), timed_match.move_times().to_vec());
+/// };
+/// let (pgn, move_times) = play();
+/// assert_eq!(move_times[0].len(), 8);
+/// // A share of the time left and most of the increment, checked every 1024 nodes
+/// assert!(move_times[0].iter().all(|&time| time < Duration::from_millis(250)));
+/// assert_eq!(play(), (pgn, move_times));
+/// ```
+pub struct TimedMatch {
+    players: [Box<dyn TimedPlayer>; 2],
+    control: TimeControl,
+    adjudication: Adjudication,
+    event: String,
+    games: Vec<PgnGame>,
+    move_times: Vec<Vec<Duration>>
+}
+
+impl TimedMatch {
+
+    pub fn new(first: Box<dyn TimedPlayer>, second: Box<dyn TimedPlayer>, control: TimeControl) -> TimedMatch {
+        TimedMatch {
+            players: [first, second],
+            control,
+            adjudication: Adjudication::default(),
+            event: "Match".to_string(),
+            games: vec![],
+            move_times: vec![]
+        }
+    }
+
+    /// Sets the rules to end games early by, resign rules are ignored since timed players don't report scores
+    pub fn set_adjudication(&mut self, adjudication: Adjudication) {
+        self.adjudication = adjudication;
+    }
+
+    /// Sets the Event header of the games
+    pub fn set_event(&mut self, event: &str) {
+        self.event = event.to_string();
+    }
+
+    /// Plays the given number of games
+    pub fn play(&mut self, n_games: usize) {
+        for _ in 0..n_games {
+            self.play_game();
+        }
+    }
+
+    /// Plays pairs of games until the test accepts a hypothesis about the first player or 'max_games' games have
+    /// been played, like [Match::play_sprt]
+    pub fn play_sprt(&mut self, sprt: &Sprt, max_games: usize) -> SprtStatus {
+        let mut status = sprt.status(&self.score());
+        while status == SprtStatus::Continue && self.games.len() + 2 <= max_games {
+            self.play_game();
+            self.play_game();
+            status = sprt.status(&self.score());
+        }
+        return status;
+    }
+
+    /// Plays one game and returns it
+    pub fn play_game(&mut self) -> &PgnGame {
+        let round = self.games.len() + 1;
+        // The index of the player with White
+        let white = self.games.len() % 2;
+        for player in self.players.iter_mut() {
+            player.new_game();
+        }
+
+        let start = Game::starting_position();
+        let mut game = start.clone();
+        let mut clock = Clock::new(self.control.clone());
+        let mut moves = vec![];
+        let mut move_times = vec![];
+        let result;
+        let termination;
+        loop {
+            let state = game.get_game_state();
+            if state == GameState::Checkmate || state == GameState::Stalemate ||
+                state == GameState::FivefoldRepetition || state == GameState::DrawBy75MoveRule {
+                result = game.result().unwrap().to_string();
+                termination = Termination::Normal;
+                break;
+            }
+            if let Some(adjudicated) = adjudicate(&game, &self.adjudication, None) {
+                result = adjudicated.to_string();
+                termination = Termination::Adjudication;
+                break;
+            }
+
+            let player = &mut self.players[white ^ game.turn];
+            let (mv, used) = player.choose_move(&game, &clock.limits(game.turn));
+            move_times.push(used);
+            if !clock.record_move(game.turn, used) {
+                result = game.timeout_result(game.turn).to_string();
+                termination = Termination::TimeForfeit;
+                break;
+            }
+            let mv = match mv {
+                Some(mv) => mv,
+                None => {
+                    result = GameResult::win_for(game.turn ^ 1).to_string();
+                    termination = Termination::Normal;
+                    break;
+                }
+            };
+            if !game.legal_moves_cached().contains(&mv) {
+                result = GameResult::win_for(game.turn ^ 1).to_string();
+                termination = Termination::RulesInfraction;
+                break;
+            }
+            game.make_move(mv);
+            moves.push(mv);
+        }
+
+        let names = [self.players[white].name(), self.players[white ^ 1].name()];
+        let mut headers = game_headers(&self.event, round, names, &result, termination);
+        headers.push(("TimeControl".to_string(), self.control.to_string()));
+        self.games.push(PgnGame {
+            headers,
+            start,
+            moves,
+            annotations: BTreeMap::new(),
+            result
+        });
+        self.move_times.push(move_times);
+        return self.games.last().unwrap();
+    }
+
+    /// Returns the games played so far
+    pub fn games(&self) -> &[PgnGame] {
+        return &self.games;
+    }
+
+    /// Returns the time every move of the games played so far took, in the order of the moves. A game lost on time
+    /// has one more time than moves, the time of the move that wasn't made in time.
+    pub fn move_times(&self) -> &[Vec<Duration>] {
+        return &self.move_times;
+    }
+
+    /// Returns the results of the games played so far for the first player
+    pub fn score(&self) -> MatchScore {
+        return match_score(&self.games);
+    }
+
+    /// Writes all games played so far as PGN
+    pub fn to_pgn(&self) -> String {
+        return self.games.iter().map(|game| game.to_string()).collect::<Vec<_>>().join("\n");
+    }
+}
+
+/// Returns the results of the games for the player with White in the odd numbered games
+fn match_score(games: &[PgnGame]) -> MatchScore {
+    let mut score = MatchScore::default();
+    for (i, game) in games.iter().enumerate() {
+        let first_player_won;
+        if i.is_multiple_of(2) {
+            first_player_won = "1-0";
+        }
+        else {
+            first_player_won = "0-1";
+        }
+        if game.result == first_player_won {
+            score.wins += 1;
+        }
+        else if game.result == "1/2-1/2" {
+            score.draws += 1;
+        }
+        else {
+            score.losses += 1;
+        }
+    }
+    return score;
+}
+
+/// Returns the headers of a game played in a match
+fn game_headers(event: &str, round: usize, names: [String; 2], result: &str, termination: Termination) -> Vec<(String, String)> {
+    let [white, black] = names;
+    return vec![
+        ("Event".to_string(), event.to_string()),
+        ("Site".to_string(), "?".to_string()),
+        ("Date".to_string(), "????.??.??".to_string()),
+        ("Round".to_string(), round.to_string()),
+        ("White".to_string(), white),
+        ("Black".to_string(), black),
+        ("Result".to_string(), result.to_string()),
+        ("Termination".to_string(), termination.to_string())
+    ];
+}
+
+/// Returns the result the game should be ended with under the given rules, or None if it should go on.
+/// Games that are over by the rules of chess are always adjudicated, and the tablebase, if any, is probed first.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::matchplay::{adjudicate, Adjudication};
+/// let game = Game::new("4k3/8/8/8/8/8/8/QQ2K3 b - - 0 1");
+/// assert_eq!(adjudicate(&game, &Adjudication::default(), None), None);
+///
+/// let rules = Adjudication {
+///     material_margin: Some(1500),
+///     ..Default::default()
+/// };
+/// assert_eq!(adjudicate(&game, &rules, None), Some(GameResult::WhiteWins));
+/// ```
+pub fn adjudicate(game: &Game, rules: &Adjudication, tablebase: Option<&dyn Tablebase>) -> Option<GameResult> {
+    let state = game.get_game_state();
+    if state == GameState::Checkmate || state == GameState::Stalemate ||
+        state == GameState::FivefoldRepetition || state == GameState::DrawBy75MoveRule {
+        return game.result();
+    }
+
+    if let Some(result) = tablebase.and_then(|tablebase| tablebase.probe(game)) {
+        return Some(result);
+    }
+    if (state == GameState::InsufficientMaterial && rules.insufficient_material) ||
+        (state == GameState::DrawBy50MoveRule && rules.fifty_move_rule) ||
+        (rules.dead_position && game.is_dead_position()) ||
+        (rules.threefold_repetition && game.repetition_count() >= 3) ||
+        rules.max_moves.is_some_and(|max_moves| game.fullmove_number() > max_moves) {
+        return Some(GameResult::Draw);
+    }
+    if let Some(margin) = rules.material_margin {
+        let balance = game.material_balance();
+        if balance >= margin {
+            return Some(GameResult::WhiteWins);
+        }
+        if -balance >= margin {
+            return Some(GameResult::BlackWins);
+        }
+    }
+    return None;
+}
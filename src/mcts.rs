@@ -0,0 +1,293 @@
+//! A reference Monte Carlo tree search
+//!
+//! [Mcts] grows a tree in a [NodeArena] one iteration at a time: it selects a path down the tree by UCT, expands the
+//! leaf it reaches with a child for every legal move, evaluates the leaf with a [LeafEvaluator] and backs the value
+//! up the path. After the last iteration the most visited move is played.
+//!
+//! Leaves are evaluated by random playouts with [PlayoutEvaluator], whose playout policy can be any
+//! [Player](crate::matchplay::Player), or by anything else implementing [LeafEvaluator], for example a neural
+//! network giving a value and prior probabilities for the moves. With priors the selection uses the PUCT formula
+//! of AlphaZero instead of UCT.
+//!
+//! Together with [crate::engine::Engine] it lets the two search paradigms be compared on the same board, and it
+//! implements [Player] so the two can play a [crate::matchplay::Match].
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::matchplay::RandomPlayer;
+//! # use olindba_chess::mcts::{Mcts, MctsConfig, PlayoutEvaluator};
+//! # use olindba_chess::notation::to_uci;
+//! let evaluator = PlayoutEvaluator::new(RandomPlayer::new(7), 20);
+//! let mut mcts = Mcts::new(evaluator, MctsConfig { iterations: 300, ..Default::default() });
+//! // Mate in one with the rook
+//! let mv = mcts.search(&Game::new("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1")).unwrap();
+//! assert_eq!(to_uci(&mv), "a1a8");
+//!
+//! let stats = mcts.root_statistics();
+//! assert_eq!(stats.len(), 17);
+//! assert_eq!(stats.iter().map(|stats| stats.visits).sum::<u32>(), 299);
+//! ```
+
+use crate::matchplay::Player;
+use crate::tree::{NodeArena, NodeId};
+use crate::{Game, GameResult, Move, MoveList};
+
+/// The result of evaluating a leaf of the tree
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LeafEvaluation {
+    /// The value of the position from -1 for a loss to 1 for a win, for the side to move
+    pub value: f64,
+    /// The prior probability of each of the legal moves, in the order they were given, or None to select by UCT
+    pub priors: Option<Vec<f32>>
+}
+
+/// Evaluates the leaves of the tree, see the [module documentation](self)
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::mcts::{LeafEvaluation, LeafEvaluator, Mcts, MctsConfig};
+/// # use olindba_chess::notation::to_uci;
+/// /// Thinks every position is equal and only likes pushing the e-pawn
+/// struct PawnPusher;
+///
+/// impl LeafEvaluator for PawnPusher {
+///     fn evaluate(&mut self, _game: &Game, moves: &[Move]) -> LeafEvaluation {
+///         let priors = moves.iter().map(|mv| if to_uci(mv) == "e2e4" { 0.9 } else { 0.005 }).collect();
+///         return LeafEvaluation { value: 0.0, priors: Some(priors) };
+///     }
+/// }
+///
+/// let mut mcts = Mcts::new(PawnPusher, MctsConfig { iterations: 50, ..Default::default() });
+/// let mv = mcts.search(&Game::starting_position()).unwrap();
+/// assert_eq!(to_uci(&mv), "e2e4");
+/// ```
+pub trait LeafEvaluator {
+
+    /// Evaluates a position that isn't over, given its legal moves
+    fn evaluate(&mut self, game: &Game, moves: &[Move]) -> LeafEvaluation;
+}
+
+/// Returns the value of a result from -1 to 1 for the given color
+pub fn result_value(result: GameResult, color: usize) -> f64 {
+    if result == GameResult::Draw {
+        return 0.0;
+    }
+    if result == GameResult::win_for(color) {
+        return 1.0;
+    }
+    return -1.0;
+}
+
+/// Plays the game on from the position with the moves of the policy for at most 'max_plies' plies and returns the
+/// result, or None if the game wasn't over by then or the policy resigned
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::matchplay::RandomPlayer;
+/// # use olindba_chess::mcts::playout;
+/// let game = Game::starting_position();
+/// assert_eq!(playout(&game, &mut RandomPlayer::new(1), 0), None);
+/// // A game that is over has its result without any moves
+/// let mated = Game::new("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3");
+/// assert_eq!(playout(&mated, &mut RandomPlayer::new(1), 0), Some(GameResult::BlackWins));
+/// // A random game of two bare kings is drawn right away
+/// let kings = Game::new("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+/// assert_eq!(playout(&kings, &mut RandomPlayer::new(1), 100), Some(GameResult::Draw));
+/// ```
+pub fn playout(game: &Game, policy: &mut dyn Player, max_plies: usize) -> Option<GameResult> {
+    let mut game = game.clone();
+    for _ in 0..max_plies {
+        if let Some(result) = game.result() {
+            return Some(result);
+        }
+        game.make_move(policy.choose_move(&game)?);
+    }
+    return game.result();
+}
+
+/// Evaluates leaves by a playout with a policy, a random one for plain Monte Carlo tree search. A playout that
+/// doesn't end within its plies counts as a draw.
+pub struct PlayoutEvaluator<P: Player> {
+    pub policy: P,
+    pub max_plies: usize
+}
+
+impl<P: Player> PlayoutEvaluator<P> {
+
+    pub fn new(policy: P, max_plies: usize) -> PlayoutEvaluator<P> {
+        PlayoutEvaluator {
+            policy,
+            max_plies
+        }
+    }
+}
+
+impl<P: Player> LeafEvaluator for PlayoutEvaluator<P> {
+    fn evaluate(&mut self, game: &Game, _moves: &[Move]) -> LeafEvaluation {
+        let value = playout(game, &mut self.policy, self.max_plies)
+            .map_or(0.0, |result| result_value(result, game.turn));
+        return LeafEvaluation { value, priors: None };
+    }
+}
+
+/// Settings of a [Mcts]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MctsConfig {
+    /// The number of iterations of a search, each adding at most one expanded node to the tree
+    pub iterations: usize,
+    /// How much unexplored moves are favoured over moves with good results, sqrt(2) by default
+    pub exploration: f64
+}
+
+impl Default for MctsConfig {
+    fn default() -> MctsConfig {
+        MctsConfig {
+            iterations: 1000,
+            exploration: std::f64::consts::SQRT_2
+        }
+    }
+}
+
+/// The statistics of a move at the root after a search
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MoveStatistics {
+    pub mv: Move,
+    pub visits: u32,
+    /// The average value of the move for the side to move at the root, from -1 to 1
+    pub mean_value: f64,
+    pub prior: f32
+}
+
+/// A Monte Carlo tree search, see the [module documentation](self)
+pub struct Mcts<E: LeafEvaluator> {
+    evaluator: E,
+    config: MctsConfig,
+    arena: NodeArena,
+    root: Option<NodeId>
+}
+
+impl<E: LeafEvaluator> Mcts<E> {
+
+    pub fn new(evaluator: E, config: MctsConfig) -> Mcts<E> {
+        Mcts {
+            evaluator,
+            config,
+            arena: NodeArena::new(),
+            root: None
+        }
+    }
+
+    /// Returns the tree of the last search
+    pub fn arena(&self) -> &NodeArena {
+        return &self.arena;
+    }
+
+    /// Returns the root of the tree of the last search
+    pub fn root(&self) -> Option<NodeId> {
+        return self.root;
+    }
+
+    /// Searches the position for the configured number of iterations and returns the most visited move, or None
+    /// if the game is over
+    pub fn search(&mut self, game: &Game) -> Option<Move> {
+        self.arena.clear();
+        let root = self.arena.add_root();
+        self.root = Some(root);
+        for _ in 0..self.config.iterations {
+            self.iterate(game, root);
+        }
+        let best = self.arena.most_visited_child(root)?;
+        return self.arena[best].mv;
+    }
+
+    /// Returns the statistics of the moves at the root of the last search, in the order of the legal moves
+    pub fn root_statistics(&self) -> Vec<MoveStatistics> {
+        let root = match self.root {
+            Some(root) => root,
+            None => return vec![]
+        };
+        return self.arena.children(root)
+            .map(|child| {
+                let node = &self.arena[child];
+                return MoveStatistics {
+                    mv: node.mv.unwrap(),
+                    visits: node.visits,
+                    mean_value: node.mean_value(),
+                    prior: node.prior
+                };
+            })
+            .collect();
+    }
+
+    /// Selects a path from the root to a leaf, expands and evaluates the leaf and backs its value up the path
+    fn iterate(&mut self, root_game: &Game, root: NodeId) {
+        let mut game = root_game.clone();
+        let mut node = root;
+        while self.arena[node].is_expanded() && !self.arena[node].is_terminal() {
+            node = self.select_child(node);
+            game.make_move(self.arena[node].mv.unwrap());
+        }
+
+        // Values are backed up from the point of view of the side that moved into the leaf
+        if let Some(result) = game.result() {
+            self.arena.expand_with(node, &[]);
+            self.arena.backpropagate(node, result_value(result, game.turn ^ 1));
+            return;
+        }
+        let mut moves = MoveList::new();
+        game.legal_moves_into(&mut moves);
+        let evaluation = self.evaluator.evaluate(&game, &moves);
+        self.arena.expand_with(node, &moves);
+        if let Some(priors) = evaluation.priors {
+            for (child, prior) in self.arena.children(node).collect::<Vec<_>>().into_iter().zip(priors) {
+                self.arena[child].prior = prior;
+            }
+        }
+        self.arena.backpropagate(node, -evaluation.value);
+    }
+
+    /// Returns the child with the highest UCT score, or PUCT score when the children have prior probabilities.
+    /// Unvisited children come first under UCT.
+    fn select_child(&self, node: NodeId) -> NodeId {
+        let parent_visits = self.arena[node].visits.max(1) as f64;
+        let use_priors = self.arena.children(node).any(|child| self.arena[child].prior > 0.0);
+        let exploration = self.config.exploration;
+        let score = |child: NodeId| {
+            let child = &self.arena[child];
+            if use_priors {
+                return child.mean_value()
+                    + exploration * child.prior as f64 * parent_visits.sqrt() / (1.0 + child.visits as f64);
+            }
+            if child.visits == 0 {
+                return f64::INFINITY;
+            }
+            return child.mean_value() + exploration * (parent_visits.ln() / child.visits as f64).sqrt();
+        };
+        let mut best = None;
+        let mut best_score = f64::NEG_INFINITY;
+        for child in self.arena.children(node) {
+            let child_score = score(child);
+            if best.is_none() || child_score > best_score {
+                best = Some(child);
+                best_score = child_score;
+            }
+        }
+        return best.unwrap();
+    }
+}
+
+impl<E: LeafEvaluator> Player for Mcts<E> {
+    fn name(&self) -> String {
+        return format!("MCTS {}", self.config.iterations);
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Option<Move> {
+        return self.search(game);
+    }
+}
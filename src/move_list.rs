@@ -0,0 +1,89 @@
+use crate::Move;
+
+/// The most legal moves any position has is 218, the list has room for a few more
+const CAPACITY: usize = 256;
+
+/// A list of moves stored inline without allocating, filled by [crate::Game::legal_moves_into].
+/// It derefs to a slice of moves, so it can be iterated, indexed and searched like a Vec.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// let game = Game::starting_position();
+/// let mut moves = MoveList::new();
+/// game.legal_moves_into(&mut moves);
+/// assert_eq!(moves.len(), 20);
+/// assert!(moves.iter().any(|mv| mv.is_double_pawn_push()));
+/// assert_eq!(moves.to_vec(), game.get_all_legal_moves());
+/// ```
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; CAPACITY],
+    len: usize
+}
+
+impl Default for MoveList {
+    fn default() -> MoveList {
+        MoveList::new()
+    }
+}
+
+impl MoveList {
+
+    pub fn new() -> MoveList {
+        MoveList {
+            moves: [Move { chess_move: 0 }; CAPACITY],
+            len: 0
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Adds a move at the end of the list
+    ///
+    /// # Panics
+    /// If the list already holds 256 moves
+    pub fn push(&mut self, mv: Move) {
+        assert!(self.len < CAPACITY, "a move list holds at most {} moves", CAPACITY);
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    pub fn as_slice(&self) -> &[Move] {
+        return &self.moves[..self.len];
+    }
+}
+
+impl std::ops::Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        return self.as_slice();
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> std::slice::Iter<'a, Move> {
+        return self.as_slice().iter();
+    }
+}
+
+impl std::fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+impl PartialEq for MoveList {
+    fn eq(&self, other: &MoveList) -> bool {
+        return self.as_slice() == other.as_slice();
+    }
+}
+
+impl Eq for MoveList {}
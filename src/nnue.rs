@@ -0,0 +1,352 @@
+//! Evaluating positions with NNUE networks in the format of Stockfish 12
+//!
+//! The networks have HalfKP inputs, a feature transformer of 256 neurons for each side and two hidden layers of 32
+//! neurons, the architecture of the 'nn-*.nnue' files released with Stockfish 12 to 13. [NnueEvaluator::evaluate]
+//! evaluates a position from scratch. In a search an [NnueAccumulator] is kept up to date move by move as an
+//! [IncrementalTerm], so only the small layers after the feature transformer are computed for each position.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use olindba_chess::*;
+//! # use olindba_chess::eval::{update_for_move, IncrementalTerm};
+//! # use olindba_chess::nnue::NnueEvaluator;
+//! let evaluator = NnueEvaluator::load("nn-62ef826d1a6d.nnue").unwrap();
+//! let mut game = Game::starting_position();
+//! let mut accumulator = evaluator.accumulator();
+//! accumulator.refresh(&game);
+//!
+//! let mv = game.get_all_legal_moves()[0];
+//! update_for_move(&mut accumulator, &game, &mv);
+//! game.make_move(mv);
+//! assert_eq!(evaluator.evaluate_accumulator(&accumulator, game.turn), evaluator.evaluate(&game));
+//! ```
+
+use std::path::Path;
+
+use crate::eval::IncrementalTerm;
+use crate::{Game, Square, EMPTY, KING, WHITE};
+
+const VERSION: u32 = 0x7AF32F16;
+/// The number of HalfKP features of one side, 641 piece-square combinations for each square of the king
+const INPUTS: usize = 64 * 641;
+const HALF_DIMENSIONS: usize = 256;
+const HIDDEN: usize = 32;
+/// The scale of the network's output to Stockfish's internal units
+const OUTPUT_SCALE: i32 = 16;
+const WEIGHT_SCALE_BITS: i32 = 6;
+
+/// The reason a network couldn't be loaded
+#[derive(Debug)]
+pub enum NnueError {
+    Io(std::io::Error),
+    /// The file is of another version of the format, or not a network at all
+    UnsupportedVersion(u32),
+    /// The file ends before all weights are read
+    Truncated
+}
+
+impl std::fmt::Display for NnueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NnueError::Io(error) => write!(f, "{}", error),
+            NnueError::UnsupportedVersion(version) => write!(f, "unsupported network version {:#x}", version),
+            NnueError::Truncated => write!(f, "the network file is truncated")
+        }
+    }
+}
+
+impl std::error::Error for NnueError {}
+
+impl From<std::io::Error> for NnueError {
+    fn from(error: std::io::Error) -> NnueError {
+        NnueError::Io(error)
+    }
+}
+
+/// Reads little endian values from the bytes of a network file
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], NnueError> {
+        if self.position + n > self.bytes.len() {
+            return Err(NnueError::Truncated);
+        }
+        let bytes = &self.bytes[self.position..self.position + n];
+        self.position += n;
+        return Ok(bytes);
+    }
+
+    fn u32(&mut self) -> Result<u32, NnueError> {
+        let bytes = self.take(4)?;
+        return Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+    }
+
+    fn i32s(&mut self, n: usize) -> Result<Vec<i32>, NnueError> {
+        return Ok(self.take(4 * n)?.chunks_exact(4)
+            .map(|bytes| i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])).collect());
+    }
+
+    fn i16s(&mut self, n: usize) -> Result<Vec<i16>, NnueError> {
+        return Ok(self.take(2 * n)?.chunks_exact(2).map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]])).collect());
+    }
+
+    fn i8s(&mut self, n: usize) -> Result<Vec<i8>, NnueError> {
+        return Ok(self.take(n)?.iter().map(|&byte| byte as i8).collect());
+    }
+}
+
+/// A fully connected layer, with the weights of each output neuron stored together
+struct Layer {
+    biases: Vec<i32>,
+    weights: Vec<i8>
+}
+
+impl Layer {
+    fn read(reader: &mut Reader, inputs: usize, outputs: usize) -> Result<Layer, NnueError> {
+        let biases = reader.i32s(outputs)?;
+        let weights = reader.i8s(inputs * outputs)?;
+        return Ok(Layer { biases, weights });
+    }
+
+    fn propagate(&self, input: &[u8], output: &mut [i32]) {
+        for (i, (value, bias)) in output.iter_mut().zip(&self.biases).enumerate() {
+            let weights = &self.weights[i * input.len()..(i + 1) * input.len()];
+            *value = bias + weights.iter().zip(input).map(|(&weight, &x)| weight as i32 * x as i32).sum::<i32>();
+        }
+    }
+}
+
+fn clipped_relu(values: &[i32], output: &mut [u8]) {
+    for (x, value) in output.iter_mut().zip(values) {
+        *x = (value >> WEIGHT_SCALE_BITS).clamp(0, 127) as u8;
+    }
+}
+
+/// A loaded network
+pub struct NnueEvaluator {
+    description: String,
+    transformer_biases: Vec<i16>,
+    transformer_weights: Vec<i16>,
+    hidden1: Layer,
+    hidden2: Layer,
+    output: Layer
+}
+
+impl NnueEvaluator {
+
+    /// Reads a network file
+    pub fn load(path: impl AsRef<Path>) -> Result<NnueEvaluator, NnueError> {
+        return NnueEvaluator::from_bytes(&std::fs::read(path)?);
+    }
+
+    /// Reads a network from the contents of a network file
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::nnue::NnueEvaluator;
+    /// // A network where every weight is zero and the output bias is 16 times 150
+    /// let mut bytes = vec![];
+    /// bytes.extend(0x7AF32F16u32.to_le_bytes());
+    /// bytes.extend([0; 8]);
+    /// bytes.resize(bytes.len() + 4 + 2 * 256 + 2 * 41024 * 256, 0);
+    /// bytes.resize(bytes.len() + 4 + 4 * 32 + 512 * 32 + 4 * 32 + 32 * 32, 0);
+    /// bytes.extend(2400i32.to_le_bytes());
+    /// bytes.resize(bytes.len() + 32, 0);
+    /// let evaluator = NnueEvaluator::from_bytes(&bytes).unwrap();
+    /// assert_eq!(evaluator.evaluate(&Game::starting_position()), 150);
+    ///
+    /// assert!(NnueEvaluator::from_bytes(&bytes[..1000]).is_err());
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<NnueEvaluator, NnueError> {
+        let mut reader = Reader { bytes, position: 0 };
+        let version = reader.u32()?;
+        if version != VERSION {
+            return Err(NnueError::UnsupportedVersion(version));
+        }
+        // The hashes of the architecture aren't checked, a network of another architecture fails on its size
+        let _hash = reader.u32()?;
+        let description_length = reader.u32()? as usize;
+        let description = String::from_utf8_lossy(reader.take(description_length)?).into_owned();
+
+        let _transformer_hash = reader.u32()?;
+        let transformer_biases = reader.i16s(HALF_DIMENSIONS)?;
+        let transformer_weights = reader.i16s(HALF_DIMENSIONS * INPUTS)?;
+
+        let _network_hash = reader.u32()?;
+        let hidden1 = Layer::read(&mut reader, 2 * HALF_DIMENSIONS, HIDDEN)?;
+        let hidden2 = Layer::read(&mut reader, HIDDEN, HIDDEN)?;
+        let output = Layer::read(&mut reader, HIDDEN, 1)?;
+        if reader.position != bytes.len() {
+            return Err(NnueError::UnsupportedVersion(version));
+        }
+
+        return Ok(NnueEvaluator {
+            description,
+            transformer_biases,
+            transformer_weights,
+            hidden1,
+            hidden2,
+            output
+        });
+    }
+
+    /// Returns the description stored in the network file
+    pub fn description(&self) -> &str {
+        return &self.description;
+    }
+
+    /// Returns an accumulator for an empty board, see [NnueAccumulator]
+    pub fn accumulator(&self) -> NnueAccumulator<'_> {
+        NnueAccumulator {
+            evaluator: self,
+            pieces: [None; 64],
+            king_squares: [None; 2],
+            accumulation: [[0; HALF_DIMENSIONS]; 2]
+        }
+    }
+
+    /// Evaluates the position from the point of view of the side to move, in Stockfish's internal units where
+    /// a pawn is worth around 200
+    pub fn evaluate(&self, game: &Game) -> i32 {
+        let mut accumulator = self.accumulator();
+        accumulator.refresh(game);
+        return self.evaluate_accumulator(&accumulator, game.turn);
+    }
+
+    /// Evaluates the position the accumulator has been kept up to date with, from the point of view of 'turn'
+    pub fn evaluate_accumulator(&self, accumulator: &NnueAccumulator, turn: usize) -> i32 {
+        let mut input = [0; 2 * HALF_DIMENSIONS];
+        for (half, perspective) in input.chunks_exact_mut(HALF_DIMENSIONS).zip([turn, turn ^ 1]) {
+            for (x, &value) in half.iter_mut().zip(&accumulator.accumulation[perspective]) {
+                *x = value.clamp(0, 127) as u8;
+            }
+        }
+        let mut sums = [0; HIDDEN];
+        let mut hidden = [0; HIDDEN];
+        self.hidden1.propagate(&input, &mut sums);
+        clipped_relu(&sums, &mut hidden);
+        self.hidden2.propagate(&hidden, &mut sums);
+        clipped_relu(&sums, &mut hidden);
+        let mut output = [0; 1];
+        self.output.propagate(&hidden, &mut output);
+        return output[0] / OUTPUT_SCALE;
+    }
+
+    fn add_feature(&self, accumulation: &mut [i16; HALF_DIMENSIONS], feature: usize, sign: i16) {
+        let weights = &self.transformer_weights[feature * HALF_DIMENSIONS..(feature + 1) * HALF_DIMENSIONS];
+        for (value, &weight) in accumulation.iter_mut().zip(weights) {
+            *value = value.wrapping_add(sign.wrapping_mul(weight));
+        }
+    }
+}
+
+/// The sums of the feature transformer for both sides, updated as pieces are added and removed.
+/// When a king moves, the sums of its side are recomputed from all pieces since every HalfKP feature of that side
+/// depends on the square of the king. Only the king checks are about counts, a king promoted to is left out like
+/// the kings are. Added piece by piece, the first king of a color is taken as that one.
+#[derive(Clone)]
+pub struct NnueAccumulator<'a> {
+    evaluator: &'a NnueEvaluator,
+    /// The piece type and color on each square, counted from a1
+    pieces: [Option<(usize, usize)>; 64],
+    /// The squares of the kings checks are about, None while such a king is lifted during a move
+    king_squares: [Option<usize>; 2],
+    accumulation: [[i16; HALF_DIMENSIONS]; 2]
+}
+
+impl NnueAccumulator<'_> {
+
+    /// Returns the HalfKP feature of a piece that isn't a king as seen by the given side, with squares counted
+    /// from a1 and rotated for Black
+    fn feature(&self, perspective: usize, piece_type: usize, color: usize, square: usize) -> Option<usize> {
+        let orientation = if perspective == WHITE { 0 } else { 63 };
+        let king_square = self.king_squares[perspective]? ^ orientation;
+        let mut piece_index = 2 * (piece_type - 1);
+        if color != perspective {
+            piece_index += 1;
+        }
+        return Some((square ^ orientation) + 1 + 64 * piece_index + 641 * king_square);
+    }
+
+    fn update(&mut self, piece_type: usize, color: usize, square: usize, sign: i16) {
+        for perspective in [WHITE, WHITE ^ 1] {
+            if let Some(feature) = self.feature(perspective, piece_type, color, square) {
+                self.evaluator.add_feature(&mut self.accumulation[perspective], feature, sign);
+            }
+        }
+    }
+
+    fn refresh_perspective(&mut self, perspective: usize) {
+        self.accumulation[perspective].copy_from_slice(&self.evaluator.transformer_biases);
+        for square in 0..64 {
+            if let Some((piece_type, color)) = self.pieces[square] {
+                if piece_type == KING {
+                    continue;
+                }
+                if let Some(feature) = self.feature(perspective, piece_type, color, square) {
+                    self.evaluator.add_feature(&mut self.accumulation[perspective], feature, 1);
+                }
+            }
+        }
+    }
+}
+
+impl IncrementalTerm for NnueAccumulator<'_> {
+    fn add_piece(&mut self, piece_type: usize, color: usize, square: Square) {
+        let square = square.to_a1_indexing();
+        self.pieces[square] = Some((piece_type, color));
+        if piece_type == KING {
+            if self.king_squares[color].is_none() {
+                self.king_squares[color] = Some(square);
+                self.refresh_perspective(color);
+            }
+        }
+        else {
+            self.update(piece_type, color, square, 1);
+        }
+    }
+
+    fn remove_piece(&mut self, piece_type: usize, color: usize, square: Square) {
+        let square = square.to_a1_indexing();
+        self.pieces[square] = None;
+        if piece_type == KING {
+            if self.king_squares[color] == Some(square) {
+                self.king_squares[color] = None;
+            }
+        }
+        else if piece_type != EMPTY {
+            self.update(piece_type, color, square, -1);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.pieces = [None; 64];
+        self.king_squares = [None; 2];
+        for accumulation in self.accumulation.iter_mut() {
+            accumulation.copy_from_slice(&self.evaluator.transformer_biases);
+        }
+    }
+
+    /// Takes the kings from the game, so a king promoted to on a square before the king's isn't taken for it
+    fn refresh(&mut self, game: &Game) {
+        self.clear();
+        for color in [WHITE, WHITE ^ 1] {
+            self.king_squares[color] = Some(game.king_square(color).to_a1_indexing());
+        }
+        for square in 0..64 {
+            let piece = game.board[square];
+            if piece.get_type() != EMPTY {
+                self.pieces[Square::new(square).to_a1_indexing()] = Some((piece.get_type(), piece.get_color()));
+            }
+        }
+        for perspective in [WHITE, WHITE ^ 1] {
+            self.refresh_perspective(perspective);
+        }
+    }
+}
@@ -0,0 +1,579 @@
+//! Reading and writing moves in UCI notation (e2e4, e7e8q) and standard algebraic notation (e4, Nxf3+, O-O)
+//!
+//! GUIs and engines disagree on how castling is written, so the functions ending in '_with' take a
+//! [NotationConfig] selecting the castling notation. Reading accepts all castling notations regardless.
+//! The config also selects the [PieceLetters] of SAN, for figurine notation (♘f3) and the letters of other
+//! languages (Sf3 in German). Figurines are read in every config.
+//!
+//! Whole games can be written as a numbered move list with [Game::moves_to_string] and replayed from one with
+//! [Game::apply_move_list], a simpler format than PGN for test fixtures and storage.
+
+use crate::{Game, Move, Square, EMPTY, PAWN, KNIGHT, BISHOP, ROOK, QUEEN, KING, WHITE};
+
+/// The reason a move in text form couldn't be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotationError {
+    /// The text isn't a move in the expected notation
+    InvalidSyntax(String),
+    /// No legal move in the position matches the text
+    IllegalMove(String),
+    /// Several legal moves in the position match the text
+    AmbiguousMove(String)
+}
+
+impl std::fmt::Display for NotationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NotationError::InvalidSyntax(text) => write!(f, "'{}' is not a valid move", text),
+            NotationError::IllegalMove(text) => write!(f, "'{}' is not a legal move in this position", text),
+            NotationError::AmbiguousMove(text) => write!(f, "'{}' matches several legal moves", text)
+        }
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+/// How castling moves are written in UCI notation
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum UciCastling {
+    /// The king moves to its destination square, e1g1, as in standard UCI
+    #[default]
+    KingDestination,
+    /// The king captures its own rook, e1h1, as in Chess960 UCI
+    KingToRook
+}
+
+/// How castling moves are written in SAN
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SanCastling {
+    /// Castling is written with capital letter O, O-O and O-O-O, as in PGN
+    #[default]
+    LetterO,
+    /// Castling is written with zeros, 0-0 and 0-0-0, as in FIDE notation
+    Zeros
+}
+
+/// The letters pieces are written with in SAN
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PieceLetters {
+    /// N, B, R, Q and K, as in PGN
+    #[default]
+    English,
+    /// The figurines of the white pieces, ♘, ♗, ♖, ♕ and ♔, for both colors
+    Figurine,
+    /// S, L, T, D and K
+    German,
+    /// C, F, T, D and R
+    French,
+    /// C, A, T, D and R
+    Spanish,
+    /// C, A, T, D and R
+    Italian,
+    /// P, L, T, D and K
+    Dutch
+}
+
+impl PieceLetters {
+
+    /// Returns the letter used for a piece type, pawns have no letter
+    pub fn letter(&self, piece_type: usize) -> Option<char> {
+        let letters = match self {
+            PieceLetters::English => "NBRQK",
+            PieceLetters::Figurine => "♘♗♖♕♔",
+            PieceLetters::German => "SLTDK",
+            PieceLetters::French => "CFTDR",
+            PieceLetters::Spanish | PieceLetters::Italian => "CATDR",
+            PieceLetters::Dutch => "PLTDK"
+        };
+        let index = match piece_type {
+            KNIGHT => 0,
+            BISHOP => 1,
+            ROOK => 2,
+            QUEEN => 3,
+            KING => 4,
+            _ => return None
+        };
+        return letters.chars().nth(index);
+    }
+
+    /// Returns the piece type a letter stands for. The figurines of both colors are accepted whatever the letters
+    /// are, except the pawn figurines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::PieceLetters;
+    /// assert_eq!(PieceLetters::German.piece_type('S'), Some(KNIGHT));
+    /// assert_eq!(PieceLetters::German.piece_type('N'), None);
+    /// assert_eq!(PieceLetters::French.piece_type('R'), Some(KING));
+    /// assert_eq!(PieceLetters::English.piece_type('♞'), Some(KNIGHT));
+    /// ```
+    pub fn piece_type(&self, letter: char) -> Option<usize> {
+        // The white king to pawn come first, then the black ones
+        if ('♔'..='♟').contains(&letter) {
+            let piece_type = [KING, QUEEN, ROOK, BISHOP, KNIGHT, PAWN][(letter as usize - '♔' as usize) % 6];
+            return Some(piece_type).filter(|&piece_type| piece_type != PAWN);
+        }
+        return [KNIGHT, BISHOP, ROOK, QUEEN, KING].into_iter()
+            .find(|&piece_type| self.letter(piece_type) == Some(letter));
+    }
+}
+
+/// Settings for writing moves, and for reading SAN
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NotationConfig {
+    pub uci_castling: UciCastling,
+    pub san_castling: SanCastling,
+    pub piece_letters: PieceLetters
+}
+
+/// Returns the letter used for a piece type in SAN, pawns have no letter
+pub fn piece_letter(piece_type: usize) -> Option<char> {
+    return match piece_type {
+        KNIGHT => Some('N'),
+        BISHOP => Some('B'),
+        ROOK => Some('R'),
+        QUEEN => Some('Q'),
+        KING => Some('K'),
+        _ => None
+    };
+}
+
+fn piece_from_letter(letter: char) -> Option<usize> {
+    return match letter {
+        'N' => Some(KNIGHT),
+        'B' => Some(BISHOP),
+        'R' => Some(ROOK),
+        'Q' => Some(QUEEN),
+        'K' => Some(KING),
+        _ => None
+    };
+}
+
+/// Writes a move in UCI notation, example 'e2e4' or 'e7e8q'. Castling is written as the king moving to its destination.
+pub fn to_uci(mv: &Move) -> String {
+    let mut uci = format!("{}{}", Square::new(mv.get_from()), Square::new(mv.get_to()));
+    if let Some(letter) = piece_letter(mv.get_promotion_type()) {
+        uci.push(letter.to_ascii_lowercase());
+    }
+    return uci;
+}
+
+/// Writes a move in UCI notation with the castling notation of the given config
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::notation::{to_uci_with, NotationConfig, UciCastling};
+/// let game = Game::new("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+/// let castle = game.get_legal_moves(60).into_iter().find(|mv| mv.is_king_castle()).unwrap();
+/// let config = NotationConfig { uci_castling: UciCastling::KingToRook, ..Default::default() };
+/// assert_eq!(to_uci_with(&game, &castle, &config), "e1h1");
+/// assert_eq!(to_uci_with(&game, &castle, &NotationConfig::default()), "e1g1");
+/// ```
+pub fn to_uci_with(game: &Game, mv: &Move, config: &NotationConfig) -> String {
+    if mv.is_castle() && config.uci_castling == UciCastling::KingToRook {
+        let (rook_square, _) = game.castling_rook_move(mv);
+        return format!("{}{}", Square::new(mv.get_from()), Square::new(rook_square));
+    }
+    return to_uci(mv);
+}
+
+/// Reads a move in UCI notation and returns the matching legal move.
+/// Castling is accepted both as the king moving to its destination and as the king moving to the rook.
+pub fn parse_uci(game: &Game, uci: &str) -> Result<Move, NotationError> {
+    let invalid = || NotationError::InvalidSyntax(uci.to_string());
+    if !uci.is_ascii() || uci.len() < 4 || uci.len() > 5 {
+        return Err(invalid());
+    }
+    let from = Square::from_algebraic(&uci[0..2]).ok_or_else(invalid)?;
+    let to = Square::from_algebraic(&uci[2..4]).ok_or_else(invalid)?;
+    let mut promotion = EMPTY;
+    if uci.len() == 5 {
+        promotion = uci[4..].chars().next()
+            .and_then(|letter| piece_from_letter(letter.to_ascii_uppercase()))
+            .ok_or_else(invalid)?;
+    }
+
+    for mv in game.legal_moves_cached().iter() {
+        if mv.get_from() == from.index() && mv.get_to() == to.index() && mv.get_promotion_type() == promotion {
+            return Ok(*mv);
+        }
+    }
+    for mv in game.legal_moves_cached().iter() {
+        if mv.is_castle() && mv.get_from() == from.index() && game.castling_rook_move(mv).0 == to.index() {
+            return Ok(*mv);
+        }
+    }
+    return Err(NotationError::IllegalMove(uci.to_string()));
+}
+
+/// Writes a legal move in standard algebraic notation, including check and checkmate markers
+pub fn to_san(game: &Game, mv: &Move) -> String {
+    return to_san_with(game, mv, &NotationConfig::default());
+}
+
+/// Writes a legal move in standard algebraic notation with the castling notation and piece letters of the given
+/// config
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::notation::{parse_san, to_san_with, NotationConfig, PieceLetters};
+/// let game = Game::new("4k3/P7/8/8/8/8/8/4K1N1 w - - 0 1");
+/// let german = NotationConfig { piece_letters: PieceLetters::German, ..Default::default() };
+/// let figurine = NotationConfig { piece_letters: PieceLetters::Figurine, ..Default::default() };
+/// assert_eq!(to_san_with(&game, &parse_san(&game, "Nf3").unwrap(), &german), "Sf3");
+/// assert_eq!(to_san_with(&game, &parse_san(&game, "Nf3").unwrap(), &figurine), "♘f3");
+/// assert_eq!(to_san_with(&game, &parse_san(&game, "a8=Q").unwrap(), &german), "a8=D+");
+///
+/// // A check that ends the game by the 75-move rule is no checkmate
+/// let game = Game::new("8/R7/8/8/3R4/8/4K3/1k6 w - - 149 76");
+/// assert_eq!(to_san_with(&game, &parse_san(&game, "Rb7").unwrap(), &german), "Tb7+");
+/// ```
+pub fn to_san_with(game: &Game, mv: &Move, config: &NotationConfig) -> String {
+    let mut san = to_san_without_suffix(game, mv, config.piece_letters);
+    if mv.is_castle() && config.san_castling == SanCastling::Zeros {
+        san = san.replace('O', "0");
+    }
+
+    // The move only has to be made to tell check from checkmate
+    if game.gives_check(mv) {
+        let mut game_after = game.clone();
+        game_after.make_move(*mv);
+        if game_after.is_checkmated() {
+            san.push('#');
+        }
+        else {
+            san.push('+');
+        }
+    }
+    return san;
+}
+
+fn to_san_without_suffix(game: &Game, mv: &Move, letters: PieceLetters) -> String {
+    if mv.is_king_castle() {
+        return "O-O".to_string();
+    }
+    if mv.is_queen_castle() {
+        return "O-O-O".to_string();
+    }
+
+    let from = Square::new(mv.get_from());
+    let to = Square::new(mv.get_to());
+    let piece_type = game.board[from.index()].get_type();
+    let mut san = String::new();
+
+    if piece_type == PAWN {
+        if mv.is_capture() {
+            san.push_str(&from.to_string()[0..1]);
+        }
+    }
+    else {
+        san.push(letters.letter(piece_type).unwrap());
+
+        san.push_str(&disambiguate(game, mv));
+    }
+
+    if mv.is_capture() {
+        san.push('x');
+    }
+    san.push_str(&to.to_string());
+
+    if let Some(letter) = letters.letter(mv.get_promotion_type()) {
+        san.push('=');
+        san.push(letter);
+    }
+    return san;
+}
+
+/// Returns what has to be written between the piece letter and the destination of a legal move in SAN to tell it
+/// apart from moves of the same piece type to the same square: nothing, the file, the rank or the whole square.
+/// Always empty for pawn moves and castling.
+///
+/// The legal moves are only looked at if another piece of the same type attacks the destination.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::notation::{disambiguate, parse_uci};
+/// let game = Game::new("4k3/8/8/1N3N2/8/1N6/8/4K3 w - - 0 1");
+/// assert_eq!(disambiguate(&game, &parse_uci(&game, "f5d4").unwrap()), "f");
+/// assert_eq!(disambiguate(&game, &parse_uci(&game, "b3d4").unwrap()), "3");
+/// assert_eq!(disambiguate(&game, &parse_uci(&game, "b5d4").unwrap()), "b5");
+/// assert_eq!(disambiguate(&game, &parse_uci(&game, "f5h6").unwrap()), "");
+/// ```
+pub fn disambiguate(game: &Game, mv: &Move) -> String {
+    let from = mv.from_square();
+    let piece = game.board[from.index()];
+    if piece.get_type() == PAWN || mv.is_castle() {
+        return String::new();
+    }
+
+    let may_be_ambiguous = (0..64).any(|square| square != from.index() && 
+        game.board[square].get_type() == piece.get_type() && game.board[square].get_color() == piece.get_color() &&
+        game.attacks_from(square) & (1 << mv.get_to()) != 0);
+    if !may_be_ambiguous {
+        return String::new();
+    }
+
+    let mut same_file = false;
+    let mut same_row = false;
+    let mut ambiguous = false;
+    for other in game.legal_moves_cached().iter() {
+        if other.get_to() != mv.get_to() || other.get_from() == mv.get_from() ||
+        game.board[other.get_from()].get_type() != piece.get_type() {
+            continue;
+        }
+        ambiguous = true;
+        let other_from = other.from_square();
+        if other_from.column() == from.column() {
+            same_file = true;
+        }
+        if other_from.row() == from.row() {
+            same_row = true;
+        }
+    }
+    if !ambiguous {
+        return String::new();
+    }
+    if !same_file {
+        return from.to_string()[0..1].to_string();
+    }
+    if !same_row {
+        return from.to_string()[1..2].to_string();
+    }
+    return from.to_string();
+}
+
+/// Reads a move in standard algebraic notation and returns the matching legal move.
+/// Check markers, annotations such as '!?' and missing or superfluous disambiguation are accepted, and so are
+/// figurines in place of the piece letters.
+pub fn parse_san(game: &Game, san: &str) -> Result<Move, NotationError> {
+    return parse_san_with(game, san, &NotationConfig::default());
+}
+
+/// Reads a move in standard algebraic notation with the piece letters of the given config, like [parse_san].
+/// Only the letters of the config are read, since a letter can stand for different pieces in different languages.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::notation::{parse_san, parse_san_with, NotationConfig, PieceLetters, NotationError};
+/// let game = Game::starting_position();
+/// let german = NotationConfig { piece_letters: PieceLetters::German, ..Default::default() };
+/// assert_eq!(parse_san_with(&game, "Sf3", &german), parse_san(&game, "Nf3"));
+/// assert_eq!(parse_san_with(&game, "♘f3", &german), parse_san(&game, "Nf3"));
+/// assert_eq!(parse_san_with(&game, "Nf3", &german), Err(NotationError::InvalidSyntax("Nf3".to_string())));
+/// ```
+pub fn parse_san_with(game: &Game, san: &str, config: &NotationConfig) -> Result<Move, NotationError> {
+    let invalid = || NotationError::InvalidSyntax(san.to_string());
+    let letters = config.piece_letters;
+    let text = san.trim().trim_end_matches(['+', '#', '!', '?']);
+    if text.is_empty() {
+        return Err(invalid());
+    }
+
+    let legal_moves = game.legal_moves_cached();
+    if text == "O-O" || text == "0-0" || text == "O-O-O" || text == "0-0-0" {
+        let queen_side = text.len() == 5;
+        return legal_moves.iter()
+            .find(|mv| (queen_side && mv.is_queen_castle()) || (!queen_side && mv.is_king_castle()))
+            .copied()
+            .ok_or_else(|| NotationError::IllegalMove(san.to_string()));
+    }
+
+    let mut text = text;
+    let mut piece_type = PAWN;
+    let first = text.chars().next().unwrap();
+    if let Some(piece) = letters.piece_type(first) {
+        piece_type = piece;
+        text = &text[first.len_utf8()..];
+    }
+
+    let mut promotion = EMPTY;
+    if let Some(last) = text.chars().last() {
+        if let Some(piece) = letters.piece_type(last) {
+            if piece_type != PAWN {
+                return Err(invalid());
+            }
+            promotion = piece;
+            text = text[..text.len() - last.len_utf8()].trim_end_matches('=');
+        }
+    }
+
+    if !text.is_ascii() || text.len() < 2 {
+        return Err(invalid());
+    }
+    let to = Square::from_algebraic(&text[text.len() - 2..]).ok_or_else(invalid)?;
+    let mut from_column = None;
+    let mut from_row = None;
+    for c in text[..text.len() - 2].chars() {
+        match c {
+            'a'..='h' if from_column.is_none() => from_column = Some(c as usize - 'a' as usize),
+            '1'..='8' if from_row.is_none() => from_row = Some('8' as usize - c as usize),
+            'x' | ':' | '-' => {},
+            _ => return Err(invalid())
+        }
+    }
+
+    let mut found: Option<Move> = None;
+    for mv in legal_moves.iter() {
+        let from = Square::new(mv.get_from());
+        if mv.get_to() != to.index() || game.board[from.index()].get_type() != piece_type ||
+        mv.get_promotion_type() != promotion || mv.is_castle() ||
+        from_column.is_some_and(|column| column != from.column()) || from_row.is_some_and(|row| row != from.row()) {
+            continue;
+        }
+        if found.is_some() {
+            return Err(NotationError::AmbiguousMove(san.to_string()));
+        }
+        found = Some(*mv);
+    }
+    return found.ok_or_else(|| NotationError::IllegalMove(san.to_string()));
+}
+
+/// Reads a move written in either UCI notation or standard algebraic notation
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::notation::{parse_move, to_san, to_uci};
+/// let game = Game::starting_position();
+/// let mv = parse_move(&game, "Nf3").unwrap();
+/// assert_eq!(to_uci(&mv), "g1f3");
+/// assert_eq!(to_san(&game, &parse_move(&game, "e2e4").unwrap()), "e4");
+/// ```
+pub fn parse_move(game: &Game, text: &str) -> Result<Move, NotationError> {
+    return match parse_uci(game, text) {
+        Err(NotationError::InvalidSyntax(_)) => parse_san(game, text),
+        result => result
+    };
+}
+
+/// The notation of the moves in a move list, see [Game::moves_to_string]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Notation {
+    Uci,
+    San
+}
+
+/// A move of a move list that couldn't be made, see [Game::apply_move_list]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveListError {
+    /// The number of moves of the list before the failing one
+    pub ply: usize,
+    /// The fullmove number of the failing move
+    pub fullmove_number: usize,
+    pub error: NotationError
+}
+
+impl std::fmt::Display for MoveListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "move {} of the list (fullmove {}): {}", self.ply + 1, self.fullmove_number, self.error)
+    }
+}
+
+impl std::error::Error for MoveListError {}
+
+impl Game {
+
+    /// Writes the moves made since the position was set up as a numbered move list, for example
+    /// '1. e4 e5 2. Nf3', starting from the fullmove number of the position. A list starting with a move of Black
+    /// starts with '1...'.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::Notation;
+    /// let mut game = Game::starting_position();
+    /// game.apply_move_list("1. e4 e5 2. Nf3", Notation::San).unwrap();
+    /// assert_eq!(game.moves_to_string(Notation::San), "1. e4 e5 2. Nf3");
+    /// assert_eq!(game.moves_to_string(Notation::Uci), "1. e2e4 e7e5 2. g1f3");
+    ///
+    /// let mut game = Game::new("4k3/8/8/8/8/8/8/4K2R b K - 0 30");
+    /// game.apply_move_list("e8d7 e1g1", Notation::Uci).unwrap();
+    /// assert_eq!(game.moves_to_string(Notation::San), "30... Kd7 31. O-O");
+    /// ```
+    pub fn moves_to_string(&self, notation: Notation) -> String {
+        let mut game = self.clone();
+        let mut moves = vec![];
+        while let Some(mv) = game.unmake_move() {
+            moves.push(mv);
+        }
+
+        let mut tokens = vec![];
+        for (ply, mv) in moves.into_iter().rev().enumerate() {
+            if game.turn == WHITE {
+                tokens.push(format!("{}.", game.fullmove_number()));
+            }
+            else if ply == 0 {
+                tokens.push(format!("{}...", game.fullmove_number()));
+            }
+            match notation {
+                Notation::Uci => tokens.push(to_uci(&mv)),
+                Notation::San => tokens.push(to_san(&game, &mv))
+            }
+            game.make_move(mv);
+        }
+        return tokens.join(" ");
+    }
+
+    /// Makes the moves of a move list, as written by [Game::moves_to_string], and returns how many there were.
+    /// Move numbers are optional and a result such as '1-0' at the end is ignored. If a move can't be made the
+    /// game is left unchanged and the error tells which move it was and why.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::{MoveListError, Notation, NotationError};
+    /// let mut game = Game::starting_position();
+    /// assert_eq!(game.apply_move_list("1.e4 e5 2.Qh5 Nc6 3.Bc4 Nf6 4.Qxf7# 1-0", Notation::San), Ok(7));
+    /// assert_eq!(game.get_game_state(), GameState::Checkmate);
+    ///
+    /// let mut game = Game::starting_position();
+    /// let error = game.apply_move_list("1. e2e4 e7e5 2. e4e5", Notation::Uci).unwrap_err();
+    /// assert_eq!(error, MoveListError {
+    ///     ply: 2,
+    ///     fullmove_number: 2,
+    ///     error: NotationError::IllegalMove("e4e5".to_string())
+    /// });
+    /// assert_eq!(error.to_string(), "move 3 of the list (fullmove 2): 'e4e5' is not a legal move in this position");
+    /// assert_eq!(game.ply(), 0);
+    /// ```
+    pub fn apply_move_list(&mut self, moves: &str, notation: Notation) -> Result<usize, MoveListError> {
+        let mut game = self.clone();
+        let mut ply = 0;
+        for token in moves.split_whitespace() {
+            if token == "1-0" || token == "0-1" || token == "1/2-1/2" || token == "*" {
+                continue;
+            }
+            // A move number can be written apart from its move or joined to it as in '1.e4', but '0-0' is castling
+            let mut text = token.trim_start_matches(|c: char| c.is_ascii_digit()).trim_start_matches('.');
+            if !token[..token.len() - text.len()].ends_with('.') {
+                text = token;
+            }
+            if text.is_empty() {
+                continue;
+            }
+
+            let mv = match notation {
+                Notation::Uci => parse_uci(&game, text),
+                Notation::San => parse_san(&game, text)
+            };
+            let mv = mv.map_err(|error| MoveListError { ply, fullmove_number: game.fullmove_number(), error })?;
+            game.make_move(mv);
+            ply += 1;
+        }
+        *self = game;
+        return Ok(ply);
+    }
+}
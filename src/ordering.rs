@@ -0,0 +1,221 @@
+//! Move ordering utilities for search
+//!
+//! Captures are ordered with [sort_moves_mvv_lva] (most valuable victim, least valuable attacker), while
+//! quiet moves are usually ordered with a [KillerTable] and a [HistoryTable] filled in during search.
+//! [static_exchange] tells whether a capture wins material once all recaptures on its square are played out.
+
+use crate::{Game, Move, Piece, EMPTY, PAWN, KNIGHT, BISHOP, ROOK, QUEEN, KING};
+
+/// Returns the value of a piece type in centipawns, the king is given a value larger than all other pieces combined
+pub fn piece_value(piece_type: usize) -> i32 {
+    return match piece_type {
+        PAWN => 100,
+        KNIGHT => 320,
+        BISHOP => 330,
+        ROOK => 500,
+        QUEEN => 900,
+        KING => 20000,
+        _ => 0
+    };
+}
+
+/// Returns the MVV-LVA score of a move, higher scores should be searched first.
+/// Quiet moves score 0 unless they are promotions.
+pub fn mvv_lva_score(game: &Game, mv: &Move) -> i32 {
+    let mut score = 0;
+    if mv.is_capture() {
+        let victim = captured_piece_type(game, mv);
+        let attacker = game.board[mv.get_from()].get_type();
+        score += 10 * piece_value(victim) - piece_value(attacker) / 100 + 1;
+    }
+    if mv.is_promotion() {
+        score += piece_value(mv.get_promotion_type());
+    }
+    return score;
+}
+
+/// Sorts the moves so the most valuable victims captured by the least valuable attackers come first,
+/// followed by promotions and then quiet moves in their original order
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::ordering::sort_moves_mvv_lva;
+/// // The pawn on d4 can capture either a knight or a queen
+/// let game = Game::new("4k3/8/8/2q1n3/3P4/8/8/4K3 w - - 0 1");
+/// let mut moves = game.get_all_legal_moves();
+/// sort_moves_mvv_lva(&game, &mut moves);
+/// assert_eq!(moves[0].get_to(), Square::from_algebraic("c5").unwrap().index());
+/// ```
+pub fn sort_moves_mvv_lva(game: &Game, moves: &mut [Move]) {
+    moves.sort_by_key(|mv| -mvv_lva_score(game, mv));
+}
+
+/// Stores two quiet moves per ply that caused a beta cutoff
+pub struct KillerTable {
+    killers: Vec<[Option<Move>; 2]>
+}
+
+impl KillerTable {
+
+    /// Creates a killer table for searches up to the given number of plies
+    pub fn new(max_ply: usize) -> KillerTable {
+        KillerTable {
+            killers: vec![[None; 2]; max_ply]
+        }
+    }
+
+    /// Stores a killer move at the given ply, the older killer is pushed out
+    pub fn store(&mut self, ply: usize, mv: Move) {
+        if ply >= self.killers.len() || self.killers[ply][0] == Some(mv) {
+            return;
+        }
+        self.killers[ply][1] = self.killers[ply][0];
+        self.killers[ply][0] = Some(mv);
+    }
+
+    /// Returns the killer moves at the given ply, the most recent first
+    pub fn get(&self, ply: usize) -> [Option<Move>; 2] {
+        if ply >= self.killers.len() {
+            return [None; 2];
+        }
+        return self.killers[ply];
+    }
+
+    /// Returns true if the move is one of the killers at the given ply
+    pub fn is_killer(&self, ply: usize, mv: Move) -> bool {
+        return self.get(ply).contains(&Some(mv));
+    }
+
+    /// Removes all killer moves
+    pub fn clear(&mut self) {
+        for killers in self.killers.iter_mut() {
+            *killers = [None; 2];
+        }
+    }
+}
+
+/// Stores a score for every from and to square pair per color, increased when a quiet move causes a beta cutoff
+pub struct HistoryTable {
+    history: Box<[[[i32; 64]; 64]; 2]>
+}
+
+impl HistoryTable {
+
+    pub fn new() -> HistoryTable {
+        HistoryTable {
+            history: Box::new([[[0; 64]; 64]; 2])
+        }
+    }
+
+    /// Rewards a move made by the given color that caused a cutoff at the given remaining depth
+    pub fn update(&mut self, color: usize, mv: Move, depth: usize) {
+        let entry = &mut self.history[color][mv.get_from()][mv.get_to()];
+        *entry = entry.saturating_add((depth * depth) as i32);
+    }
+
+    /// Returns the history score of a move made by the given color
+    pub fn score(&self, color: usize, mv: Move) -> i32 {
+        return self.history[color][mv.get_from()][mv.get_to()];
+    }
+
+    /// Halves all scores, so old searches weigh less than new ones
+    pub fn age(&mut self) {
+        for entry in self.history.iter_mut().flatten().flatten() {
+            *entry /= 2;
+        }
+    }
+
+    /// Resets all scores to zero
+    pub fn clear(&mut self) {
+        for entry in self.history.iter_mut().flatten().flatten() {
+            *entry = 0;
+        }
+    }
+}
+
+impl Default for HistoryTable {
+    fn default() -> HistoryTable {
+        HistoryTable::new()
+    }
+}
+
+/// Returns the type of the captured piece for a capture, or EMPTY for a quiet move
+pub fn captured_piece_type(game: &Game, mv: &Move) -> usize {
+    if !mv.is_capture() {
+        return EMPTY;
+    }
+    if mv.is_ep_capture() {
+        return PAWN;
+    }
+    return game.board[mv.get_to()].get_type();
+}
+
+/// Returns the material the side making the move wins in centipawns once both sides have captured on its
+/// destination for as long as it pays off, the static exchange evaluation. Every piece attacking the square
+/// takes part, least valuable first, including those behind other attackers along the same line. Pins are
+/// ignored, a king only recaptures on a square the opponent no longer attacks.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::ordering::static_exchange;
+/// # use olindba_chess::notation::parse_uci;
+/// // The pawn takes a knight defended by a pawn
+/// let game = Game::new("4k3/2p5/3n4/4P3/8/8/8/4K3 w - - 0 1");
+/// assert_eq!(static_exchange(&game, &parse_uci(&game, "e5d6").unwrap()), 220);
+/// // The queen takes a pawn defended by a pawn
+/// let game = Game::new("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1");
+/// assert_eq!(static_exchange(&game, &parse_uci(&game, "d1d5").unwrap()), -800);
+/// // The rook behind the queen recaptures, so the queen goes for a pawn and a rook
+/// let game = Game::new("3rk3/8/8/3p4/8/8/3Q4/3RK3 w - - 0 1");
+/// assert_eq!(static_exchange(&game, &parse_uci(&game, "d2d5").unwrap()), -300);
+/// ```
+pub fn static_exchange(game: &Game, mv: &Move) -> i32 {
+    let to = mv.get_to();
+    let mut board = game.detached();
+    let mut gains = vec![piece_value(captured_piece_type(game, mv))];
+    let mut on_square = piece_value(game.board[mv.get_from()].get_type());
+    if mv.is_promotion() {
+        gains[0] += piece_value(mv.get_promotion_type()) - piece_value(PAWN);
+        on_square = piece_value(mv.get_promotion_type());
+    }
+    if mv.is_ep_capture() {
+        let captured = mv.get_from() / 8 * 8 + to % 8;
+        board.board[captured] = Piece::empty();
+    }
+    board.board[to] = board.board[mv.get_from()];
+    board.board[mv.get_from()] = Piece::empty();
+
+    let mut color = game.board[mv.get_from()].get_color() ^ 1;
+    while let Some(attacker) = least_valuable_attacker(&board, to, color) {
+        let attacker_type = board.board[attacker].get_type();
+        if attacker_type == KING && least_valuable_attacker(&board, to, color ^ 1).is_some() {
+            break;
+        }
+        gains.push(on_square - gains[gains.len() - 1]);
+        on_square = piece_value(attacker_type);
+        board.board[to] = board.board[attacker];
+        board.board[attacker] = Piece::empty();
+        color ^= 1;
+    }
+    // Each side stops capturing when going on would lose more than stopping
+    while gains.len() > 1 {
+        let last = gains.pop().unwrap();
+        let previous = gains.len() - 1;
+        gains[previous] = -(-gains[previous]).max(last);
+    }
+    return gains[0];
+}
+
+/// Returns the square of the least valuable piece of the color attacking the square
+fn least_valuable_attacker(game: &Game, square: usize, color: usize) -> Option<usize> {
+    return (0..64)
+        .filter(|&from| {
+            let piece = game.board[from];
+            piece.get_type() != EMPTY && piece.get_color() == color && game.attacks_from(from) & (1 << square) != 0
+        })
+        .min_by_key(|&from| piece_value(game.board[from].get_type()));
+}
@@ -0,0 +1,622 @@
+//! Reading and writing games in PGN format
+//!
+//! [parse_game] reads a single game from a string. [PgnStream] reads databases of any size one game at a time
+//! from a [std::io::BufRead], either into [PgnGame]s or through a [Visitor] that can skip the movetext of games
+//! when only the headers are wanted. A [PgnGame] is written back to PGN with its [std::fmt::Display] implementation.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::pgn::PgnStream;
+//! let database = "[Event \"First\"]\n[Result \"1-0\"]\n\n1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0\n\n\
+//!                 [Event \"Second\"]\n[Result \"*\"]\n\n1. d4 {a comment} d5 (1... Nf6) *\n";
+//! let games: Vec<_> = PgnStream::new(database.as_bytes()).map(|game| game.unwrap()).collect();
+//! assert_eq!(games.len(), 2);
+//! assert_eq!(games[0].header("Event"), Some("First"));
+//! assert_eq!(games[0].moves.len(), 7);
+//! assert_eq!(games[1].moves.len(), 2);
+//! assert_eq!(games[1].result, "*");
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::BufRead;
+
+use crate::fen::{parse_fen, to_fen, FenError};
+use crate::notation::{parse_san_with, to_san, NotationConfig, NotationError};
+use crate::{Annotation, Game, GameResult, Move, Termination, WHITE};
+
+/// The reason a game couldn't be read. A [PgnStream] goes on with the next game after an invalid move or FEN tag.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::pgn::{PgnError, PgnStream};
+/// let database = "[FEN \"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq\"]\n\n1. e4 *\n\n\
+///                 [Event \"Next\"]\n\n1. d4 *\n";
+/// let mut stream = PgnStream::new(database.as_bytes());
+/// assert!(matches!(stream.next(), Some(Err(PgnError::InvalidFen(_)))));
+/// assert_eq!(stream.next().unwrap().unwrap().header("Event"), Some("Next"));
+/// ```
+#[derive(Debug)]
+pub enum PgnError {
+    /// Reading from the underlying reader failed
+    Io(std::io::Error),
+    /// The move at the given ply, counted from 0, isn't a legal move in SAN
+    InvalidMove { ply: usize, error: NotationError },
+    /// The FEN tag doesn't describe a position
+    InvalidFen(FenError)
+}
+
+impl std::fmt::Display for PgnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PgnError::Io(error) => write!(f, "failed to read PGN: {}", error),
+            PgnError::InvalidMove { ply, error } => write!(f, "ply {}: {}", ply + 1, error),
+            PgnError::InvalidFen(error) => write!(f, "invalid FEN tag: {}", error)
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+impl From<std::io::Error> for PgnError {
+    fn from(error: std::io::Error) -> PgnError {
+        PgnError::Io(error)
+    }
+}
+
+/// A game read from PGN. Variations aren't kept, the comments, glyphs and evaluations of the moves of the main line
+/// are kept as [Annotation]s.
+#[derive(Clone)]
+pub struct PgnGame {
+    /// The header tags in the order they appeared
+    pub headers: Vec<(String, String)>,
+    /// The starting position, given by the FEN tag or the standard starting position
+    pub start: Game,
+    /// The moves of the main line
+    pub moves: Vec<Move>,
+    /// The annotations of the moves by their plies, counted from 0 as the moves
+    pub annotations: BTreeMap<usize, Annotation>,
+    /// The game termination marker, '1-0', '0-1', '1/2-1/2' or '*'
+    pub result: String
+}
+
+impl PgnGame {
+
+    /// Returns the value of the first header tag with the given name
+    pub fn header(&self, key: &str) -> Option<&str> {
+        return self.headers.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str());
+    }
+
+    /// Returns the position after all moves of the main line, with their annotations. A result the position doesn't
+    /// have on the board, such as after a resignation, is kept as the [ending](Game::end) of the game with the reason
+    /// given by the Termination tag, or [Termination::Normal] without one.
+    pub fn final_position(&self) -> Game {
+        let mut game = self.start.clone();
+        for mv in self.moves.iter() {
+            game.make_move(*mv);
+        }
+        for (&ply, annotation) in self.annotations.range(..self.moves.len()) {
+            game.annotate(ply, annotation.clone());
+        }
+        if let Some(result) = GameResult::parse(&self.result) {
+            let termination = self.header("Termination").and_then(Termination::parse).unwrap_or(Termination::Normal);
+            if game.result() != Some(result) || termination != Termination::Normal {
+                game.end(result, termination);
+            }
+        }
+        return game;
+    }
+}
+
+impl From<&Game> for PgnGame {
+    /// Returns the moves of the game's history with their annotations, starting from the position the history
+    /// starts in, which is given in a FEN tag unless it is the standard starting position. The result is the
+    /// game's result if it is over, and a game [ended](Game::end) off the board gets a Termination tag.
+    fn from(game: &Game) -> PgnGame {
+        let mut start = game.clone();
+        while start.unmake_move().is_some() {}
+
+        let mut headers = vec![];
+        let fen = to_fen(&start);
+        if fen != to_fen(&Game::starting_position()) {
+            headers.push(("SetUp".to_string(), "1".to_string()));
+            headers.push(("FEN".to_string(), fen));
+        }
+        let result = game.result().map_or("*".to_string(), |result| result.to_string());
+        headers.push(("Result".to_string(), result.clone()));
+        if let Some((_, termination)) = game.ending() {
+            headers.push(("Termination".to_string(), termination.to_string()));
+        }
+        return PgnGame {
+            headers,
+            start,
+            moves: game.history().iter().map(|entry| entry.mv).collect(),
+            annotations: game.annotations().clone(),
+            result
+        };
+    }
+}
+
+impl std::fmt::Display for PgnGame {
+    /// Writes the headers and the main line in SAN with the annotations, with the movetext wrapped at 80 characters
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::Evaluation;
+    /// # use olindba_chess::pgn::parse_game;
+    /// let game = parse_game("[Event \"Casual\"]\n\n1. f3 e5 2. g4 Qh4# 0-1").unwrap();
+    /// assert_eq!(game.to_string(), "[Event \"Casual\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n");
+    ///
+    /// // Suffix annotations are written as glyphs and the evaluation is taken out of the comment
+    /// let game = parse_game("1. e4! {[%eval 0.3] [%clk 0:05:00]} e5?! $10 (1... c5) *").unwrap();
+    /// assert_eq!(game.annotations[&0].eval, Some(Evaluation::Centipawns(30)));
+    /// assert_eq!(game.annotations[&0].comment.as_deref(), Some("[%clk 0:05:00]"));
+    /// assert_eq!(game.annotations[&1].nags, [6, 10]);
+    /// assert_eq!(game.to_string(), "\n1. e4 $1 {[%eval 0.30] [%clk 0:05:00]} 1... e5 $6 $10 *\n");
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (key, value) in self.headers.iter() {
+            writeln!(f, "[{} \"{}\"]", key, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        writeln!(f)?;
+
+        let mut tokens = vec![];
+        let mut game = self.start.clone();
+        let mut annotated = false;
+        for (ply, mv) in self.moves.iter().enumerate() {
+            if game.turn == WHITE {
+                tokens.push(format!("{}.", game.fullmove_number()));
+            }
+            else if ply == 0 || annotated {
+                tokens.push(format!("{}...", game.fullmove_number()));
+            }
+            tokens.push(to_san(&game, mv));
+            game.make_move(*mv);
+
+            let annotation = self.annotations.get(&ply);
+            annotated = annotation.is_some();
+            tokens.extend(annotation.map(Annotation::pgn_tokens).unwrap_or_default());
+        }
+        tokens.push(self.result.clone());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length != 0 && line_length + 1 + token.len() > 80 {
+                writeln!(f)?;
+                line_length = 0;
+            }
+            if line_length != 0 {
+                write!(f, " ")?;
+                line_length += 1;
+            }
+            write!(f, "{}", token)?;
+            line_length += token.len();
+        }
+        writeln!(f)
+    }
+}
+
+/// Receives the parts of a game as a [PgnStream] reads them
+#[allow(unused_variables)]
+pub trait Visitor {
+    type Output;
+
+    /// Called before anything else of a game is read
+    fn begin_game(&mut self) {}
+
+    /// Called for every header tag
+    fn header(&mut self, key: &str, value: &str) {}
+
+    /// Called after the header tags, return false to skip the movetext of the game
+    fn end_headers(&mut self) -> bool { true }
+
+    /// Called for every move in SAN, including moves inside variations
+    fn san(&mut self, san: &str) {}
+
+    /// Called for numeric annotation glyphs such as '$1'
+    fn nag(&mut self, nag: u8) {}
+
+    /// Called for the contents of every comment
+    fn comment(&mut self, comment: &str) {}
+
+    /// Called when a variation starts, the following moves are alternatives to the previous move
+    fn begin_variation(&mut self) {}
+
+    /// Called when a variation ends
+    fn end_variation(&mut self) {}
+
+    /// Called for the game termination marker
+    fn outcome(&mut self, result: &str) {}
+
+    /// Called after the whole game has been read, returns what the stream yields for the game
+    fn end_game(&mut self) -> Self::Output;
+}
+
+/// Builds [PgnGame]s, the visitor used when a [PgnStream] is iterated
+#[derive(Default)]
+pub struct GameBuilder {
+    headers: Vec<(String, String)>,
+    start: Option<Game>,
+    game: Option<Game>,
+    moves: Vec<Move>,
+    annotations: BTreeMap<usize, Annotation>,
+    result: String,
+    variation_depth: usize,
+    error: Option<PgnError>,
+    notation: NotationConfig
+}
+
+impl GameBuilder {
+
+    /// Creates a builder that reads the moves with the piece letters of the given config
+    pub fn with_notation(notation: NotationConfig) -> GameBuilder {
+        return GameBuilder { notation, ..Default::default() };
+    }
+
+    /// Returns the annotation of the last move of the main line, or None inside a variation or before the first move
+    fn last_annotation(&mut self) -> Option<&mut Annotation> {
+        if self.variation_depth > 0 || self.error.is_some() || self.moves.is_empty() {
+            return None;
+        }
+        return Some(self.annotations.entry(self.moves.len() - 1).or_default());
+    }
+}
+
+impl Visitor for GameBuilder {
+    type Output = Result<PgnGame, PgnError>;
+
+    fn begin_game(&mut self) {
+        *self = GameBuilder::with_notation(self.notation);
+    }
+
+    fn header(&mut self, key: &str, value: &str) {
+        self.headers.push((key.to_string(), value.to_string()));
+    }
+
+    fn end_headers(&mut self) -> bool {
+        let fen = self.headers.iter().find(|(key, _)| key == "FEN").map(|(_, value)| value.as_str());
+        let start = match fen.map(parse_fen) {
+            Some(Ok(start)) => start,
+            Some(Err(error)) => {
+                // The moves can't be read without the position, so the movetext is skipped
+                self.error = Some(PgnError::InvalidFen(error));
+                return false;
+            },
+            None => Game::starting_position()
+        };
+        self.game = Some(start.clone());
+        self.start = Some(start);
+        return true;
+    }
+
+    fn san(&mut self, san: &str) {
+        if self.variation_depth > 0 || self.error.is_some() {
+            return;
+        }
+        let game = self.game.as_mut().unwrap();
+        match parse_san_with(game, san, &self.notation) {
+            Ok(mv) => {
+                game.make_move(mv);
+                self.moves.push(mv);
+                // Move suffix annotations stand for the first six glyphs
+                let suffix = &san[san.trim_end_matches(['!', '?']).len()..];
+                if let Some(nag) = ["!", "?", "!!", "??", "!?", "?!"].iter().position(|&glyph| glyph == suffix) {
+                    self.nag(nag as u8 + 1);
+                }
+            },
+            Err(error) => self.error = Some(PgnError::InvalidMove { ply: self.moves.len(), error })
+        }
+    }
+
+    fn nag(&mut self, nag: u8) {
+        if let Some(annotation) = self.last_annotation() {
+            annotation.nags.push(nag);
+        }
+    }
+
+    fn comment(&mut self, comment: &str) {
+        if let Some(annotation) = self.last_annotation() {
+            annotation.add_comment(comment);
+        }
+    }
+
+    fn begin_variation(&mut self) {
+        self.variation_depth += 1;
+    }
+
+    fn end_variation(&mut self) {
+        self.variation_depth = self.variation_depth.saturating_sub(1);
+    }
+
+    fn outcome(&mut self, result: &str) {
+        self.result = result.to_string();
+    }
+
+    fn end_game(&mut self) -> Result<PgnGame, PgnError> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        if self.start.is_none() {
+            self.end_headers();
+        }
+        if self.result.is_empty() {
+            self.result = "*".to_string();
+        }
+        return Ok(PgnGame {
+            headers: std::mem::take(&mut self.headers),
+            start: self.start.take().unwrap(),
+            moves: std::mem::take(&mut self.moves),
+            annotations: std::mem::take(&mut self.annotations).into_iter()
+                .filter(|(_, annotation)| !annotation.is_empty())
+                .collect(),
+            result: std::mem::take(&mut self.result)
+        });
+    }
+}
+
+/// Reads PGN games one at a time from a reader, without loading the whole database into memory
+pub struct PgnStream<R: BufRead> {
+    reader: R,
+    pending_line: Option<String>,
+    failed: bool,
+    notation: NotationConfig
+}
+
+impl<R: BufRead> PgnStream<R> {
+
+    pub fn new(reader: R) -> PgnStream<R> {
+        PgnStream {
+            reader,
+            pending_line: None,
+            failed: false,
+            notation: NotationConfig::default()
+        }
+    }
+
+    /// Reads the moves of the games with the piece letters of the given config, for PGN written in another
+    /// language. Figurines are read either way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::notation::{NotationConfig, PieceLetters};
+    /// # use olindba_chess::pgn::PgnStream;
+    /// let pgn = "[Result \"*\"]\n\n1. e4 e5 2. Sf3 Sc6 3. Lb5 a6 4. O-O *\n";
+    /// let german = NotationConfig { piece_letters: PieceLetters::German, ..Default::default() };
+    /// let game = PgnStream::new(pgn.as_bytes()).with_notation(german).next().unwrap().unwrap();
+    /// assert_eq!(game.moves.len(), 7);
+    /// assert!(PgnStream::new(pgn.as_bytes()).next().unwrap().is_err());
+    /// ```
+    pub fn with_notation(mut self, notation: NotationConfig) -> PgnStream<R> {
+        self.notation = notation;
+        return self;
+    }
+
+    fn read_line(&mut self) -> std::io::Result<Option<String>> {
+        if let Some(line) = self.pending_line.take() {
+            return Ok(Some(line));
+        }
+        let mut bytes = vec![];
+        if self.reader.read_until(b'\n', &mut bytes)? == 0 {
+            return Ok(None);
+        }
+        return Ok(Some(String::from_utf8_lossy(&bytes).trim().to_string()));
+    }
+
+    /// Reads the next game through the given visitor, returns None once there are no more games
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::pgn::{PgnStream, Visitor};
+    /// // Collects the White player of every game without parsing any moves
+    /// struct WhitePlayer(Option<String>);
+    ///
+    /// impl Visitor for WhitePlayer {
+    ///     type Output = Option<String>;
+    ///     fn begin_game(&mut self) { self.0 = None; }
+    ///     fn header(&mut self, key: &str, value: &str) {
+    ///         if key == "White" { self.0 = Some(value.to_string()); }
+    ///     }
+    ///     fn end_headers(&mut self) -> bool { false }
+    ///     fn end_game(&mut self) -> Option<String> { self.0.take() }
+    /// }
+    ///
+    /// let database = "[White \"Carlsen\"]\n\n1. e4 e5 1-0\n[White \"Nakamura\"]\n\n1. d4 0-1\n";
+    /// let mut stream = PgnStream::new(database.as_bytes());
+    /// let mut visitor = WhitePlayer(None);
+    /// assert_eq!(stream.read_game(&mut visitor).unwrap().unwrap(), Some("Carlsen".to_string()));
+    /// assert_eq!(stream.read_game(&mut visitor).unwrap().unwrap(), Some("Nakamura".to_string()));
+    /// assert!(stream.read_game(&mut visitor).is_none());
+    /// ```
+    pub fn read_game<V: Visitor>(&mut self, visitor: &mut V) -> Option<std::io::Result<V::Output>> {
+        let result = self.read_game_inner(visitor).transpose();
+        if let Some(Err(_)) = result {
+            self.failed = true;
+        }
+        return result;
+    }
+
+    fn read_game_inner<V: Visitor>(&mut self, visitor: &mut V) -> std::io::Result<Option<V::Output>> {
+        let mut line;
+        loop {
+            line = match self.read_line()? {
+                Some(line) => line,
+                None => return Ok(None)
+            };
+            if !line.is_empty() && !line.starts_with('%') {
+                break;
+            }
+        }
+
+        visitor.begin_game();
+        while line.starts_with('[') {
+            if let Some((key, value)) = parse_header(&line) {
+                visitor.header(key, &value);
+            }
+            line = match self.read_line()? {
+                Some(line) => line,
+                None => {
+                    visitor.end_headers();
+                    return Ok(Some(visitor.end_game()));
+                }
+            };
+        }
+
+        let wants_movetext = visitor.end_headers();
+        let mut tokenizer = Tokenizer::default();
+        loop {
+            if line.starts_with('[') && !tokenizer.in_comment {
+                self.pending_line = Some(line);
+                break;
+            }
+            let finished;
+            if wants_movetext {
+                finished = tokenizer.feed(&line, visitor);
+            }
+            else {
+                finished = tokenizer.feed(&line, &mut SkipMovetext);
+            }
+            if finished {
+                break;
+            }
+            line = match self.read_line()? {
+                Some(line) => line,
+                None => break
+            };
+        }
+        return Ok(Some(visitor.end_game()));
+    }
+}
+
+impl<R: BufRead> Iterator for PgnStream<R> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Result<PgnGame, PgnError>> {
+        if self.failed {
+            return None;
+        }
+        return match self.read_game(&mut GameBuilder::with_notation(self.notation))? {
+            Ok(game) => Some(game),
+            Err(error) => Some(Err(PgnError::Io(error)))
+        };
+    }
+}
+
+/// Reads a single game in PGN format
+pub fn parse_game(pgn: &str) -> Result<PgnGame, PgnError> {
+    let mut stream = PgnStream::new(pgn.as_bytes());
+    return match stream.next() {
+        Some(game) => game,
+        None => GameBuilder::default().end_game()
+    };
+}
+
+/// A visitor that ignores everything, used to step over movetext that isn't wanted
+struct SkipMovetext;
+
+impl Visitor for SkipMovetext {
+    type Output = ();
+
+    fn end_game(&mut self) {}
+}
+
+fn parse_header(line: &str) -> Option<(&str, String)> {
+    let inner = line.strip_prefix('[')?.trim_end().strip_suffix(']')?;
+    let (key, value) = inner.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    return Some((key, value.replace("\\\"", "\"").replace("\\\\", "\\")));
+}
+
+/// Splits movetext into tokens, keeping track of comments that span several lines
+#[derive(Default)]
+struct Tokenizer {
+    in_comment: bool,
+    comment: String
+}
+
+impl Tokenizer {
+
+    /// Passes the tokens of a line to the visitor, returns true once the game termination marker is read
+    fn feed<V: Visitor>(&mut self, line: &str, visitor: &mut V) -> bool {
+        let mut rest = line;
+        loop {
+            if self.in_comment {
+                match rest.find('}') {
+                    Some(end) => {
+                        self.comment.push_str(&rest[..end]);
+                        visitor.comment(self.comment.trim());
+                        self.comment.clear();
+                        self.in_comment = false;
+                        rest = &rest[end + 1..];
+                    },
+                    None => {
+                        self.comment.push_str(rest);
+                        self.comment.push(' ');
+                        return false;
+                    }
+                }
+            }
+
+            rest = rest.trim_start();
+            let c = match rest.chars().next() {
+                Some(c) => c,
+                None => return false
+            };
+            match c {
+                '{' => {
+                    self.in_comment = true;
+                    rest = &rest[1..];
+                },
+                ';' => {
+                    visitor.comment(rest[1..].trim());
+                    return false;
+                },
+                '(' => {
+                    visitor.begin_variation();
+                    rest = &rest[1..];
+                },
+                ')' => {
+                    visitor.end_variation();
+                    rest = &rest[1..];
+                },
+                _ => {
+                    let end = rest.find(|c: char| c.is_whitespace() || "{}();".contains(c)).unwrap_or(rest.len());
+                    let token = &rest[..end];
+                    rest = &rest[end..];
+                    if self.read_token(token, visitor) {
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+
+    fn read_token<V: Visitor>(&mut self, token: &str, visitor: &mut V) -> bool {
+        if token == "1-0" || token == "0-1" || token == "1/2-1/2" || token == "*" {
+            visitor.outcome(token);
+            return true;
+        }
+        if let Some(nag) = token.strip_prefix('$') {
+            if let Ok(nag) = nag.parse::<u8>() {
+                visitor.nag(nag);
+            }
+            return false;
+        }
+
+        let san = token.trim_start_matches(|c: char| c.is_ascii_digit()).trim_start_matches('.');
+        if san.is_empty() {
+            return false;
+        }
+        if token[..token.len() - san.len()].ends_with('.') {
+            visitor.san(san);
+        }
+        else {
+            // Either no move number or castling written with zeros, '0-0'
+            visitor.san(token);
+        }
+        return false;
+    }
+}
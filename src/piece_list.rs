@@ -0,0 +1,105 @@
+use crate::fen::placement;
+use crate::{CastlingRights, Game, InvariantError, Piece, Square, BLACK, EMPTY, KING, PAWN, WHITE};
+
+/// Why [Game::from_piece_list] couldn't create a position
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PieceListError {
+    /// The piece type or the color given for the square isn't valid
+    InvalidPiece(Square),
+    /// More than one piece was given for the square
+    OccupiedSquare(Square),
+    /// The en passant square isn't on the rank behind a pawn that could have just made a double push
+    EnPassantSquare(Square),
+    /// A castling right was given without the king and rook to castle with
+    CastlingRights(CastlingRights),
+    /// The position is illegal, see [Game::check_invariants]
+    Invariant(InvariantError)
+}
+
+impl std::fmt::Display for PieceListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PieceListError::InvalidPiece(square) => write!(f, "invalid piece on {}", square),
+            PieceListError::OccupiedSquare(square) => write!(f, "more than one piece on {}", square),
+            PieceListError::EnPassantSquare(square) => write!(f, "{} can't be an en passant square", square),
+            PieceListError::CastlingRights(rights) => write!(f, "the castling rights {} aren't possible", rights),
+            PieceListError::Invariant(error) => write!(f, "{}", error)
+        }
+    }
+}
+
+impl std::error::Error for PieceListError {}
+
+impl From<InvariantError> for PieceListError {
+    fn from(error: InvariantError) -> PieceListError {
+        return PieceListError::Invariant(error);
+    }
+}
+
+impl Game {
+
+    /// Creates a position from a list of pieces as (square, piece type, color) instead of a FEN string. The en
+    /// passant square is the square behind the pawn that just made a double push, as in FEN, and the castling
+    /// rights name the outermost rook on each side of the king. The halfmove clock starts at 0 and the game at
+    /// move 1.
+    ///
+    /// The position is checked with [Game::check_invariants], and every castling right has to have its king and
+    /// rook on their starting squares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::fen::to_fen;
+    /// let square = |name| Square::from_algebraic(name).unwrap();
+    /// let pieces = [
+    ///     (square("e1"), KING, WHITE),
+    ///     (square("h1"), ROOK, WHITE),
+    ///     (square("e8"), KING, BLACK),
+    ///     (square("d4"), PAWN, BLACK),
+    ///     (square("e4"), PAWN, WHITE)
+    /// ];
+    /// let rights = CastlingRights { white_king_side: true, ..Default::default() };
+    /// let game = Game::from_piece_list(&pieces, BLACK, rights, Some(square("e3"))).unwrap();
+    /// assert_eq!(to_fen(&game), "4k3/8/8/8/3pP3/8/8/4K2R b K e3 0 1");
+    ///
+    /// let rights = CastlingRights { white_queen_side: true, ..Default::default() };
+    /// assert_eq!(Game::from_piece_list(&pieces, BLACK, rights, None).err(),
+    ///            Some(PieceListError::CastlingRights(rights)));
+    /// assert_eq!(Game::from_piece_list(&pieces[1..], WHITE, CastlingRights::default(), None).err(),
+    ///            Some(PieceListError::Invariant(InvariantError::KingCount { color: WHITE, count: 0 })));
+    /// ```
+    pub fn from_piece_list(pieces: &[(Square, usize, usize)], turn: usize, castling_rights: CastlingRights,
+                           en_passant: Option<Square>) -> Result<Game, PieceListError> {
+        if turn != WHITE && turn != BLACK {
+            return Err(PieceListError::Invariant(InvariantError::InvalidTurn(turn)));
+        }
+        let mut layout = Game::new("8/8/8/8/8/8/8/8 w - - 0 1");
+        for &(square, piece_type, color) in pieces {
+            if !(PAWN..=KING).contains(&piece_type) || (color != WHITE && color != BLACK) {
+                return Err(PieceListError::InvalidPiece(square));
+            }
+            if layout.board[square.index()].get_type() != EMPTY {
+                return Err(PieceListError::OccupiedSquare(square));
+            }
+            layout.board[square.index()] = Piece::new(piece_type, color, EMPTY);
+        }
+
+        let mut en_passant_field = "-".to_string();
+        if let Some(square) = en_passant {
+            if square.relative_rank(turn ^ 1) != 3 {
+                return Err(PieceListError::EnPassantSquare(square));
+            }
+            en_passant_field = square.to_string();
+        }
+        let turn_field = if turn == WHITE { "w" } else { "b" };
+        let fen = format!("{} {} {} {} 0 1", placement(&layout), turn_field, castling_rights, en_passant_field);
+        let game = Game::new(&fen);
+
+        game.check_invariants()?;
+        if game.castling_rights() != castling_rights {
+            return Err(PieceListError::CastlingRights(castling_rights));
+        }
+        return Ok(game);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{Game, BLACK, EMPTY};
+
+/// The number of planes written by [Game::to_planes]
+pub const N_PLANES: usize = 19;
+
+impl Game {
+
+    /// Returns the position as binary planes of 64 bits, one bit per square with bit n set for square n
+    /// as indexed in [Game::board], so the first bit is a8 and the last h1:
+    /// * 0-5: White's pawns, knights, bishops, rooks, queens and king
+    /// * 6-11: Black's pieces in the same order
+    /// * 12-15: all set if White can castle king side, White queen side, Black king side and Black queen side
+    /// * 16: the square a pawn can be captured en passant on, behind the pawn
+    /// * 17: all set if Black is to move
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let planes = Game::starting_position().to_bit_planes();
+    /// assert_eq!(planes[0], 0x00ff_0000_0000_0000);
+    /// assert_eq!(planes[11], 1 << 4);
+    /// assert_eq!(planes[12], u64::MAX);
+    /// assert_eq!(planes[17], 0);
+    /// ```
+    pub fn to_bit_planes(&self) -> [u64; N_PLANES - 1] {
+        let mut planes = [0; N_PLANES - 1];
+        for square in 0..64 {
+            let piece = self.board[square];
+            if piece.get_type() != EMPTY {
+                planes[piece.get_color() * 6 + piece.get_type() - 1] |= 1 << square;
+            }
+        }
+        for (i, right) in self.castling_rights().as_array().into_iter().enumerate() {
+            if right {
+                planes[12 + i] = u64::MAX;
+            }
+        }
+        if let Some(square) = self.en_passant_square() {
+            planes[16] = 1 << square.index();
+        }
+        if self.turn == BLACK {
+            planes[17] = u64::MAX;
+        }
+        return planes;
+    }
+
+    /// Returns the position as [N_PLANES] planes of 64 values for neural networks, plane after plane with the
+    /// squares in the order of [Game::board]. The first 18 planes are the planes of [Game::to_bit_planes] with
+    /// 1.0 for set bits and the last plane holds the halfmove clock divided by 100 on every square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::new("4k3/8/8/8/8/8/8/4K3 b - - 50 80");
+    /// let planes = game.to_planes();
+    /// assert_eq!(planes.len(), N_PLANES * 64);
+    /// assert_eq!(planes[5 * 64 + 60], 1.0);
+    /// assert_eq!(planes[17 * 64], 1.0);
+    /// assert_eq!(planes[18 * 64 + 10], 0.5);
+    /// ```
+    pub fn to_planes(&self) -> [f32; N_PLANES * 64] {
+        let mut planes = [0.0; N_PLANES * 64];
+        for (i, bits) in self.to_bit_planes().into_iter().enumerate() {
+            for square in 0..64 {
+                if bits & (1 << square) != 0 {
+                    planes[i * 64 + square] = 1.0;
+                }
+            }
+        }
+        let halfmove_clock = self.half_move_clock as f32 / 100.0;
+        for value in planes[(N_PLANES - 1) * 64..].iter_mut() {
+            *value = halfmove_clock;
+        }
+        return planes;
+    }
+}
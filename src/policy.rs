@@ -0,0 +1,159 @@
+//! Move indices for the policy heads of chess neural networks
+//!
+//! Both schemes see the board from the side to move, so Black's moves are mirrored vertically before they
+//! are encoded, and both count squares from a1 as in [Square::to_a1_indexing].
+//!
+//! * [PolicyScheme::AlphaZero] has 73 planes of 64 squares and the index is 'plane * 64 + from'. Planes 0-55 are
+//!   queen moves, 7 distances in each of the directions north, north-east, east, south-east, south, south-west,
+//!   west and north-west, with the plane 'direction * 7 + distance - 1'. Planes 56-63 are the knight moves
+//!   (+1, +2), (+2, +1), (+2, -1), (+1, -2), (-1, -2), (-2, -1), (-2, +1), (-1, +2) as (file, rank) offsets and
+//!   planes 64-72 the underpromotions to knight, bishop and rook, each capturing towards the a-file, moving
+//!   straight or capturing towards the h-file, as '64 + piece * 3 + direction'. Queen promotions and castling
+//!   are queen moves, castling with the king moving to its destination or to the rook if it already stands there.
+//! * [PolicyScheme::Lc0] lists the 1792 queen and knight moves between two squares, ordered by the square they
+//!   start on and then the square they end on, followed by the promotions to queen, rook and bishop
+//!   from the 7th rank. Knight promotions share the index of the plain move and castling is encoded as the
+//!   king moving to the square of its rook, as in Leela Chess Zero.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::policy::PolicyScheme;
+//! let game = Game::starting_position();
+//! for scheme in [PolicyScheme::AlphaZero, PolicyScheme::Lc0] {
+//!     for mv in game.get_all_legal_moves() {
+//!         let index = mv.to_policy_index(&game, scheme);
+//!         assert!(index < scheme.size());
+//!         assert_eq!(Move::from_policy_index(&game, scheme, index), Some(mv));
+//!     }
+//! }
+//! let e2e4 = game.get_legal_moves(52).into_iter().find(|mv| mv.get_to() == 36).unwrap();
+//! assert_eq!(e2e4.to_policy_index(&game, PolicyScheme::AlphaZero), 64 + 12);
+//! assert_eq!(e2e4.to_policy_index(&game, PolicyScheme::Lc0), 322);
+//! ```
+
+use std::sync::OnceLock;
+
+use crate::{Game, Move, Square, BISHOP, KNIGHT, QUEEN, ROOK};
+
+/// A way of numbering moves for a policy head
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PolicyScheme {
+    /// The 73 planes of 8x8 squares of AlphaZero, 4672 indices
+    AlphaZero,
+    /// The 1858 moves of Leela Chess Zero
+    Lc0
+}
+
+impl PolicyScheme {
+
+    /// Returns the number of indices of the scheme
+    pub fn size(&self) -> usize {
+        return match self {
+            PolicyScheme::AlphaZero => 73 * 64,
+            PolicyScheme::Lc0 => 1858
+        };
+    }
+}
+
+/// The (file, rank) steps of the queen directions and the knight moves in the order of the AlphaZero planes
+const QUEEN_DIRECTIONS: [(i32, i32); 8] = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+const KNIGHT_MOVES: [(i32, i32); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+
+/// The Lc0 index of each move between two squares counted from a1, or u16::MAX if it isn't a queen or
+/// knight move, followed by the index of the first promotion
+struct Lc0Table {
+    moves: [u16; 64 * 64],
+    promotions: u16
+}
+
+fn lc0_table() -> &'static Lc0Table {
+    static TABLE: OnceLock<Lc0Table> = OnceLock::new();
+    return TABLE.get_or_init(|| {
+        let mut table = Lc0Table {
+            moves: [u16::MAX; 64 * 64],
+            promotions: 0
+        };
+        let mut index = 0;
+        for from in 0..64 {
+            for to in 0..64 {
+                let file_step = (to % 8) as i32 - (from % 8) as i32;
+                let rank_step = (to / 8) as i32 - (from / 8) as i32;
+                let queen_move = from != to && (file_step == 0 || rank_step == 0 || file_step.abs() == rank_step.abs());
+                let knight_move = file_step.abs() * rank_step.abs() == 2;
+                if queen_move || knight_move {
+                    table.moves[from * 64 + to] = index;
+                    index += 1;
+                }
+            }
+        }
+        table.promotions = index;
+        return table;
+    });
+}
+
+/// Returns the squares of the move counted from a1 and seen from the side to move, with the king's destination
+/// for castling decided by the scheme
+fn relative_squares(game: &Game, mv: &Move, scheme: PolicyScheme) -> (usize, usize) {
+    let from = mv.get_from();
+    let mut to = mv.get_to();
+    if mv.is_castle() && (scheme == PolicyScheme::Lc0 || from == to) {
+        to = game.castling_rook_move(mv).0;
+    }
+    let from = Square::new(from).relative_to(game.turn).to_a1_indexing();
+    let to = Square::new(to).relative_to(game.turn).to_a1_indexing();
+    return (from, to);
+}
+
+impl Move {
+
+    /// Returns the index of a legal move of the game in the given scheme
+    pub fn to_policy_index(&self, game: &Game, scheme: PolicyScheme) -> usize {
+        let (from, to) = relative_squares(game, self, scheme);
+        let file_step = (to % 8) as i32 - (from % 8) as i32;
+        let rank_step = (to / 8) as i32 - (from / 8) as i32;
+        let promotion = self.get_promotion_type();
+
+        if scheme == PolicyScheme::Lc0 {
+            let table = lc0_table();
+            if promotion == QUEEN || promotion == ROOK || promotion == BISHOP {
+                // Three promotions for each of the 22 moves from the 7th rank, ordered by starting file and direction
+                let pair = (3 * (from % 8) as i32 + file_step) as usize;
+                let piece = match promotion {
+                    QUEEN => 0,
+                    ROOK => 1,
+                    _ => 2
+                };
+                return table.promotions as usize + pair * 3 + piece;
+            }
+            return table.moves[from * 64 + to] as usize;
+        }
+
+        let plane;
+        if promotion == KNIGHT || promotion == BISHOP || promotion == ROOK {
+            plane = 64 + (promotion - KNIGHT) * 3 + (file_step + 1) as usize;
+        }
+        else if let Some(knight) = KNIGHT_MOVES.iter().position(|&step| step == (file_step, rank_step)) {
+            plane = 56 + knight;
+        }
+        else {
+            let distance = file_step.abs().max(rank_step.abs());
+            let direction = QUEEN_DIRECTIONS.iter()
+                .position(|&step| step == (file_step.signum(), rank_step.signum()))
+                .unwrap();
+            plane = direction * 7 + distance as usize - 1;
+        }
+        return plane * 64 + from;
+    }
+
+    /// Returns the legal move of the game with the given index in the scheme, or None if no legal move has it
+    pub fn from_policy_index(game: &Game, scheme: PolicyScheme, index: usize) -> Option<Move> {
+        return game.legal_moves_cached().iter().copied().find(|mv| mv.to_policy_index(game, scheme) == index);
+    }
+}
+
+/// Returns the policy index of every legal move of the game in the order of [Game::get_all_legal_moves]
+pub fn legal_move_indices(game: &Game, scheme: PolicyScheme) -> Vec<usize> {
+    return game.legal_moves_cached().iter().map(|mv| mv.to_policy_index(game, scheme)).collect();
+}
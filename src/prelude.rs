@@ -0,0 +1,28 @@
+//! The types and functions most code using the crate needs, to import with a single line
+//!
+//! Colors and piece types are plain numbers, so their constants are part of the prelude. Everything else stays
+//! in its module, like the engine and the PGN visitors.
+//!
+//! # Examples
+//!
+//! ```
+//! use olindba_chess::prelude::*;
+//!
+//! let mut game = Game::starting_position();
+//! for san in ["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"] {
+//!     let mv = parse_san(&game, san).unwrap();
+//!     game.make_move(mv);
+//! }
+//! assert_eq!(game.get_game_state(), GameState::Checkmate);
+//! assert_eq!(game.result(), Some(GameResult::WhiteWins));
+//! assert_eq!(game.board[Square::F7.index()].get_color(), WHITE);
+//! assert_eq!(PgnGame::from(&game).moves.len(), 7);
+//! ```
+
+pub use crate::{
+    CastlingRights, Game, GameResult, GameState, Move, MoveOutcome, Piece, Promotion, Rules, Termination,
+    Bitboard, File, Rank, Square, BLACK, WHITE, EMPTY, PAWN, KNIGHT, BISHOP, ROOK, QUEEN, KING
+};
+pub use crate::fen::{parse_fen, to_fen};
+pub use crate::notation::{parse_move, parse_san, parse_uci, to_san, to_uci, Notation, NotationConfig};
+pub use crate::pgn::{parse_game, PgnGame};
@@ -0,0 +1,257 @@
+//! Protocol buffer messages for positions, moves, clocks and games, for clients written in other languages
+//!
+//! The messages are described by the schema in [SCHEMA], from which clients in other languages generate their
+//! code, and are encoded and decoded with the methods of [prost::Message], which is re-exported as [Message].
+//! Positions are sent as the pieces on the board rather than FEN and moves as their squares rather than SAN, so
+//! neither side has to parse notation. Decoding checks that positions are legal and that moves can be made.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::fen::to_fen;
+//! # use olindba_chess::notation::Notation;
+//! # use olindba_chess::proto::{GameRecord, Message};
+//! let mut game = Game::starting_position();
+//! game.apply_move_list("1. e4 c5 2. Nf3 d6 3. d4 cxd4", Notation::San).unwrap();
+//! let bytes = GameRecord::from(&game).encode_to_vec();
+//! assert!(bytes.len() < 128);
+//!
+//! let received = GameRecord::decode(bytes.as_slice()).unwrap().to_game().unwrap();
+//! assert_eq!(to_fen(&received), to_fen(&game));
+//! assert_eq!(received.history().len(), 6);
+//! ```
+
+use crate::clock::Clock;
+use crate::{CastlingRights, Game, PieceListError, Square, BLACK, EMPTY, WHITE};
+
+pub use prost::Message;
+
+/// The schema of the messages, to generate code for other languages from
+pub const SCHEMA: &str = include_str!("../proto/olindba_chess.proto");
+
+/// Why a message couldn't be turned into a position or game
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProtoError {
+    /// A field has a value it can't have
+    InvalidField(&'static str),
+    /// The pieces and rights don't make a legal position, see [Game::from_piece_list]
+    Position(PieceListError),
+    /// The move of the ply, counted from 0, isn't legal in the position it is made in
+    IllegalMove(usize)
+}
+
+impl std::fmt::Display for ProtoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ProtoError::InvalidField(field) => write!(f, "invalid {}", field),
+            ProtoError::Position(error) => write!(f, "{}", error),
+            ProtoError::IllegalMove(ply) => write!(f, "illegal move at ply {}", ply)
+        }
+    }
+}
+
+impl std::error::Error for ProtoError {}
+
+impl From<PieceListError> for ProtoError {
+    fn from(error: PieceListError) -> ProtoError {
+        return ProtoError::Position(error);
+    }
+}
+
+/// A position without the moves that led to it
+#[derive(Clone, PartialEq, Message)]
+pub struct Position {
+    /// One byte per square from a8 to h1, 0 for an empty square, otherwise the piece type plus 8 for a black piece
+    #[prost(bytes = "vec", tag = "1")]
+    pub board: Vec<u8>,
+    #[prost(uint32, tag = "2")]
+    pub turn: u32,
+    /// 1 White king side, 2 White queen side, 4 Black king side and 8 Black queen side, see [Game::from_piece_list]
+    #[prost(uint32, tag = "3")]
+    pub castling_rights: u32,
+    /// The square behind a pawn that just made a double push, as in FEN
+    #[prost(uint32, optional, tag = "4")]
+    pub en_passant: Option<u32>,
+    #[prost(uint32, tag = "5")]
+    pub halfmove_clock: u32,
+    #[prost(uint32, tag = "6")]
+    pub fullmove_number: u32
+}
+
+impl From<&Game> for Position {
+    fn from(game: &Game) -> Position {
+        let board = game.board.iter().map(|piece| {
+            if piece.get_type() == EMPTY {
+                return 0;
+            }
+            return (piece.get_type() | piece.get_color() << 3) as u8;
+        }).collect();
+        let castling_rights = game.castling_rights().as_array().iter().enumerate()
+            .map(|(bit, &right)| (right as u32) << bit)
+            .sum();
+        return Position {
+            board,
+            turn: game.turn as u32,
+            castling_rights,
+            en_passant: game.en_passant_square().map(|square| square.index() as u32),
+            halfmove_clock: game.halfmove_clock() as u32,
+            fullmove_number: game.fullmove_number() as u32
+        };
+    }
+}
+
+impl Position {
+
+    /// Returns the position as a game without history
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::fen::to_fen;
+    /// # use olindba_chess::proto::{Position, ProtoError};
+    /// let game = Game::new("r3k2r/8/8/3pP3/8/8/8/R3K2R w Kq d6 0 20");
+    /// let position = Position::from(&game);
+    /// assert_eq!(position.castling_rights, 0b1001);
+    /// assert_eq!(to_fen(&position.to_game().unwrap()), to_fen(&game));
+    ///
+    /// let no_board = Position { board: vec![], ..position };
+    /// assert_eq!(no_board.to_game().err(), Some(ProtoError::InvalidField("board")));
+    /// ```
+    pub fn to_game(&self) -> Result<Game, ProtoError> {
+        if self.board.len() != 64 {
+            return Err(ProtoError::InvalidField("board"));
+        }
+        if self.castling_rights > 0b1111 {
+            return Err(ProtoError::InvalidField("castling_rights"));
+        }
+        if self.fullmove_number == 0 {
+            return Err(ProtoError::InvalidField("fullmove_number"));
+        }
+        let en_passant = match self.en_passant {
+            Some(square) if square >= 64 => return Err(ProtoError::InvalidField("en_passant")),
+            Some(square) => Some(Square::new(square as usize)),
+            None => None
+        };
+
+        let pieces: Vec<(Square, usize, usize)> = self.board.iter().enumerate()
+            .filter(|(_, &byte)| byte != 0)
+            .map(|(square, &byte)| (Square::new(square), (byte & 0x7) as usize, (byte >> 3) as usize))
+            .collect();
+        let mut rights = [false; 4];
+        for (bit, right) in rights.iter_mut().enumerate() {
+            *right = self.castling_rights & 1 << bit != 0;
+        }
+        let mut game = Game::from_piece_list(&pieces, self.turn as usize, CastlingRights::from_array(rights),
+                                             en_passant)?;
+        game.half_move_clock = self.halfmove_clock as usize;
+        game.full_move_number = self.fullmove_number as usize;
+        return Ok(game);
+    }
+}
+
+/// A move, castling is the king moving to its destination
+#[derive(Clone, PartialEq, Message)]
+pub struct Move {
+    #[prost(uint32, tag = "1")]
+    pub from: u32,
+    #[prost(uint32, tag = "2")]
+    pub to: u32,
+    /// The piece type promoted to, 0 if the move isn't a promotion
+    #[prost(uint32, tag = "3")]
+    pub promotion: u32
+}
+
+impl From<crate::Move> for Move {
+    fn from(mv: crate::Move) -> Move {
+        return Move {
+            from: mv.get_from() as u32,
+            to: mv.get_to() as u32,
+            promotion: mv.get_promotion_type() as u32
+        };
+    }
+}
+
+impl Move {
+
+    /// Returns the legal move of the game the message describes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::proto::{Message, Move};
+    /// let game = Game::new("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+    /// let promotion = game.get_legal_moves(Square::A7)[0];
+    /// let bytes = Move::from(promotion).encode_to_vec();
+    /// assert_eq!(Move::decode(bytes.as_slice()).unwrap().to_move(&game), Some(promotion));
+    /// assert_eq!(Move { from: 8, to: 0, promotion: 0 }.to_move(&game), None);
+    /// ```
+    pub fn to_move(&self, game: &Game) -> Option<crate::Move> {
+        return game.legal_moves_cached().iter().copied().find(|mv| mv.get_from() as u32 == self.from &&
+            mv.get_to() as u32 == self.to && mv.get_promotion_type() as u32 == self.promotion);
+    }
+}
+
+/// The time left on both clocks
+#[derive(Clone, PartialEq, Message)]
+pub struct ClockState {
+    #[prost(uint64, tag = "1")]
+    pub white_ms: u64,
+    #[prost(uint64, tag = "2")]
+    pub black_ms: u64,
+    /// The color whose time ran out
+    #[prost(uint32, optional, tag = "3")]
+    pub flagged: Option<u32>
+}
+
+impl From<&Clock> for ClockState {
+    fn from(clock: &Clock) -> ClockState {
+        return ClockState {
+            white_ms: clock.remaining(WHITE).as_millis() as u64,
+            black_ms: clock.remaining(BLACK).as_millis() as u64,
+            flagged: clock.flagged().map(|color| color as u32)
+        };
+    }
+}
+
+/// A game as the position it started in and the moves made since, with the clocks if there are any
+#[derive(Clone, PartialEq, Message)]
+pub struct GameRecord {
+    #[prost(message, optional, tag = "1")]
+    pub start: Option<Position>,
+    #[prost(message, repeated, tag = "2")]
+    pub moves: Vec<Move>,
+    #[prost(message, optional, tag = "3")]
+    pub clock: Option<ClockState>
+}
+
+impl From<&Game> for GameRecord {
+    /// Returns the game's history, without clocks
+    fn from(game: &Game) -> GameRecord {
+        let mut start = game.clone();
+        while start.unmake_move().is_some() {}
+        return GameRecord {
+            start: Some(Position::from(&start)),
+            moves: game.history().iter().map(|entry| Move::from(entry.mv)).collect(),
+            clock: None
+        };
+    }
+}
+
+impl GameRecord {
+
+    /// Returns the game with its moves made from the starting position, which is the standard one if missing
+    pub fn to_game(&self) -> Result<Game, ProtoError> {
+        let mut game = match &self.start {
+            Some(start) => start.to_game()?,
+            None => Game::starting_position()
+        };
+        for (ply, mv) in self.moves.iter().enumerate() {
+            let mv = mv.to_move(&game).ok_or(ProtoError::IllegalMove(ply))?;
+            game.make_move(mv);
+        }
+        return Ok(game);
+    }
+}
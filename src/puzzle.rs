@@ -0,0 +1,231 @@
+//! Verifying tactics puzzles given as a position and a solution line
+//!
+//! A solution line alternates between the solver's moves and the opponent's replies, starting and ending
+//! with a move by the solver. Moves can be written in either SAN or UCI notation. Lichess stores its puzzles with
+//! the opponent's move leading to the puzzle first, such lines are read with [Puzzle::from_lichess].
+//!
+//! The solver's moves have to be the winning ones, as far as that can be told without searching: no other move may
+//! checkmate at any point of the line, and the line has to end in checkmate or with the solver ahead in material
+//! once the captures on the board are played out. Any checkmate is accepted in place of a mating last move.
+
+use crate::notation::{parse_move, to_uci, NotationError};
+use crate::ordering::static_exchange;
+use crate::{Game, Move, WHITE};
+
+/// The reason a solution line isn't a valid puzzle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PuzzleError {
+    /// The solution line has no moves
+    EmptySolution,
+    /// The solution line ends with a reply from the opponent instead of a move by the solver
+    EndsWithOpponentMove,
+    /// The move at the given ply, counted from 0, couldn't be played
+    InvalidMove { ply: usize, error: NotationError },
+    /// Another move of the solver mates or wins as much as the move at the given ply
+    AmbiguousMove { ply: usize, alternative: Move },
+    /// The line neither checkmates nor wins material
+    NotWinning
+}
+
+impl std::fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PuzzleError::EmptySolution => write!(f, "the solution has no moves"),
+            PuzzleError::EndsWithOpponentMove => write!(f, "the solution ends with a move by the opponent"),
+            PuzzleError::InvalidMove { ply, error } => write!(f, "move {} of the solution: {}", ply + 1, error),
+            PuzzleError::AmbiguousMove { ply, alternative } => {
+                write!(f, "move {} of the solution isn't the only winning one, {} is too", ply + 1, to_uci(alternative))
+            },
+            PuzzleError::NotWinning => write!(f, "the solution neither checkmates nor wins material")
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {}
+
+/// A position together with a verified solution line
+pub struct Puzzle {
+    game: Game,
+    /// The opponent's move leading to the puzzle, made before the solution
+    setup: Option<Move>,
+    solution: Vec<Move>
+}
+
+impl Puzzle {
+
+    /// Creates a puzzle from a position and a solution line, see [verify] for the rules the line must follow
+    pub fn new(game: &Game, solution: &[&str]) -> Result<Puzzle, PuzzleError> {
+        let solution = play_line(game, solution, false)?;
+        return Ok(Puzzle {
+            game: game.clone(),
+            setup: None,
+            solution
+        });
+    }
+
+    /// Creates a puzzle from a position and moves as lichess stores them, the opponent's move leading to the puzzle
+    /// followed by the solution line. Plies of errors count the opponent's first move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::puzzle::{Puzzle, PuzzleError};
+    /// // Black's rook steps into the rook of White
+    /// let game = Game::new("r5k1/5ppp/8/8/8/8/5PPP/R5K1 b - - 0 1");
+    /// let puzzle = Puzzle::from_lichess(&game, &["a8a7", "a1a7"]).unwrap();
+    /// assert_eq!(puzzle.setup().map(|mv| mv.get_to()), Some(8));
+    /// assert_eq!(puzzle.solution().len(), 1);
+    /// assert_eq!(puzzle.position_at(0).turn, WHITE);
+    ///
+    /// let puzzle = Puzzle::from_lichess(&game, &["a8a7", "a1a8"]);
+    /// assert!(matches!(puzzle, Err(PuzzleError::InvalidMove { ply: 1, .. })));
+    /// ```
+    pub fn from_lichess(game: &Game, moves: &[&str]) -> Result<Puzzle, PuzzleError> {
+        let mut line = play_line(game, moves, true)?;
+        let setup = line.remove(0);
+        return Ok(Puzzle {
+            game: game.clone(),
+            setup: Some(setup),
+            solution: line
+        });
+    }
+
+    /// Returns the position the puzzle was given in, before the opponent's move leading to it if there is one
+    pub fn game(&self) -> &Game {
+        return &self.game;
+    }
+
+    /// Returns the opponent's move leading to the puzzle of a puzzle read with [Puzzle::from_lichess]
+    pub fn setup(&self) -> Option<Move> {
+        return self.setup;
+    }
+
+    /// Returns the moves of the solution line
+    pub fn solution(&self) -> &[Move] {
+        return &self.solution;
+    }
+
+    /// Returns the position before the move at the given ply of the solution
+    pub fn position_at(&self, ply: usize) -> Game {
+        let mut game = self.game.clone();
+        for mv in self.setup.iter().chain(self.solution.iter().take(ply)) {
+            game.make_move(*mv);
+        }
+        return game;
+    }
+
+    /// Returns true if a solver's candidate move at the given ply is accepted.
+    /// The candidate is accepted if it matches the solution, or if the solution checkmates and so does the candidate.
+    pub fn accepts(&self, ply: usize, candidate: Move) -> bool {
+        if ply >= self.solution.len() || !ply.is_multiple_of(2) {
+            return false;
+        }
+        if candidate == self.solution[ply] {
+            return true;
+        }
+        let game = self.position_at(ply);
+        return is_mating_move(&game, self.solution[ply]) && is_mating_move(&game, candidate);
+    }
+}
+
+/// Verifies that a solution line is a puzzle, see the [module documentation](self) for the rules it has to follow.
+/// Use [Puzzle::accepts] to compare a solver's candidate moves against the verified line.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::puzzle::{verify, Puzzle, PuzzleError};
+/// let game = Game::new("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+/// assert_eq!(verify(&game, &["Ra8#"]), Ok(()));
+/// assert_eq!(verify(&game, &["a1a8", "g8h8"]), Err(PuzzleError::EndsWithOpponentMove));
+/// assert_eq!(verify(&Game::starting_position(), &["a3"]), Err(PuzzleError::NotWinning));
+///
+/// // The knight is won, but mate was possible instead
+/// let game = Game::new("6k1/5ppp/8/8/8/8/8/R1n3K1 w - - 0 1");
+/// assert!(matches!(verify(&game, &["Rxc1"]), Err(PuzzleError::AmbiguousMove { ply: 0, .. })));
+///
+/// // Any checkmate is accepted in place of the final move
+/// let game = Game::new("6k1/5ppp/8/8/8/8/8/RR4K1 w - - 0 1");
+/// let puzzle = Puzzle::new(&game, &["Ra8#"]).unwrap();
+/// let other_mate = game.get_legal_moves(57).into_iter().find(|mv| mv.get_to() == 1).unwrap();
+/// assert!(puzzle.accepts(0, other_mate));
+/// ```
+pub fn verify(game: &Game, solution: &[&str]) -> Result<(), PuzzleError> {
+    play_line(game, solution, false)?;
+    return Ok(());
+}
+
+/// Plays the line and checks it is a puzzle. With a setup move the first move is the opponent's.
+fn play_line(game: &Game, line: &[&str], setup: bool) -> Result<Vec<Move>, PuzzleError> {
+    let first_solver_ply = setup as usize;
+    if line.len() <= first_solver_ply {
+        return Err(PuzzleError::EmptySolution);
+    }
+    if (line.len() - first_solver_ply).is_multiple_of(2) {
+        return Err(PuzzleError::EndsWithOpponentMove);
+    }
+
+    let mut game = game.clone();
+    let mut moves = vec![];
+    let mut balance = 0;
+    for (ply, text) in line.iter().enumerate() {
+        let mv = parse_move(&game, text).map_err(|error| PuzzleError::InvalidMove { ply, error })?;
+        if ply == first_solver_ply {
+            balance = solver_balance(&game, game.turn);
+        }
+        if ply % 2 == first_solver_ply % 2 {
+            if let Some(alternative) = winning_alternative(&game, mv, ply == line.len() - 1) {
+                return Err(PuzzleError::AmbiguousMove { ply, alternative });
+            }
+        }
+        game.make_move(mv);
+        moves.push(mv);
+    }
+
+    // The opponent is to move, and gets the best of its captures on the board
+    if !game.is_checkmated() {
+        let solver = game.turn ^ 1;
+        let recapture = game.legal_moves_cached().iter()
+            .filter(|mv| mv.is_capture())
+            .map(|mv| static_exchange(&game, mv))
+            .fold(0, i32::max);
+        if solver_balance(&game, solver) - recapture <= balance {
+            return Err(PuzzleError::NotWinning);
+        }
+    }
+    return Ok(moves);
+}
+
+/// Returns the material of the solver minus the material of the opponent
+fn solver_balance(game: &Game, solver: usize) -> i32 {
+    if solver == WHITE {
+        return game.material_balance();
+    }
+    return -game.material_balance();
+}
+
+/// Returns a move of the solver that wins at least as much as the solution's move: a checkmate when the move
+/// doesn't mate, and for the last move of the line a capture winning as much material
+fn winning_alternative(game: &Game, mv: Move, last: bool) -> Option<Move> {
+    let mates = game.mating_moves();
+    if mates.contains(&mv) {
+        return None;
+    }
+    if let Some(&mate) = mates.first() {
+        return Some(mate);
+    }
+    if !last || !mv.is_capture() {
+        return None;
+    }
+    let gain = static_exchange(game, &mv);
+    return game.legal_moves_cached().iter().copied()
+        .find(|&other| other != mv && other.is_capture() && static_exchange(game, &other) >= gain.max(1));
+}
+
+fn is_mating_move(game: &Game, mv: Move) -> bool {
+    let mut game_after = game.clone();
+    game_after.make_move(mv);
+    return game_after.is_checkmated();
+}
@@ -0,0 +1,62 @@
+use rand::Rng;
+
+use crate::{Game, GameState, Move};
+
+impl Game {
+
+    /// Returns a uniformly random legal move, or None if there are no legal moves.
+    /// The move is picked while the moves are generated, so no list of moves is allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let mut rng = rand::thread_rng();
+    /// let game = Game::starting_position();
+    /// let mv = game.random_legal_move(&mut rng).unwrap();
+    /// assert!(game.get_all_legal_moves().contains(&mv));
+    /// ```
+    pub fn random_legal_move(&self, rng: &mut impl Rng) -> Option<Move> {
+        if self.is_automatically_terminated() {
+            return None;
+        }
+        return self.sample_legal_move(rng);
+    }
+
+    /// Plays random legal moves until the game ends or 'max_plies' moves have been made and returns the game state
+    /// of the final position. The playout stops at every draw, including the 50-move rule and insufficient material.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let mut rng = rand::thread_rng();
+    /// let mut game = Game::new("7k/8/8/8/8/8/8/K7 w - - 0 1");
+    /// assert_eq!(game.random_playout(&mut rng, 100), GameState::InsufficientMaterial);
+    /// ```
+    pub fn random_playout(&mut self, rng: &mut impl Rng, max_plies: usize) -> GameState {
+        for _ in 0..max_plies {
+            if self.half_move_clock >= self.rules.claimable_draw_halfmoves || self.has_insufficient_material() || self.repetition_count() >= 5 {
+                break;
+            }
+            match self.sample_legal_move(rng) {
+                Some(mv) => self.make_move(mv),
+                None => break
+            }
+        }
+        return self.get_game_state();
+    }
+
+    /// Picks a legal move with reservoir sampling, every move replaces the chosen one with probability 1/n
+    fn sample_legal_move(&self, rng: &mut impl Rng) -> Option<Move> {
+        let mut chosen = None;
+        let mut n_moves = 0;
+        self.move_generator().generate_legal_moves(self, 0..64, &mut |mv| {
+            n_moves += 1;
+            if rng.gen_range(0..n_moves) == 0 {
+                chosen = Some(mv);
+            }
+        });
+        return chosen;
+    }
+}
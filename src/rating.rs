@@ -0,0 +1,176 @@
+//! Rating math for estimating and tracking the strength of players
+//!
+//! The Elo functions convert between rating differences and expected scores, where a score counts a win as 1, a
+//! draw as 0.5 and a loss as 0. [performance_rating] estimates the rating a set of results was played at, and
+//! [Glicko2Rating] keeps ratings over many games with the Glicko-2 system, which also tracks how reliable each rating
+//! is. The results of a [MatchScore](crate::matchplay::MatchScore) convert to an Elo difference with
+//! [MatchScore::elo_difference](crate::matchplay::MatchScore::elo_difference).
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::rating::{elo_difference, expected_score};
+//! assert_eq!(expected_score(0.0), 0.5);
+//! assert!((expected_score(400.0) - 10.0 / 11.0).abs() < 1e-9);
+//! assert!((elo_difference(0.75) - 190.85).abs() < 0.01);
+//! ```
+
+use std::f64::consts::PI;
+
+/// The factor between Glicko ratings and the internal Glicko-2 scale
+const GLICKO2_SCALE: f64 = 173.7178;
+
+/// Returns the score a player is expected to make against an opponent rated the given number of Elo points lower
+pub fn expected_score(elo_difference: f64) -> f64 {
+    return 1.0 / (1.0 + 10f64.powf(-elo_difference / 400.0));
+}
+
+/// Returns the Elo difference at which the score is expected, the inverse of [expected_score]. A score of 0 or 1
+/// gives an infinite difference.
+pub fn elo_difference(score: f64) -> f64 {
+    return -400.0 * (1.0 / score - 1.0).log10();
+}
+
+/// Returns the rating at which the results, pairs of the opponent's rating and the score against them, are
+/// expected. Returns None when there are no results or they are all wins or all losses, which fit any rating high
+/// or low enough.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::rating::performance_rating;
+/// let results = [(1600.0, 1.0), (1800.0, 0.5), (2000.0, 0.0)];
+/// assert!((performance_rating(&results).unwrap() - 1800.0).abs() < 0.01);
+/// assert!((performance_rating(&[(1500.0, 1.0), (1500.0, 0.5)]).unwrap() - 1690.85).abs() < 0.01);
+/// assert_eq!(performance_rating(&[(1500.0, 1.0), (1700.0, 1.0)]), None);
+/// ```
+pub fn performance_rating(results: &[(f64, f64)]) -> Option<f64> {
+    let total: f64 = results.iter().map(|&(_, score)| score).sum();
+    if results.is_empty() || total <= 0.0 || total >= results.len() as f64 {
+        return None;
+    }
+    let expected_total = |rating: f64| -> f64 {
+        return results.iter().map(|&(opponent, _)| expected_score(rating - opponent)).sum();
+    };
+    // The expected total grows with the rating, so the rating making it the actual total can be bisected
+    let lowest = results.iter().map(|&(opponent, _)| opponent).fold(f64::INFINITY, f64::min);
+    let highest = results.iter().map(|&(opponent, _)| opponent).fold(f64::NEG_INFINITY, f64::max);
+    let (mut low, mut high) = (lowest - 4000.0, highest + 4000.0);
+    while high - low > 1e-6 {
+        let middle = (low + high) / 2.0;
+        if expected_total(middle) < total {
+            low = middle;
+        }
+        else {
+            high = middle;
+        }
+    }
+    return Some((low + high) / 2.0);
+}
+
+/// A Glicko-2 rating, the rating itself on the Elo scale with its deviation, which shrinks as more games are
+/// played, and the volatility, how much the player's strength is expected to change between rating periods
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Glicko2Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64
+}
+
+impl Default for Glicko2Rating {
+    /// The rating of a new player, 1500 with a deviation of 350 and a volatility of 0.06
+    fn default() -> Glicko2Rating {
+        return Glicko2Rating { rating: 1500.0, deviation: 350.0, volatility: 0.06 };
+    }
+}
+
+impl Glicko2Rating {
+
+    /// Returns the rating after a rating period with the games, pairs of the opponent's rating at the start of the
+    /// period and the score against them. Tau limits how fast the volatility changes, usually between 0.3 and 1.2.
+    /// Without games only the deviation grows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::rating::Glicko2Rating;
+    /// // The example of Glickman's description of the system
+    /// let player = Glicko2Rating { rating: 1500.0, deviation: 200.0, volatility: 0.06 };
+    /// let opponent = |rating, deviation| Glicko2Rating { rating, deviation, volatility: 0.06 };
+    /// let games = [(opponent(1400.0, 30.0), 1.0), (opponent(1550.0, 100.0), 0.0), (opponent(1700.0, 300.0), 0.0)];
+    /// let updated = player.update(&games, 0.5);
+    /// assert!((updated.rating - 1464.05).abs() < 0.01);
+    /// assert!((updated.deviation - 151.52).abs() < 0.01);
+    /// assert!((updated.volatility - 0.05999).abs() < 0.00001);
+    ///
+    /// let idle = player.update(&[], 0.5);
+    /// assert_eq!(idle.rating, 1500.0);
+    /// assert!(idle.deviation > 200.0);
+    /// ```
+    pub fn update(&self, games: &[(Glicko2Rating, f64)], tau: f64) -> Glicko2Rating {
+        let mu = (self.rating - 1500.0) / GLICKO2_SCALE;
+        let phi = self.deviation / GLICKO2_SCALE;
+        if games.is_empty() {
+            let deviation = (phi * phi + self.volatility * self.volatility).sqrt() * GLICKO2_SCALE;
+            return Glicko2Rating { deviation, ..*self };
+        }
+
+        // The estimated variance of the rating from the games alone and the improvement they suggest
+        let mut inverse_variance = 0.0;
+        let mut weighted_surprise = 0.0;
+        for (opponent, score) in games {
+            let opponent_mu = (opponent.rating - 1500.0) / GLICKO2_SCALE;
+            let g = 1.0 / (1.0 + 3.0 * (opponent.deviation / GLICKO2_SCALE).powi(2) / (PI * PI)).sqrt();
+            let expected = 1.0 / (1.0 + (-g * (mu - opponent_mu)).exp());
+            inverse_variance += g * g * expected * (1.0 - expected);
+            weighted_surprise += g * (score - expected);
+        }
+        let variance = 1.0 / inverse_variance;
+        let delta = variance * weighted_surprise;
+
+        // The new volatility solves f(x) = 0 for x = ln(volatility²), found with the Illinois algorithm
+        let a = (self.volatility * self.volatility).ln();
+        let f = |x: f64| -> f64 {
+            let ex = x.exp();
+            let denominator = phi * phi + variance + ex;
+            return ex * (delta * delta - phi * phi - variance - ex) / (2.0 * denominator * denominator)
+                - (x - a) / (tau * tau);
+        };
+        let mut low = a;
+        let mut high;
+        if delta * delta > phi * phi + variance {
+            high = (delta * delta - phi * phi - variance).ln();
+        }
+        else {
+            let mut k = 1.0;
+            while f(a - k * tau) < 0.0 {
+                k += 1.0;
+            }
+            high = a - k * tau;
+        }
+        let (mut f_low, mut f_high) = (f(low), f(high));
+        while (high - low).abs() > 1e-6 {
+            let next = low + (low - high) * f_low / (f_high - f_low);
+            let f_next = f(next);
+            if f_next * f_high <= 0.0 {
+                low = high;
+                f_low = f_high;
+            }
+            else {
+                f_low /= 2.0;
+            }
+            high = next;
+            f_high = f_next;
+        }
+        let volatility = (low / 2.0).exp();
+
+        let pre_period_phi = (phi * phi + volatility * volatility).sqrt();
+        let new_phi = 1.0 / (1.0 / (pre_period_phi * pre_period_phi) + 1.0 / variance).sqrt();
+        let new_mu = mu + new_phi * new_phi * weighted_surprise;
+        return Glicko2Rating {
+            rating: new_mu * GLICKO2_SCALE + 1500.0,
+            deviation: new_phi * GLICKO2_SCALE,
+            volatility
+        };
+    }
+}
@@ -0,0 +1,367 @@
+//! Opening repertoires for training
+//!
+//! A [Repertoire] maps positions, keyed by their [Zobrist hash](crate::Game::hash) so transpositions share their
+//! moves, to the moves the player has prepared in them and the replies they expect from the opponent. Every move has
+//! a weight, a comment and [Training] statistics that schedule it for review with the SM-2 spaced repetition
+//! algorithm. Days are counted by the caller, for example as days since the Unix epoch.
+//!
+//! A repertoire is saved as text with one line per move, see [Repertoire::to_text].
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::notation::parse_san;
+//! # use olindba_chess::repertoire::Repertoire;
+//! let mut repertoire = Repertoire::new(WHITE);
+//! let mut game = Game::starting_position();
+//! for san in ["e4", "e5", "Nf3", "Nc6", "Bb5"] {
+//!     let mv = parse_san(&game, san).unwrap();
+//!     repertoire.add_move(&game, mv);
+//!     game.make_move(mv);
+//! }
+//!
+//! // Reaching the position after 1. Nf3 e5 2. e4 Nc6 by transposition
+//! let mut game = Game::starting_position();
+//! for san in ["Nf3", "e5", "e4", "Nc6"] {
+//!     game.make_move(parse_san(&game, san).unwrap());
+//! }
+//! let moves = repertoire.moves(&game);
+//! assert_eq!(moves.len(), 1);
+//! assert_eq!(moves[0].mv, parse_san(&game, "Bb5").unwrap());
+//!
+//! // Every move of White is due for its first review
+//! assert_eq!(repertoire.due(0).len(), 3);
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::fen::{parse_fen, to_fen};
+use crate::notation::{parse_uci, to_uci, NotationError};
+use crate::{Game, Move, WHITE};
+
+/// The reason a saved repertoire couldn't be read
+#[derive(Debug)]
+pub enum RepertoireError {
+    Io(std::io::Error),
+    /// The line, counted from 1, doesn't have the expected fields
+    InvalidLine(usize),
+    /// The move on the line, counted from 1, isn't legal in its position
+    InvalidMove { line: usize, error: NotationError }
+}
+
+impl std::fmt::Display for RepertoireError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RepertoireError::Io(error) => write!(f, "failed to read the repertoire: {}", error),
+            RepertoireError::InvalidLine(line) => write!(f, "line {}: invalid repertoire entry", line),
+            RepertoireError::InvalidMove { line, error } => write!(f, "line {}: {}", line, error)
+        }
+    }
+}
+
+impl std::error::Error for RepertoireError {}
+
+impl From<std::io::Error> for RepertoireError {
+    fn from(error: std::io::Error) -> RepertoireError {
+        RepertoireError::Io(error)
+    }
+}
+
+/// The review schedule of a move, updated by the SM-2 algorithm
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Training {
+    /// The number of successful reviews in a row
+    pub repetitions: u32,
+    /// The number of times the move was forgotten
+    pub lapses: u32,
+    /// The number of days until the next review
+    pub interval: u32,
+    /// How quickly the interval grows, in percent
+    pub ease: u32,
+    /// The day the move is due for review
+    pub due: u64
+}
+
+impl Default for Training {
+    /// A move that hasn't been reviewed and is due right away
+    fn default() -> Training {
+        Training {
+            repetitions: 0,
+            lapses: 0,
+            interval: 0,
+            ease: 250,
+            due: 0
+        }
+    }
+}
+
+impl Training {
+
+    /// Records a review on the given day, graded from 0 (forgotten) to 5 (perfect recall).
+    /// Grades below 3 count as forgotten and start the schedule over.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::repertoire::Training;
+    /// let mut training = Training::default();
+    /// training.review(5, 100);
+    /// assert_eq!(training.due, 101);
+    /// training.review(4, 101);
+    /// assert_eq!(training.due, 107);
+    /// training.review(4, 107);
+    /// assert_eq!((training.interval, training.due), (16, 123));
+    ///
+    /// training.review(1, 123);
+    /// assert_eq!((training.repetitions, training.lapses, training.due), (0, 1, 124));
+    /// ```
+    pub fn review(&mut self, grade: u8, today: u64) {
+        let grade = grade.min(5) as i32;
+        if grade >= 3 {
+            self.interval = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval * self.ease + 50) / 100
+            };
+            self.repetitions += 1;
+        }
+        else {
+            self.repetitions = 0;
+            self.interval = 1;
+            self.lapses += 1;
+        }
+        let ease_change = 10 - (5 - grade) * (8 + (5 - grade) * 2);
+        self.ease = (self.ease as i32 + ease_change).max(130) as u32;
+        self.due = today + self.interval as u64;
+    }
+}
+
+/// A move of the repertoire
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RepertoireMove {
+    pub mv: Move,
+    /// How often the move should be chosen compared to the other moves in the same position
+    pub weight: u32,
+    pub comment: String,
+    pub training: Training
+}
+
+/// A position of the repertoire and its moves
+struct Node {
+    fen: String,
+    moves: Vec<RepertoireMove>
+}
+
+/// A tree of prepared moves, see the [module documentation](self)
+pub struct Repertoire {
+    color: usize,
+    /// The positions in the order they were added
+    nodes: Vec<Node>,
+    index: HashMap<u64, usize>
+}
+
+impl Repertoire {
+
+    /// Creates an empty repertoire for the player of the given color
+    pub fn new(color: usize) -> Repertoire {
+        Repertoire {
+            color,
+            nodes: vec![],
+            index: HashMap::new()
+        }
+    }
+
+    /// Returns the color of the player the repertoire is for
+    pub fn color(&self) -> usize {
+        return self.color;
+    }
+
+    /// Returns the number of moves in the repertoire
+    pub fn len(&self) -> usize {
+        return self.nodes.iter().map(|node| node.moves.len()).sum();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    /// Adds a legal move of the position with weight 1 and returns it, or returns the move if it is already there
+    pub fn add_move(&mut self, game: &Game, mv: Move) -> &mut RepertoireMove {
+        let key = game.hash();
+        let index = match self.index.get(&key) {
+            Some(&index) => index,
+            None => {
+                self.nodes.push(Node { fen: to_fen(game), moves: vec![] });
+                self.index.insert(key, self.nodes.len() - 1);
+                self.nodes.len() - 1
+            }
+        };
+        let moves = &mut self.nodes[index].moves;
+        let position = match moves.iter().position(|stored| stored.mv == mv) {
+            Some(position) => position,
+            None => {
+                moves.push(RepertoireMove { mv, weight: 1, comment: String::new(), training: Training::default() });
+                moves.len() - 1
+            }
+        };
+        return &mut moves[position];
+    }
+
+    /// Adds every move of a line of legal moves played from the position
+    pub fn add_line(&mut self, game: &Game, moves: &[Move]) {
+        let mut game = game.clone();
+        for &mv in moves {
+            self.add_move(&game, mv);
+            game.make_move(mv);
+        }
+    }
+
+    /// Returns the moves of the position in the order they were added. In positions where the player is to move
+    /// these are the prepared moves, otherwise the expected replies.
+    pub fn moves(&self, game: &Game) -> &[RepertoireMove] {
+        return match self.index.get(&game.hash()) {
+            Some(&index) => &self.nodes[index].moves,
+            None => &[]
+        };
+    }
+
+    /// Returns the stored move of the position, to change its weight, comment or training
+    pub fn get_mut(&mut self, game: &Game, mv: Move) -> Option<&mut RepertoireMove> {
+        let index = *self.index.get(&game.hash())?;
+        return self.nodes[index].moves.iter_mut().find(|stored| stored.mv == mv);
+    }
+
+    /// Removes the move from the position, returns false if it wasn't in the repertoire.
+    /// The positions after the move stay in the repertoire, since they may also be reached in other ways.
+    pub fn remove_move(&mut self, game: &Game, mv: Move) -> bool {
+        let index = match self.index.get(&game.hash()) {
+            Some(&index) => index,
+            None => return false
+        };
+        let moves = &mut self.nodes[index].moves;
+        let length = moves.len();
+        moves.retain(|stored| stored.mv != mv);
+        return moves.len() != length;
+    }
+
+    /// Returns the positions and moves of the player that are due for review on the given day, the most overdue
+    /// first
+    pub fn due(&self, today: u64) -> Vec<(Game, &RepertoireMove)> {
+        let mut due = vec![];
+        for node in self.nodes.iter() {
+            let game = match parse_fen(&node.fen) {
+                Ok(game) if game.turn == self.color => game,
+                _ => continue
+            };
+            for stored in node.moves.iter().filter(|stored| stored.training.due <= today) {
+                due.push((game.clone(), stored));
+            }
+        }
+        due.sort_by_key(|(_, stored)| stored.training.due);
+        return due;
+    }
+
+    /// Writes the repertoire as text. The first line is 'white' or 'black', followed by one line per move with
+    /// tab separated fields: the FEN of the position, the move in UCI, the weight, the repetitions, lapses, interval,
+    /// ease and due day of the training, and the comment with tabs, newlines and backslashes escaped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::parse_uci;
+    /// # use olindba_chess::repertoire::Repertoire;
+    /// let game = Game::starting_position();
+    /// let mut repertoire = Repertoire::new(BLACK);
+    /// let e4 = parse_uci(&game, "e2e4").unwrap();
+    /// let stored = repertoire.add_move(&game, e4);
+    /// stored.comment = "Most common".to_string();
+    /// stored.training.review(4, 20000);
+    ///
+    /// let text = repertoire.to_text();
+    /// assert_eq!(text, "black\nrnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\te2e4\t1\t1\t0\t1\t250\t20001\tMost common\n");
+    /// let loaded = Repertoire::from_text(&text).unwrap();
+    /// assert_eq!(loaded.moves(&game), repertoire.moves(&game));
+    /// assert!(Repertoire::from_text("white\nnot a repertoire").is_err());
+    /// assert!(Repertoire::from_text("white\n8/8/8/8 w - -\te2e4\t1\t1\t0\t1\t250\t1\tc\n").is_err());
+    /// ```
+    pub fn to_text(&self) -> String {
+        let mut text = if self.color == WHITE { "white\n" } else { "black\n" }.to_string();
+        for node in self.nodes.iter() {
+            for stored in node.moves.iter() {
+                let training = &stored.training;
+                let comment = stored.comment.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n");
+                text.push_str(&format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n", node.fen, to_uci(&stored.mv), stored.weight,
+                    training.repetitions, training.lapses, training.interval, training.ease, training.due, comment));
+            }
+        }
+        return text;
+    }
+
+    /// Reads a repertoire written by [Repertoire::to_text]
+    pub fn from_text(text: &str) -> Result<Repertoire, RepertoireError> {
+        let mut lines = text.lines();
+        let mut repertoire = match lines.next().map(str::trim) {
+            Some("white") => Repertoire::new(WHITE),
+            Some("black") => Repertoire::new(WHITE ^ 1),
+            _ => return Err(RepertoireError::InvalidLine(1))
+        };
+        for (i, line) in lines.enumerate() {
+            let line_number = i + 2;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 9 {
+                return Err(RepertoireError::InvalidLine(line_number));
+            }
+            let number = |field: &str| field.parse::<u64>().map_err(|_| RepertoireError::InvalidLine(line_number));
+            let game = parse_fen(fields[0]).map_err(|_| RepertoireError::InvalidLine(line_number))?;
+            let mv = parse_uci(&game, fields[1]).map_err(|error| RepertoireError::InvalidMove { line: line_number, error })?;
+            let weight = number(fields[2])? as u32;
+            let training = Training {
+                repetitions: number(fields[3])? as u32,
+                lapses: number(fields[4])? as u32,
+                interval: number(fields[5])? as u32,
+                ease: number(fields[6])? as u32,
+                due: number(fields[7])?
+            };
+            let stored = repertoire.add_move(&game, mv);
+            stored.weight = weight;
+            stored.training = training;
+            stored.comment = unescape(fields[8]);
+        }
+        return Ok(repertoire);
+    }
+
+    /// Saves the repertoire to a file, see [Repertoire::to_text]
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        return std::fs::write(path, self.to_text());
+    }
+
+    /// Loads a repertoire saved with [Repertoire::save]
+    pub fn load(path: impl AsRef<Path>) -> Result<Repertoire, RepertoireError> {
+        return Repertoire::from_text(&std::fs::read_to_string(path)?);
+    }
+}
+
+/// Reverses the escaping of comments done by [Repertoire::to_text]
+fn unescape(comment: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = comment.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => {}
+        }
+    }
+    return unescaped;
+}
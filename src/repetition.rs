@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use crate::Game;
+
+/// A position on the stack of a [RepetitionTracker]
+struct TrackedPosition {
+    hash: u64,
+    /// The index of the previous position with the same hash, if any
+    previous: Option<usize>,
+    /// The index of the first position after the last irreversible move
+    reversible_start: usize
+}
+
+/// Finds repetitions along a line of play in constant time, as needed inside a search. Positions are pushed as
+/// moves are made and popped as they are taken back, and [RepetitionTracker::is_repetition] answers whether the
+/// current position has occurred since the last irreversible move, including the positions of the game the
+/// tracker was created from.
+///
+/// Unlike [Game::repetition_count] this finds twofold repetitions without going through the history, which is what
+/// a search usually scores as a draw.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::notation::parse_uci;
+/// let mut game = Game::starting_position();
+/// let mut tracker = RepetitionTracker::new(&game);
+/// for mv in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+///     game.make_move(parse_uci(&game, mv).unwrap());
+///     tracker.push(&game);
+/// }
+/// assert!(tracker.is_repetition());
+/// assert_eq!(tracker.len(), 5);
+///
+/// tracker.pop();
+/// assert!(!tracker.is_repetition());
+/// ```
+pub struct RepetitionTracker {
+    positions: Vec<TrackedPosition>,
+    /// The index of the latest position with each hash
+    latest: HashMap<u64, usize>
+}
+
+impl RepetitionTracker {
+
+    /// Creates a tracker holding the positions of the game since its last irreversible move, the current one last
+    pub fn new(game: &Game) -> RepetitionTracker {
+        let mut tracker = RepetitionTracker {
+            positions: vec![],
+            latest: HashMap::new()
+        };
+        for entry in &game.history[game.last_irreversible_ply()..] {
+            tracker.push_hash(entry.hash, false);
+        }
+        tracker.push_hash(game.hash(), false);
+        return tracker;
+    }
+
+    /// Pushes the position of the game after a move was made, see [Game::last_irreversible_ply] for when the move
+    /// was irreversible
+    pub fn push(&mut self, game: &Game) {
+        self.push_hash(game.hash(), game.last_irreversible_ply() == game.ply());
+    }
+
+    /// Pushes a position by its hash, an irreversible move leading to it hides all earlier positions
+    pub fn push_hash(&mut self, hash: u64, irreversible: bool) {
+        let index = self.positions.len();
+        let reversible_start = match self.positions.last() {
+            Some(last) if !irreversible => last.reversible_start,
+            _ => index
+        };
+        let previous = self.latest.insert(hash, index);
+        self.positions.push(TrackedPosition { hash, previous, reversible_start });
+    }
+
+    /// Removes the latest position and returns its hash
+    pub fn pop(&mut self) -> Option<u64> {
+        let position = self.positions.pop()?;
+        match position.previous {
+            Some(previous) => self.latest.insert(position.hash, previous),
+            None => self.latest.remove(&position.hash)
+        };
+        return Some(position.hash);
+    }
+
+    /// Returns the number of positions on the stack
+    pub fn len(&self) -> usize {
+        return self.positions.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.positions.is_empty();
+    }
+
+    /// Returns true if the latest position has occurred before since the last irreversible move
+    pub fn is_repetition(&self) -> bool {
+        return self.positions.last()
+            .is_some_and(|last| last.previous.is_some_and(|previous| previous >= last.reversible_start));
+    }
+
+    /// Returns true if a position with the hash is on the stack since the last irreversible move
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::parse_uci;
+    /// let mut game = Game::starting_position();
+    /// let start = game.hash();
+    /// let mut tracker = RepetitionTracker::new(&game);
+    /// game.make_move(parse_uci(&game, "e2e4").unwrap());
+    /// tracker.push(&game);
+    /// assert!(tracker.contains(game.hash()));
+    /// // The pawn move can't be undone, so the starting position is out of reach
+    /// assert!(!tracker.contains(start));
+    /// ```
+    pub fn contains(&self, hash: u64) -> bool {
+        let last = match self.positions.last() {
+            Some(last) => last,
+            None => return false
+        };
+        return self.latest.get(&hash).is_some_and(|&index| index >= last.reversible_start);
+    }
+}
+
+/// The counters a GUI shows next to the board to tell how close the game is to a draw by the fifty-move rule or by
+/// repetition, see [Game::draw_counters]. The rules claims are counted against are those of [Game::rules].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DrawCounters {
+    /// The half moves since the last capture or pawn move, see [Game::halfmove_clock]
+    pub halfmove_clock: usize,
+    /// How many times the current position has occurred, see [Game::repetition_count]
+    pub repetition_count: usize,
+    /// The half moves left until the fifty-move rule can be claimed, 0 once it can
+    pub halfmoves_until_fifty_move_claim: usize,
+    /// The half moves left until the seventy-five-move rule ends the game, 0 once it has
+    pub halfmoves_until_seventy_five_move_draw: usize,
+    /// The side to move can claim a draw, by the fifty-move rule or because the position occurred three times
+    pub can_claim_draw: bool
+}
+
+impl Game {
+
+    /// Returns the counters of the draw rules for the current position, see [DrawCounters]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::Notation;
+    /// let mut game = Game::new("4k3/8/8/8/8/8/8/R3K3 w - - 90 70");
+    /// let counters = game.draw_counters();
+    /// assert_eq!(counters.halfmove_clock, 90);
+    /// assert_eq!(counters.halfmoves_until_fifty_move_claim, 10);
+    /// assert_eq!(counters.halfmoves_until_seventy_five_move_draw, 60);
+    /// assert!(!counters.can_claim_draw);
+    ///
+    /// game.apply_move_list("Ra2 Kf8 Ra1 Ke8 Ra2 Kf8 Ra1 Ke8", Notation::San).unwrap();
+    /// let counters = game.draw_counters();
+    /// assert_eq!(counters.repetition_count, 3);
+    /// assert_eq!(counters.halfmoves_until_fifty_move_claim, 2);
+    /// assert!(counters.can_claim_draw);
+    /// ```
+    pub fn draw_counters(&self) -> DrawCounters {
+        let rules = self.rules();
+        let halfmove_clock = self.halfmove_clock();
+        let repetition_count = self.repetition_count();
+        return DrawCounters {
+            halfmove_clock,
+            repetition_count,
+            halfmoves_until_fifty_move_claim: rules.claimable_draw_halfmoves.saturating_sub(halfmove_clock),
+            halfmoves_until_seventy_five_move_draw: rules.automatic_draw_halfmoves.saturating_sub(halfmove_clock),
+            can_claim_draw: repetition_count >= 3 || halfmove_clock >= rules.claimable_draw_halfmoves
+        };
+    }
+}
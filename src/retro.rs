@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use crate::{Game, MoveGenerator, Piece, PositionCache, Square, BISHOP, BLACK, EMPTY, HAS_MOVED, KING, KNIGHT, PAWN,
+            QUEEN, ROOK, WHITE};
+
+/// A move taken back, see [Game::predecessor_moves]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Unmove {
+    /// The square the piece moved from, where it stands before the move
+    pub from: Square,
+    /// The square the piece moved to, where it stands now
+    pub to: Square,
+    /// The type of the piece the move captured, which is put back, or EMPTY
+    pub uncaptured: usize,
+    /// The move was a promotion, so the piece goes back as a pawn
+    pub unpromotion: bool,
+    /// The move was an en passant capture, so the captured pawn goes back beside the square the pawn moved from
+    pub en_passant: bool
+}
+
+impl Unmove {
+
+    fn new(from: usize, to: usize, uncaptured: usize) -> Unmove {
+        Unmove {
+            from: Square::new(from),
+            to: Square::new(to),
+            uncaptured,
+            unpromotion: false,
+            en_passant: false
+        }
+    }
+
+    /// Returns the square of the piece the move captured, if it captured one
+    pub fn captured_square(&self) -> Option<Square> {
+        if self.en_passant {
+            return Some(Square::from_column_row(self.to.column(), self.from.row()));
+        }
+        if self.uncaptured != EMPTY {
+            return Some(self.to);
+        }
+        return None;
+    }
+}
+
+impl Game {
+
+    /// Returns the moves that could have led to the current position: every move of the side that just moved,
+    /// with every piece it could have captured, from which the position before the move is legal. If the position
+    /// has an en passant square, the last move can only be the double push it comes from.
+    ///
+    /// Castling is never taken back and the positions before the moves have no castling rights, so the moves
+    /// are those of the retrograde problems and endgame tablebases, where castling doesn't matter. The halfmove
+    /// clock isn't taken into account either.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::fen::to_fen;
+    /// let game = Game::new("4k3/8/8/8/8/8/8/K6R b - - 0 1");
+    /// let unmoves = game.predecessor_moves();
+    /// // The rook came from one of 11 squares, not e1 or h8 where it would have given check, and the king from one
+    /// // of 3, each having captured nothing or a piece other than a pawn, which can't stand on the back rank
+    /// assert_eq!(unmoves.len(), (11 + 3) * 5);
+    ///
+    /// let unmove = unmoves.iter().find(|unmove| unmove.from == Square::H5 && unmove.uncaptured == KNIGHT).unwrap();
+    /// assert_eq!(to_fen(&game.predecessor(unmove)), "4k3/8/8/7R/8/8/8/K6n w - - 0 1");
+    ///
+    /// let game = Game::new("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1");
+    /// assert_eq!(game.predecessor_moves().len(), 1);
+    /// ```
+    pub fn predecessor_moves(&self) -> Vec<Unmove> {
+        return self.unmoves(true);
+    }
+
+    /// Returns the predecessor moves that neither captured nor promoted, so the material before them is the same
+    pub(crate) fn quiet_predecessor_moves(&self) -> Vec<Unmove> {
+        return self.unmoves(false);
+    }
+
+    fn unmoves(&self, with_captures: bool) -> Vec<Unmove> {
+        let mover = self.turn ^ 1;
+        let mut candidates = vec![];
+
+        // A pawn that can be captured en passant has just made its double push
+        if self.possible_ep_capture < 64 {
+            let to = self.possible_ep_capture;
+            let from = (to as isize - 16 * pawn_direction(mover)) as usize;
+            candidates.push(Unmove::new(from, to, EMPTY));
+        }
+        else {
+            for to in 0..64 {
+                let piece = self.board[to];
+                if piece.get_type() == EMPTY || piece.get_color() != mover {
+                    continue;
+                }
+                if piece.get_type() == PAWN {
+                    self.pawn_unmoves(to, with_captures, &mut candidates);
+                }
+                else {
+                    self.piece_unmoves(to, with_captures, &mut candidates);
+                }
+            }
+        }
+
+        return candidates.into_iter()
+            .filter(|unmove| self.predecessor(unmove).check_invariants().is_ok())
+            .collect();
+    }
+
+    /// Returns the position before the move, with no castling rights, no history and the halfmove clock lowered
+    /// by one for a reversible move or 0 otherwise
+    pub fn predecessor(&self, unmove: &Unmove) -> Game {
+        let mover = self.turn ^ 1;
+        let from = unmove.from.index();
+        let to = unmove.to.index();
+        let mut board = self.board;
+
+        let mut moving = board[to];
+        if unmove.unpromotion {
+            moving = Piece::new(PAWN, mover, EMPTY);
+        }
+        board[from] = moving;
+        board[to] = Piece::empty();
+        if let Some(captured_square) = unmove.captured_square() {
+            let captured_type = if unmove.en_passant { PAWN } else { unmove.uncaptured };
+            board[captured_square.index()] = Piece::new(captured_type, self.turn, EMPTY);
+        }
+        // Nothing has castling rights
+        for square in 0..64 {
+            if board[square].get_type() == EMPTY {
+                board[square] = Piece::empty();
+            }
+            else {
+                board[square].set_flags(HAS_MOVED);
+            }
+        }
+
+        let mut king_square = self.king_square;
+        if moving.get_type() == KING {
+            king_square[mover] = from;
+        }
+        let mut possible_ep_capture = 64;
+        if unmove.en_passant {
+            possible_ep_capture = unmove.captured_square().unwrap().index();
+        }
+        let irreversible = moving.get_type() == PAWN || unmove.captured_square().is_some();
+        let half_move_clock = if irreversible { 0 } else { self.half_move_clock.saturating_sub(1) };
+        let mut full_move_number = self.full_move_number;
+        if mover == BLACK {
+            full_move_number = full_move_number.saturating_sub(1).max(1);
+        }
+
+        return Game {
+            board,
+            turn: mover,
+            possible_ep_capture,
+            king_square,
+            half_move_clock,
+            full_move_number,
+            history: vec![],
+            last_irreversible_ply: 0,
+            last_move: None,
+            annotations: BTreeMap::new(),
+            ending: None,
+            automatic_termination: self.automatic_termination,
+            analysis_mode: self.analysis_mode,
+            rules: self.rules,
+            eval_terms: None,
+            cache: PositionCache::new()
+        };
+    }
+
+    /// Adds the unmoves of the piece other than a pawn on the square, with captures including promotions taken back
+    fn piece_unmoves(&self, to: usize, with_captures: bool, candidates: &mut Vec<Unmove>) {
+        let square = Square::new(to);
+        let mover = self.turn ^ 1;
+        let mut uncaptured_types = uncaptured_types(square);
+        if !with_captures {
+            uncaptured_types = &uncaptured_types[..1];
+        }
+
+        // Pieces move the same way backwards, along rays that have to be empty
+        let attacks = MoveGenerator::new().attacks_from(self, to);
+        for from in 0..64 {
+            if attacks & (1 << from) == 0 || self.board[from].get_type() != EMPTY {
+                continue;
+            }
+            for &uncaptured in uncaptured_types {
+                candidates.push(Unmove::new(from, to, uncaptured));
+            }
+        }
+
+        let piece_type = self.board[to].get_type();
+        if !with_captures || square.relative_rank(mover) != 8 || !(KNIGHT..=QUEEN).contains(&piece_type) {
+            return;
+        }
+        let behind = (to as isize - 8 * pawn_direction(mover)) as usize;
+        for from in pawn_capture_origins(to, mover) {
+            if self.board[from].get_type() == EMPTY {
+                for &uncaptured in uncaptured_types.iter().skip(1) {
+                    candidates.push(Unmove { unpromotion: true, ..Unmove::new(from, to, uncaptured) });
+                }
+            }
+        }
+        if self.board[behind].get_type() == EMPTY {
+            candidates.push(Unmove { unpromotion: true, ..Unmove::new(behind, to, EMPTY) });
+        }
+    }
+
+    /// Adds the unmoves of the pawn on the square: pushes, and with captures captures and en passant captures
+    fn pawn_unmoves(&self, to: usize, with_captures: bool, candidates: &mut Vec<Unmove>) {
+        let square = Square::new(to);
+        let mover = self.turn ^ 1;
+        let direction = pawn_direction(mover);
+        let back = |steps: isize| (to as isize - 8 * steps * direction) as usize;
+
+        if square.relative_rank(mover) >= 3 && self.board[back(1)].get_type() == EMPTY {
+            candidates.push(Unmove::new(back(1), to, EMPTY));
+        }
+        if square.relative_rank(mover) == 4 && self.board[back(1)].get_type() == EMPTY
+            && self.board[back(2)].get_type() == EMPTY {
+            candidates.push(Unmove::new(back(2), to, EMPTY));
+        }
+        if !with_captures || square.relative_rank(mover) < 3 {
+            return;
+        }
+        for from in pawn_capture_origins(to, mover) {
+            if self.board[from].get_type() != EMPTY {
+                continue;
+            }
+            for &uncaptured in uncaptured_types(square).iter().skip(1) {
+                candidates.push(Unmove::new(from, to, uncaptured));
+            }
+            // The pawn captured en passant stood beside the square the capturing pawn came from, after a double
+            // push across the square the capturing pawn moved to
+            let captured = Square::from_column_row(square.column(), Square::new(from).row()).index();
+            let origin = (to as isize + 8 * direction) as usize;
+            if square.relative_rank(mover) == 6 && self.board[captured].get_type() == EMPTY
+                && self.board[origin].get_type() == EMPTY {
+                candidates.push(Unmove { en_passant: true, ..Unmove::new(from, to, EMPTY) });
+            }
+        }
+    }
+}
+
+/// Returns the direction a pawn of the color moves in, as a change of its square index per rank
+fn pawn_direction(color: usize) -> isize {
+    if color == WHITE {
+        return -1;
+    }
+    return 1;
+}
+
+/// Returns the squares a pawn of the color could have captured from to reach the square
+fn pawn_capture_origins(to: usize, color: usize) -> Vec<usize> {
+    let square = Square::new(to);
+    let row = (square.row() as isize - pawn_direction(color)) as usize;
+    let mut origins = vec![];
+    if square.column() > 0 {
+        origins.push(Square::from_column_row(square.column() - 1, row).index());
+    }
+    if square.column() < 7 {
+        origins.push(Square::from_column_row(square.column() + 1, row).index());
+    }
+    return origins;
+}
+
+/// Returns what a move to the square could have captured, EMPTY first for no capture
+fn uncaptured_types(square: Square) -> &'static [usize] {
+    if square.relative_rank(WHITE) == 1 || square.relative_rank(WHITE) == 8 {
+        return &[EMPTY, KNIGHT, BISHOP, ROOK, QUEEN];
+    }
+    return &[EMPTY, PAWN, KNIGHT, BISHOP, ROOK, QUEEN];
+}
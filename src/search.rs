@@ -0,0 +1,136 @@
+//! Scores and progress reports shared by searches
+//!
+//! A [Score] is either centipawns or the moves until mate, from the point of view of the side to move. Searches
+//! compare plain numbers where a mate in n plies scores [MATE_SCORE] minus n, and [Score::from_value] and
+//! [Score::to_value] convert between the two. A search reports a [SearchInfo] for every completed depth, which is
+//! written as an 'info' line by [crate::uci::run] and returned by [crate::engine::Engine], and
+//! [crate::uci::AnalysisInfo] reads the same scores from the output of other engines.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::search::{Score, SearchInfo, MATE_SCORE};
+//! # use olindba_chess::transposition::Bound;
+//! // Mating in three moves is five plies away
+//! let score = Score::from_value(MATE_SCORE - 5);
+//! assert_eq!(score, Score::Mate(3));
+//! assert_eq!(score.to_value(), MATE_SCORE - 5);
+//! assert!(Score::Mate(3) > Score::Centipawns(900) && Score::Centipawns(-900) > Score::Mate(-3));
+//!
+//! let info = SearchInfo { depth: 7, score: Score::Centipawns(35), bound: Bound::Lower, nodes: 4000,
+//!                         ..Default::default() };
+//! assert_eq!(info.to_string(), "info depth 7 score cp 35 lowerbound nodes 4000");
+//! ```
+
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use crate::notation::to_uci;
+use crate::transposition::Bound;
+use crate::Move;
+
+/// The value of delivering checkmate right away, a mate in n plies scores n less
+pub const MATE_SCORE: i32 = 30000;
+/// Values further than this from zero are mates
+pub(crate) const MATE_THRESHOLD: i32 = MATE_SCORE - 1000;
+
+/// The score of a position from the point of view of the side to move, ordered from being mated right away to
+/// mating right away
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Score {
+    Centipawns(i32),
+    /// Moves until mate, negative if the side to move gets mated and 0 if it is checkmated
+    Mate(i32)
+}
+
+impl Default for Score {
+    fn default() -> Score {
+        return Score::Centipawns(0);
+    }
+}
+
+impl Score {
+
+    /// Returns the score of a search value, which is a mate when it is within 1000 of [MATE_SCORE]
+    pub fn from_value(value: i32) -> Score {
+        if value > MATE_THRESHOLD {
+            return Score::Mate((MATE_SCORE - value + 1) / 2);
+        }
+        if value < -MATE_THRESHOLD {
+            return Score::Mate(-(MATE_SCORE + value) / 2);
+        }
+        return Score::Centipawns(value);
+    }
+
+    /// Returns the search value of the score, mates are taken to be as close as their number of moves allows
+    pub fn to_value(&self) -> i32 {
+        let longest_mate = MATE_SCORE - MATE_THRESHOLD - 1;
+        return match *self {
+            Score::Centipawns(centipawns) => centipawns.clamp(-MATE_THRESHOLD, MATE_THRESHOLD),
+            Score::Mate(moves) if moves > 0 => MATE_SCORE - (moves.saturating_mul(2) - 1).min(longest_mate),
+            Score::Mate(moves) => -MATE_SCORE + moves.saturating_mul(-2).min(longest_mate)
+        };
+    }
+
+    pub fn is_mate(&self) -> bool {
+        return matches!(self, Score::Mate(_));
+    }
+}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Score) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Score) -> Ordering {
+        return self.to_value().cmp(&other.to_value());
+    }
+}
+
+/// Writes the score as in UCI, 'cp 35' or 'mate -2'
+impl std::fmt::Display for Score {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Score::Centipawns(centipawns) => write!(f, "cp {}", centipawns),
+            Score::Mate(moves) => write!(f, "mate {}", moves)
+        }
+    }
+}
+
+/// Progress reported by a search, written as an 'info' line
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchInfo {
+    pub depth: usize,
+    pub score: Score,
+    /// Whether the score is exact or the search only found it to be a lower or upper bound
+    pub bound: Bound,
+    pub nodes: u64,
+    pub time: Duration,
+    /// The principal variation
+    pub pv: Vec<Move>
+}
+
+impl std::fmt::Display for SearchInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "info depth {} score {}", self.depth, self.score)?;
+        match self.bound {
+            Bound::Exact => {},
+            Bound::Lower => write!(f, " lowerbound")?,
+            Bound::Upper => write!(f, " upperbound")?
+        }
+        write!(f, " nodes {}", self.nodes)?;
+        if !self.time.is_zero() {
+            write!(f, " time {}", self.time.as_millis())?;
+        }
+        if !self.pv.is_empty() {
+            write!(f, " pv")?;
+            for mv in self.pv.iter() {
+                write!(f, " {}", to_uci(mv))?;
+            }
+        }
+        return Ok(());
+    }
+}
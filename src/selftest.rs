@@ -0,0 +1,340 @@
+//! Perft positions with known move counts and a self-test of the move generation
+//!
+//! [TEST_POSITIONS] holds well known perft positions along with edge cases for castling, en passant captures
+//! that expose the king, promotions and stalemates. The counts are the number of leaf nodes at each depth and are
+//! the same as printed by 'go perft' in Stockfish, so [perft_divide] can be compared move by move with it
+//! when a count is off.
+//!
+//! [perft_suite] lists every count as a [PerftCase] and [run_perft_case] runs one and times it, for benchmarks
+//! and tests outside the crate that want the same positions as [selftest].
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::selftest::{perft, selftest_to_depth};
+//! # use olindba_chess::Game;
+//! assert_eq!(perft(&Game::starting_position(), 3), 8902);
+//! assert_eq!(selftest_to_depth(2), Ok(()));
+//! ```
+
+use std::time::{Duration, Instant};
+
+use crate::notation::to_uci;
+use crate::{Game, Move, MoveList};
+
+/// A position with the number of leaf nodes at each depth, starting at depth 1
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TestPosition {
+    pub name: &'static str,
+    pub fen: &'static str,
+    pub perft: &'static [u64]
+}
+
+/// The positions run by [selftest]
+pub const TEST_POSITIONS: &[TestPosition] = &[
+    TestPosition {
+        name: "starting position",
+        fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        perft: &[20, 400, 8902, 197281]
+    },
+    TestPosition {
+        name: "kiwipete",
+        fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        perft: &[48, 2039, 97862]
+    },
+    TestPosition {
+        name: "rook endgame with pins",
+        fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        perft: &[14, 191, 2812, 43238, 674624]
+    },
+    TestPosition {
+        name: "promotions and castling",
+        fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        perft: &[6, 264, 9467]
+    },
+    TestPosition {
+        name: "promotion with check",
+        fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        perft: &[44, 1486, 62379]
+    },
+    TestPosition {
+        name: "middlegame",
+        fen: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        perft: &[46, 2079, 89890]
+    },
+    TestPosition {
+        name: "underpromotions",
+        fen: "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        perft: &[24, 496, 9483, 182838]
+    },
+    TestPosition {
+        name: "en passant giving discovered check",
+        fen: "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+        perft: &[15, 126, 1928, 13931, 206379]
+    },
+    TestPosition {
+        name: "en passant exposing the king",
+        fen: "8/5bk1/8/2Pp4/8/1K6/8/8 w - d6 0 1",
+        perft: &[8, 104, 736, 9287, 62297]
+    },
+    TestPosition {
+        name: "en passant exposing the king, mirrored",
+        fen: "8/8/1k6/8/2pP4/8/5BK1/8 b - d3 0 1",
+        perft: &[8, 104, 736, 9287, 62297]
+    },
+    TestPosition {
+        name: "en passant along the rank",
+        fen: "3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1",
+        perft: &[18, 92, 1670, 10138, 185429]
+    },
+    TestPosition {
+        name: "en passant by a pinned pawn",
+        fen: "8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1",
+        perft: &[13, 102, 1266, 10276, 135655]
+    },
+    TestPosition {
+        name: "castling giving check",
+        fen: "5k2/8/8/8/8/8/8/4K2R w K - 0 1",
+        perft: &[15, 66, 1198, 6399, 120330]
+    },
+    TestPosition {
+        name: "long castling giving check",
+        fen: "3k4/8/8/8/8/8/8/R3K3 w Q - 0 1",
+        perft: &[16, 71, 1286, 7418, 141077]
+    },
+    TestPosition {
+        name: "castling rights lost by capture",
+        fen: "r3k2r/1b4bq/8/8/8/8/7B/R3K2R w KQkq - 0 1",
+        perft: &[26, 1141, 27826]
+    },
+    TestPosition {
+        name: "castling prevented",
+        fen: "r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1",
+        perft: &[44, 1494, 50509]
+    },
+    TestPosition {
+        name: "promotion out of check",
+        fen: "2K2r2/4P3/8/8/8/8/8/3k4 w - - 0 1",
+        perft: &[11, 133, 1442, 19174, 266199]
+    },
+    TestPosition {
+        name: "discovered check",
+        fen: "8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1",
+        perft: &[29, 165, 5160, 31961]
+    },
+    TestPosition {
+        name: "promotion giving check",
+        fen: "4k3/1P6/8/8/8/8/K7/8 w - - 0 1",
+        perft: &[9, 40, 472, 2661, 38983]
+    },
+    TestPosition {
+        name: "underpromotion giving check",
+        fen: "8/P1k5/K7/8/8/8/8/8 w - - 0 1",
+        perft: &[6, 27, 273, 1329, 18135]
+    },
+    TestPosition {
+        name: "self stalemate",
+        fen: "K1k5/8/P7/8/8/8/8/8 w - - 0 1",
+        perft: &[2, 6, 13, 63, 382, 2217]
+    },
+    TestPosition {
+        name: "stalemate and checkmate",
+        fen: "8/k1P5/8/1K6/8/8/8/8 w - - 0 1",
+        perft: &[10, 25, 268, 926, 10857, 43261]
+    },
+    TestPosition {
+        name: "stalemate and checkmate with a knight",
+        fen: "8/8/2k5/5q2/5n2/8/5K2/8 b - - 0 1",
+        perft: &[37, 183, 6559, 23527]
+    },
+    TestPosition {
+        name: "stalemate",
+        fen: "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+        perft: &[0]
+    },
+    TestPosition {
+        name: "Chess960",
+        fen: "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        perft: &[21, 528, 12189]
+    },
+    TestPosition {
+        name: "Chess960 with the rooks on the e and h files",
+        fen: "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+        perft: &[21, 807, 18002]
+    },
+    TestPosition {
+        name: "Chess960 with the king off its rank",
+        fen: "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9",
+        perft: &[22, 593, 13440]
+    },
+    TestPosition {
+        name: "Chess960 castling with the king and rook on their destinations",
+        fen: "1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1",
+        perft: &[24, 479, 10954]
+    },
+    TestPosition {
+        name: "Chess960 castling blocked by the own rook",
+        fen: "4k3/8/8/8/8/8/8/rRK4R w B - 0 1",
+        perft: &[16, 174, 3817]
+    }
+];
+
+/// A count of [TEST_POSITIONS] that the move generation got wrong
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Discrepancy {
+    pub name: &'static str,
+    pub fen: &'static str,
+    pub depth: usize,
+    pub expected: u64,
+    pub found: u64
+}
+
+impl std::fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} ({}): perft {} is {}, expected {}", self.name, self.fen, self.depth, self.found, self.expected)
+    }
+}
+
+/// A position of [TEST_POSITIONS] with the number of leaf nodes at one depth
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PerftCase {
+    pub name: &'static str,
+    pub fen: &'static str,
+    pub depth: usize,
+    pub nodes: u64
+}
+
+/// The outcome of running a [PerftCase]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PerftRun {
+    pub case: PerftCase,
+    /// The number of leaf nodes counted
+    pub found: u64,
+    pub elapsed: Duration
+}
+
+impl PerftRun {
+
+    /// Returns true if the count matched the expected one
+    pub fn passed(&self) -> bool {
+        return self.found == self.case.nodes;
+    }
+
+    /// Returns the leaf nodes counted per second
+    pub fn nodes_per_second(&self) -> f64 {
+        return self.found as f64 / self.elapsed.as_secs_f64().max(1e-9);
+    }
+}
+
+/// Returns every count of [TEST_POSITIONS], position by position and from the shallowest depth. Among them are
+/// the starting position, Kiwipete and positions 3 to 6 of the Chess Programming Wiki, under descriptive names.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::selftest::{perft_suite, run_perft_case};
+/// let suite = perft_suite();
+/// let kiwipete: Vec<_> = suite.iter().filter(|case| case.name == "kiwipete").collect();
+/// assert_eq!(kiwipete.iter().map(|case| case.nodes).collect::<Vec<_>>(), [48, 2039, 97862]);
+///
+/// let run = run_perft_case(kiwipete[1]);
+/// assert!(run.passed());
+/// assert_eq!(run.found, 2039);
+/// assert!(run.nodes_per_second() > 0.0);
+/// ```
+pub fn perft_suite() -> Vec<PerftCase> {
+    let mut cases = vec![];
+    for position in TEST_POSITIONS {
+        for (i, &nodes) in position.perft.iter().enumerate() {
+            cases.push(PerftCase { name: position.name, fen: position.fen, depth: i + 1, nodes });
+        }
+    }
+    return cases;
+}
+
+/// Counts the leaf nodes of the case and times the count
+pub fn run_perft_case(case: &PerftCase) -> PerftRun {
+    let game = Game::new(case.fen);
+    let start = Instant::now();
+    let found = perft(&game, case.depth);
+    return PerftRun { case: *case, found, elapsed: start.elapsed() };
+}
+
+/// Returns the number of leaf nodes of the tree of legal moves of the given depth
+pub fn perft(game: &Game, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if depth == 1 {
+        return game.count_legal_moves() as u64;
+    }
+    let mut moves = MoveList::new();
+    game.legal_moves_into(&mut moves);
+    let mut nodes = 0;
+    for &mv in moves.iter() {
+        let mut child = game.clone();
+        child.make_move(mv);
+        nodes += perft(&child, depth - 1);
+    }
+    return nodes;
+}
+
+/// Returns the perft of the given depth below each legal move, like 'go perft' in Stockfish
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::selftest::perft_divide;
+/// let divide = perft_divide(&Game::starting_position(), 2);
+/// assert_eq!(divide.len(), 20);
+/// assert!(divide.iter().all(|(_, nodes)| *nodes == 20));
+/// ```
+pub fn perft_divide(game: &Game, depth: usize) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return vec![];
+    }
+    return game.get_all_legal_moves().into_iter().map(|mv| {
+        let mut child = game.clone();
+        child.make_move(mv);
+        return (mv, perft(&child, depth - 1));
+    }).collect();
+}
+
+/// Formats the result of [perft_divide] the way Stockfish prints it, one 'move: nodes' line per move
+/// followed by the total
+pub fn format_divide(divide: &[(Move, u64)]) -> String {
+    let mut text = String::new();
+    for (mv, nodes) in divide {
+        text.push_str(&format!("{}: {}\n", to_uci(mv), nodes));
+    }
+    text.push_str(&format!("\nNodes searched: {}\n", divide.iter().map(|(_, nodes)| nodes).sum::<u64>()));
+    return text;
+}
+
+/// Runs every position of [TEST_POSITIONS] at every depth and returns the counts that are wrong.
+/// Takes around a second in a release build.
+pub fn selftest() -> Result<(), Vec<Discrepancy>> {
+    return selftest_to_depth(usize::MAX);
+}
+
+/// Same as [selftest] but only up to the given depth, for a quicker check
+pub fn selftest_to_depth(max_depth: usize) -> Result<(), Vec<Discrepancy>> {
+    let mut discrepancies = vec![];
+    for case in perft_suite().iter().filter(|case| case.depth <= max_depth) {
+        let run = run_perft_case(case);
+        if !run.passed() {
+            discrepancies.push(Discrepancy {
+                name: case.name,
+                fen: case.fen,
+                depth: case.depth,
+                expected: case.nodes,
+                found: run.found
+            });
+        }
+    }
+    if discrepancies.is_empty() {
+        return Ok(());
+    }
+    return Err(discrepancies);
+}
@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+
+use crate::{Game, GameResult, InvariantError, Move, Piece, PositionCache, PromotionSet, Rules, Square,
+            StalemateOutcome, Termination, TimeoutRule, BLACK, EMPTY, KING, WHITE};
+
+const MAGIC: &[u8; 4] = b"OLCG";
+/// The version written by [Game::to_bytes], older versions are still read by [Game::from_bytes]
+const FORMAT_VERSION: u16 = 1;
+const HEADER_LEN: usize = 97;
+const NO_EP_CAPTURE: u8 = 0xff;
+/// The results of an ending, written as their index plus one so 0 means the game wasn't ended
+const RESULTS: [GameResult; 3] = [GameResult::WhiteWins, GameResult::BlackWins, GameResult::Draw];
+const TERMINATIONS: [Termination; 6] = [Termination::Normal, Termination::Resignation, Termination::TimeForfeit,
+                                        Termination::Abandoned, Termination::Adjudication,
+                                        Termination::RulesInfraction];
+
+/// Why [Game::from_bytes] couldn't read a game
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes don't start with the magic number of the format
+    Magic,
+    /// The format version is newer than this version of the crate
+    UnsupportedVersion(u16),
+    /// The bytes end before the game does, or continue after it
+    Length { expected: usize, actual: usize },
+    /// The byte of the square isn't a piece
+    InvalidPiece(Square),
+    /// A field other than the board has a value it can't have
+    InvalidField(&'static str),
+    /// The starting position is illegal, see [Game::check_invariants]
+    Invariant(InvariantError),
+    /// The move of the ply, counted from 0, isn't legal in the position it is made in
+    IllegalMove(usize)
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DecodeError::Magic => write!(f, "not a serialized game"),
+            DecodeError::UnsupportedVersion(version) => write!(f, "unsupported format version {}", version),
+            DecodeError::Length { expected, actual } => write!(f, "expected {} bytes, found {}", expected, actual),
+            DecodeError::InvalidPiece(square) => write!(f, "invalid piece on {}", square),
+            DecodeError::InvalidField(field) => write!(f, "invalid {}", field),
+            DecodeError::Invariant(error) => write!(f, "{}", error),
+            DecodeError::IllegalMove(ply) => write!(f, "illegal move at ply {}", ply)
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<InvariantError> for DecodeError {
+    fn from(error: InvariantError) -> DecodeError {
+        return DecodeError::Invariant(error);
+    }
+}
+
+/// Reads the little endian fields of the header
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize
+}
+
+impl Reader<'_> {
+
+    fn u8(&mut self) -> u8 {
+        self.offset += 1;
+        return self.bytes[self.offset - 1];
+    }
+
+    fn u16(&mut self) -> u16 {
+        self.offset += 2;
+        return u16::from_le_bytes([self.bytes[self.offset - 2], self.bytes[self.offset - 1]]);
+    }
+
+    fn u32(&mut self) -> u32 {
+        let mut field = [0; 4];
+        field.copy_from_slice(&self.bytes[self.offset..self.offset + 4]);
+        self.offset += 4;
+        return u32::from_le_bytes(field);
+    }
+
+    fn flag(&mut self, name: &'static str) -> Result<bool, DecodeError> {
+        return match self.u8() {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(DecodeError::InvalidField(name))
+        };
+    }
+}
+
+impl Game {
+
+    /// Returns the game in a versioned binary format meant for saving games, which later versions of the crate
+    /// keep reading with [Game::from_bytes]. Unlike FEN it keeps the moves of [Game::history], so repetitions
+    /// before the current position still count after loading, as well as the rules and settings of the game.
+    ///
+    /// All numbers are little endian. Version 1 is laid out as:
+    /// * bytes 0-3: the magic number "OLCG"
+    /// * bytes 4-5: the format version
+    /// * bytes 6-69: the position before the first move of the history, one byte per square from a8 to h1 with
+    ///   the piece type in bits 0-2, the color in bit 3 and in bit 4 whether the piece has moved, which decides
+    ///   the castling rights
+    /// * byte 70: the side to move, byte 71: the square of the pawn that can be captured en passant or 255
+    /// * bytes 72-75 and 76-79: the halfmove clock and the fullmove number
+    /// * bytes 80-81: automatic termination and analysis mode, as 0 or 1
+    /// * bytes 82-85 and 86-89: the claimable and automatic draw halfmoves of [Rules], byte 90: the stalemate
+    ///   outcome (draw, win, loss), byte 91: the promotion pieces with bit n for piece type n, byte 92: the timeout
+    ///   rule (FIDE, USCF), byte 93: mandatory capture
+    /// * byte 94: the result the game was [ended](Game::end) with off the board (none, 1-0, 0-1, 1/2-1/2), byte 95:
+    ///   the reason (normal, resignation, time forfeit, abandoned, adjudication, rules infraction)
+    /// * byte 96: reserved, 0
+    /// * then the number of moves as 4 bytes, followed by the moves of the history as [Move::to_u16]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::fen::to_fen;
+    /// # use olindba_chess::notation::Notation;
+    /// let mut game = Game::starting_position();
+    /// game.apply_move_list("Nf3 Nf6 Ng1 Ng8 Nf3 Nf6 Ng1", Notation::San).unwrap();
+    /// let bytes = game.to_bytes();
+    /// assert_eq!(&bytes[..6], b"OLCG\x01\x00");
+    /// assert_eq!(bytes.len(), 97 + 4 + 7 * 2);
+    ///
+    /// let mut loaded = Game::from_bytes(&bytes).unwrap();
+    /// assert_eq!(to_fen(&loaded), to_fen(&game));
+    /// assert_eq!(loaded.history(), game.history());
+    /// // The earlier occurrences of the position still count
+    /// loaded.apply_move_list("Ng8", Notation::San).unwrap();
+    /// assert_eq!(loaded.repetition_count(), 3);
+    /// loaded.resign(WHITE);
+    /// let ending = Game::from_bytes(&loaded.to_bytes()).unwrap().ending();
+    /// assert_eq!(ending, Some((GameResult::BlackWins, Termination::Resignation)));
+    ///
+    /// assert_eq!(Game::from_bytes(&bytes[..110]).err(), Some(DecodeError::Length { expected: 115, actual: 110 }));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut start = self.clone();
+        while start.unmake_move().is_some() {}
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + 4 + 2 * self.history.len());
+        bytes.extend(MAGIC);
+        bytes.extend(FORMAT_VERSION.to_le_bytes());
+        for square in 0..64 {
+            let piece = start.board[square];
+            if piece.get_type() == EMPTY {
+                bytes.push(0);
+            }
+            else {
+                bytes.push((piece.get_type() | piece.get_color() << 3 | piece.get_flags() << 4) as u8);
+            }
+        }
+        bytes.push(start.turn as u8);
+        bytes.push(if start.possible_ep_capture < 64 { start.possible_ep_capture as u8 } else { NO_EP_CAPTURE });
+        bytes.extend((start.half_move_clock as u32).to_le_bytes());
+        bytes.extend((start.full_move_number as u32).to_le_bytes());
+        bytes.push(self.automatic_termination as u8);
+        bytes.push(self.analysis_mode as u8);
+
+        let rules = self.rules;
+        bytes.extend((rules.claimable_draw_halfmoves as u32).to_le_bytes());
+        bytes.extend((rules.automatic_draw_halfmoves as u32).to_le_bytes());
+        bytes.push(match rules.stalemate {
+            StalemateOutcome::Draw => 0,
+            StalemateOutcome::Win => 1,
+            StalemateOutcome::Loss => 2
+        });
+        bytes.push(rules.promotions.pieces);
+        bytes.push(match rules.timeout {
+            TimeoutRule::Fide => 0,
+            TimeoutRule::Uscf => 1
+        });
+        bytes.push(rules.mandatory_capture as u8);
+        let (result, termination) = match self.ending {
+            Some((result, termination)) => (RESULTS.iter().position(|&r| r == result).unwrap() as u8 + 1,
+                                            TERMINATIONS.iter().position(|&t| t == termination).unwrap() as u8),
+            None => (0, 0)
+        };
+        bytes.extend([result, termination, 0]);
+
+        bytes.extend((self.history.len() as u32).to_le_bytes());
+        for entry in self.history.iter() {
+            bytes.extend(entry.mv.to_u16().to_le_bytes());
+        }
+        return bytes;
+    }
+
+    /// Reads a game written by [Game::to_bytes] in this or an earlier version of the crate. The moves are made
+    /// again, so the history is rebuilt exactly and every move is checked to be legal.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Game, DecodeError> {
+        if bytes.len() < 6 || &bytes[..4] != MAGIC {
+            return Err(DecodeError::Magic);
+        }
+        let mut reader = Reader { bytes, offset: 4 };
+        let version = reader.u16();
+        if version > FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        if bytes.len() < HEADER_LEN + 4 {
+            return Err(DecodeError::Length { expected: HEADER_LEN + 4, actual: bytes.len() });
+        }
+
+        let mut board = [Piece::empty(); 64];
+        let mut king_square = [64; 2];
+        for square in 0..64 {
+            let byte = reader.u8() as usize;
+            let piece_type = byte & 0x07;
+            if byte == 0 {
+                continue;
+            }
+            if byte >> 5 != 0 || piece_type == EMPTY || piece_type > KING {
+                return Err(DecodeError::InvalidPiece(Square::new(square)));
+            }
+            board[square] = Piece::new(piece_type, (byte >> 3) & 1, byte >> 4);
+            if piece_type == KING {
+                king_square[(byte >> 3) & 1] = square;
+            }
+        }
+        let turn = reader.u8() as usize;
+        if turn != WHITE && turn != BLACK {
+            return Err(DecodeError::Invariant(InvariantError::InvalidTurn(turn)));
+        }
+        let possible_ep_capture = match reader.u8() {
+            NO_EP_CAPTURE => 64,
+            square if square < 64 => square as usize,
+            _ => return Err(DecodeError::InvalidField("en passant square"))
+        };
+        let half_move_clock = reader.u32() as usize;
+        let full_move_number = reader.u32() as usize;
+        let automatic_termination = reader.flag("automatic termination")?;
+        let analysis_mode = reader.flag("analysis mode")?;
+
+        let claimable_draw_halfmoves = reader.u32() as usize;
+        let automatic_draw_halfmoves = reader.u32() as usize;
+        let stalemate = match reader.u8() {
+            0 => StalemateOutcome::Draw,
+            1 => StalemateOutcome::Win,
+            2 => StalemateOutcome::Loss,
+            _ => return Err(DecodeError::InvalidField("stalemate outcome"))
+        };
+        let promotions = reader.u8();
+        if promotions & !PromotionSet::WITH_KING.pieces != 0 {
+            return Err(DecodeError::InvalidField("promotion pieces"));
+        }
+        let timeout = match reader.u8() {
+            0 => TimeoutRule::Fide,
+            1 => TimeoutRule::Uscf,
+            _ => return Err(DecodeError::InvalidField("timeout rule"))
+        };
+        let mandatory_capture = reader.flag("mandatory capture")?;
+        let ending = match (reader.u8() as usize, reader.u8() as usize) {
+            (0, _) => None,
+            (result, termination) if result <= RESULTS.len() && termination < TERMINATIONS.len() => {
+                Some((RESULTS[result - 1], TERMINATIONS[termination]))
+            },
+            _ => return Err(DecodeError::InvalidField("ending"))
+        };
+        reader.offset = HEADER_LEN;
+
+        let n_moves = reader.u32() as usize;
+        let expected = HEADER_LEN + 4 + 2 * n_moves;
+        if bytes.len() != expected {
+            return Err(DecodeError::Length { expected, actual: bytes.len() });
+        }
+
+        let mut game = Game {
+            board,
+            turn,
+            possible_ep_capture,
+            king_square,
+            half_move_clock,
+            full_move_number,
+            history: vec![],
+            last_irreversible_ply: 0,
+            last_move: None,
+            annotations: BTreeMap::new(),
+            ending: None,
+            automatic_termination,
+            analysis_mode,
+            rules: Rules {
+                claimable_draw_halfmoves,
+                automatic_draw_halfmoves,
+                stalemate,
+                promotions: PromotionSet { pieces: promotions },
+                timeout,
+                mandatory_capture
+            },
+            eval_terms: None,
+            cache: PositionCache::new()
+        };
+        game.check_invariants()?;
+
+        for ply in 0..n_moves {
+            let mv = Move::from_u16(reader.u16()).ok_or(DecodeError::IllegalMove(ply))?;
+            if !game.legal_moves_cached().contains(&mv) {
+                return Err(DecodeError::IllegalMove(ply));
+            }
+            game.make_move(mv);
+        }
+        game.ending = ending;
+        return Ok(game);
+    }
+}
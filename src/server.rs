@@ -0,0 +1,309 @@
+//! A reference server hosting games between two clients over TCP
+//!
+//! The [GameServer] accepts two connections, the first plays White and the second Black, and relays their moves.
+//! Messages are JSON objects, one per line, with their kind in the 'type' field and their other fields in any order.
+//! The server checks every move, runs the clocks and appends finished games to a PGN file. The protocol runs over
+//! plain TCP, clients in a browser need a proxy that forwards WebSocket messages as lines.
+//!
+//! Clients send:
+//! * `{"type":"move","move":"e2e4"}`, a move in UCI notation
+//! * `{"type":"resign"}`
+//!
+//! The server sends:
+//! * `{"type":"welcome","color":"white"}` when a client connects
+//! * a snapshot to both clients when the game starts and after every move, see [snapshot_message]
+//! * `{"type":"error","message":"..."}` to a client whose message was rejected, like an illegal move or a move out of
+//!   turn
+//! * `{"type":"game_over","result":"0-1","termination":"normal","pgn":"..."}` to both clients when the game ends
+//!
+//! A client that disconnects loses the game by abandonment, and one whose time runs out loses on time unless the
+//! opponent can't mate, see [Game::timeout_result].
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::server::GameServer;
+//! # use std::io::{BufRead, BufReader, Write};
+//! # use std::net::TcpStream;
+//! let mut server = GameServer::bind("127.0.0.1:0").unwrap();
+//! let address = server.local_addr().unwrap();
+//! let host = std::thread::spawn(move || server.host_game().unwrap());
+//!
+//! let white = TcpStream::connect(address).unwrap();
+//! let mut white_messages = BufReader::new(white.try_clone().unwrap()).lines();
+//! assert_eq!(white_messages.next().unwrap().unwrap(), r#"{"color":"white","type":"welcome"}"#);
+//! let black = TcpStream::connect(address).unwrap();
+//! let mut black_messages = BufReader::new(black.try_clone().unwrap()).lines().skip(1);
+//!
+//! // Both clients get a snapshot at the start and after every move
+//! let mut next_snapshots = || (white_messages.next().unwrap().unwrap(), black_messages.next().unwrap().unwrap());
+//! let (snapshot, _) = next_snapshots();
+//! assert!(snapshot.contains(r#""turn":"white""#));
+//! for (mut client, mv) in [(&white, "f2f3"), (&black, "e7e5"), (&white, "g2g4"), (&black, "d8h4")] {
+//!     writeln!(client, r#"{{"type":"move","move":"{}"}}"#, mv).unwrap();
+//!     let (snapshot, _) = next_snapshots();
+//!     assert!(snapshot.contains(&format!(r#""last_move":"{}""#, mv)));
+//! }
+//! let (game_over, _) = next_snapshots();
+//! assert!(game_over.contains(r#""result":"0-1""#));
+//!
+//! let game = host.join().unwrap();
+//! assert_eq!(game.result, "0-1");
+//! assert_eq!(game.moves.len(), 4);
+//! ```
+
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::clock::{Clock, DelayKind, TimeControl};
+use crate::fen::to_fen;
+use crate::notation::{parse_uci, to_uci};
+use crate::pgn::PgnGame;
+use crate::{Game, GameResult, Termination, BLACK, WHITE};
+
+/// A message sent by a client
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClientMessage {
+    /// A move in UCI notation
+    Move(String),
+    Resign
+}
+
+impl ClientMessage {
+
+    /// Reads a message from a line of JSON, returns None if it isn't a message of the protocol
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::server::ClientMessage;
+    /// let message = ClientMessage::parse(r#"{"type":"move","move":"e2e4"}"#);
+    /// assert_eq!(message, Some(ClientMessage::Move("e2e4".to_string())));
+    /// assert_eq!(ClientMessage::parse(r#"{"type":"resign"}"#), Some(ClientMessage::Resign));
+    /// assert_eq!(ClientMessage::parse(r#"{"type":"move"}"#), None);
+    /// ```
+    pub fn parse(line: &str) -> Option<ClientMessage> {
+        let value: Value = serde_json::from_str(line).ok()?;
+        return match value["type"].as_str()? {
+            "move" => Some(ClientMessage::Move(value["move"].as_str()?.to_string())),
+            "resign" => Some(ClientMessage::Resign),
+            _ => None
+        };
+    }
+}
+
+/// Returns the snapshot the server sends after every move: the position as FEN, the side to move, the last move and
+/// the legal moves in UCI notation, the state of the game and the milliseconds left on the clocks, if there are
+/// clocks. For example `{"type":"snapshot","fen":"...","turn":"black","last_move":"e2e4","legal_moves":["a7a6",...],
+/// "state":"in progress","clock":{"white":299500,"black":300000}}`, though the fields may come in any order.
+pub fn snapshot_message(game: &Game, clock: Option<&Clock>) -> String {
+    let legal_moves: Vec<String> = game.legal_moves_cached().iter().map(to_uci).collect();
+    let clock = clock.map(|clock| json!({
+        "white": clock.remaining(WHITE).as_millis() as u64,
+        "black": clock.remaining(BLACK).as_millis() as u64
+    }));
+    return json!({
+        "type": "snapshot",
+        "fen": to_fen(game),
+        "turn": color_name(game.turn),
+        "last_move": game.last_move().map(|mv| to_uci(&mv)),
+        "legal_moves": legal_moves,
+        "state": game.get_game_state().to_string(),
+        "clock": clock
+    }).to_string();
+}
+
+fn color_name(color: usize) -> &'static str {
+    if color == WHITE {
+        return "white";
+    }
+    return "black";
+}
+
+/// What the threads reading from the clients pass on, a line or None when the connection closed
+type ClientLine = (usize, Option<String>);
+
+/// Hosts games between two clients, see the [module documentation](self)
+pub struct GameServer {
+    listener: TcpListener,
+    control: Option<TimeControl>,
+    pgn_path: Option<PathBuf>,
+    event: String
+}
+
+impl GameServer {
+
+    /// Creates a server listening on the address, without clocks and without saving games
+    pub fn bind(address: impl ToSocketAddrs) -> std::io::Result<GameServer> {
+        return Ok(GameServer {
+            listener: TcpListener::bind(address)?,
+            control: None,
+            pgn_path: None,
+            event: "?".to_string()
+        });
+    }
+
+    /// Returns the address the server listens on, which tells the port when bound to port 0
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        return self.listener.local_addr();
+    }
+
+    /// Plays the following games with clocks
+    pub fn set_time_control(&mut self, control: TimeControl) {
+        self.control = Some(control);
+    }
+
+    /// Appends every finished game to the file in PGN, creating it if needed
+    pub fn set_pgn_path(&mut self, path: impl Into<PathBuf>) {
+        self.pgn_path = Some(path.into());
+    }
+
+    /// Sets the Event tag of the saved games
+    pub fn set_event(&mut self, event: &str) {
+        self.event = event.to_string();
+    }
+
+    /// Waits for two clients and plays a game between them from the starting position. Returns the game once it is
+    /// over, after sending it to the clients and saving it. Fails if accepting a client or saving the game fails,
+    /// a client failing during the game loses it instead.
+    pub fn host_game(&mut self) -> std::io::Result<PgnGame> {
+        let mut clients = vec![];
+        let mut addresses = vec![];
+        for color in [WHITE, BLACK] {
+            let (mut stream, address) = self.listener.accept()?;
+            send(&mut stream, &json!({ "type": "welcome", "color": color_name(color) }).to_string());
+            clients.push(stream);
+            addresses.push(address.to_string());
+        }
+
+        let (sender, receiver) = mpsc::channel::<ClientLine>();
+        for (color, stream) in clients.iter().enumerate() {
+            let reader = BufReader::new(stream.try_clone()?);
+            let sender = sender.clone();
+            std::thread::spawn(move || {
+                for line in reader.lines() {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(_) => break
+                    };
+                    if !line.trim().is_empty() && sender.send((color, Some(line))).is_err() {
+                        return;
+                    }
+                }
+                let _ = sender.send((color, None));
+            });
+        }
+
+        let mut game = Game::starting_position();
+        let mut clock = self.control.clone().map(Clock::new);
+        let mut move_start = Instant::now();
+        broadcast(&mut clients, &snapshot_message(&game, clock.as_ref()));
+        while game.ending().is_none() && !game.is_game_over() {
+            let received = match &clock {
+                Some(clock) => receiver.recv_timeout(time_left(clock, game.turn, move_start)),
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            };
+            let (color, line) = match received {
+                Ok(received) => received,
+                Err(RecvTimeoutError::Timeout) => {
+                    clock.as_mut().unwrap().record_move(game.turn, move_start.elapsed());
+                    game.end(game.timeout_result(game.turn), Termination::TimeForfeit);
+                    break;
+                },
+                Err(RecvTimeoutError::Disconnected) => break
+            };
+            let line = match line {
+                Some(line) => line,
+                None => {
+                    game.end(GameResult::win_for(color ^ 1), Termination::Abandoned);
+                    break;
+                }
+            };
+
+            match ClientMessage::parse(&line) {
+                Some(ClientMessage::Resign) => game.resign(color),
+                Some(ClientMessage::Move(_)) if color != game.turn => {
+                    send_error(&mut clients[color], "it is the other side's turn");
+                },
+                Some(ClientMessage::Move(uci)) => {
+                    let mv = match parse_uci(&game, &uci) {
+                        Ok(mv) => mv,
+                        Err(error) => {
+                            send_error(&mut clients[color], &error.to_string());
+                            continue;
+                        }
+                    };
+                    if let Some(clock) = clock.as_mut() {
+                        if !clock.record_move(color, move_start.elapsed()) {
+                            game.end(game.timeout_result(color), Termination::TimeForfeit);
+                            break;
+                        }
+                    }
+                    game.make_move(mv);
+                    move_start = Instant::now();
+                    broadcast(&mut clients, &snapshot_message(&game, clock.as_ref()));
+                },
+                None => send_error(&mut clients[color], "not a message of the protocol")
+            }
+        }
+
+        let mut pgn = PgnGame::from(&game);
+        let termination = game.termination().unwrap_or(Termination::Normal);
+        let mut headers = vec![
+            ("Event".to_string(), self.event.clone()),
+            ("White".to_string(), addresses[WHITE].clone()),
+            ("Black".to_string(), addresses[BLACK].clone())
+        ];
+        if let Some(control) = &self.control {
+            headers.push(("TimeControl".to_string(), control.to_string()));
+        }
+        headers.append(&mut pgn.headers);
+        pgn.headers = headers;
+
+        broadcast(&mut clients, &json!({
+            "type": "game_over",
+            "result": pgn.result,
+            "termination": termination.to_string(),
+            "pgn": pgn.to_string()
+        }).to_string());
+        for client in clients.iter() {
+            let _ = client.shutdown(std::net::Shutdown::Both);
+        }
+        if let Some(path) = &self.pgn_path {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", pgn)?;
+        }
+        return Ok(pgn);
+    }
+}
+
+/// Returns how long the side to move may still think before its time runs out, including a simple delay
+fn time_left(clock: &Clock, color: usize, move_start: Instant) -> Duration {
+    let mut available = clock.remaining(color);
+    if clock.time_control().delay_kind == DelayKind::Simple {
+        available += clock.period(color).delay;
+    }
+    // Bronstein delays are given back after the move, so they don't lengthen the time the move may take
+    return available.saturating_sub(move_start.elapsed());
+}
+
+/// Sends a line to a client, a client that can't be written to notices when its connection closes
+fn send(client: &mut TcpStream, message: &str) {
+    let _ = writeln!(client, "{}", message);
+}
+
+fn send_error(client: &mut TcpStream, message: &str) {
+    send(client, &json!({ "type": "error", "message": message }).to_string());
+}
+
+fn broadcast(clients: &mut [TcpStream], message: &str) {
+    for client in clients.iter_mut() {
+        send(client, message);
+    }
+}
@@ -0,0 +1,111 @@
+//! Conversions to and from the types of the [shakmaty](https://docs.rs/shakmaty) crate
+
+use shakmaty::fen::Fen;
+use shakmaty::{CastlingMode, Chess, EnPassantMode, PositionError, Role};
+
+use crate::fen::to_fen;
+use crate::notation::parse_uci;
+use crate::{Game, Move, Square, BISHOP, KNIGHT, PAWN, QUEEN, ROOK};
+
+impl From<Square> for shakmaty::Square {
+    fn from(square: Square) -> shakmaty::Square {
+        return shakmaty::Square::new(square.to_a1_indexing() as u32);
+    }
+}
+
+impl From<shakmaty::Square> for Square {
+    fn from(square: shakmaty::Square) -> Square {
+        return Square::from_a1_indexing(u32::from(square) as usize);
+    }
+}
+
+/// Converts the position, with the castling rights read as in Chess960 so that any castling rook is kept
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// use shakmaty::{Chess, Position};
+/// let game = Game::new("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+/// let position = Chess::try_from(&game).unwrap();
+/// assert_eq!(position.legal_moves().len(), game.count_legal_moves());
+/// assert!(game.get_all_legal_moves().iter().all(|mv| position.is_legal(game.to_shakmaty_move(mv))));
+/// assert_eq!(Game::from(&position).to_string(), game.to_string());
+/// ```
+impl TryFrom<&Game> for Chess {
+    type Error = PositionError<Chess>;
+
+    fn try_from(game: &Game) -> Result<Chess, PositionError<Chess>> {
+        let fen: Fen = to_fen(game).parse().expect("the crate writes valid FEN strings");
+        return fen.into_position(CastlingMode::Chess960);
+    }
+}
+
+impl From<&Chess> for Game {
+    fn from(position: &Chess) -> Game {
+        return Game::new(&Fen::from_position(position, EnPassantMode::Legal).to_string());
+    }
+}
+
+fn role(piece_type: usize) -> Role {
+    return match piece_type {
+        PAWN => Role::Pawn,
+        KNIGHT => Role::Knight,
+        BISHOP => Role::Bishop,
+        ROOK => Role::Rook,
+        QUEEN => Role::Queen,
+        _ => Role::King
+    };
+}
+
+impl Game {
+
+    /// Returns the legal move of the game as a move of shakmaty
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::new("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+    /// for mv in game.get_all_legal_moves() {
+    ///     assert_eq!(game.from_shakmaty_move(&game.to_shakmaty_move(&mv)), Some(mv));
+    /// }
+    /// let castle = game.get_all_legal_moves().into_iter().find(|mv| mv.is_castle()).unwrap();
+    /// assert_eq!(game.to_shakmaty_move(&castle).to(), shakmaty::Square::H1);
+    /// ```
+    pub fn to_shakmaty_move(&self, mv: &Move) -> shakmaty::Move {
+        let from = shakmaty::Square::from(mv.from_square());
+        let to = shakmaty::Square::from(mv.to_square());
+        if mv.is_castle() {
+            let (rook_square, _) = self.castling_rook_move(mv);
+            return shakmaty::Move::Castle {
+                king: from,
+                rook: Square::new(rook_square).into()
+            };
+        }
+        if mv.is_ep_capture() {
+            return shakmaty::Move::EnPassant { from, to };
+        }
+        let mut capture = None;
+        if mv.is_capture() {
+            capture = Some(role(self.board[mv.get_to()].get_type()));
+        }
+        let mut promotion = None;
+        if mv.is_promotion() {
+            promotion = Some(role(mv.get_promotion_type()));
+        }
+        return shakmaty::Move::Normal {
+            role: role(self.board[mv.get_from()].get_type()),
+            from,
+            capture,
+            to,
+            promotion
+        };
+    }
+
+    /// Returns the legal move of the game matching a move of shakmaty, or None if there is no such move
+    pub fn from_shakmaty_move(&self, mv: &shakmaty::Move) -> Option<Move> {
+        let uci = mv.to_uci(CastlingMode::Chess960).to_string();
+        return parse_uci(self, &uci).ok();
+    }
+}
@@ -0,0 +1,126 @@
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::{Game, Move};
+
+/// The current game with the number of changes made to it
+struct SharedState {
+    game: Arc<Game>,
+    version: u64
+}
+
+struct SharedInner {
+    state: Mutex<SharedState>,
+    changed: Condvar
+}
+
+/// A game shared between threads, for example a game thread making moves and rendering threads drawing the board.
+/// Clones share the same game.
+///
+/// Readers get the current position as an immutable `Arc<Game>`, which only takes the lock long enough to clone the
+/// `Arc`, so a slow reader never holds up the game thread. Every change increases the version, and readers can wait
+/// for the next change with [SharedGame::wait_for_change] instead of polling.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::notation::parse_uci;
+/// let shared = SharedGame::new(Game::starting_position());
+/// let renderer = shared.clone();
+/// let thread = std::thread::spawn(move || {
+///     let (game, version) = renderer.wait_for_change(0);
+///     return (game.last_move(), version);
+/// });
+///
+/// let before = shared.snapshot();
+/// let mv = parse_uci(&before, "e2e4").unwrap();
+/// assert!(shared.make_move(mv));
+/// assert_eq!(thread.join().unwrap(), (Some(mv), 1));
+/// // Snapshots taken before a move keep the position they were taken in
+/// assert_eq!(before.last_move(), None);
+/// assert!(!shared.make_move(mv));
+/// ```
+#[derive(Clone)]
+pub struct SharedGame {
+    inner: Arc<SharedInner>
+}
+
+impl SharedGame {
+
+    pub fn new(game: Game) -> SharedGame {
+        SharedGame {
+            inner: Arc::new(SharedInner {
+                state: Mutex::new(SharedState { game: Arc::new(game), version: 0 }),
+                changed: Condvar::new()
+            })
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SharedState> {
+        return self.inner.state.lock().unwrap();
+    }
+
+    /// Returns the current game, which doesn't change when moves are made later
+    pub fn snapshot(&self) -> Arc<Game> {
+        return Arc::clone(&self.lock().game);
+    }
+
+    /// Returns the current game with its version
+    pub fn versioned_snapshot(&self) -> (Arc<Game>, u64) {
+        let state = self.lock();
+        return (Arc::clone(&state.game), state.version);
+    }
+
+    /// Returns the number of changes made to the game so far
+    pub fn version(&self) -> u64 {
+        return self.lock().version;
+    }
+
+    /// Changes the game and wakes the threads waiting for a change. The game is copied first if a snapshot of it
+    /// is still held, so snapshots never change.
+    pub fn update<T>(&self, change: impl FnOnce(&mut Game) -> T) -> T {
+        let mut state = self.lock();
+        let result = change(Arc::make_mut(&mut state.game));
+        state.version += 1;
+        drop(state);
+        self.inner.changed.notify_all();
+        return result;
+    }
+
+    /// Makes the move if it is legal, returns false and changes nothing otherwise
+    pub fn make_move(&self, mv: Move) -> bool {
+        let mut state = self.lock();
+        if !state.game.legal_moves_cached().contains(&mv) {
+            return false;
+        }
+        Arc::make_mut(&mut state.game).make_move(mv);
+        state.version += 1;
+        drop(state);
+        self.inner.changed.notify_all();
+        return true;
+    }
+
+    /// Replaces the game, for example when a new game starts
+    pub fn replace(&self, game: Game) {
+        self.update(|current| *current = game);
+    }
+
+    /// Waits until the version is newer than 'version' and returns the game with its version. Returns right away
+    /// if the game already changed.
+    pub fn wait_for_change(&self, version: u64) -> (Arc<Game>, u64) {
+        let state = self.inner.changed.wait_while(self.lock(), |state| state.version <= version).unwrap();
+        return (Arc::clone(&state.game), state.version);
+    }
+
+    /// Like [SharedGame::wait_for_change], but returns None if the game didn't change within the timeout
+    pub fn wait_for_change_timeout(&self, version: u64, timeout: Duration) -> Option<(Arc<Game>, u64)> {
+        let (state, result) = self.inner.changed
+            .wait_timeout_while(self.lock(), timeout, |state| state.version <= version)
+            .unwrap();
+        if result.timed_out() {
+            return None;
+        }
+        return Some((Arc::clone(&state.game), state.version));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::fen::to_fen;
+use crate::notation::to_uci;
+use crate::{Game, Move, Square};
+
+/// Everything a client needs to show the current position and let the user move, see [Game::snapshot]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub fen: String,
+    pub last_move: Option<Move>,
+    /// The square of the king of the side to move if it is in check
+    pub checked_king: Option<Square>,
+    /// The squares of the pieces giving check
+    pub checkers: Vec<Square>,
+    /// The squares each piece of the side to move can move to, for every piece that has a legal move
+    pub destinations: Vec<(Square, Vec<Square>)>
+}
+
+impl Snapshot {
+
+    /// Writes the snapshot as a JSON object with squares and the last move written as in UCI, for example
+    /// '{"fen":"...","last_move":"e2e4","checked_king":null,"checkers":[],"destinations":{"a7":["a6","a5"],...}}'
+    pub fn to_json(&self) -> String {
+        let quoted = |text: String| format!("\"{}\"", text);
+        let square_list = |squares: &[Square]| {
+            let squares: Vec<String> = squares.iter().map(|square| quoted(square.to_string())).collect();
+            return format!("[{}]", squares.join(","));
+        };
+
+        let last_move = self.last_move.map_or("null".to_string(), |mv| quoted(to_uci(&mv)));
+        let checked_king = self.checked_king.map_or("null".to_string(), |square| quoted(square.to_string()));
+        let destinations: Vec<String> = self.destinations.iter()
+            .map(|(from, to)| format!("{}:{}", quoted(from.to_string()), square_list(to)))
+            .collect();
+        return format!("{{\"fen\":{},\"last_move\":{},\"checked_king\":{},\"checkers\":{},\"destinations\":{{{}}}}}",
+            quoted(self.fen.clone()), last_move, checked_king, square_list(&self.checkers), destinations.join(","));
+    }
+}
+
+impl Game {
+
+    /// Returns the position as a [Snapshot], meant as the single message a server sends to its clients after
+    /// every move
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let mut game = Game::starting_position();
+    /// for (from, to) in [(53, 45), (12, 28), (54, 38), (3, 39)] {
+    ///     game.make_move_from_to(from, to, None);
+    /// }
+    /// let snapshot = game.snapshot();
+    /// assert_eq!(snapshot.checked_king, Some(Square::new(60)));
+    /// assert_eq!(snapshot.checkers, vec![Square::new(39)]);
+    /// assert!(snapshot.destinations.is_empty());
+    /// assert_eq!(snapshot.to_json(), concat!(
+    ///     "{\"fen\":\"rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3\",",
+    ///     "\"last_move\":\"d8h4\",\"checked_king\":\"e1\",\"checkers\":[\"h4\"],\"destinations\":{}}"));
+    ///
+    /// let snapshot = Game::starting_position().snapshot();
+    /// assert_eq!(snapshot.last_move, None);
+    /// assert_eq!(snapshot.destinations.len(), 10);
+    /// assert_eq!(snapshot.destinations[0], (Square::new(48), vec![Square::new(32), Square::new(40)]));
+    /// ```
+    pub fn snapshot(&self) -> Snapshot {
+        let mut checked_king = None;
+        let mut checkers = vec![];
+        if self.is_in_check() {
+            checked_king = Some(Square::new(self.king_square[self.turn]));
+            checkers = self.checkers();
+        }
+
+        let mut destinations: Vec<(Square, Vec<Square>)> = vec![];
+        for mv in self.legal_moves_cached().iter() {
+            let from = Square::new(mv.get_from());
+            let to = Square::new(mv.get_to());
+            let index = match destinations.iter().position(|(square, _)| *square == from) {
+                Some(index) => index,
+                None => {
+                    destinations.push((from, vec![]));
+                    destinations.len() - 1
+                }
+            };
+            // Promotions to different pieces share a destination
+            if !destinations[index].1.contains(&to) {
+                destinations[index].1.push(to);
+            }
+        }
+        destinations.sort_by_key(|(from, _)| from.index());
+
+        return Snapshot {
+            fen: to_fen(self),
+            last_move: self.last_move,
+            checked_king,
+            checkers,
+            destinations
+        };
+    }
+}
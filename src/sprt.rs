@@ -0,0 +1,98 @@
+//! Sequential probability ratio tests for deciding whether one player is stronger than another
+//!
+//! An [Sprt] tests the hypothesis H0, that the first player of a match is elo0 Elo stronger than the second, against
+//! H1, that it is elo1 Elo stronger, usually with elo0 at 0 and elo1 at a small gain. As games are played the
+//! log-likelihood ratio of the results is compared to bounds given by the error rates alpha and beta: once it drops
+//! below the lower bound H0 is accepted, once it rises above the upper bound H1 is accepted, and otherwise the match
+//! goes on. This usually decides long before a match of fixed length would, with the chance of accepting H1 when H0
+//! holds at most alpha and the chance of accepting H0 when H1 holds at most beta.
+//!
+//! The ratio is computed with the normal approximation of the generalized test used by Fishtest and cutechess-cli,
+//! from the wins, draws and losses of a [MatchScore]. [Match::play_sprt](crate::matchplay::Match::play_sprt) and
+//! [TimedMatch::play_sprt](crate::matchplay::TimedMatch::play_sprt) play a match until the test decides.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::matchplay::MatchScore;
+//! # use olindba_chess::sprt::{Sprt, SprtStatus};
+//! let sprt = Sprt::new(0.0, 10.0);
+//! let score = MatchScore { wins: 60, draws: 100, losses: 40 };
+//! assert!((sprt.llr(&score) - 1.005).abs() < 0.001);
+//! assert_eq!(sprt.status(&score), SprtStatus::Continue);
+//!
+//! // The same results over ten times as many games are enough
+//! let score = MatchScore { wins: 600, draws: 1000, losses: 400 };
+//! assert_eq!(sprt.status(&score), SprtStatus::AcceptH1);
+//! ```
+
+use crate::matchplay::MatchScore;
+use crate::rating::expected_score;
+
+/// The outcome of a test so far
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SprtStatus {
+    /// The results don't decide yet, more games are needed
+    Continue,
+    /// The first player is at most elo0 stronger
+    AcceptH0,
+    /// The first player is at least elo1 stronger
+    AcceptH1
+}
+
+/// A sequential probability ratio test, see the [module documentation](self)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sprt {
+    /// The Elo difference of H0
+    pub elo0: f64,
+    /// The Elo difference of H1
+    pub elo1: f64,
+    /// The chance of accepting H1 when H0 holds
+    pub alpha: f64,
+    /// The chance of accepting H0 when H1 holds
+    pub beta: f64
+}
+
+impl Sprt {
+
+    /// Creates a test of the Elo differences with alpha and beta at 0.05
+    pub fn new(elo0: f64, elo1: f64) -> Sprt {
+        return Sprt { elo0, elo1, alpha: 0.05, beta: 0.05 };
+    }
+
+    /// Returns the lower and upper bound of the log-likelihood ratio, where H0 and H1 are accepted
+    pub fn bounds(&self) -> (f64, f64) {
+        return ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln());
+    }
+
+    /// Returns the log-likelihood ratio of H1 over H0 given the results. It is 0 until the first player has won,
+    /// drawn and lost at least one game each, before which the spread of the results can't be estimated.
+    pub fn llr(&self, score: &MatchScore) -> f64 {
+        if score.wins == 0 || score.draws == 0 || score.losses == 0 {
+            return 0.0;
+        }
+        let games = (score.wins + score.draws + score.losses) as f64;
+        let wins = score.wins as f64 / games;
+        let draws = score.draws as f64 / games;
+        // The mean and variance of the score of a game, counting a draw as half a point
+        let mean = wins + draws / 2.0;
+        let variance = wins + draws / 4.0 - mean * mean;
+        let variance_of_mean = variance / games;
+
+        let (score0, score1) = (expected_score(self.elo0), expected_score(self.elo1));
+        return (score1 - score0) * (2.0 * mean - score0 - score1) / (2.0 * variance_of_mean);
+    }
+
+    /// Returns whether the results accept one of the hypotheses
+    pub fn status(&self, score: &MatchScore) -> SprtStatus {
+        let llr = self.llr(score);
+        let (lower, upper) = self.bounds();
+        if llr <= lower {
+            return SprtStatus::AcceptH0;
+        }
+        if llr >= upper {
+            return SprtStatus::AcceptH1;
+        }
+        return SprtStatus::Continue;
+    }
+}
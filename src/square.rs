@@ -0,0 +1,410 @@
+use crate::{WHITE, BLACK};
+
+/// The color of a square on the board, a1 is dark and h1 is light
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SquareColor {
+    Light,
+    Dark
+}
+
+/// A file of the board, from the a-file to the h-file
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum File {
+    A, B, C, D, E, F, G, H
+}
+
+impl File {
+
+    pub const ALL: [File; 8] = [File::A, File::B, File::C, File::D, File::E, File::F, File::G, File::H];
+
+    /// Returns the file with the given index, 0 being the a-file as in [Square::column]
+    pub fn from_index(index: usize) -> Option<File> {
+        return File::ALL.get(index).copied();
+    }
+
+    /// Returns the index of the file, 0 for the a-file
+    pub fn index(&self) -> usize {
+        return *self as usize;
+    }
+}
+
+impl std::fmt::Display for File {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", (b'a' + self.index() as u8) as char)
+    }
+}
+
+/// A rank of the board, from the 1st rank where White starts to the 8th rank
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    First, Second, Third, Fourth, Fifth, Sixth, Seventh, Eighth
+}
+
+impl Rank {
+
+    pub const ALL: [Rank; 8] = [
+        Rank::First, Rank::Second, Rank::Third, Rank::Fourth, Rank::Fifth, Rank::Sixth, Rank::Seventh, Rank::Eighth
+    ];
+
+    /// Returns the rank with the given index, 0 being the 1st rank. Note that [Square::row] counts the other way.
+    pub fn from_index(index: usize) -> Option<Rank> {
+        return Rank::ALL.get(index).copied();
+    }
+
+    /// Returns the index of the rank, 0 for the 1st rank
+    pub fn index(&self) -> usize {
+        return *self as usize;
+    }
+}
+
+impl std::fmt::Display for Rank {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.index() + 1)
+    }
+}
+
+/// A square on the board, indexed the same way as [crate::Game::board]:
+/// 0 is a8, 7 is h8, 56 is a1 and 63 is h1.
+/// 
+/// Functions of the crate taking a square accept either a Square or an index in this order. An index outside the
+/// board is treated like a square the function can do nothing with, so a move to it is illegal. Functions that
+/// report errors take a Square, converted from an index with [Square::try_from]. Indices in the order 
+/// common with bitboards, where 0 is a1 and 63 is h8, are converted with [Square::from_a1_indexing] and 
+/// [Square::to_a1_indexing].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Square {
+    index: usize
+}
+
+impl Square {
+
+    // The squares by name, in the order of their indices from a8 to h1
+    pub const A8: Square = Square { index: 0 };
+    pub const B8: Square = Square { index: 1 };
+    pub const C8: Square = Square { index: 2 };
+    pub const D8: Square = Square { index: 3 };
+    pub const E8: Square = Square { index: 4 };
+    pub const F8: Square = Square { index: 5 };
+    pub const G8: Square = Square { index: 6 };
+    pub const H8: Square = Square { index: 7 };
+    pub const A7: Square = Square { index: 8 };
+    pub const B7: Square = Square { index: 9 };
+    pub const C7: Square = Square { index: 10 };
+    pub const D7: Square = Square { index: 11 };
+    pub const E7: Square = Square { index: 12 };
+    pub const F7: Square = Square { index: 13 };
+    pub const G7: Square = Square { index: 14 };
+    pub const H7: Square = Square { index: 15 };
+    pub const A6: Square = Square { index: 16 };
+    pub const B6: Square = Square { index: 17 };
+    pub const C6: Square = Square { index: 18 };
+    pub const D6: Square = Square { index: 19 };
+    pub const E6: Square = Square { index: 20 };
+    pub const F6: Square = Square { index: 21 };
+    pub const G6: Square = Square { index: 22 };
+    pub const H6: Square = Square { index: 23 };
+    pub const A5: Square = Square { index: 24 };
+    pub const B5: Square = Square { index: 25 };
+    pub const C5: Square = Square { index: 26 };
+    pub const D5: Square = Square { index: 27 };
+    pub const E5: Square = Square { index: 28 };
+    pub const F5: Square = Square { index: 29 };
+    pub const G5: Square = Square { index: 30 };
+    pub const H5: Square = Square { index: 31 };
+    pub const A4: Square = Square { index: 32 };
+    pub const B4: Square = Square { index: 33 };
+    pub const C4: Square = Square { index: 34 };
+    pub const D4: Square = Square { index: 35 };
+    pub const E4: Square = Square { index: 36 };
+    pub const F4: Square = Square { index: 37 };
+    pub const G4: Square = Square { index: 38 };
+    pub const H4: Square = Square { index: 39 };
+    pub const A3: Square = Square { index: 40 };
+    pub const B3: Square = Square { index: 41 };
+    pub const C3: Square = Square { index: 42 };
+    pub const D3: Square = Square { index: 43 };
+    pub const E3: Square = Square { index: 44 };
+    pub const F3: Square = Square { index: 45 };
+    pub const G3: Square = Square { index: 46 };
+    pub const H3: Square = Square { index: 47 };
+    pub const A2: Square = Square { index: 48 };
+    pub const B2: Square = Square { index: 49 };
+    pub const C2: Square = Square { index: 50 };
+    pub const D2: Square = Square { index: 51 };
+    pub const E2: Square = Square { index: 52 };
+    pub const F2: Square = Square { index: 53 };
+    pub const G2: Square = Square { index: 54 };
+    pub const H2: Square = Square { index: 55 };
+    pub const A1: Square = Square { index: 56 };
+    pub const B1: Square = Square { index: 57 };
+    pub const C1: Square = Square { index: 58 };
+    pub const D1: Square = Square { index: 59 };
+    pub const E1: Square = Square { index: 60 };
+    pub const F1: Square = Square { index: 61 };
+    pub const G1: Square = Square { index: 62 };
+    pub const H1: Square = Square { index: 63 };
+
+    /// Creates a square from an index between 0 and 63 inclusive
+    ///
+    /// # Panics
+    /// If the index is outside the board
+    pub fn new(index: usize) -> Square {
+        assert!(index < 64, "square index {} is outside the board", index);
+        return Square { index };
+    }
+
+    /// Creates a square from its column (0 is the a-file) and row (0 is the 8th rank)
+    pub fn from_column_row(column: usize, row: usize) -> Square {
+        return Square::new(row * 8 + column);
+    }
+
+    /// Creates the square on the file and rank, so code doesn't depend on the order squares are indexed in
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let e1 = Square::at(File::E, Rank::First);
+    /// assert_eq!(e1, Square::E1);
+    /// assert_eq!(e1.to_string(), "e1");
+    /// assert_eq!((e1.file_of(), e1.rank_of()), (File::E, Rank::First));
+    /// assert_eq!(Square::A8.index(), 0);
+    /// assert_eq!(Square::H1.index(), 63);
+    /// ```
+    pub fn at(file: File, rank: Rank) -> Square {
+        return Square::from_column_row(file.index(), 7 - rank.index());
+    }
+
+    /// Creates a square from an index where 0 is a1, 7 is h1, 56 is a8 and 63 is h8
+    ///
+    /// # Panics
+    /// If the index is outside the board
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let e2 = Square::from_a1_indexing(12);
+    /// assert_eq!(e2, Square::from_algebraic("e2").unwrap());
+    /// assert_eq!(e2.index(), 52);
+    /// assert_eq!(e2.to_a1_indexing(), 12);
+    /// ```
+    pub fn from_a1_indexing(index: usize) -> Square {
+        assert!(index < 64, "square index {} is outside the board", index);
+        return Square::new(index ^ 56);
+    }
+
+    /// Returns the index of the square where 0 is a1 and 63 is h8
+    pub fn to_a1_indexing(&self) -> usize {
+        return self.index ^ 56;
+    }
+
+    /// Parses a square in algebraic notation, example 'e3'
+    pub fn from_algebraic(alg_not: &str) -> Option<Square> {
+        let mut chars = alg_not.chars();
+        let file = chars.next()?;
+        let rank = chars.next()?;
+        if chars.next().is_some() || !('a'..='h').contains(&file) || !('1'..='8').contains(&rank) {
+            return None;
+        }
+        let column = file as usize - 'a' as usize;
+        let row = '8' as usize - rank as usize;
+        return Some(Square::from_column_row(column, row));
+    }
+
+    /// Returns the index of the square, usable with [crate::Game::board]
+    pub fn index(&self) -> usize {
+        return self.index;
+    }
+
+    /// Returns the column of the square, indexed from left to right
+    pub fn column(&self) -> usize {
+        return self.index % 8;
+    }
+
+    /// Returns the row of the square, indexed from up to down
+    pub fn row(&self) -> usize {
+        return self.index / 8;
+    }
+
+    /// Returns the file of the square, see [Square::column] for its index
+    pub fn file_of(&self) -> File {
+        return File::ALL[self.column()];
+    }
+
+    /// Returns the rank of the square, see [Square::rank] for its number
+    pub fn rank_of(&self) -> Rank {
+        return Rank::ALL[self.rank() - 1];
+    }
+
+    /// Returns the rank of the square as written in algebraic notation, 1 for the rank White starts on
+    pub fn rank(&self) -> usize {
+        return 8 - self.row();
+    }
+
+    /// Returns the rank of the square as seen by the given color, 1 for the color's own back rank
+    pub fn relative_rank(&self, color: usize) -> usize {
+        if color == BLACK {
+            return self.row() + 1;
+        }
+        return self.rank();
+    }
+
+    /// Returns true if the square is on the given color's back rank
+    pub fn is_back_rank(&self, color: usize) -> bool {
+        return self.relative_rank(color) == 1;
+    }
+
+    /// Returns true if a pawn of the given color is promoted when reaching the square
+    pub fn is_promotion_rank(&self, color: usize) -> bool {
+        return self.relative_rank(color) == 8;
+    }
+
+    /// Returns the square mirrored across the horizontal middle of the board, e2 becomes e7
+    pub fn flip_vertical(&self) -> Square {
+        return Square::new(self.index ^ 56);
+    }
+
+    /// Returns the square mirrored across the vertical middle of the board, b3 becomes g3
+    pub fn flip_horizontal(&self) -> Square {
+        return Square::new(self.index ^ 7);
+    }
+
+    /// Returns the square as seen from the given color, flipping it vertically for Black
+    pub fn relative_to(&self, color: usize) -> Square {
+        if color == WHITE {
+            return *self;
+        }
+        return self.flip_vertical();
+    }
+
+    /// Returns whether the square is light or dark
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// assert_eq!(Square::from_algebraic("a1").unwrap().color(), SquareColor::Dark);
+    /// assert_eq!(Square::from_algebraic("h1").unwrap().color(), SquareColor::Light);
+    /// assert_eq!(Square::from_algebraic("d1").unwrap().color(), SquareColor::Light);
+    /// ```
+    pub fn color(&self) -> SquareColor {
+        if (self.row() + self.column()).is_multiple_of(2) {
+            return SquareColor::Light;
+        }
+        return SquareColor::Dark;
+    }
+
+    /// Returns the number of king moves between the squares on an empty board,
+    /// the larger of the file and rank distances
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let e4 = Square::from_algebraic("e4").unwrap();
+    /// let g7 = Square::from_algebraic("g7").unwrap();
+    /// assert_eq!(e4.distance(g7), 3);
+    /// assert_eq!(e4.manhattan_distance(g7), 5);
+    /// assert_eq!(e4.distance(e4), 0);
+    /// ```
+    pub fn distance(&self, other: Square) -> usize {
+        return self.column().abs_diff(other.column()).max(self.row().abs_diff(other.row()));
+    }
+
+    /// Returns the sum of the file and rank distances between the squares
+    pub fn manhattan_distance(&self, other: Square) -> usize {
+        return self.column().abs_diff(other.column()) + self.row().abs_diff(other.row());
+    }
+
+    /// Returns the number of king moves to the closest of the four center squares d4, e4, d5 and e5
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// assert_eq!(Square::from_algebraic("e5").unwrap().center_distance(), 0);
+    /// assert_eq!(Square::from_algebraic("b7").unwrap().center_distance(), 2);
+    /// assert_eq!(Square::from_algebraic("h1").unwrap().center_distance(), 3);
+    /// ```
+    pub fn center_distance(&self) -> usize {
+        let file_distance = (2 * self.column()).abs_diff(7) / 2;
+        let rank_distance = (2 * self.row()).abs_diff(7) / 2;
+        return file_distance.max(rank_distance);
+    }
+
+    /// Returns the number of king moves to the closest edge of the board
+    pub fn edge_distance(&self) -> usize {
+        return self.column().min(7 - self.column()).min(self.row()).min(7 - self.row());
+    }
+
+    /// Returns true if the squares are on the same file
+    pub fn same_file(&self, other: Square) -> bool {
+        return self.column() == other.column();
+    }
+
+    /// Returns true if the squares are on the same rank
+    pub fn same_rank(&self, other: Square) -> bool {
+        return self.row() == other.row();
+    }
+
+    /// Returns true if the squares are on the same diagonal or anti-diagonal, a square is on the same diagonal
+    /// as itself
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let c1 = Square::from_algebraic("c1").unwrap();
+    /// assert!(c1.same_diagonal(Square::from_algebraic("h6").unwrap()));
+    /// assert!(c1.same_diagonal(Square::from_algebraic("a3").unwrap()));
+    /// assert!(!c1.same_diagonal(Square::from_algebraic("c8").unwrap()));
+    /// assert!(c1.same_file(Square::from_algebraic("c8").unwrap()));
+    /// ```
+    pub fn same_diagonal(&self, other: Square) -> bool {
+        return self.column().abs_diff(other.column()) == self.row().abs_diff(other.row());
+    }
+}
+
+impl std::fmt::Display for Square {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let file = (b'a' + self.column() as u8) as char;
+        write!(f, "{}{}", file, self.rank())
+    }
+}
+
+/// The error of converting an index outside the board to a [Square]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SquareIndexError(pub usize);
+
+impl std::fmt::Display for SquareIndexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "square index {} is outside the board", self.0)
+    }
+}
+
+impl std::error::Error for SquareIndexError {}
+
+impl TryFrom<usize> for Square {
+    type Error = SquareIndexError;
+
+    /// Converts an index where 0 is a8, see [Square::new]. The functions taking squares accept these indices too,
+    /// and treat an index outside the board like a square they can't do anything with.
+    fn try_from(index: usize) -> Result<Square, SquareIndexError> {
+        if index >= 64 {
+            return Err(SquareIndexError(index));
+        }
+        return Ok(Square { index });
+    }
+}
+
+/// Converts the argument of a function taking a square or an index, None for an index outside the board
+pub(crate) fn to_square(square: impl TryInto<Square>) -> Option<Square> {
+    return square.try_into().ok();
+}
+
+impl From<Square> for usize {
+    fn from(square: Square) -> usize {
+        return square.index();
+    }
+}
@@ -0,0 +1,180 @@
+//! Opening statistics of game collections, as shown by an opening explorer
+//!
+//! An [OpeningTree] counts the results of the games reaching each position of their openings and of every move
+//! played from there. Positions are keyed by their [Zobrist hash](crate::Game::hash), so games reaching a position by
+//! different move orders are counted together. Games are added one at a time or read from a [PgnStream], which
+//! doesn't hold the whole collection in memory.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::notation::{parse_san, Notation};
+//! # use olindba_chess::pgn::PgnStream;
+//! # use olindba_chess::stats::OpeningTree;
+//! let database = "[Result \"1-0\"]\n\n1. e4 e5 2. Nf3 Nc6 1-0\n\n\
+//!                 [Result \"1/2-1/2\"]\n\n1. Nf3 Nc6 2. e4 e5 1/2-1/2\n\n\
+//!                 [Result \"0-1\"]\n\n1. d4 d5 0-1\n\n\
+//!                 [Result \"*\"]\n\n1. e4 c5 *\n";
+//! let mut tree = OpeningTree::new(20);
+//! assert_eq!(tree.add_stream(PgnStream::new(database.as_bytes())).unwrap(), 3);
+//!
+//! let start = Game::starting_position();
+//! let moves = tree.moves(&start);
+//! assert_eq!(moves.len(), 3);
+//! assert_eq!(moves[0].mv, parse_san(&start, "e4").unwrap());
+//! assert_eq!((moves[0].results.white_wins, moves[0].results.draws), (1, 0));
+//!
+//! // Both move orders reach the same position
+//! let mut game = Game::starting_position();
+//! game.apply_move_list("1. e4 e5 2. Nf3 Nc6", Notation::San).unwrap();
+//! let results = tree.position(&game).unwrap().results;
+//! assert_eq!(results.total(), 2);
+//! assert_eq!(results.white_score(), 0.75);
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
+
+use crate::pgn::{PgnError, PgnGame, PgnStream};
+use crate::{Game, GameResult, Move};
+
+/// The results of a number of games, counted from White's point of view
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResultCounts {
+    pub white_wins: usize,
+    pub draws: usize,
+    pub black_wins: usize
+}
+
+impl ResultCounts {
+
+    /// Returns the number of games
+    pub fn total(&self) -> usize {
+        return self.white_wins + self.draws + self.black_wins;
+    }
+
+    /// Returns the points White scored per game, counting a draw as half a point, or 0.5 without games
+    pub fn white_score(&self) -> f64 {
+        if self.total() == 0 {
+            return 0.5;
+        }
+        return (self.white_wins as f64 + self.draws as f64 / 2.0) / self.total() as f64;
+    }
+
+    fn add(&mut self, result: GameResult) {
+        match result {
+            GameResult::WhiteWins => self.white_wins += 1,
+            GameResult::Draw => self.draws += 1,
+            GameResult::BlackWins => self.black_wins += 1
+        }
+    }
+}
+
+/// A move played in a position with the results of the games that played it
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MoveStats {
+    pub mv: Move,
+    pub results: ResultCounts
+}
+
+/// A position of an [OpeningTree]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PositionStats {
+    /// The results of the games that reached the position
+    pub results: ResultCounts,
+    /// The moves played in the position, the most played first
+    pub moves: Vec<MoveStats>
+}
+
+/// The positions of the openings of a collection of games, see the [module documentation](self)
+pub struct OpeningTree {
+    max_plies: usize,
+    positions: HashMap<u64, PositionStats>,
+    games: usize
+}
+
+impl OpeningTree {
+
+    /// Creates an empty tree counting the first 'max_plies' moves of every game
+    pub fn new(max_plies: usize) -> OpeningTree {
+        return OpeningTree { max_plies, positions: HashMap::new(), games: 0 };
+    }
+
+    /// Returns the number of games added
+    pub fn games(&self) -> usize {
+        return self.games;
+    }
+
+    /// Returns the number of positions in the tree
+    pub fn len(&self) -> usize {
+        return self.positions.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.positions.is_empty();
+    }
+
+    /// Counts the game in every position of its opening. Returns false and skips the game if it has no result,
+    /// like a game still in progress. A position repeated within the opening of a game counts once for it.
+    pub fn add_game(&mut self, pgn: &PgnGame) -> bool {
+        let result = match GameResult::parse(&pgn.result) {
+            Some(result) => result,
+            None => return false
+        };
+        let mut game = pgn.start.clone();
+        let mut seen = HashSet::new();
+        for ply in 0..=pgn.moves.len().min(self.max_plies) {
+            let first_visit = seen.insert(game.hash());
+            let position = self.positions.entry(game.hash()).or_default();
+            if first_visit {
+                position.results.add(result);
+            }
+            let mv = match pgn.moves.get(ply) {
+                Some(&mv) if ply < self.max_plies => mv,
+                _ => break
+            };
+            if first_visit {
+                match position.moves.iter_mut().position(|stats| stats.mv == mv) {
+                    Some(index) => position.moves[index].results.add(result),
+                    None => {
+                        let mut results = ResultCounts::default();
+                        results.add(result);
+                        position.moves.push(MoveStats { mv, results });
+                    }
+                }
+                position.moves.sort_by_key(|stats| std::cmp::Reverse(stats.results.total()));
+            }
+            game.make_move(mv);
+        }
+        self.games += 1;
+        return true;
+    }
+
+    /// Adds every game of the stream and returns how many were added. Games with illegal moves or an invalid FEN tag
+    /// and games without a result are skipped, an error reading the stream stops it and is returned.
+    pub fn add_stream<R: BufRead>(&mut self, stream: PgnStream<R>) -> std::io::Result<usize> {
+        let mut added = 0;
+        for game in stream {
+            match game {
+                Ok(game) => added += self.add_game(&game) as usize,
+                Err(PgnError::Io(error)) => return Err(error),
+                Err(PgnError::InvalidMove { .. } | PgnError::InvalidFen(_)) => continue
+            }
+        }
+        return Ok(added);
+    }
+
+    /// Returns the statistics of the position, or None if no game reached it
+    pub fn position(&self, game: &Game) -> Option<&PositionStats> {
+        return self.positions.get(&game.hash());
+    }
+
+    /// Returns the moves played in the position, the most played first
+    pub fn moves(&self, game: &Game) -> &[MoveStats] {
+        return match self.positions.get(&game.hash()) {
+            Some(position) => &position.moves,
+            None => &[]
+        };
+    }
+}
@@ -0,0 +1,300 @@
+use crate::ordering::piece_value;
+use crate::{CastlingRights, Game, GameState, Square, StalemateOutcome, BLACK, EMPTY, KING, WHITE};
+
+/// The result of a finished game, displayed as in PGN: '1-0', '0-1' or '1/2-1/2'
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw
+}
+
+impl GameResult {
+
+    /// Returns the result of a game won by the given color
+    pub fn win_for(color: usize) -> GameResult {
+        if color == WHITE {
+            return GameResult::WhiteWins;
+        }
+        return GameResult::BlackWins;
+    }
+
+    /// Returns the color that won, or None for a draw
+    pub fn winner(&self) -> Option<usize> {
+        return match self {
+            GameResult::WhiteWins => Some(WHITE),
+            GameResult::BlackWins => Some(BLACK),
+            GameResult::Draw => None
+        };
+    }
+
+    /// Reads a result as written in PGN, which fails for '*' and anything else
+    pub fn parse(result: &str) -> Option<GameResult> {
+        return match result {
+            "1-0" => Some(GameResult::WhiteWins),
+            "0-1" => Some(GameResult::BlackWins),
+            "1/2-1/2" => Some(GameResult::Draw),
+            _ => None
+        };
+    }
+
+    fn as_str(&self) -> &'static str {
+        return match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2"
+        };
+    }
+}
+
+impl std::fmt::Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Why a game ended, displayed as in the Termination tag of PGN, for example 'time forfeit'
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Termination {
+    /// The game ended on the board or by agreement
+    Normal,
+    /// A player resigned. PGN counts resignations as normal terminations, they are written as 'resignation' so the
+    /// reason survives writing and reading the game.
+    Resignation,
+    /// A player ran out of time, which is a draw when the opponent can't mate, see [Game::timeout_result]
+    TimeForfeit,
+    /// A player left the game
+    Abandoned,
+    /// An arbiter or the rules of a match decided the result before the game was over
+    Adjudication,
+    /// A player lost by breaking the rules, such as by an illegal move
+    RulesInfraction
+}
+
+impl Termination {
+
+    /// Reads the value of a Termination tag, ignoring case
+    pub fn parse(termination: &str) -> Option<Termination> {
+        return match termination.trim().to_ascii_lowercase().as_str() {
+            "normal" => Some(Termination::Normal),
+            "resignation" => Some(Termination::Resignation),
+            "time forfeit" => Some(Termination::TimeForfeit),
+            "abandoned" => Some(Termination::Abandoned),
+            "adjudication" => Some(Termination::Adjudication),
+            "rules infraction" => Some(Termination::RulesInfraction),
+            _ => None
+        };
+    }
+}
+
+impl std::fmt::Display for Termination {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let termination = match self {
+            Termination::Normal => "normal",
+            Termination::Resignation => "resignation",
+            Termination::TimeForfeit => "time forfeit",
+            Termination::Abandoned => "abandoned",
+            Termination::Adjudication => "adjudication",
+            Termination::RulesInfraction => "rules infraction"
+        };
+        write!(f, "{}", termination)
+    }
+}
+
+impl GameState {
+
+    /// Returns the result of a game in this state under the standard rules as written in PGN, '1-0', '0-1', 
+    /// '1/2-1/2' or '*' if it isn't over. See [Game::result] for the result under the rules of a game.
+    /// 'side_to_move' is the color to move in the position the state belongs to.
+    pub fn result(&self, side_to_move: usize) -> &'static str {
+        return match self {
+            GameState::InProgress | GameState::Check => "*",
+            GameState::Checkmate if side_to_move == WHITE => "0-1",
+            GameState::Checkmate => "1-0",
+            _ => "1/2-1/2"
+        };
+    }
+}
+
+impl std::fmt::Display for GameState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let description = match self {
+            GameState::InProgress => "in progress",
+            GameState::Check => "check",
+            GameState::Checkmate => "checkmate",
+            GameState::Stalemate => "draw by stalemate",
+            GameState::InsufficientMaterial => "draw by insufficient material",
+            GameState::DrawBy50MoveRule => "draw by the 50-move rule",
+            GameState::FivefoldRepetition => "draw by fivefold repetition",
+            GameState::DrawBy75MoveRule => "draw by the 75-move rule"
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// An overview of the current position, meant for debugging and logging
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameSummary {
+    pub side_to_move: usize,
+    /// The material of White minus the material of Black, in centipawns
+    pub material_balance: i32,
+    pub in_check: bool,
+    pub castling_rights: CastlingRights,
+    /// The square a pawn can be captured en passant on, behind the pawn that just made a double push
+    pub en_passant_square: Option<Square>,
+    pub halfmove_clock: usize,
+    pub game_state: GameState,
+    /// The result and reason the game was ended with off the board, see [Game::end]
+    pub ending: Option<(GameResult, Termination)>
+}
+
+impl GameSummary {
+
+    /// Returns the result of the game as written in PGN, the one it was ended with or see [GameState::result]
+    pub fn result(&self) -> &'static str {
+        return match self.ending {
+            Some((result, _)) => result.as_str(),
+            None => self.game_state.result(self.side_to_move)
+        };
+    }
+
+    /// Returns why the game ended, see [Game::termination]
+    pub fn termination(&self) -> Option<Termination> {
+        return match self.ending {
+            Some((_, termination)) => Some(termination),
+            None if self.result() != "*" => Some(Termination::Normal),
+            None => None
+        };
+    }
+}
+
+impl std::fmt::Display for GameSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let side_to_move = if self.side_to_move == WHITE { "White" } else { "Black" };
+        let en_passant_square = self.en_passant_square.map_or("-".to_string(), |square| square.to_string());
+        writeln!(f, "Side to move:     {}", side_to_move)?;
+        writeln!(f, "Material balance: {:+}", self.material_balance)?;
+        writeln!(f, "In check:         {}", if self.in_check { "yes" } else { "no" })?;
+        writeln!(f, "Castling rights:  {}", self.castling_rights)?;
+        writeln!(f, "En passant:       {}", en_passant_square)?;
+        writeln!(f, "Halfmove clock:   {}", self.halfmove_clock)?;
+        write!(f, "Game state:       {} ({}", self.game_state, self.result())?;
+        if let Some((_, termination)) = self.ending {
+            write!(f, ", {}", termination)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Game {
+
+    /// Returns an overview of the current position
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::new("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3");
+    /// let summary = game.summary();
+    /// assert_eq!(summary.game_state, GameState::Checkmate);
+    /// assert_eq!(summary.result(), "0-1");
+    /// assert_eq!(summary.castling_rights.to_string(), "KQkq");
+    /// println!("{}", summary);
+    /// ```
+    pub fn summary(&self) -> GameSummary {
+        return GameSummary {
+            side_to_move: self.turn,
+            material_balance: self.material_balance(),
+            in_check: self.is_in_check(),
+            castling_rights: self.castling_rights(),
+            en_passant_square: self.en_passant_square(),
+            halfmove_clock: self.halfmove_clock(),
+            game_state: self.get_game_state(),
+            ending: self.ending
+        };
+    }
+
+    /// Ends the game off the board with the result, for example by a resignation, a loss on time or adjudication.
+    /// The ending is kept until a move is made or taken back and is written to PGN with a Termination tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::Notation;
+    /// # use olindba_chess::pgn::{parse_game, PgnGame};
+    /// let mut game = Game::starting_position();
+    /// game.apply_move_list("e4", Notation::San).unwrap();
+    /// game.end(game.timeout_result(BLACK), Termination::TimeForfeit);
+    /// assert_eq!(game.result(), Some(GameResult::WhiteWins));
+    /// assert_eq!(game.summary().termination(), Some(Termination::TimeForfeit));
+    ///
+    /// let pgn = PgnGame::from(&game);
+    /// assert_eq!(pgn.header("Termination"), Some("time forfeit"));
+    /// assert_eq!(pgn.to_string(), "[Result \"1-0\"]\n[Termination \"time forfeit\"]\n\n1. e4 1-0\n");
+    /// let read = parse_game(&pgn.to_string()).unwrap().final_position();
+    /// assert_eq!(read.ending(), Some((GameResult::WhiteWins, Termination::TimeForfeit)));
+    ///
+    /// game.unmake_move();
+    /// assert_eq!(game.result(), None);
+    /// ```
+    pub fn end(&mut self, result: GameResult, termination: Termination) {
+        self.ending = Some((result, termination));
+    }
+
+    /// Ends the game with a resignation by the given color
+    pub fn resign(&mut self, color: usize) {
+        self.end(GameResult::win_for(color ^ 1), Termination::Resignation);
+    }
+
+    /// Returns the result and reason the game was ended with by [Game::end], if it was
+    pub fn ending(&self) -> Option<(GameResult, Termination)> {
+        return self.ending;
+    }
+
+    /// Returns why the game ended: the reason it was ended with, or [Termination::Normal] if it is over on the
+    /// board. Returns None if the game isn't over.
+    pub fn termination(&self) -> Option<Termination> {
+        if let Some((_, termination)) = self.ending {
+            return Some(termination);
+        }
+        return self.result().map(|_| Termination::Normal);
+    }
+
+    /// Returns the result of the game: the one it was [ended](Game::end) with, or the result under its
+    /// [crate::Rules] if it is over on the board, or None if the game isn't over.
+    /// Draws that can only be claimed, by the 50-move rule or insufficient material, count as draws.
+    pub fn result(&self) -> Option<GameResult> {
+        if let Some((result, _)) = self.ending {
+            return Some(result);
+        }
+        return match self.get_game_state() {
+            GameState::InProgress | GameState::Check => None,
+            GameState::Checkmate => Some(GameResult::win_for(self.turn ^ 1)),
+            GameState::Stalemate => match self.rules().stalemate {
+                StalemateOutcome::Draw => Some(GameResult::Draw),
+                StalemateOutcome::Win => Some(GameResult::win_for(self.turn)),
+                StalemateOutcome::Loss => Some(GameResult::win_for(self.turn ^ 1))
+            },
+            _ => Some(GameResult::Draw)
+        };
+    }
+
+    /// Returns the material of White minus the material of Black in centipawns, kings not counted
+    pub fn material_balance(&self) -> i32 {
+        let mut material_balance = 0;
+        for square in 0..64 {
+            let piece = self.board[square];
+            if piece.get_type() == EMPTY || piece.get_type() == KING {
+                continue;
+            }
+            if piece.get_color() == WHITE {
+                material_balance += piece_value(piece.get_type());
+            }
+            else {
+                material_balance -= piece_value(piece.get_type());
+            }
+        }
+        return material_balance;
+    }
+}
@@ -0,0 +1,389 @@
+//! Endgame tablebases generated inside the crate
+//!
+//! [Tablebase::generate] computes the result of every position of an endgame with at most four pieces, kings
+//! included, by retrograde analysis: starting from the checkmates it takes moves back with
+//! [Game::predecessor_moves](crate::Game::predecessor_moves) and marks the positions from which one side can force
+//! them, one ply further from mate at a time. Captures and promotions lead to smaller endgames, which are generated
+//! first. Every position not won for either side is a draw.
+//!
+//! Besides win, draw or loss the tables keep the number of plies to mate with perfect play, so
+//! [Tablebase::best_move] makes progress instead of shuffling between won positions. In a release build KQK and KRK
+//! take a few seconds and KPK, which needs the tables of the pieces the pawn promotes to, about half a minute. The
+//! four piece endgames are 64 times larger.
+//!
+//! Positions with castling rights or a possible en passant capture aren't in the tables.
+//!
+//! A [Tablebase] also implements [matchplay::Tablebase], so a [Match](matchplay::Match) can adjudicate its games
+//! with it once they reach a generated endgame.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use olindba_chess::*;
+//! # use olindba_chess::tablebase::{Probe, Tablebase, Wdl};
+//! let mut tablebase = Tablebase::new();
+//! tablebase.generate(&[PAWN], &[]);
+//!
+//! // With White to move the king gets in front of the pawn, with Black to move the black king takes the opposition
+//! let probe = tablebase.probe(&Game::new("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1")).unwrap();
+//! assert_eq!(probe, Probe { wdl: Wdl::Win, plies: 43 });
+//! let probe = tablebase.probe(&Game::new("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1")).unwrap();
+//! assert_eq!(probe.wdl, Wdl::Draw);
+//! // The same endgame with the colors swapped
+//! let probe = tablebase.probe(&Game::new("8/4p3/8/4k3/8/4K3/8/8 w - - 0 1")).unwrap();
+//! assert_eq!(probe.wdl, Wdl::Loss);
+//! ```
+
+use std::collections::HashMap;
+
+use crate::matchplay;
+use crate::{CastlingRights, Game, GameResult, Move, MoveList, Piece, BLACK, EMPTY, HAS_MOVED, KING, KNIGHT, PAWN, QUEEN, WHITE};
+
+/// The result of a position for the side to move
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Wdl {
+    Loss,
+    Draw,
+    Win
+}
+
+/// A position looked up in a [Tablebase]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Probe {
+    pub wdl: Wdl,
+    /// The number of plies until mate with perfect play, 0 for a draw
+    pub plies: usize
+}
+
+/// The material of an endgame besides the kings, the piece types of each color in ascending order
+type Material = (Vec<usize>, Vec<usize>);
+
+const DRAW: u16 = u16::MAX;
+const LOSS: u16 = 0x8000;
+
+/// The results of every position of one endgame, indexed by the side to move and the squares of the kings and the
+/// other pieces
+struct Table {
+    pieces: Vec<(usize, usize)>,
+    results: Vec<u16>,
+    empty: Game
+}
+
+impl Table {
+
+    fn size(n_pieces: usize) -> usize {
+        return 2 * 64usize.pow(n_pieces as u32 + 2);
+    }
+
+    /// Returns the position of the index, or None if it isn't a legal position
+    fn position(&self, index: usize) -> Option<Game> {
+        let mut squares = vec![];
+        let mut rest = index;
+        for _ in 0..self.pieces.len() + 2 {
+            squares.push(rest % 64);
+            rest /= 64;
+        }
+        let turn = rest;
+
+        let mut game = self.empty.clone();
+        let pieces = [(KING, WHITE), (KING, BLACK)].into_iter().chain(self.pieces.iter().copied());
+        for (&square, (piece_type, color)) in squares.iter().zip(pieces) {
+            if game.board[square].get_type() != EMPTY {
+                return None;
+            }
+            // Nothing is marked unmoved, so nothing can castle
+            game.board[square] = Piece::new(piece_type, color, HAS_MOVED);
+        }
+        game.king_square = [squares[0], squares[1]];
+        game.turn = turn;
+        if game.check_invariants().is_err() {
+            return None;
+        }
+        return Some(game);
+    }
+
+    /// Returns the index of the position, which has to have the material of the table
+    fn index(&self, game: &Game) -> usize {
+        let mut squares = vec![game.king_square[WHITE], game.king_square[BLACK]];
+        let mut used = 0u64;
+        for &(piece_type, color) in self.pieces.iter() {
+            // Pieces of the same type are taken in the order of their squares
+            let square = (0..64).find(|&square| used & (1 << square) == 0
+                && game.board[square].get_type() == piece_type && game.board[square].get_color() == color).unwrap();
+            used |= 1 << square;
+            squares.push(square);
+        }
+        return squares.iter().rev().fold(game.turn, |index, &square| index * 64 + square);
+    }
+
+    fn get(&self, game: &Game) -> Probe {
+        return decode(self.results[self.index(game)]);
+    }
+}
+
+fn decode(result: u16) -> Probe {
+    if result == DRAW {
+        return Probe { wdl: Wdl::Draw, plies: 0 };
+    }
+    if result & LOSS != 0 {
+        return Probe { wdl: Wdl::Loss, plies: (result & !LOSS) as usize };
+    }
+    return Probe { wdl: Wdl::Win, plies: result as usize };
+}
+
+/// Returns the material of the position besides the kings
+fn material(game: &Game) -> Material {
+    let mut material: Material = (vec![], vec![]);
+    for square in 0..64 {
+        let piece = game.board[square];
+        if piece.get_type() != EMPTY && piece.get_type() != KING {
+            if piece.get_color() == WHITE {
+                material.0.push(piece.get_type());
+            }
+            else {
+                material.1.push(piece.get_type());
+            }
+        }
+    }
+    material.0.sort();
+    material.1.sort();
+    return material;
+}
+
+/// Returns the legal moves of the position
+fn legal_moves(game: &Game) -> MoveList {
+    let mut moves = MoveList::new();
+    game.legal_moves_into(&mut moves);
+    return moves;
+}
+
+/// Endgame tables generated in memory, see the [module documentation](self)
+#[derive(Default)]
+pub struct Tablebase {
+    tables: HashMap<Material, Table>
+}
+
+impl Tablebase {
+
+    pub fn new() -> Tablebase {
+        Tablebase {
+            tables: HashMap::new()
+        }
+    }
+
+    /// Generates the table of the endgame with the given pieces besides the kings, and the tables of every
+    /// endgame it can turn into by captures and promotions. Tables that already exist aren't generated again.
+    ///
+    /// # Panics
+    /// If there are more than two pieces besides the kings or a piece is a king or not a piece at all
+    pub fn generate(&mut self, white: &[usize], black: &[usize]) {
+        assert!(white.len() + black.len() <= 2, "only endgames of up to four pieces can be generated");
+        assert!(white.iter().chain(black).all(|piece_type| (PAWN..=QUEEN).contains(piece_type)), "not a piece");
+        let mut key: Material = (white.to_vec(), black.to_vec());
+        key.0.sort();
+        key.1.sort();
+        if self.tables.contains_key(&key) {
+            return;
+        }
+
+        for color in [WHITE, BLACK] {
+            let pieces = if color == WHITE { &key.0 } else { &key.1 };
+            for i in 0..pieces.len() {
+                let mut fewer = pieces.clone();
+                let piece_type = fewer.remove(i);
+                let mut children = vec![fewer.clone()];
+                if piece_type == PAWN {
+                    children.extend((KNIGHT..=QUEEN).map(|promotion| [fewer.clone(), vec![promotion]].concat()));
+                }
+                for child in children {
+                    if color == WHITE {
+                        self.generate(&child, &key.1);
+                    }
+                    else {
+                        self.generate(&key.0, &child);
+                    }
+                }
+            }
+        }
+
+        let pieces = key.0.iter().map(|&piece_type| (piece_type, WHITE))
+            .chain(key.1.iter().map(|&piece_type| (piece_type, BLACK)))
+            .collect();
+        let table = self.solve(pieces);
+        self.tables.insert(key, table);
+    }
+
+    /// Computes the table of the endgame, whose smaller endgames have been generated
+    fn solve(&self, pieces: Vec<(usize, usize)>) -> Table {
+        let size = Table::size(pieces.len());
+        let mut table = Table { pieces, results: vec![DRAW; size], empty: Game::new("8/8/8/8/8/8/8/8 w - - 0 1") };
+        let mut solved = vec![false; size];
+        // The moves staying in the table whose result isn't known yet
+        let mut remaining = vec![0u8; size];
+        // The longest loss a move leaving the table leads to, or DRAW if one of them draws or wins
+        let mut exit_loss = vec![0u16; size];
+        // Positions to be solved, by their number of plies to mate and whether they are won
+        let mut queue: Vec<Vec<(usize, bool)>> = vec![vec![]];
+        let push = |queue: &mut Vec<Vec<(usize, bool)>>, plies: usize, index: usize, win: bool| {
+            if queue.len() <= plies {
+                queue.resize(plies + 1, vec![]);
+            }
+            queue[plies].push((index, win));
+        };
+
+        for index in 0..size {
+            let game = match table.position(index) {
+                Some(game) => game,
+                None => continue
+            };
+            // Identical pieces on swapped squares are the same position, which only has the index of its board
+            if table.index(&game) != index {
+                continue;
+            }
+            let moves = legal_moves(&game);
+            if moves.is_empty() {
+                if game.is_in_check() {
+                    push(&mut queue, 0, index, false);
+                }
+                else {
+                    solved[index] = true;
+                }
+                continue;
+            }
+
+            let mut shortest_win = None;
+            for &mv in moves.iter() {
+                if !mv.is_capture() && !mv.is_promotion() {
+                    remaining[index] += 1;
+                    continue;
+                }
+                let mut child = game.clone();
+                child.make_move(mv);
+                let probe = self.tables[&material(&child)].get(&child);
+                match probe.wdl {
+                    Wdl::Loss => shortest_win = Some(shortest_win.map_or(probe.plies + 1, |plies: usize| plies.min(probe.plies + 1))),
+                    Wdl::Draw => exit_loss[index] = DRAW,
+                    Wdl::Win if exit_loss[index] != DRAW => {
+                        exit_loss[index] = exit_loss[index].max(probe.plies as u16 + 1);
+                    }
+                    Wdl::Win => {}
+                }
+            }
+            if let Some(plies) = shortest_win {
+                exit_loss[index] = DRAW;
+                push(&mut queue, plies, index, true);
+            }
+            else if remaining[index] == 0 && exit_loss[index] != DRAW {
+                push(&mut queue, exit_loss[index] as usize, index, false);
+            }
+        }
+
+        let mut plies = 0;
+        while plies < queue.len() {
+            let positions = std::mem::take(&mut queue[plies]);
+            for (index, win) in positions {
+                if solved[index] {
+                    continue;
+                }
+                solved[index] = true;
+                table.results[index] = if win { plies as u16 } else { LOSS | plies as u16 };
+
+                let game = table.position(index).unwrap();
+                for unmove in game.quiet_predecessor_moves() {
+                    let predecessor = table.index(&game.predecessor(&unmove));
+                    if solved[predecessor] {
+                        continue;
+                    }
+                    if !win {
+                        push(&mut queue, plies + 1, predecessor, true);
+                        continue;
+                    }
+                    remaining[predecessor] -= 1;
+                    if remaining[predecessor] == 0 && exit_loss[predecessor] != DRAW {
+                        push(&mut queue, (plies + 1).max(exit_loss[predecessor] as usize), predecessor, false);
+                    }
+                }
+            }
+            plies += 1;
+        }
+        return table;
+    }
+
+    /// Returns the result of the position for the side to move, or None if its endgame hasn't been generated or
+    /// it has castling rights or a possible en passant capture. An endgame generated for one color is also
+    /// probed for the other.
+    pub fn probe(&self, game: &Game) -> Option<Probe> {
+        if game.castling_rights() != CastlingRights::default() || legal_moves(game).iter().any(|mv| mv.is_ep_capture()) {
+            return None;
+        }
+        let (white, black) = material(game);
+        if let Some(table) = self.tables.get(&(white.clone(), black.clone())) {
+            return Some(table.get(game));
+        }
+        let mirrored = game.mirrored();
+        return self.tables.get(&(black, white)).map(|table| table.get(&mirrored));
+    }
+
+    /// Returns the move with the best result for the side to move: the fastest win, a draw, or the slowest loss.
+    /// Returns None if the position can't be probed or has no legal moves.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::to_uci;
+    /// # use olindba_chess::tablebase::Tablebase;
+    /// let mut tablebase = Tablebase::new();
+    /// tablebase.generate(&[ROOK], &[]);
+    /// let game = Game::new("k7/8/1K6/8/8/8/8/7R w - - 0 1");
+    /// assert_eq!(to_uci(&tablebase.best_move(&game).unwrap()), "h1h8");
+    /// ```
+    pub fn best_move(&self, game: &Game) -> Option<Move> {
+        self.probe(game)?;
+        let mut best = None;
+        let mut best_score = i64::MIN;
+        for &mv in legal_moves(game).iter() {
+            let mut child = game.clone();
+            child.make_move(mv);
+            let probe = self.probe(&child)?;
+            // The score of the move for the side making it
+            let score = match probe.wdl {
+                Wdl::Loss => 1_000_000 - probe.plies as i64,
+                Wdl::Draw => 0,
+                Wdl::Win => -1_000_000 + probe.plies as i64
+            };
+            if score > best_score {
+                best = Some(mv);
+                best_score = score;
+            }
+        }
+        return best;
+    }
+}
+
+impl matchplay::Tablebase for Tablebase {
+    /// Returns the result of the position with perfect play, for adjudicating the games of a
+    /// [Match](matchplay::Match)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::matchplay::{self, Match, RandomPlayer};
+    /// # use olindba_chess::tablebase::Tablebase;
+    /// let mut tablebase = Tablebase::new();
+    /// tablebase.generate(&[ROOK], &[]);
+    /// let game = Game::new("k7/8/1K6/8/8/8/8/7R b - - 0 1");
+    /// assert_eq!(matchplay::Tablebase::probe(&tablebase, &game), Some(GameResult::WhiteWins));
+    ///
+    /// let mut random_match = Match::new(Box::new(RandomPlayer::new(1)), Box::new(RandomPlayer::new(2)));
+    /// random_match.set_tablebase(Box::new(tablebase));
+    /// ```
+    fn probe(&self, game: &Game) -> Option<GameResult> {
+        return match Tablebase::probe(self, game)?.wdl {
+            Wdl::Win => Some(GameResult::win_for(game.turn)),
+            Wdl::Draw => Some(GameResult::Draw),
+            Wdl::Loss => Some(GameResult::win_for(game.turn ^ 1))
+        };
+    }
+}
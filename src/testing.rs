@@ -0,0 +1,80 @@
+//! [proptest] strategies for property-based testing of code built on the crate
+//!
+//! The games are made by playing random legal moves, so every generated position is reachable and shrinks towards
+//! shorter games with earlier moves in the move lists.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::testing::game;
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(game in game(40))| {
+//!     prop_assert_eq!(game.check_invariants(), Ok(()));
+//!     for mv in game.get_all_legal_moves() {
+//!         let mut next = game.clone();
+//!         next.make_move(mv);
+//!         prop_assert_eq!(next.check_invariants(), Ok(()));
+//!     }
+//! });
+//! ```
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+use crate::{Game, Move};
+
+/// Plays the move picked by each index until the indices run out or the game has no legal moves
+fn play(game: &mut Game, indices: &[Index]) -> Vec<Move> {
+    let mut moves = vec![];
+    for index in indices {
+        let legal_moves = game.legal_moves_cached();
+        if legal_moves.is_empty() {
+            break;
+        }
+        let mv = *index.get(&legal_moves);
+        game.make_move(mv);
+        moves.push(mv);
+    }
+    return moves;
+}
+
+/// Generates games of up to 'max_plies' random legal moves from the starting position
+pub fn game(max_plies: usize) -> impl Strategy<Value = Game> {
+    return game_from(Game::starting_position(), max_plies);
+}
+
+/// Generates games of up to 'max_plies' random legal moves from the given position
+pub fn game_from(start: Game, max_plies: usize) -> impl Strategy<Value = Game> {
+    return vec(any::<Index>(), 0..=max_plies).prop_map(move |indices| {
+        let mut game = start.clone();
+        play(&mut game, &indices);
+        return game;
+    });
+}
+
+/// Generates sequences of up to 'max_plies' legal moves from the given position, fewer if the game ends first
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::testing::move_sequence;
+/// use proptest::prelude::*;
+///
+/// let start = Game::new("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+/// proptest!(|(moves in move_sequence(start.clone(), 10))| {
+///     let mut game = start.clone();
+///     for mv in moves {
+///         prop_assert!(game.get_all_legal_moves().contains(&mv));
+///         game.make_move(mv);
+///     }
+/// });
+/// ```
+pub fn move_sequence(start: Game, max_plies: usize) -> impl Strategy<Value = Vec<Move>> {
+    return vec(any::<Index>(), 0..=max_plies).prop_map(move |indices| {
+        let mut game = start.clone();
+        return play(&mut game, &indices);
+    });
+}
@@ -0,0 +1,134 @@
+use crate::{Game, Move, PositionSnapshot};
+
+/// The positions of a finished or paused game for a viewer to jump between, for example when scrubbing through a
+/// long game. Every few plies a [PositionSnapshot] is kept, so a jump restores the nearest one before the target
+/// and replays at most that many moves instead of all moves from the start. Short steps make or take back the
+/// moves in between. The games returned have the history and annotations of the moves leading to them.
+///
+/// # Examples
+///
+/// ```
+/// # use olindba_chess::*;
+/// # use olindba_chess::fen::to_fen;
+/// # use olindba_chess::notation::Notation;
+/// let mut game = Game::starting_position();
+/// game.apply_move_list("1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6", Notation::San)
+///     .unwrap();
+/// game.annotate(4, Annotation { comment: Some("The Ruy Lopez".to_string()), ..Default::default() });
+///
+/// let mut timeline = Timeline::with_interval(&game, 4);
+/// assert_eq!(timeline.len(), 14);
+/// assert_eq!(timeline.ply(), 14);
+///
+/// let position = timeline.seek_to(5);
+/// assert_eq!(to_fen(position), "r1bqkbnr/pppp1ppp/2n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3");
+/// assert_eq!(position.history().len(), 5);
+/// assert_eq!(position.annotation(4).unwrap().comment.as_deref(), Some("The Ruy Lopez"));
+///
+/// // Steps of a ply make or take back the move
+/// assert_eq!(timeline.seek_to(6).last_move(), Some(game.history()[5].mv));
+/// assert_eq!(timeline.seek_to(0).ply(), 0);
+/// assert_eq!(to_fen(timeline.seek_to(100)), to_fen(&game));
+/// ```
+pub struct Timeline {
+    /// The game at its last ply
+    end: Game,
+    moves: Vec<Move>,
+    /// The positions at every multiple of the interval
+    snapshots: Vec<PositionSnapshot>,
+    interval: usize,
+    current: Game
+}
+
+impl Timeline {
+
+    /// The number of plies between snapshots used by [Timeline::new]
+    pub const DEFAULT_INTERVAL: usize = 16;
+
+    /// Creates a timeline of the moves made in the game, standing at its current position
+    pub fn new(game: &Game) -> Timeline {
+        return Timeline::with_interval(game, Timeline::DEFAULT_INTERVAL);
+    }
+
+    /// Creates a timeline keeping a snapshot every 'interval' plies, more often costs memory and less often makes
+    /// jumps replay more moves
+    ///
+    /// # Panics
+    /// If the interval is 0
+    pub fn with_interval(game: &Game, interval: usize) -> Timeline {
+        assert!(interval > 0, "the interval between snapshots can't be 0");
+        let moves: Vec<Move> = game.history().iter().map(|entry| entry.mv).collect();
+        let mut snapshots = vec![];
+        let mut start = game.clone();
+        loop {
+            if start.ply().is_multiple_of(interval) {
+                snapshots.push(start.position_snapshot());
+            }
+            if start.unmake_move().is_none() {
+                break;
+            }
+        }
+        snapshots.reverse();
+        return Timeline { end: game.clone(), moves, snapshots, interval, current: game.clone() };
+    }
+
+    /// Returns the number of plies of the game
+    pub fn len(&self) -> usize {
+        return self.moves.len();
+    }
+
+    /// Returns true if no moves were made in the game
+    pub fn is_empty(&self) -> bool {
+        return self.moves.is_empty();
+    }
+
+    /// Returns the ply of the position the timeline stands at
+    pub fn ply(&self) -> usize {
+        return self.current.ply();
+    }
+
+    /// Returns the position the timeline stands at
+    pub fn game(&self) -> &Game {
+        return &self.current;
+    }
+
+    /// Returns the moves of the game
+    pub fn moves(&self) -> &[Move] {
+        return &self.moves;
+    }
+
+    /// Goes to the position after the given number of plies, or the last position if the game is shorter, and
+    /// returns it
+    pub fn seek_to(&mut self, ply: usize) -> &Game {
+        let ply = ply.min(self.len());
+        let current = self.current.ply();
+        if ply == self.len() {
+            self.current = self.end.clone();
+        }
+        else if ply <= current && current - ply <= self.interval {
+            for _ in ply..current {
+                self.current.unmake_move();
+            }
+        }
+        else if ply > current && ply - current <= self.interval {
+            self.replay_to(ply);
+        }
+        else {
+            let mut game = self.end.clone();
+            game.restore(&self.snapshots[ply / self.interval]);
+            self.current = game;
+            self.replay_to(ply);
+        }
+        return &self.current;
+    }
+
+    /// Makes the moves from the current position up to the ply, with their annotations
+    fn replay_to(&mut self, ply: usize) {
+        for made in self.current.ply()..ply {
+            self.current.make_move(self.moves[made]);
+            if let Some(annotation) = self.end.annotation(made) {
+                self.current.annotate(made, annotation.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{Game, GameResult, Square, SquareColor, TimeoutRule, BISHOP, EMPTY, KING, KNIGHT};
+
+impl Game {
+
+    /// Returns true if the given color has the material to win when its opponent runs out of time, under the
+    /// [TimeoutRule] of the game's rules.
+    ///
+    /// With [TimeoutRule::Fide] this is false when the color can't checkmate by any series of legal moves: with a
+    /// lone king, a single knight against a lone king, bishops on squares of one color against a king with at most
+    /// bishops on squares of that color, or when the position is dead as recognized by [Game::is_dead_position].
+    /// With [TimeoutRule::Uscf] only the material of the color counts, and the exception for positions with a forced
+    /// mate isn't recognized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// // A knight can't mate a bare king, but the black pawn could block its king's escape
+    /// assert!(!Game::new("4k3/8/8/8/8/8/8/4KN2 w - - 0 1").can_force_mate(WHITE));
+    /// assert!(Game::new("4k3/4p3/8/8/8/8/8/4KN2 w - - 0 1").can_force_mate(WHITE));
+    ///
+    /// let mut game = Game::new("4k3/4p3/8/8/8/8/8/4KN2 w - - 0 1");
+    /// game.set_rules(Rules { timeout: TimeoutRule::Uscf, ..Default::default() });
+    /// assert!(!game.can_force_mate(WHITE));
+    /// assert!(game.can_force_mate(BLACK));
+    /// ```
+    pub fn can_force_mate(&self, color: usize) -> bool {
+        let material = [Material::of(self, color), Material::of(self, color ^ 1)];
+        if material[0].pieces == 0 {
+            return false;
+        }
+        if self.rules.timeout == TimeoutRule::Uscf {
+            let minor_pieces = material[0].knights + material[0].bishops;
+            let only_minors = material[0].pieces == minor_pieces;
+            return !(only_minors && (minor_pieces == 1 || (minor_pieces == 2 && material[0].knights == 2)));
+        }
+
+        if material[0].pieces == 1 && material[0].knights == 1 && material[1].pieces == 0 {
+            return false;
+        }
+        // Bishops alone can't cover the squares of the other color around the king, and only bishops of that color
+        // could block them
+        let only_bishops = |material: &Material| material.pieces == material.bishops;
+        for (square_color, other_color) in [(0, 1), (1, 0)] {
+            if only_bishops(&material[0]) && only_bishops(&material[1]) && !material[0].bishop_colors[other_color]
+                && material[0].bishop_colors[square_color] && !material[1].bishop_colors[other_color] {
+                return false;
+            }
+        }
+        return !self.is_dead_position();
+    }
+
+    /// Returns the result of the game when the given color runs out of time: a win for the opponent if it
+    /// [can force mate](Game::can_force_mate), a draw otherwise
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::new("4k3/8/8/8/8/8/8/4KB2 b - - 0 1");
+    /// assert_eq!(game.timeout_result(BLACK), GameResult::Draw);
+    /// assert_eq!(game.timeout_result(WHITE), GameResult::Draw);
+    /// assert_eq!(Game::starting_position().timeout_result(WHITE), GameResult::BlackWins);
+    /// // Bishops on squares of the same color can't mate, on squares of different colors they can
+    /// assert_eq!(Game::new("4k3/8/8/8/8/8/2b5/4KB2 b - - 0 1").timeout_result(BLACK), GameResult::Draw);
+    /// assert_eq!(Game::new("4k3/8/8/8/8/8/1b6/4KB2 b - - 0 1").timeout_result(BLACK), GameResult::WhiteWins);
+    /// ```
+    pub fn timeout_result(&self, flagged: usize) -> GameResult {
+        if self.can_force_mate(flagged ^ 1) {
+            return GameResult::win_for(flagged ^ 1);
+        }
+        return GameResult::Draw;
+    }
+}
+
+/// The pieces of one color other than the king
+struct Material {
+    pieces: usize,
+    knights: usize,
+    bishops: usize,
+    /// Whether there are bishops on light and on dark squares
+    bishop_colors: [bool; 2]
+}
+
+impl Material {
+
+    fn of(game: &Game, color: usize) -> Material {
+        let mut material = Material { pieces: 0, knights: 0, bishops: 0, bishop_colors: [false; 2] };
+        for square in 0..64 {
+            let piece = game.board[square];
+            if piece.get_color() != color || piece.get_type() == EMPTY || piece.get_type() == KING {
+                continue;
+            }
+            material.pieces += 1;
+            if piece.get_type() == KNIGHT {
+                material.knights += 1;
+            }
+            else if piece.get_type() == BISHOP {
+                material.bishops += 1;
+                material.bishop_colors[(Square::new(square).color() == SquareColor::Dark) as usize] = true;
+            }
+        }
+        return material;
+    }
+}
@@ -0,0 +1,268 @@
+//! A transposition table for search
+//!
+//! The table stores the result of searching a position under its [Zobrist hash](crate::Game::hash): the best move,
+//! the score with the kind of [Bound] it is, and the depth searched to. Each entry is packed into two 64-bit words,
+//! one holding the hash to verify that a stored entry belongs to the probed position and one holding the rest.
+//! When two positions compete for the same slot the [Replacement] scheme decides which one is kept.
+//!
+//! The table is used by [crate::engine::Engine] and can be used on its own by other searches. Following the best
+//! moves stored for a position and its successors gives the [principal variation](TranspositionTable::principal_variation).
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::transposition::{Bound, Entry, TranspositionTable};
+//! let mut table = TranspositionTable::new(1);
+//! let game = Game::starting_position();
+//! let best_move = game.get_all_legal_moves()[0];
+//! table.store(game.hash(), Entry { best_move: Some(best_move), score: 25, depth: 6, bound: Bound::Exact });
+//!
+//! let entry = table.probe(game.hash()).unwrap();
+//! assert_eq!(entry.best_move, Some(best_move));
+//! assert_eq!((entry.score, entry.depth, entry.bound), (25, 6, Bound::Exact));
+//! assert_eq!(table.probe(Game::new("4k3/8/8/8/8/8/8/4K3 w - - 0 1").hash()), None);
+//! ```
+
+use crate::{Game, Move};
+
+/// What a stored score says about the real score of the position
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Bound {
+    /// The score is exact
+    #[default]
+    Exact,
+    /// The search failed high, the real score is at least the stored score
+    Lower,
+    /// The search failed low, the real score is at most the stored score
+    Upper
+}
+
+/// Which entry is kept when a new entry maps to a slot that holds another position
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Replacement {
+    /// The new entry always replaces the old one
+    Always,
+    /// The old entry is only replaced by an entry searched at least as deep, unless it was stored
+    /// before the last call to [TranspositionTable::new_search]
+    #[default]
+    DepthPreferred
+}
+
+/// The result of searching a position
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub best_move: Option<Move>,
+    /// The score, which is stored in 16 bits
+    pub score: i16,
+    pub depth: u8,
+    pub bound: Bound
+}
+
+impl Entry {
+
+    /// Packs the entry with the generation it was stored in: the move in bits 0-15, the score in bits 16-31,
+    /// the depth in bits 32-39, the bound in bits 40-41 and the generation in bits 42-49.
+    /// Bit 50 is set for every stored entry so an empty slot is never mistaken for one, and bit 51 when there
+    /// is a move.
+    fn pack(&self, generation: u8) -> u64 {
+        let best_move = self.best_move.map_or(0, |mv| mv.to_u16() as u64 | 1 << 51);
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2
+        };
+        return best_move | (self.score as u16 as u64) << 16 | (self.depth as u64) << 32 | bound << 40
+            | (generation as u64) << 42 | 1 << 50;
+    }
+
+    fn unpack(data: u64) -> Entry {
+        let bound = match (data >> 40) & 0x3 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper
+        };
+        return Entry {
+            best_move: Some(data as u16).filter(|_| data & 1 << 51 != 0).and_then(Move::from_u16),
+            score: (data >> 16) as u16 as i16,
+            depth: (data >> 32) as u8,
+            bound
+        };
+    }
+}
+
+/// A slot of the table, empty when 'data' is 0
+#[derive(Copy, Clone, Default)]
+struct Slot {
+    key: u64,
+    data: u64
+}
+
+impl Slot {
+
+    fn generation(&self) -> u8 {
+        return (self.data >> 42) as u8;
+    }
+
+    fn depth(&self) -> u8 {
+        return (self.data >> 32) as u8;
+    }
+}
+
+/// A hash table of search results with a fixed size, see the [module documentation](self)
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    replacement: Replacement,
+    generation: u8
+}
+
+impl TranspositionTable {
+
+    /// Creates a table that uses the given number of megabytes, with [Replacement::DepthPreferred]
+    pub fn new(megabytes: usize) -> TranspositionTable {
+        return TranspositionTable::with_replacement(megabytes, Replacement::default());
+    }
+
+    pub fn with_replacement(megabytes: usize, replacement: Replacement) -> TranspositionTable {
+        let mut table = TranspositionTable {
+            slots: vec![],
+            replacement,
+            generation: 0
+        };
+        table.resize(megabytes);
+        return table;
+    }
+
+    /// Changes the size of the table to the given number of megabytes, which removes all entries.
+    /// The table always has room for at least one entry.
+    pub fn resize(&mut self, megabytes: usize) {
+        let n_slots = (megabytes * 1024 * 1024 / std::mem::size_of::<Slot>()).max(1);
+        self.slots = vec![Slot::default(); n_slots];
+        self.generation = 0;
+    }
+
+    /// Removes all entries
+    pub fn clear(&mut self) {
+        self.slots.fill(Slot::default());
+        self.generation = 0;
+    }
+
+    /// Returns the number of entries the table has room for
+    pub fn capacity(&self) -> usize {
+        return self.slots.len();
+    }
+
+    /// Marks the entries stored so far as old, call it before every search.
+    /// With [Replacement::DepthPreferred] old entries are replaced regardless of depth.
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Returns the entry stored for the position with the given hash
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        let slot = &self.slots[self.index(key)];
+        if slot.data == 0 || slot.key != key {
+            return None;
+        }
+        return Some(Entry::unpack(slot.data));
+    }
+
+    /// Stores an entry for the position with the given hash, see [Replacement] for when it is kept.
+    /// A new entry for the same position always replaces the old one, but keeps its move if it has none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::transposition::{Bound, Entry, Replacement, TranspositionTable};
+    /// // With a single slot every position competes for it
+    /// let mut table = TranspositionTable::with_replacement(0, Replacement::DepthPreferred);
+    /// let deep = Entry { best_move: None, score: 10, depth: 8, bound: Bound::Lower };
+    /// let shallow = Entry { best_move: None, score: -10, depth: 2, bound: Bound::Upper };
+    /// table.store(1, deep);
+    /// table.store(2, shallow);
+    /// assert_eq!(table.probe(1), Some(deep));
+    /// assert_eq!(table.probe(2), None);
+    ///
+    /// table.new_search();
+    /// table.store(2, shallow);
+    /// assert_eq!(table.probe(2), Some(shallow));
+    /// ```
+    pub fn store(&mut self, key: u64, mut entry: Entry) {
+        let generation = self.generation;
+        let replacement = self.replacement;
+        let index = self.index(key);
+        let slot = &mut self.slots[index];
+        if slot.data != 0 && slot.key == key {
+            if entry.best_move.is_none() {
+                entry.best_move = Entry::unpack(slot.data).best_move;
+            }
+        }
+        else if slot.data != 0 && replacement == Replacement::DepthPreferred
+            && slot.generation() == generation && slot.depth() > entry.depth {
+            return;
+        }
+        slot.key = key;
+        slot.data = entry.pack(generation);
+    }
+
+    /// Returns the principal variation of the position by following the best moves stored for it and the positions
+    /// after them, at most 'max_length' moves. The line ends at a position without a stored move, at a stored move
+    /// that isn't legal, which happens when another position with the same slot overwrote the entry, and when a
+    /// position repeats.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::notation::{parse_san, to_san};
+    /// # use olindba_chess::transposition::{Bound, Entry, TranspositionTable};
+    /// let mut table = TranspositionTable::new(1);
+    /// let game = Game::starting_position();
+    /// let mut position = game.clone();
+    /// for san in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+    ///     let mv = parse_san(&position, san).unwrap();
+    ///     table.store(position.hash(), Entry { best_move: Some(mv), score: 0, depth: 1, bound: Bound::Exact });
+    ///     position.make_move(mv);
+    /// }
+    /// // The line stops when the starting position comes back
+    /// let pv = table.principal_variation(&game, 10);
+    /// assert_eq!(pv.len(), 4);
+    /// assert_eq!(table.principal_variation(&game, 2).len(), 2);
+    /// ```
+    pub fn principal_variation(&self, game: &Game, max_length: usize) -> Vec<Move> {
+        let mut position = game.clone();
+        let mut seen = vec![];
+        let mut pv = vec![];
+        while pv.len() < max_length {
+            let key = position.hash();
+            if seen.contains(&key) {
+                break;
+            }
+            seen.push(key);
+            let mv = match self.probe(key).and_then(|entry| entry.best_move) {
+                Some(mv) => mv,
+                None => break
+            };
+            if !position.legal_moves_cached().contains(&mv) {
+                break;
+            }
+            position.make_move(mv);
+            pv.push(mv);
+        }
+        return pv;
+    }
+
+    /// Returns how full the table is in permille, counting the entries of the current search among the first
+    /// thousand slots, as reported in the 'hashfull' field of UCI
+    pub fn hashfull(&self) -> usize {
+        let sample = &self.slots[..self.slots.len().min(1000)];
+        let used = sample.iter().filter(|slot| slot.data != 0 && slot.generation() == self.generation).count();
+        return used * 1000 / sample.len();
+    }
+
+    /// Maps the hash to a slot by multiplication, so the number of slots doesn't have to be a power of two
+    fn index(&self, key: u64) -> usize {
+        return ((key as u128 * self.slots.len() as u128) >> 64) as usize;
+    }
+}
@@ -0,0 +1,232 @@
+//! An arena of search tree nodes for Monte Carlo tree search and other searches that keep their tree in memory
+//!
+//! All nodes of a [NodeArena] live in one vector and refer to each other by [NodeId], so building the tree doesn't
+//! allocate per node and dropping it is a single deallocation. The children of a node are created together from
+//! the legal moves of its position and stored next to each other, so a node only stores where its children start
+//! and how many there are. Every node keeps the statistics of Monte Carlo tree search: its visits, the sum of the
+//! values backed up through it and a prior probability.
+//!
+//! Nodes don't store positions. The position of a node is found by making the moves on the path from the root,
+//! which a search usually does anyway on its way down the tree.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::tree::NodeArena;
+//! let game = Game::starting_position();
+//! let mut arena = NodeArena::new();
+//! let root = arena.add_root();
+//! assert_eq!(arena.expand(root, &game), 20);
+//!
+//! let first = arena.children(root).next().unwrap();
+//! let child_game = arena.game_at(&game, first);
+//! assert_eq!(arena.expand(first, &child_game), 20);
+//! assert_eq!(arena.len(), 41);
+//!
+//! let grandchild = arena.children(first).next().unwrap();
+//! arena.backpropagate(grandchild, 1.0);
+//! assert_eq!((arena[grandchild].visits, arena[grandchild].total_value), (1, 1.0));
+//! // The value is from the point of view of the side that moved into the node, so it flips on the way up
+//! assert_eq!((arena[first].visits, arena[first].total_value), (1, -1.0));
+//! assert_eq!(arena[root].visits, 1);
+//! assert_eq!(arena.path(grandchild).len(), 2);
+//! ```
+
+use crate::{Game, Move, MoveList};
+
+/// The index of a node in a [NodeArena]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(u32);
+
+impl NodeId {
+
+    /// Returns the index of the node in the arena, nodes are numbered in the order they were created
+    pub fn index(&self) -> usize {
+        return self.0 as usize;
+    }
+}
+
+/// A node of the tree, the position after its move
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Node {
+    /// The move leading to the node, None for a root
+    pub mv: Option<Move>,
+    pub parent: Option<NodeId>,
+    /// The number of times the node was visited
+    pub visits: u32,
+    /// The sum of the values backed up through the node, from the point of view of the side that made its move
+    pub total_value: f64,
+    /// The prior probability of the move, for example from a policy network, 0 until it is set
+    pub prior: f32,
+    first_child: u32,
+    n_children: u32,
+    expanded: bool
+}
+
+impl Node {
+
+    fn new(mv: Option<Move>, parent: Option<NodeId>) -> Node {
+        Node {
+            mv,
+            parent,
+            visits: 0,
+            total_value: 0.0,
+            prior: 0.0,
+            first_child: 0,
+            n_children: 0,
+            expanded: false
+        }
+    }
+
+    /// Returns the average value backed up through the node, 0 if it hasn't been visited
+    pub fn mean_value(&self) -> f64 {
+        if self.visits == 0 {
+            return 0.0;
+        }
+        return self.total_value / self.visits as f64;
+    }
+
+    /// Returns true if the children of the node have been created
+    pub fn is_expanded(&self) -> bool {
+        return self.expanded;
+    }
+
+    /// Returns true if the node has been expanded and has no children, so its position has no legal moves
+    pub fn is_terminal(&self) -> bool {
+        return self.expanded && self.n_children == 0;
+    }
+
+    pub fn child_count(&self) -> usize {
+        return self.n_children as usize;
+    }
+}
+
+/// A pool of tree nodes, see the [module documentation](self)
+#[derive(Clone, Debug, Default)]
+pub struct NodeArena {
+    nodes: Vec<Node>
+}
+
+impl NodeArena {
+
+    pub fn new() -> NodeArena {
+        NodeArena {
+            nodes: vec![]
+        }
+    }
+
+    /// Creates an arena with room for the given number of nodes before it has to grow
+    pub fn with_capacity(capacity: usize) -> NodeArena {
+        NodeArena {
+            nodes: Vec::with_capacity(capacity)
+        }
+    }
+
+    /// Removes all nodes and keeps the memory for the next tree
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
+    /// Returns the number of nodes in the arena
+    pub fn len(&self) -> usize {
+        return self.nodes.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.nodes.is_empty();
+    }
+
+    /// Adds a node without a parent and returns it
+    pub fn add_root(&mut self) -> NodeId {
+        self.nodes.push(Node::new(None, None));
+        return NodeId(self.nodes.len() as u32 - 1);
+    }
+
+    /// Creates the children of the node from the legal moves of its position and returns how many there are.
+    /// Does nothing if the node is already expanded.
+    pub fn expand(&mut self, node: NodeId, game: &Game) -> usize {
+        let mut moves = MoveList::new();
+        game.legal_moves_into(&mut moves);
+        return self.expand_with(node, &moves);
+    }
+
+    /// Creates a child of the node for each of the moves, for example a subset of the legal moves chosen by a
+    /// policy, and returns how many there are. Does nothing if the node is already expanded.
+    ///
+    /// # Panics
+    /// If the arena would hold more than 2^32 nodes
+    pub fn expand_with(&mut self, node: NodeId, moves: &[Move]) -> usize {
+        if self.nodes[node.index()].expanded {
+            return self.nodes[node.index()].child_count();
+        }
+        let first_child = self.nodes.len();
+        assert!(first_child + moves.len() <= u32::MAX as usize, "the node arena is full");
+        self.nodes.extend(moves.iter().map(|&mv| Node::new(Some(mv), Some(node))));
+
+        let parent = &mut self.nodes[node.index()];
+        parent.first_child = first_child as u32;
+        parent.n_children = moves.len() as u32;
+        parent.expanded = true;
+        return moves.len();
+    }
+
+    /// Returns the children of the node in the order of the moves they were created from
+    pub fn children(&self, node: NodeId) -> impl DoubleEndedIterator<Item = NodeId> + ExactSizeIterator {
+        let parent = &self.nodes[node.index()];
+        return (parent.first_child..parent.first_child + parent.n_children).map(NodeId);
+    }
+
+    /// Returns the child that was visited the most, the first of them on a tie, or None if the node has no children
+    pub fn most_visited_child(&self, node: NodeId) -> Option<NodeId> {
+        return self.children(node).rev().max_by_key(|&child| self.nodes[child.index()].visits);
+    }
+
+    /// Returns the moves from the root of the node's tree to the node
+    pub fn path(&self, node: NodeId) -> Vec<Move> {
+        let mut moves = vec![];
+        let mut current = &self.nodes[node.index()];
+        while let (Some(mv), Some(parent)) = (current.mv, current.parent) {
+            moves.push(mv);
+            current = &self.nodes[parent.index()];
+        }
+        moves.reverse();
+        return moves;
+    }
+
+    /// Returns the position of the node, given the position of the root of its tree
+    pub fn game_at(&self, root_game: &Game, node: NodeId) -> Game {
+        let mut game = root_game.clone();
+        for mv in self.path(node) {
+            game.make_move(mv);
+        }
+        return game;
+    }
+
+    /// Adds a visit with the value, from the point of view of the side that moved into the node, to the node and
+    /// every node above it, flipping the sign of the value at every step up
+    pub fn backpropagate(&mut self, node: NodeId, mut value: f64) {
+        let mut current = Some(node);
+        while let Some(id) = current {
+            let node = &mut self.nodes[id.index()];
+            node.visits += 1;
+            node.total_value += value;
+            value = -value;
+            current = node.parent;
+        }
+    }
+}
+
+impl std::ops::Index<NodeId> for NodeArena {
+    type Output = Node;
+
+    fn index(&self, node: NodeId) -> &Node {
+        return &self.nodes[node.index()];
+    }
+}
+
+impl std::ops::IndexMut<NodeId> for NodeArena {
+    fn index_mut(&mut self, node: NodeId) -> &mut Node {
+        return &mut self.nodes[node.index()];
+    }
+}
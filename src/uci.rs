@@ -0,0 +1,712 @@
+//! The UCI protocol for chess engines
+//!
+//! [run] implements the engine side of the protocol around anything implementing [SearchEngine]. It keeps track of
+//! the position and the options, runs searches on a separate thread so 'stop' and 'isready' are answered while
+//! searching, and writes the 'info' and 'bestmove' lines. [Client] is the other side, it runs an external engine
+//! and reads its analysis.
+//!
+//! # Examples
+//!
+//! ```
+//! # use olindba_chess::*;
+//! # use olindba_chess::search::{Score, SearchInfo};
+//! # use olindba_chess::uci::{run, SearchControl, SearchEngine, SearchLimits};
+//! struct FirstMove;
+//!
+//! impl SearchEngine for FirstMove {
+//!     fn name(&self) -> String { "First move".to_string() }
+//!     fn author(&self) -> String { "Anonymous".to_string() }
+//!     fn search(&mut self, game: &Game, limits: &SearchLimits, control: &SearchControl,
+//!               info: &mut dyn FnMut(&SearchInfo)) -> Option<Move> {
+//!         let mv = game.get_all_legal_moves().first().copied();
+//!         info(&SearchInfo { depth: 1, score: Score::Centipawns(0), nodes: 1, pv: mv.into_iter().collect(), ..Default::default() });
+//!         return mv;
+//!     }
+//! }
+//!
+//! // The position with an invalid FEN is ignored
+//! let input = "uci\nisready\nposition startpos moves e2e4\nposition fen 8/8/8 w - -\ngo depth 1\nquit\n";
+//! let mut output = vec![];
+//! run(FirstMove, input.as_bytes(), &mut output).unwrap();
+//! let output = String::from_utf8(output).unwrap();
+//! assert!(output.starts_with("id name First move\nid author Anonymous\nuciok\nreadyok\n"));
+//! assert!(output.contains("info depth 1 score cp 0 nodes 1 pv b8a6\n"));
+//! assert!(output.ends_with("bestmove b8a6\n"));
+//!
+//! // The end of the input stops a pondering search like 'quit'
+//! let mut output = vec![];
+//! run(FirstMove, "position startpos\ngo ponder\n".as_bytes(), &mut output).unwrap();
+//! assert!(String::from_utf8(output).unwrap().contains("\nbestmove "));
+//! ```
+
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::ScopedJoinHandle;
+use std::time::Duration;
+
+use crate::fen::{parse_fen, to_fen};
+use crate::notation::{parse_uci, to_uci};
+use crate::search::Score;
+use crate::transposition::Bound;
+use crate::{Game, Move};
+
+pub use crate::search::SearchInfo;
+
+/// The limits of a search, as given by the 'go' command. Unset limits are None.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub depth: Option<usize>,
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+    /// The remaining time of White and Black
+    pub time: [Option<Duration>; 2],
+    /// The increment of White and Black
+    pub increment: [Option<Duration>; 2],
+    pub moves_to_go: Option<usize>,
+    /// Search until 'stop' is received
+    pub infinite: bool,
+    /// Search the position after the expected reply while the opponent thinks. The time limits only count from
+    /// 'ponderhit', which means the reply was played.
+    pub ponder: bool
+}
+
+/// The signals a running search receives from the GUI
+#[derive(Debug, Default)]
+pub struct SearchControl {
+    stop: AtomicBool,
+    pondering: AtomicBool
+}
+
+impl SearchControl {
+
+    pub fn new(pondering: bool) -> SearchControl {
+        SearchControl {
+            stop: AtomicBool::new(false),
+            pondering: AtomicBool::new(pondering)
+        }
+    }
+
+    /// Tells the search to stop as soon as possible
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        return self.stop.load(Ordering::Relaxed);
+    }
+
+    /// Tells a pondering search that the expected reply was played, the search goes on as a normal search
+    pub fn ponderhit(&self) {
+        self.pondering.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        return self.pondering.load(Ordering::Relaxed);
+    }
+}
+
+/// The type of an engine option and its default value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OptionKind {
+    Check { default: bool },
+    Spin { default: i64, min: i64, max: i64 },
+    Combo { default: String, values: Vec<String> },
+    Button,
+    String { default: String }
+}
+
+/// An option an engine announces in response to 'uci'
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EngineOption {
+    pub name: String,
+    pub kind: OptionKind
+}
+
+impl std::fmt::Display for EngineOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "option name {} type ", self.name)?;
+        match &self.kind {
+            OptionKind::Check { default } => write!(f, "check default {}", default),
+            OptionKind::Spin { default, min, max } => write!(f, "spin default {} min {} max {}", default, min, max),
+            OptionKind::Combo { default, values } => {
+                write!(f, "combo default {}", default)?;
+                for value in values {
+                    write!(f, " var {}", value)?;
+                }
+                return Ok(());
+            },
+            OptionKind::Button => write!(f, "button"),
+            OptionKind::String { default } => write!(f, "string default {}", default)
+        }
+    }
+}
+
+/// A chess engine that can be run with [run]
+#[allow(unused_variables)]
+pub trait SearchEngine: Send {
+
+    fn name(&self) -> String;
+
+    fn author(&self) -> String;
+
+    /// Returns the options announced in response to 'uci'
+    fn options(&self) -> Vec<EngineOption> { vec![] }
+
+    /// Called for 'setoption', the value is empty for buttons
+    fn set_option(&mut self, name: &str, value: &str) {}
+
+    /// Called for 'ucinewgame'
+    fn new_game(&mut self) {}
+
+    /// Searches the position within the limits and returns the best move, or None if there are no legal moves.
+    /// The search has to return soon after it is stopped through the control. 'info' can be called any number of
+    /// times to report progress, the second move of the last principal variation is sent as the move to ponder on.
+    fn search(&mut self, game: &Game, limits: &SearchLimits, control: &SearchControl,
+              info: &mut dyn FnMut(&SearchInfo)) -> Option<Move>;
+}
+
+/// A search running on its own thread
+struct RunningSearch<'scope> {
+    control: Arc<SearchControl>,
+    handle: ScopedJoinHandle<'scope, ()>
+}
+
+impl RunningSearch<'_> {
+
+    fn stop(self) {
+        self.control.stop();
+        self.wait();
+    }
+
+    fn wait(self) {
+        // A panicking engine has already stopped, there's nothing more to do about it here
+        let _ = self.handle.join();
+    }
+}
+
+/// Runs the UCI protocol for the engine, reading commands from 'input' and writing responses to 'output'
+/// until 'quit' is received or the input ends, either of which stops a running search
+pub fn run<E: SearchEngine, R: BufRead, W: Write + Send>(engine: E, input: R, output: W) -> std::io::Result<()> {
+    let engine = &Mutex::new(engine);
+    let output = &Mutex::new(output);
+    let mut game = Game::starting_position();
+
+    std::thread::scope(|scope| {
+        let mut search: Option<RunningSearch> = None;
+        let result = (|| -> std::io::Result<()> {
+            for line in input.lines() {
+                let line = line?;
+                let mut tokens = line.split_whitespace();
+                let command = match tokens.next() {
+                    Some(command) => command,
+                    None => continue
+                };
+
+                // Only 'stop', 'ponderhit' and 'isready' may arrive while searching, wait for the search to finish for
+                // anything else
+                if command != "isready" && command != "ponderhit" {
+                    if let Some(running) = search.take() {
+                        if command == "stop" || command == "quit" {
+                            running.stop();
+                        }
+                        else {
+                            running.wait();
+                        }
+                    }
+                }
+
+                match command {
+                    "uci" => {
+                        let engine = engine.lock().unwrap();
+                        let mut output = output.lock().unwrap();
+                        writeln!(output, "id name {}", engine.name())?;
+                        writeln!(output, "id author {}", engine.author())?;
+                        for option in engine.options() {
+                            writeln!(output, "{}", option)?;
+                        }
+                        writeln!(output, "uciok")?;
+                        output.flush()?;
+                    },
+                    "isready" => {
+                        let mut output = output.lock().unwrap();
+                        writeln!(output, "readyok")?;
+                        output.flush()?;
+                    },
+                    "ucinewgame" => {
+                        engine.lock().unwrap().new_game();
+                        game = Game::starting_position();
+                    },
+                    "setoption" => {
+                        let (name, value) = parse_setoption(tokens.collect());
+                        engine.lock().unwrap().set_option(&name, &value);
+                    },
+                    "position" => {
+                        if let Some(position) = parse_position(tokens.collect()) {
+                            game = position;
+                        }
+                    },
+                    "ponderhit" => {
+                        if let Some(running) = &search {
+                            running.control.ponderhit();
+                        }
+                    },
+                    "go" => {
+                        let limits = parse_go(tokens.collect());
+                        let control = Arc::new(SearchControl::new(limits.ponder));
+                        let game = game.clone();
+                        let search_control = Arc::clone(&control);
+                        let handle = std::thread::Builder::new().spawn_scoped(scope, move || {
+                            let mut engine = engine.lock().unwrap();
+                            let mut ponder_move = None;
+                            let best_move = engine.search(&game, &limits, &search_control, &mut |info| {
+                                ponder_move = info.pv.get(1).copied();
+                                let mut output = output.lock().unwrap();
+                                let _ = writeln!(output, "{}", info);
+                                let _ = output.flush();
+                            });
+                            // The best move of a pondering search may only be sent after 'ponderhit' or 'stop'
+                            while search_control.is_pondering() && !search_control.is_stopped() {
+                                std::thread::sleep(Duration::from_millis(1));
+                            }
+                            let mut output = output.lock().unwrap();
+                            match (best_move, ponder_move) {
+                                (Some(best_move), Some(ponder_move)) => {
+                                    let _ = writeln!(output, "bestmove {} ponder {}", to_uci(&best_move),
+                                        to_uci(&ponder_move));
+                                },
+                                (Some(best_move), None) => {
+                                    let _ = writeln!(output, "bestmove {}", to_uci(&best_move));
+                                },
+                                (None, _) => {
+                                    let _ = writeln!(output, "bestmove 0000");
+                                }
+                            }
+                            let _ = output.flush();
+                        })?;
+                        search = Some(RunningSearch { control, handle });
+                    },
+                    "quit" => break,
+                    _ => {}
+                }
+            }
+            return Ok(());
+        })();
+        // A search still running when the input ends or fails is stopped like on 'quit', a pondering or infinite
+        // search would never finish otherwise
+        if let Some(running) = search.take() {
+            running.stop();
+        }
+        return result;
+    })
+}
+
+/// Runs the UCI protocol for the engine over standard input and output
+pub fn run_stdio<E: SearchEngine>(engine: E) -> std::io::Result<()> {
+    return run(engine, std::io::stdin().lock(), std::io::stdout());
+}
+
+/// Reads the arguments of 'setoption name <name> [value <value>]', names and values can contain spaces
+fn parse_setoption(tokens: Vec<&str>) -> (String, String) {
+    let value_index = tokens.iter().position(|&token| token == "value").unwrap_or(tokens.len());
+    let name = tokens[..value_index].iter().skip_while(|&&token| token == "name").copied().collect::<Vec<_>>().join(" ");
+    let value = tokens.get(value_index + 1..).unwrap_or(&[]).join(" ");
+    return (name, value);
+}
+
+/// Reads the arguments of 'position [startpos | fen <fen>] [moves <moves>]', stopping at the first illegal move.
+/// Returns None if the FEN is invalid, the command is then ignored.
+fn parse_position(tokens: Vec<&str>) -> Option<Game> {
+    let moves_index = tokens.iter().position(|&token| token == "moves").unwrap_or(tokens.len());
+    let mut game = match tokens.first() {
+        Some(&"startpos") => Game::starting_position(),
+        Some(&"fen") => parse_fen(&tokens[1..moves_index].join(" ")).ok()?,
+        _ => return None
+    };
+    for text in tokens.iter().skip(moves_index + 1) {
+        match parse_uci(&game, text) {
+            Ok(mv) => game.make_move(mv),
+            Err(_) => break
+        }
+    }
+    return Some(game);
+}
+
+/// Reads the arguments of 'go', unknown arguments are ignored
+fn parse_go(tokens: Vec<&str>) -> SearchLimits {
+    let mut limits = SearchLimits::default();
+    let mut i = 0;
+    while i < tokens.len() {
+        let value = tokens.get(i + 1).and_then(|value| value.parse::<u64>().ok());
+        let millis = value.map(Duration::from_millis);
+        match tokens[i] {
+            "depth" => limits.depth = value.map(|depth| depth as usize),
+            "nodes" => limits.nodes = value,
+            "movetime" => limits.movetime = millis,
+            "wtime" => limits.time[0] = millis,
+            "btime" => limits.time[1] = millis,
+            "winc" => limits.increment[0] = millis,
+            "binc" => limits.increment[1] = millis,
+            "movestogo" => limits.moves_to_go = value.map(|moves| moves as usize),
+            "infinite" => limits.infinite = true,
+            "ponder" => limits.ponder = true,
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        if tokens[i] == "infinite" || tokens[i] == "ponder" {
+            i += 1;
+        }
+        else {
+            i += 2;
+        }
+    }
+    return limits;
+}
+
+/// A line of analysis read from an engine's 'info' output. Fields the engine didn't send are None or empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnalysisInfo {
+    pub depth: Option<usize>,
+    pub seldepth: Option<usize>,
+    /// The index of the line, starting at 1, when the engine searches several lines
+    pub multipv: Option<usize>,
+    /// The score from the point of view of the side to move
+    pub score: Option<Score>,
+    /// Whether the score is exact or only a lower or upper bound
+    pub bound: Bound,
+    pub nodes: Option<u64>,
+    pub nps: Option<u64>,
+    pub time: Option<Duration>,
+    /// The principal variation, cut off at the first move that isn't legal
+    pub pv: Vec<Move>
+}
+
+impl AnalysisInfo {
+
+    /// Reads an 'info' line sent by an engine searching the given position
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// # use olindba_chess::search::Score;
+    /// # use olindba_chess::transposition::Bound;
+    /// # use olindba_chess::uci::AnalysisInfo;
+    /// let game = Game::starting_position();
+    /// let info = AnalysisInfo::parse(&game, "info depth 12 seldepth 15 multipv 1 score cp 31 nodes 90210 pv e2e4 e7e5").unwrap();
+    /// assert_eq!(info.depth, Some(12));
+    /// assert_eq!(info.score, Some(Score::Centipawns(31)));
+    /// assert_eq!(info.pv.len(), 2);
+    ///
+    /// let info = AnalysisInfo::parse(&game, "info depth 20 score mate -4 upperbound nodes 1000").unwrap();
+    /// assert_eq!((info.score, info.bound), (Some(Score::Mate(-4)), Bound::Upper));
+    /// ```
+    pub fn parse(game: &Game, line: &str) -> Option<AnalysisInfo> {
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        if tokens.first() != Some(&"info") {
+            return None;
+        }
+        let mut info = AnalysisInfo::default();
+        let number = |i: usize| tokens.get(i).and_then(|token| token.parse::<i64>().ok());
+        let mut i = 1;
+        while i < tokens.len() {
+            match tokens[i] {
+                "depth" => info.depth = number(i + 1).map(|depth| depth as usize),
+                "seldepth" => info.seldepth = number(i + 1).map(|depth| depth as usize),
+                "multipv" => info.multipv = number(i + 1).map(|index| index as usize),
+                "nodes" => info.nodes = number(i + 1).map(|nodes| nodes as u64),
+                "nps" => info.nps = number(i + 1).map(|nps| nps as u64),
+                "time" => info.time = number(i + 1).map(|millis| Duration::from_millis(millis as u64)),
+                "score" => {
+                    match tokens.get(i + 1) {
+                        Some(&"cp") => info.score = number(i + 2).map(|score| Score::Centipawns(score as i32)),
+                        Some(&"mate") => info.score = number(i + 2).map(|moves| Score::Mate(moves as i32)),
+                        _ => {}
+                    }
+                    i += 1;
+                },
+                "pv" => {
+                    let mut position = game.clone();
+                    for text in tokens[i + 1..].iter() {
+                        match parse_uci(&position, text) {
+                            Ok(mv) => {
+                                position.make_move(mv);
+                                info.pv.push(mv);
+                            },
+                            Err(_) => break
+                        }
+                    }
+                    break;
+                },
+                // The rest of the line is free text
+                "string" => break,
+                "lowerbound" | "upperbound" => {
+                    info.bound = if tokens[i] == "lowerbound" { Bound::Lower } else { Bound::Upper };
+                    i += 1;
+                    continue;
+                },
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            }
+            i += 2;
+        }
+        return Some(info);
+    }
+}
+
+/// The outcome of a search by an external engine
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Analysis {
+    /// None if the engine answered 'bestmove 0000' or with a move that isn't legal
+    pub best_move: Option<Move>,
+    /// The reply the engine expects to the best move
+    pub ponder: Option<Move>,
+    /// Every 'info' line sent during the search, in order
+    pub info: Vec<AnalysisInfo>
+}
+
+/// An external UCI engine running as a child process
+///
+/// # Examples
+///
+/// ```no_run
+/// # use olindba_chess::*;
+/// # use olindba_chess::uci::{Client, SearchLimits};
+/// let mut engine = Client::spawn("stockfish").unwrap();
+/// engine.set_option("Threads", "4").unwrap();
+/// let limits = SearchLimits { depth: Some(20), ..Default::default() };
+/// let analysis = engine.analyze(&Game::starting_position(), &limits, |info| {
+///     println!("depth {:?} score {:?}", info.depth, info.score);
+///     return true;
+/// }).unwrap();
+/// println!("best move {:?}", analysis.best_move);
+/// ```
+pub struct Client {
+    process: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    name: String,
+    author: String,
+    options: Vec<EngineOption>
+}
+
+impl Client {
+
+    /// Starts the engine and waits for it to finish the 'uci' handshake
+    pub fn spawn<S: AsRef<OsStr>>(program: S) -> std::io::Result<Client> {
+        let mut process = Command::new(program).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let input = process.stdin.take().unwrap();
+        let output = BufReader::new(process.stdout.take().unwrap());
+        let mut client = Client {
+            process,
+            input,
+            output,
+            name: String::new(),
+            author: String::new(),
+            options: vec![]
+        };
+
+        client.send("uci")?;
+        loop {
+            let line = client.read_line()?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                client.name = name.to_string();
+            }
+            else if let Some(author) = line.strip_prefix("id author ") {
+                client.author = author.to_string();
+            }
+            else if let Some(option) = parse_option(&line) {
+                client.options.push(option);
+            }
+            else if line == "uciok" {
+                return Ok(client);
+            }
+        }
+    }
+
+    /// Returns the name the engine gave in the handshake
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    /// Returns the author the engine gave in the handshake
+    pub fn author(&self) -> &str {
+        return &self.author;
+    }
+
+    /// Returns the options the engine announced in the handshake
+    pub fn options(&self) -> &[EngineOption] {
+        return &self.options;
+    }
+
+    /// Sets an option and waits for the engine to be ready, leave the value empty for buttons
+    pub fn set_option(&mut self, name: &str, value: &str) -> std::io::Result<()> {
+        if value.is_empty() {
+            self.send(&format!("setoption name {}", name))?;
+        }
+        else {
+            self.send(&format!("setoption name {} value {}", name, value))?;
+        }
+        return self.wait_until_ready();
+    }
+
+    /// Tells the engine that the next position is from a new game
+    pub fn new_game(&mut self) -> std::io::Result<()> {
+        self.send("ucinewgame")?;
+        return self.wait_until_ready();
+    }
+
+    /// Sends 'isready' and waits for 'readyok'
+    pub fn wait_until_ready(&mut self) -> std::io::Result<()> {
+        self.send("isready")?;
+        while self.read_line()? != "readyok" {}
+        return Ok(());
+    }
+
+    /// Searches the position within the limits. 'on_info' is called for every 'info' line as it arrives,
+    /// returning false from it stops the search early, which is the only way to end an infinite search.
+    /// The engine is given the moves of the game's history, so it knows which positions were repeated.
+    pub fn analyze<F: FnMut(&AnalysisInfo) -> bool>(&mut self, game: &Game, limits: &SearchLimits,
+                                                     mut on_info: F) -> std::io::Result<Analysis> {
+        self.send(&position_command(game))?;
+        self.send(&go_command(limits))?;
+
+        let mut analysis = Analysis::default();
+        let mut stopped = false;
+        loop {
+            let line = self.read_line()?;
+            if let Some(info) = AnalysisInfo::parse(game, &line) {
+                if !on_info(&info) && !stopped {
+                    self.send("stop")?;
+                    stopped = true;
+                }
+                analysis.info.push(info);
+            }
+            else if line.starts_with("bestmove") {
+                let tokens = line.split_whitespace().collect::<Vec<_>>();
+                analysis.best_move = tokens.get(1).and_then(|text| parse_uci(game, text).ok());
+                if let (Some(best_move), Some(&"ponder"), Some(text)) = (analysis.best_move, tokens.get(2), tokens.get(3)) {
+                    let mut position = game.clone();
+                    position.make_move(best_move);
+                    analysis.ponder = parse_uci(&position, text).ok();
+                }
+                return Ok(analysis);
+            }
+        }
+    }
+
+    fn send(&mut self, command: &str) -> std::io::Result<()> {
+        writeln!(self.input, "{}", command)?;
+        return self.input.flush();
+    }
+
+    fn read_line(&mut self) -> std::io::Result<String> {
+        let mut line = String::new();
+        if self.output.read_line(&mut line)? == 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "the engine closed its output"));
+        }
+        return Ok(line.trim().to_string());
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        if self.send("quit").is_err() {
+            let _ = self.process.kill();
+        }
+        let _ = self.process.wait();
+    }
+}
+
+/// Writes the 'position' command for the position the game's history starts in and the moves made since
+fn position_command(game: &Game) -> String {
+    let mut start = game.clone();
+    while start.unmake_move().is_some() {}
+    let mut command = format!("position fen {}", to_fen(&start));
+    if !game.history().is_empty() {
+        command.push_str(" moves");
+        for entry in game.history() {
+            command.push(' ');
+            command.push_str(&to_uci(&entry.mv));
+        }
+    }
+    return command;
+}
+
+/// Writes the 'go' command for the limits
+fn go_command(limits: &SearchLimits) -> String {
+    let mut command = "go".to_string();
+    if let Some(depth) = limits.depth {
+        command.push_str(&format!(" depth {}", depth));
+    }
+    if let Some(nodes) = limits.nodes {
+        command.push_str(&format!(" nodes {}", nodes));
+    }
+    if let Some(movetime) = limits.movetime {
+        command.push_str(&format!(" movetime {}", movetime.as_millis()));
+    }
+    for (i, color) in ["w", "b"].into_iter().enumerate() {
+        if let Some(time) = limits.time[i] {
+            command.push_str(&format!(" {}time {}", color, time.as_millis()));
+        }
+        if let Some(increment) = limits.increment[i] {
+            command.push_str(&format!(" {}inc {}", color, increment.as_millis()));
+        }
+    }
+    if let Some(moves_to_go) = limits.moves_to_go {
+        command.push_str(&format!(" movestogo {}", moves_to_go));
+    }
+    if limits.infinite {
+        command.push_str(" infinite");
+    }
+    if limits.ponder {
+        command.push_str(" ponder");
+    }
+    return command;
+}
+
+/// Reads an 'option' line sent during the handshake
+fn parse_option(line: &str) -> Option<EngineOption> {
+    let tokens = line.split_whitespace().collect::<Vec<_>>();
+    if tokens.first() != Some(&"option") || tokens.get(1) != Some(&"name") {
+        return None;
+    }
+    let type_index = tokens.iter().position(|&token| token == "type")?;
+    let name = tokens[2..type_index].join(" ");
+
+    // The values can contain spaces, so every value runs until the next keyword
+    let keywords = ["default", "min", "max", "var"];
+    let mut values: Vec<(&str, String)> = vec![];
+    for &token in tokens[type_index + 2..].iter() {
+        if keywords.contains(&token) {
+            values.push((token, String::new()));
+        }
+        else if let Some((_, value)) = values.last_mut() {
+            if !value.is_empty() {
+                value.push(' ');
+            }
+            value.push_str(token);
+        }
+    }
+    let value = |keyword: &str| values.iter().find(|(key, _)| *key == keyword).map(|(_, value)| value.clone()).unwrap_or_default();
+    let number = |keyword: &str| value(keyword).parse::<i64>().unwrap_or(0);
+
+    let kind = match *tokens.get(type_index + 1)? {
+        "check" => OptionKind::Check { default: value("default") == "true" },
+        "spin" => OptionKind::Spin { default: number("default"), min: number("min"), max: number("max") },
+        "combo" => OptionKind::Combo {
+            default: value("default"),
+            values: values.iter().filter(|(key, _)| *key == "var").map(|(_, value)| value.clone()).collect()
+        },
+        "button" => OptionKind::Button,
+        "string" => OptionKind::String { default: value("default") },
+        _ => return None
+    };
+    return Some(EngineOption { name, kind });
+}
@@ -0,0 +1,114 @@
+use crate::{square, Game, Piece, Square, EMPTY};
+
+/// Which squares a player can see in a [BoardView]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VisibilityRules {
+    /// The squares of the player's own pieces are visible
+    pub own_pieces: bool,
+    /// The squares attacked by the player's own pieces are visible, see [Game::attack_map]
+    pub attacked: bool,
+    /// Squares that are always visible, as a mask with one bit per square
+    pub always_visible: u64
+}
+
+impl VisibilityRules {
+
+    /// Nothing on the board is visible
+    pub const BLINDFOLD: VisibilityRules = VisibilityRules { own_pieces: false, attacked: false, always_visible: 0 };
+    /// Only the player's own pieces are visible, as in Kriegspiel
+    pub const OWN_PIECES: VisibilityRules = VisibilityRules { own_pieces: true, attacked: false, always_visible: 0 };
+    /// The player's own pieces and every square they attack are visible
+    pub const FOG_OF_WAR: VisibilityRules = VisibilityRules { own_pieces: true, attacked: true, always_visible: 0 };
+}
+
+/// The board as seen by one player, without the pieces on the squares hidden from them.
+/// Built from a [Game] with [Game::view_for] and read only, so it can be handed to the player as is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BoardView {
+    viewer: usize,
+    turn: usize,
+    visible: u64,
+    board: [Piece; 64]
+}
+
+impl BoardView {
+
+    /// Returns the color the board is seen by
+    pub fn viewer(&self) -> usize {
+        return self.viewer;
+    }
+
+    /// Returns the color to move
+    pub fn turn(&self) -> usize {
+        return self.turn;
+    }
+
+    /// Returns the visible squares as a mask with one bit per square
+    pub fn visible_squares(&self) -> u64 {
+        return self.visible;
+    }
+
+    /// Returns true if the viewer can see the square
+    pub fn is_visible(&self, square: impl TryInto<Square>) -> bool {
+        return match square::to_square(square) {
+            Some(square) => self.visible & (1 << square.index()) != 0,
+            None => false
+        };
+    }
+
+    /// Returns the piece on the square, which has the type EMPTY if the square is empty, or None if the square
+    /// is hidden
+    pub fn piece(&self, square: impl TryInto<Square>) -> Option<Piece> {
+        let square = square::to_square(square)?;
+        if !self.is_visible(square) {
+            return None;
+        }
+        return Some(self.board[square.index()]);
+    }
+}
+
+impl Game {
+
+    /// Returns the board as seen by the given color under the given rules
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// let game = Game::starting_position();
+    /// let view = game.view_for(WHITE, VisibilityRules::FOG_OF_WAR);
+    /// assert_eq!(view.piece(60).unwrap().get_type(), KING);
+    /// assert_eq!(view.piece(Square::from_algebraic("e3").unwrap()).unwrap().get_type(), EMPTY);
+    /// assert_eq!(view.piece(Square::from_algebraic("e4").unwrap()), None);
+    /// assert_eq!(view.piece(4), None);
+    ///
+    /// let view = game.view_for(BLACK, VisibilityRules::OWN_PIECES);
+    /// assert_eq!(view.visible_squares(), 0xffff);
+    /// ```
+    pub fn view_for(&self, color: usize, rules: VisibilityRules) -> BoardView {
+        let mut visible = rules.always_visible;
+        if rules.attacked {
+            visible |= self.attack_map(color);
+        }
+
+        let mut board = [Piece::empty(); 64];
+        for square in 0..64 {
+            let piece = self.board[square];
+            let own_piece = piece.get_type() != EMPTY && piece.get_color() == color;
+            if own_piece && rules.own_pieces {
+                visible |= 1 << square;
+            }
+            // Only the type and color are copied, the flags would tell which rooks can still castle
+            if visible & (1 << square) != 0 && piece.get_type() != EMPTY {
+                board[square] = Piece::new(piece.get_type(), piece.get_color(), 0);
+            }
+        }
+
+        return BoardView {
+            viewer: color,
+            turn: self.turn,
+            visible,
+            board
+        };
+    }
+}
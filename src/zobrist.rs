@@ -0,0 +1,112 @@
+//! Zobrist hashing of positions
+//!
+//! The keys are generated at compile time from a fixed seed, so hashes are the same across runs and platforms.
+
+use crate::{Game, Move, EMPTY, WHITE, PAWN};
+
+const PIECE_KEYS: usize = 0;
+const SIDE_KEY: usize = 2 * 6 * 64;
+const CASTLING_KEYS: usize = SIDE_KEY + 1;
+const EP_KEYS: usize = CASTLING_KEYS + 4;
+const N_KEYS: usize = EP_KEYS + 8;
+
+const fn generate_keys() -> [u64; N_KEYS] {
+    let mut keys = [0; N_KEYS];
+    let mut state: u64 = 0x3243f6a8885a308d;
+    let mut i = 0;
+    while i < N_KEYS {
+        // splitmix64
+        state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    return keys;
+}
+
+static KEYS: [u64; N_KEYS] = generate_keys();
+
+/// Returns the key of a piece of the given color and type standing on the given square
+pub(crate) fn piece_key(color: usize, piece_type: usize, square: usize) -> u64 {
+    return KEYS[PIECE_KEYS + (color * 6 + piece_type - 1) * 64 + square];
+}
+
+/// Returns the key xored into the hash when Black is to move
+pub(crate) fn side_key() -> u64 {
+    return KEYS[SIDE_KEY];
+}
+
+/// Returns the key of a castling right, indexed as in [crate::CastlingRights::as_array]
+pub(crate) fn castling_key(right: usize) -> u64 {
+    return KEYS[CASTLING_KEYS + right];
+}
+
+/// Returns the key of an en passant capture on the given column
+pub(crate) fn ep_key(column: usize) -> u64 {
+    return KEYS[EP_KEYS + column];
+}
+
+/// Returns the hash of a line of moves extended by a move made in the position with the given hash. The order of
+/// the moves counts, so a transposition into the same position by other moves gives another hash.
+pub(crate) fn extend_line(line: u64, hash: u64, mv: &Move) -> u64 {
+    // splitmix64 of the line, the position and the move
+    let mut z = line.rotate_left(29) ^ hash ^ (mv.to_u16() as u64).wrapping_mul(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    return z ^ (z >> 31);
+}
+
+impl Game {
+
+    /// Returns the Zobrist hash of the current position.
+    ///
+    /// Positions with the same pieces, side to move, castling rights and en passant possibilities
+    /// have the same hash. The en passant column is only included when a pawn of the side to move
+    /// stands next to the pawn that just made a double push.
+    ///
+    /// The keys don't change between versions of the crate, so hashes can be stored, for example in opening books.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use olindba_chess::*;
+    /// assert_eq!(Game::starting_position().hash(), 0x134898c6d6fe85b6);
+    /// assert_eq!(Game::new("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").hash(), 0x453f007bca91bea1);
+    /// ```
+    pub fn hash(&self) -> u64 {
+        let mut hash = 0;
+        for square in 0..64 {
+            let piece = self.board[square];
+            if piece.get_type() != EMPTY {
+                hash ^= piece_key(piece.get_color(), piece.get_type(), square);
+            }
+        }
+        if self.turn != WHITE {
+            hash ^= side_key();
+        }
+        let castling_rights = self.castling_rights().as_array();
+        for right in 0..4 {
+            if castling_rights[right] {
+                hash ^= castling_key(right);
+            }
+        }
+        if self.possible_ep_capture < 64 {
+            let column = self.get_column(self.possible_ep_capture);
+            let mut capture_possible = false;
+            if column != 0 {
+                let piece = self.board[self.possible_ep_capture - 1];
+                capture_possible |= piece.get_type() == PAWN && piece.get_color() == self.turn;
+            }
+            if column != 7 {
+                let piece = self.board[self.possible_ep_capture + 1];
+                capture_possible |= piece.get_type() == PAWN && piece.get_color() == self.turn;
+            }
+            if capture_possible {
+                hash ^= ep_key(column);
+            }
+        }
+        return hash;
+    }
+}